@@ -0,0 +1,340 @@
+# German
+
+## Panes
+
+pane-config = Einstellungen
+pane-nodes = Knoten
+pane-statistics = Statistik
+pane-inspector = Inspektor
+pane-timeline = Zeitleiste
+pane-sweep = Parameterstudie
+pane-simulation = Simulation
+pane-log = Protokoll
+pane-problems = Probleme
+pane-problems-count = Probleme ({ $count })
+pane-differences = Unterschiede
+pane-history = Verlauf
+
+## Menus
+
+menu-file = Datei
+menu-edit = Bearbeiten
+menu-view = Ansicht
+menu-quit = Beenden
+menu-new-graph = Neuer Graph
+menu-open-graph = Graph öffnen…
+menu-open-recent = Zuletzt geöffnet
+menu-save-graph = Graph speichern…
+menu-export-package = Paket exportieren…
+menu-export-graph = Graph exportieren
+menu-export-image = Bild exportieren
+menu-export-plan = Plan exportieren
+menu-export-trace = Trace exportieren…
+menu-compare = Mit Datei vergleichen…
+menu-import-trace = Trace importieren…
+menu-save-checkpoint = Checkpoint speichern…
+menu-resume-checkpoint = Ab Checkpoint fortsetzen…
+menu-eval = Auswerten
+menu-undo = Rückgängig
+menu-undo-action = Rückgängig: { $action }
+menu-clear-graph = Graph leeren
+menu-reset-state = Zustand zurücksetzen
+menu-fit-graph = Graph einpassen
+menu-zoom-to-selected = Auf Auswahl zoomen
+menu-go-to-node = Gehe zu Knoten
+menu-minimap = Übersichtskarte
+menu-wire-values = Werte an Verbindungen
+menu-profiler = Profiler
+
+## Config pane
+
+config-language = Sprache
+config-profile = Profil
+config-save-profile = Profil speichern
+config-delete-profile = Profil löschen
+config-evaluation = Auswertung
+config-on-change = Bei Änderung
+config-manual = Manuell
+config-background = Im Hintergrund
+config-background-hint = Bearbeitete Knoten in einem anderen Thread neu berechnen, damit langsame Knoten den Editor nicht aufhalten
+config-evaluate-now = Jetzt auswerten
+config-reset-state = Zustand zurücksetzen
+config-highlight-changes = Änderungen hervorheben
+config-highlight-changes-hint = Die von der letzten Auswertung aktualisierten Knoten umranden und die mit geänderten Ausgaben markieren
+config-workers = Worker
+config-sources = Quellen
+config-sources-hint = Eine Quelle auslösen, um nur die nachgelagerten Knoten neu auszuwerten
+config-trigger = Auslösen
+config-groups = Gruppen
+config-group = Gruppe { $id }
+config-secrets = Geheimnisse
+config-no-secrets-file = Keine Geheimnisdatei
+config-reload-secrets = Geheimnisse neu laden
+config-plugins = Plugins
+config-plugins-hint = Knotenpakete im Plugin-Verzeichnis, die bei Änderungen neu geladen werden
+config-no-plugins-directory = Kein Plugin-Verzeichnis
+config-node-types = { $count } Knotentyp(en)
+config-reload-plugins = Plugins neu laden
+config-hooks = Hooks
+config-hooks-hint = Rhai-Skripte, die vor und nach jeder Auswertung laufen, wenn der Graph als Engine läuft
+config-before-run = Vor dem Lauf
+config-after-run = Nach dem Lauf
+config-templates = Vorlagen
+config-templates-hint = Im Menü eines Knotens gespeichert und über das Graphmenü oder die Palette hinzugefügt
+config-no-templates = Keine Vorlagen
+config-move-up = Nach oben
+config-move-down = Nach unten
+config-files = Dateien
+config-save-thumbnails = Vorschaubilder speichern
+config-save-thumbnails-hint = Neben jeder Graphdatei ein Bild des Graphen speichern, das unter Zuletzt geöffnet angezeigt wird
+config-monitoring = Überwachung
+config-serve-state = Aufgabenstatus bereitstellen auf Port
+config-serve-state-hint = Den Zustand des Aufgaben-DAG als Server-Sent Events von http://127.0.0.1:{ $port }/events an Dashboards senden, oder ihn einmalig von /state abrufen
+config-allowed-origin = Erlaubter Ursprung
+config-allowed-origin-none = keiner
+config-allowed-origin-hint = Die Webseite, die den Aufgabenstatus lesen darf, etwa http://localhost:3000. Andere Webseiten werden abgewiesen, damit sie ihn nicht von Ihrem Rechner lesen können.
+config-appearance = Darstellung
+
+## Shared
+
+none = Keine
+node = Knoten
+node-id = Knoten { $id }
+node-count = { $count } Knoten
+remove = Entfernen
+close = Schließen
+search = Suchen…
+go-to-node-hint = Klicken, um zum Knoten zu gehen
+
+## Menu hints
+
+menu-export-image-scale = Maßstab
+menu-export-image-scale-hint = Pixel pro Einheit des Graphen, unabhängig vom Zoom
+menu-export-image-statuses = Statusfarben
+menu-export-image-statuses-hint = Knoten danach umranden, wie sie bei der letzten Auswertung abgeschnitten haben
+menu-export-plan-hint = Die Stufen, in denen der Graph läuft, damit andere Werkzeuge ihn der Reihe nach ausführen können
+menu-export-trace-hint = Die letzte Auswertung für about://tracing oder Perfetto speichern
+menu-compare-hint = Zeigen, wie sich der Graph von einem gespeicherten unterscheidet
+menu-save-checkpoint-hint = Speichern, welche Aufgaben des Aufgaben-DAG erledigt sind
+menu-resume-checkpoint-hint = Den Aufgaben-DAG berechnen und die in einem Checkpoint erledigten Aufgaben überspringen
+menu-reset-state-hint = Laufende Summen und anderen von Knoten gehaltenen Zustand löschen
+menu-wire-values-hint = Jede Verbindung mit dem Wert beschriften, der durch sie fließt
+
+## Graph menu
+
+graph-description = Graph, mit Tab zwischen Knoten wechseln
+graph-add-node = Knoten hinzufügen
+graph-templates = Vorlagen
+graph-no-matching-nodes = Keine passenden Knoten
+graph-more-nodes = { $count } weitere…
+graph-comment-selected = Auswahl kommentieren
+graph-group-selected = Auswahl gruppieren ({ $count })
+
+## Node menu
+
+node-menu = Knotenmenü
+node-menu-select = Auswählen
+node-menu-deselect = Abwählen
+node-menu-open-group = Gruppe öffnen
+node-menu-rename = Umbenennen…
+node-menu-enable = Aktivieren
+node-menu-disable = Deaktivieren
+node-menu-disable-hint = Den Knoten bei der Auswertung überspringen, ihn aber im Graphen behalten
+node-menu-add-breakpoint = Haltepunkt hinzufügen
+node-menu-remove-breakpoint = Haltepunkt entfernen
+node-menu-priority = Priorität
+node-menu-priority-hint = Aufgaben mit höherer Priorität werden zuerst gestartet
+node-menu-stage = Stufe
+node-menu-save-template = Als Vorlage speichern
+node-menu-save-selected-template = Auswahl als Vorlage speichern ({ $count })
+node-menu-template-name = Vorlagenname
+node-menu-replace-template = Ersetzen
+node-menu-add-template = Speichern
+node-menu-disconnect = Trennen
+node-menu-disconnect-selected = Auswahl trennen ({ $count })
+node-menu-remove-selected = Auswahl entfernen ({ $count })
+
+## Nodes
+
+node-breakpoint = Haltepunkt
+node-breakpoint-paused = Haltepunkt, hier angehalten
+node-priority = Aufgabenpriorität { $priority }
+node-stage = Stufe „{ $stage }“
+node-computing = Wird im Hintergrund neu berechnet
+node-input-default = Standardwert, verwendet solange nichts verbunden ist
+node-disabled-passing-through = Deaktiviert, gibt seine Eingabe direkt weiter
+node-disabled-without-values = Deaktiviert, seine Ausgaben bleiben ohne Werte
+node-change-nothing = nichts
+node-change-output = Ausgabe { $output }: { $before } → { $after }
+node-leave-group = ⬆ Gruppe verlassen
+node-editing-group = Gruppe { $path } wird bearbeitet
+
+## Debugging
+
+debug = Debuggen
+debug-hint = Den Graphen auswerten und vor jedem Knoten mit Haltepunkt anhalten. Haltepunkte werden im Menü eines Knotens gesetzt.
+debug-step = Schritt
+debug-step-hint = Den nächsten Knoten aktualisieren und wieder anhalten
+debug-continue = Fortsetzen
+debug-continue-hint = Bis zum nächsten Haltepunkt ausführen
+debug-stop = Anhalten
+debug-paused = Angehalten vor { $node } { $id }, bisher { $count } aktualisiert
+
+## Stages
+
+stages = Stufen
+stages-hint = Jeder Knoten einer Stufe ist fertig, bevor ein Knoten der nächsten startet. Knoten werden in ihrem Menü einer Stufe zugeordnet.
+stages-run-earlier = Früher ausführen
+stages-add = Stufe hinzufügen
+stages-new-name = Stufe { $number }
+stages-conflict = ⚠ { $node } in { $stage } kann nie laufen, da es { $needs } aus der späteren Stufe { $later } braucht
+
+## Statistics pane
+
+statistics-last-run = Letzter Lauf: { $produced } von { $sinks } Senken erzeugt, { $updated } Knoten aktualisiert
+statistics-cache = Cache: { $entries } Einträge, { $hits } Treffer, { $misses } Fehlschläge
+statistics-cache-hint = Reine Knoten werden nicht erneut aktualisiert, wenn ihre Parameter und Eingaben einer früheren Aktualisierung entsprechen
+statistics-clear-cache = Cache leeren
+statistics-calculate-task-dag = Aufgaben-DAG berechnen
+statistics-priority = Priorität
+statistics-estimate = Geschätzte Laufzeit mit { $workers } Worker(n): { $time }
+statistics-critical-path = Kritischer Pfad, dauert mit unbegrenzten Workern { $time }:
+statistics-ready = Bereit, in der Reihenfolge, in der sie starten würden: { $tasks }
+statistics-task-id = Aufgaben-ID: { $id }
+statistics-critical = Auf dem kritischen Pfad
+statistics-runs = Läufe: { $count } ({ $elapsed })
+statistics-no-runs = Läufe: 0
+statistics-running = Läuft
+statistics-failed = Fehlgeschlagen
+statistics-complete = Abschließen
+statistics-makes-ready = Macht { $tasks } bereit
+statistics-blocked = Blockiert
+statistics-waiting-on = Wartet auf { $tasks }
+statistics-completed = Erledigt
+statistics-run-report = Laufbericht
+
+## Timeline pane
+
+timeline-trace-from = Trace aus { $name }
+timeline-no-runs = Keine Läufe aufgezeichnet
+
+## Inspector pane
+
+inspector-hint = Auf die Kopfzeile eines Knotens klicken, um ihn zu untersuchen
+inspector-label = Beschriftung
+inspector-inputs = Eingaben
+inspector-outputs = Ausgaben
+inspector-role = Rolle
+inspector-values = Werte
+inspector-error = Fehler
+inspector-executions = Ausführungen
+inspector-last-run = Letzter Lauf
+inspector-last-duration = Letzte Dauer
+
+## Problems pane
+
+problems-none = Keine Probleme gefunden
+
+## Differences pane
+
+differences-hint = Den Graphen über das Menü Datei mit einem gespeicherten vergleichen
+differences-compared-with = Verglichen mit { $name }
+differences-none = Keine Unterschiede
+differences-added = Hinzugefügt
+differences-changed = Geändert
+differences-removed = Entfernt
+differences-connected = Verbunden
+differences-disconnected = Getrennt
+differences-wire = Knoten { $from } Ausgabe { $output } → Knoten { $to } Eingabe { $input }
+
+## History pane
+
+history-snapshot-name = Name des Schnappschusses
+history-take-snapshot = Schnappschuss erstellen
+history-take-snapshot-hint = Eine Kopie des Graphen in seiner Datei behalten, um später dorthin zurückzukehren
+history-none = Keine Schnappschüsse erstellt
+history-restore = Wiederherstellen
+history-restore-hint = Den Graphen durch den Schnappschuss ersetzen, was rückgängig gemacht werden kann
+history-compare = Vergleichen
+history-compare-hint = Zeigen, wie sich der Graph seit dem Schnappschuss verändert hat
+history-delete = Löschen
+
+## Confirmation
+
+confirm-title = Sind Sie sicher?
+confirm-can-undo = Das kann im Menü Bearbeiten rückgängig gemacht werden.
+confirm-cannot-undo = Das kann nicht rückgängig gemacht werden.
+confirm = Bestätigen
+confirm-cancel = Abbrechen
+
+## Validation
+
+validation-input-not-connected = Eingang { $input } ({ $type }) ist nicht verbunden
+validation-input-may-be-needed = Eingang { $input } ({ $type }) ist nicht verbunden, wird aber eventuell benötigt
+validation-too-many-wires = Eingang { $input } hat { $count } Verbindungen, akzeptiert aber nur eine
+validation-wrong-type = Eingang { $input } erwartet { $expected }, ist aber mit { $found } von Knoten { $node } verbunden
+validation-missing-output = Eingang { $input } ist mit einem Ausgang verbunden, der nicht mehr existiert
+validation-unconnected-node = Ist mit keinem anderen Knoten verbunden
+validation-unused-results = Die Ergebnisse werden von keinem anderen Knoten verwendet
+validation-cycle = Ist Teil eines Zyklus zwischen den Knoten { $nodes }
+validation-nested-groups = Enthält Gruppen, die tiefer als { $depth } verschachtelt sind und nicht ausgewertet werden können
+
+## Node categories
+
+category-values = Werte
+category-math = Mathematik
+category-logic = Logik
+category-conversions = Umwandlungen
+category-lists = Listen
+category-flow = Fluss
+category-state = Zustand
+category-graphics = Grafik
+category-files = Dateien
+category-tasks = Aufgaben
+category-groups = Gruppen
+category-plugins = Plugins
+
+## Nodes
+
+node-number = Zahl
+node-sink = Senke
+node-plot = Diagramm
+node-table = Tabelle
+node-secret = Geheimnis
+node-argument = Argument
+node-add = Addieren
+node-expression = Ausdruck
+node-script = Skript
+node-if = Wenn
+node-greater-than = Größer als
+node-less-than = Kleiner als
+node-equals = Gleich
+node-and = Und
+node-or = Oder
+node-not = Nicht
+node-to-text = Zu Text
+node-parse-number = Zahl lesen
+node-range = Bereich
+node-list = Liste
+node-sum = Summe
+node-length = Länge
+node-map-add = Abbilden und addieren
+node-throttle = Drosseln
+node-deadband = Totband
+node-running-sum = Laufende Summe
+node-moving-average = Gleitender Durchschnitt
+node-counter = Zähler
+node-delay = Verzögerung
+node-time = Zeit
+node-make-colour = Farbe erstellen
+node-make-vector = Vektor erstellen
+node-split-vector = Vektor aufteilen
+node-read-file = Datei lesen
+node-write-file = Datei schreiben
+node-csv-source = CSV-Quelle
+node-http-request = HTTP-Anfrage
+node-command = Befehl
+node-group = Gruppe
+node-group-inputs = Gruppeneingänge
+node-group-outputs = Gruppenausgänge
+node-comment = Kommentar
@@ -0,0 +1,342 @@
+# English, which every other language falls back to for messages it is
+# missing. Ids of node types and categories are made from their names in
+# kebab case, so "Make Colour" is node-make-colour.
+
+## Panes
+
+pane-config = Config
+pane-nodes = Nodes
+pane-statistics = Statistics
+pane-inspector = Inspector
+pane-timeline = Timeline
+pane-sweep = Sweep
+pane-simulation = Simulation
+pane-log = Log
+pane-problems = Problems
+pane-problems-count = Problems ({ $count })
+pane-differences = Differences
+pane-history = History
+
+## Menus
+
+menu-file = File
+menu-edit = Edit
+menu-view = View
+menu-quit = Quit
+menu-new-graph = New Graph
+menu-open-graph = Open Graph…
+menu-open-recent = Open Recent
+menu-save-graph = Save Graph…
+menu-export-package = Export Package…
+menu-export-graph = Export Graph
+menu-export-image = Export Image
+menu-export-plan = Export Plan
+menu-export-trace = Export Trace…
+menu-compare = Compare with File…
+menu-import-trace = Import Trace…
+menu-save-checkpoint = Save Checkpoint…
+menu-resume-checkpoint = Resume Checkpoint…
+menu-eval = Eval
+menu-undo = Undo
+menu-undo-action = Undo { $action }
+menu-clear-graph = Clear Graph
+menu-reset-state = Reset State
+menu-fit-graph = Fit Graph
+menu-zoom-to-selected = Zoom to Selected
+menu-go-to-node = Go to Node
+menu-minimap = Minimap
+menu-wire-values = Wire Values
+menu-profiler = Profiler
+
+## Config pane
+
+config-language = Language
+config-profile = Profile
+config-save-profile = Save profile
+config-delete-profile = Delete profile
+config-evaluation = Evaluation
+config-on-change = On change
+config-manual = Manual
+config-background = In the background
+config-background-hint = Recalculate edited nodes on another thread, so that slow nodes don't hold up the editor
+config-evaluate-now = Evaluate now
+config-reset-state = Reset state
+config-highlight-changes = Highlight changes
+config-highlight-changes-hint = Outline the nodes updated by the last evaluation, marking those whose outputs changed
+config-workers = Workers
+config-sources = Sources
+config-sources-hint = Trigger a source to re-evaluate only the nodes downstream of it
+config-trigger = Trigger
+config-groups = Groups
+config-group = Group { $id }
+config-secrets = Secrets
+config-no-secrets-file = No secrets file
+config-reload-secrets = Reload secrets
+config-plugins = Plugins
+config-plugins-hint = Node packs in the plugins directory, reloaded when they change
+config-no-plugins-directory = No plugins directory
+config-node-types = { $count } node type(s)
+config-reload-plugins = Reload plugins
+config-hooks = Hooks
+config-hooks-hint = Rhai scripts run before and after each evaluation when the graph is run as an engine
+config-before-run = Before run
+config-after-run = After run
+config-templates = Templates
+config-templates-hint = Saved from a node's menu, and added from the graph menu or palette
+config-no-templates = No templates
+config-move-up = Move up
+config-move-down = Move down
+config-files = Files
+config-save-thumbnails = Save thumbnails
+config-save-thumbnails-hint = Save a picture of the graph next to each graph file, shown under Open Recent
+config-monitoring = Monitoring
+config-serve-state = Serve task state on port
+config-serve-state-hint = Stream the state of the task DAG to dashboards as server-sent events from http://127.0.0.1:{ $port }/events, or get it once from /state
+config-allowed-origin = Allowed origin
+config-allowed-origin-none = none
+config-allowed-origin-hint = The web page allowed to read the task state, such as http://localhost:3000. Other web pages are refused, so that they can't read it from your machine.
+config-appearance = Appearance
+
+## Shared
+
+none = None
+node = Node
+node-id = Node { $id }
+node-count = { $count } node(s)
+remove = Remove
+close = Close
+search = Search…
+go-to-node-hint = Click to go to the node
+
+## Menu hints
+
+menu-export-image-scale = Scale
+menu-export-image-scale-hint = Pixels for each unit of the graph, whatever the zoom
+menu-export-image-statuses = Status colours
+menu-export-image-statuses-hint = Outline nodes by how they fared in the last evaluation
+menu-export-plan-hint = The stages the graph runs in, for other tools to run it in order
+menu-export-trace-hint = Save the last evaluation for about://tracing or Perfetto
+menu-compare-hint = Show how the graph differs from a saved one
+menu-save-checkpoint-hint = Save which tasks of the task DAG have been completed
+menu-resume-checkpoint-hint = Calculate the task DAG, skipping the tasks completed in a checkpoint
+menu-reset-state-hint = Clear the running totals and other state kept by nodes
+menu-wire-values-hint = Label every wire with the value flowing along it
+
+## Graph menu
+
+graph-description = Graph, press Tab to move between nodes
+graph-add-node = Add node
+graph-templates = Templates
+graph-no-matching-nodes = No matching nodes
+graph-more-nodes = { $count } more…
+graph-comment-selected = Comment selected
+graph-group-selected = Group selected ({ $count })
+
+## Node menu
+
+node-menu = Node menu
+node-menu-select = Select
+node-menu-deselect = Deselect
+node-menu-open-group = Open group
+node-menu-rename = Rename…
+node-menu-enable = Enable
+node-menu-disable = Disable
+node-menu-disable-hint = Bypass the node during evaluation, keeping it in the graph
+node-menu-add-breakpoint = Add breakpoint
+node-menu-remove-breakpoint = Remove breakpoint
+node-menu-priority = Priority
+node-menu-priority-hint = Tasks with higher priorities are started first
+node-menu-stage = Stage
+node-menu-save-template = Save as template
+node-menu-save-selected-template = Save selected as template ({ $count })
+node-menu-template-name = Template name
+node-menu-replace-template = Replace
+node-menu-add-template = Save
+node-menu-disconnect = Disconnect
+node-menu-disconnect-selected = Disconnect selected ({ $count })
+node-menu-remove-selected = Remove selected ({ $count })
+
+## Nodes
+
+node-breakpoint = Breakpoint
+node-breakpoint-paused = Breakpoint, paused here
+node-priority = Task priority { $priority }
+node-stage = Stage "{ $stage }"
+node-computing = Being recalculated in the background
+node-input-default = Default, used while nothing is connected
+node-disabled-passing-through = Disabled, passing its input straight through
+node-disabled-without-values = Disabled, leaving its outputs without values
+node-change-nothing = nothing
+node-change-output = Output { $output }: { $before } → { $after }
+node-leave-group = ⬆ Leave group
+node-editing-group = Editing group { $path }
+
+## Debugging
+
+debug = Debug
+debug-hint = Evaluate the graph, pausing before each node with a breakpoint. Breakpoints are set from a node's menu.
+debug-step = Step
+debug-step-hint = Update the next node, then pause again
+debug-continue = Continue
+debug-continue-hint = Run until the next breakpoint
+debug-stop = Stop
+debug-paused = Paused before { $node } { $id }, { $count } updated so far
+
+## Stages
+
+stages = Stages
+stages-hint = Every node of a stage finishes before any node of the next one starts. Tag nodes with a stage from their menu.
+stages-run-earlier = Run earlier
+stages-add = Add stage
+stages-new-name = Stage { $number }
+stages-conflict = ⚠ { $node } in { $stage } can never run, as it needs { $needs } from the later stage { $later }
+
+## Statistics pane
+
+statistics-last-run = Last run: { $produced } of { $sinks } sinks produced, { $updated } nodes updated
+statistics-cache = Cache: { $entries } entries, { $hits } hits, { $misses } misses
+statistics-cache-hint = Pure nodes are not updated again when their parameters and inputs match an earlier update
+statistics-clear-cache = Clear cache
+statistics-calculate-task-dag = Calculate Task Dag
+statistics-priority = Priority
+statistics-estimate = Estimated run time with { $workers } worker(s): { $time }
+statistics-critical-path = Critical path, taking { $time } with unlimited workers:
+statistics-ready = Ready, in the order they would start: { $tasks }
+statistics-task-id = Task ID: { $id }
+statistics-critical = On the critical path
+statistics-runs = Runs: { $count } ({ $elapsed })
+statistics-no-runs = Runs: 0
+statistics-running = Running
+statistics-failed = Failed
+statistics-complete = Complete
+statistics-makes-ready = Makes { $tasks } ready
+statistics-blocked = Blocked
+statistics-waiting-on = Waiting on { $tasks }
+statistics-completed = Completed
+statistics-run-report = Run report
+
+## Timeline pane
+
+timeline-trace-from = Trace from { $name }
+timeline-no-runs = No runs recorded
+
+## Inspector pane
+
+inspector-hint = Click a node's header to inspect it
+inspector-label = Label
+inspector-inputs = Inputs
+inspector-outputs = Outputs
+inspector-role = Role
+inspector-values = Values
+inspector-error = Error
+inspector-executions = Executions
+inspector-last-run = Last run
+inspector-last-duration = Last duration
+
+## Problems pane
+
+problems-none = No problems found
+
+## Differences pane
+
+differences-hint = Compare the graph with a saved one from the File menu
+differences-compared-with = Compared with { $name }
+differences-none = No differences
+differences-added = Added
+differences-changed = Changed
+differences-removed = Removed
+differences-connected = Connected
+differences-disconnected = Disconnected
+differences-wire = Node { $from } output { $output } → node { $to } input { $input }
+
+## History pane
+
+history-snapshot-name = Snapshot name
+history-take-snapshot = Take Snapshot
+history-take-snapshot-hint = Keep a copy of the graph in its file, to go back to later
+history-none = No snapshots taken
+history-restore = Restore
+history-restore-hint = Replace the graph with the snapshot, which can be undone
+history-compare = Compare
+history-compare-hint = Show how the graph has changed since the snapshot
+history-delete = Delete
+
+## Confirmation
+
+confirm-title = Are you sure?
+confirm-can-undo = This can be undone from the Edit menu.
+confirm-cannot-undo = This can't be undone.
+confirm = Confirm
+confirm-cancel = Cancel
+
+## Validation
+
+validation-input-not-connected = Input { $input } ({ $type }) is not connected
+validation-input-may-be-needed = Input { $input } ({ $type }) is not connected, but may be needed
+validation-too-many-wires = Input { $input } has { $count } wires but only accepts one
+validation-wrong-type = Input { $input } expects { $expected } but is wired to { $found } from node { $node }
+validation-missing-output = Input { $input } is wired to an output that no longer exists
+validation-unconnected-node = Is not connected to any other node
+validation-unused-results = Its results are not used by any other node
+validation-cycle = Is part of a cycle between nodes { $nodes }
+validation-nested-groups = Contains groups nested more than { $depth } deep, which can't be evaluated
+
+## Node categories
+
+category-values = Values
+category-math = Math
+category-logic = Logic
+category-conversions = Conversions
+category-lists = Lists
+category-flow = Flow
+category-state = State
+category-graphics = Graphics
+category-files = Files
+category-tasks = Tasks
+category-groups = Groups
+category-plugins = Plugins
+
+## Nodes
+
+node-number = Number
+node-sink = Sink
+node-plot = Plot
+node-table = Table
+node-secret = Secret
+node-argument = Argument
+node-add = Add
+node-expression = Expression
+node-script = Script
+node-if = If
+node-greater-than = Greater Than
+node-less-than = Less Than
+node-equals = Equals
+node-and = And
+node-or = Or
+node-not = Not
+node-to-text = To Text
+node-parse-number = Parse Number
+node-range = Range
+node-list = List
+node-sum = Sum
+node-length = Length
+node-map-add = Map Add
+node-throttle = Throttle
+node-deadband = Deadband
+node-running-sum = Running Sum
+node-moving-average = Moving Average
+node-counter = Counter
+node-delay = Delay
+node-time = Time
+node-make-colour = Make Colour
+node-make-vector = Make Vector
+node-split-vector = Split Vector
+node-read-file = Read File
+node-write-file = Write File
+node-csv-source = CSV Source
+node-http-request = HTTP Request
+node-command = Command
+node-group = Group
+node-group-inputs = Group Inputs
+node-group-outputs = Group Outputs
+node-comment = Comment
@@ -0,0 +1,340 @@
+# French
+
+## Panes
+
+pane-config = Configuration
+pane-nodes = Nœuds
+pane-statistics = Statistiques
+pane-inspector = Inspecteur
+pane-timeline = Chronologie
+pane-sweep = Balayage
+pane-simulation = Simulation
+pane-log = Journal
+pane-problems = Problèmes
+pane-problems-count = Problèmes ({ $count })
+pane-differences = Différences
+pane-history = Historique
+
+## Menus
+
+menu-file = Fichier
+menu-edit = Édition
+menu-view = Affichage
+menu-quit = Quitter
+menu-new-graph = Nouveau graphe
+menu-open-graph = Ouvrir un graphe…
+menu-open-recent = Ouvrir un fichier récent
+menu-save-graph = Enregistrer le graphe…
+menu-export-package = Exporter un paquet…
+menu-export-graph = Exporter le graphe
+menu-export-image = Exporter une image
+menu-export-plan = Exporter le plan
+menu-export-trace = Exporter la trace…
+menu-compare = Comparer avec un fichier…
+menu-import-trace = Importer une trace…
+menu-save-checkpoint = Enregistrer un point de reprise…
+menu-resume-checkpoint = Reprendre depuis un point de reprise…
+menu-eval = Évaluer
+menu-undo = Annuler
+menu-undo-action = Annuler : { $action }
+menu-clear-graph = Vider le graphe
+menu-reset-state = Réinitialiser l'état
+menu-fit-graph = Ajuster le graphe
+menu-zoom-to-selected = Zoomer sur la sélection
+menu-go-to-node = Aller au nœud
+menu-minimap = Mini-carte
+menu-wire-values = Valeurs des liens
+menu-profiler = Profileur
+
+## Config pane
+
+config-language = Langue
+config-profile = Profil
+config-save-profile = Enregistrer le profil
+config-delete-profile = Supprimer le profil
+config-evaluation = Évaluation
+config-on-change = À chaque modification
+config-manual = Manuelle
+config-background = En arrière-plan
+config-background-hint = Recalculer les nœuds modifiés sur un autre fil, pour que les nœuds lents ne bloquent pas l'éditeur
+config-evaluate-now = Évaluer maintenant
+config-reset-state = Réinitialiser l'état
+config-highlight-changes = Mettre en évidence les changements
+config-highlight-changes-hint = Entourer les nœuds mis à jour par la dernière évaluation, en marquant ceux dont les sorties ont changé
+config-workers = Travailleurs
+config-sources = Sources
+config-sources-hint = Déclencher une source pour ne réévaluer que les nœuds en aval
+config-trigger = Déclencher
+config-groups = Groupes
+config-group = Groupe { $id }
+config-secrets = Secrets
+config-no-secrets-file = Aucun fichier de secrets
+config-reload-secrets = Recharger les secrets
+config-plugins = Extensions
+config-plugins-hint = Paquets de nœuds du dossier des extensions, rechargés lorsqu'ils changent
+config-no-plugins-directory = Aucun dossier d'extensions
+config-node-types = { $count } type(s) de nœud
+config-reload-plugins = Recharger les extensions
+config-hooks = Crochets
+config-hooks-hint = Scripts Rhai exécutés avant et après chaque évaluation lorsque le graphe tourne comme moteur
+config-before-run = Avant l'exécution
+config-after-run = Après l'exécution
+config-templates = Modèles
+config-templates-hint = Enregistrés depuis le menu d'un nœud, et ajoutés depuis le menu du graphe ou la palette
+config-no-templates = Aucun modèle
+config-move-up = Monter
+config-move-down = Descendre
+config-files = Fichiers
+config-save-thumbnails = Enregistrer les miniatures
+config-save-thumbnails-hint = Enregistrer une image du graphe à côté de chaque fichier, affichée sous Ouvrir un fichier récent
+config-monitoring = Surveillance
+config-serve-state = Servir l'état des tâches sur le port
+config-serve-state-hint = Diffuser l'état du DAG des tâches vers des tableaux de bord en événements envoyés par le serveur depuis http://127.0.0.1:{ $port }/events, ou l'obtenir une fois depuis /state
+config-allowed-origin = Origine autorisée
+config-allowed-origin-none = aucune
+config-allowed-origin-hint = La page web autorisée à lire l'état des tâches, comme http://localhost:3000. Les autres pages web sont refusées, pour qu'elles ne puissent pas le lire depuis votre machine.
+config-appearance = Apparence
+
+## Shared
+
+none = Aucun
+node = Nœud
+node-id = Nœud { $id }
+node-count = { $count } nœud(s)
+remove = Supprimer
+close = Fermer
+search = Rechercher…
+go-to-node-hint = Cliquer pour aller au nœud
+
+## Menu hints
+
+menu-export-image-scale = Échelle
+menu-export-image-scale-hint = Pixels par unité du graphe, quel que soit le zoom
+menu-export-image-statuses = Couleurs d'état
+menu-export-image-statuses-hint = Entourer les nœuds selon leur résultat lors de la dernière évaluation
+menu-export-plan-hint = Les étapes dans lesquelles le graphe s'exécute, pour que d'autres outils l'exécutent dans l'ordre
+menu-export-trace-hint = Enregistrer la dernière évaluation pour about://tracing ou Perfetto
+menu-compare-hint = Montrer en quoi le graphe diffère d'un graphe enregistré
+menu-save-checkpoint-hint = Enregistrer quelles tâches du DAG des tâches sont terminées
+menu-resume-checkpoint-hint = Calculer le DAG des tâches, en sautant les tâches terminées dans un point de reprise
+menu-reset-state-hint = Effacer les totaux cumulés et les autres états conservés par les nœuds
+menu-wire-values-hint = Étiqueter chaque connexion avec la valeur qui y circule
+
+## Graph menu
+
+graph-description = Graphe, appuyez sur Tab pour passer d'un nœud à l'autre
+graph-add-node = Ajouter un nœud
+graph-templates = Modèles
+graph-no-matching-nodes = Aucun nœud correspondant
+graph-more-nodes = { $count } de plus…
+graph-comment-selected = Commenter la sélection
+graph-group-selected = Grouper la sélection ({ $count })
+
+## Node menu
+
+node-menu = Menu du nœud
+node-menu-select = Sélectionner
+node-menu-deselect = Désélectionner
+node-menu-open-group = Ouvrir le groupe
+node-menu-rename = Renommer…
+node-menu-enable = Activer
+node-menu-disable = Désactiver
+node-menu-disable-hint = Ignorer le nœud pendant l'évaluation, en le gardant dans le graphe
+node-menu-add-breakpoint = Ajouter un point d'arrêt
+node-menu-remove-breakpoint = Supprimer le point d'arrêt
+node-menu-priority = Priorité
+node-menu-priority-hint = Les tâches de priorité plus élevée démarrent en premier
+node-menu-stage = Étape
+node-menu-save-template = Enregistrer comme modèle
+node-menu-save-selected-template = Enregistrer la sélection comme modèle ({ $count })
+node-menu-template-name = Nom du modèle
+node-menu-replace-template = Remplacer
+node-menu-add-template = Enregistrer
+node-menu-disconnect = Déconnecter
+node-menu-disconnect-selected = Déconnecter la sélection ({ $count })
+node-menu-remove-selected = Supprimer la sélection ({ $count })
+
+## Nodes
+
+node-breakpoint = Point d'arrêt
+node-breakpoint-paused = Point d'arrêt, en pause ici
+node-priority = Priorité de la tâche { $priority }
+node-stage = Étape « { $stage } »
+node-computing = En cours de recalcul en arrière-plan
+node-input-default = Valeur par défaut, utilisée tant que rien n'est connecté
+node-disabled-passing-through = Désactivé, transmet son entrée telle quelle
+node-disabled-without-values = Désactivé, ses sorties restent sans valeur
+node-change-nothing = rien
+node-change-output = Sortie { $output } : { $before } → { $after }
+node-leave-group = ⬆ Quitter le groupe
+node-editing-group = Modification du groupe { $path }
+
+## Debugging
+
+debug = Déboguer
+debug-hint = Évaluer le graphe en s'arrêtant avant chaque nœud ayant un point d'arrêt. Les points d'arrêt se placent depuis le menu d'un nœud.
+debug-step = Pas à pas
+debug-step-hint = Mettre à jour le nœud suivant, puis s'arrêter à nouveau
+debug-continue = Continuer
+debug-continue-hint = Exécuter jusqu'au prochain point d'arrêt
+debug-stop = Arrêter
+debug-paused = En pause avant { $node } { $id }, { $count } mis à jour jusqu'ici
+
+## Stages
+
+stages = Étapes
+stages-hint = Chaque nœud d'une étape se termine avant que ceux de l'étape suivante ne démarrent. Les nœuds reçoivent une étape depuis leur menu.
+stages-run-earlier = Exécuter plus tôt
+stages-add = Ajouter une étape
+stages-new-name = Étape { $number }
+stages-conflict = ⚠ { $node } dans { $stage } ne peut jamais s'exécuter, car il a besoin de { $needs } de l'étape ultérieure { $later }
+
+## Statistics pane
+
+statistics-last-run = Dernière exécution : { $produced } puits sur { $sinks } produits, { $updated } nœuds mis à jour
+statistics-cache = Cache : { $entries } entrées, { $hits } succès, { $misses } échecs
+statistics-cache-hint = Les nœuds purs ne sont pas mis à jour de nouveau lorsque leurs paramètres et entrées correspondent à une mise à jour précédente
+statistics-clear-cache = Vider le cache
+statistics-calculate-task-dag = Calculer le DAG des tâches
+statistics-priority = Priorité
+statistics-estimate = Durée estimée avec { $workers } travailleur(s) : { $time }
+statistics-critical-path = Chemin critique, durant { $time } avec des travailleurs illimités :
+statistics-ready = Prêtes, dans l'ordre où elles démarreraient : { $tasks }
+statistics-task-id = ID de tâche : { $id }
+statistics-critical = Sur le chemin critique
+statistics-runs = Exécutions : { $count } ({ $elapsed })
+statistics-no-runs = Exécutions : 0
+statistics-running = En cours
+statistics-failed = Échouée
+statistics-complete = Terminer
+statistics-makes-ready = Rend { $tasks } prêtes
+statistics-blocked = Bloquée
+statistics-waiting-on = En attente de { $tasks }
+statistics-completed = Terminée
+statistics-run-report = Rapport d'exécution
+
+## Timeline pane
+
+timeline-trace-from = Trace de { $name }
+timeline-no-runs = Aucune exécution enregistrée
+
+## Inspector pane
+
+inspector-hint = Cliquer sur l'en-tête d'un nœud pour l'inspecter
+inspector-label = Libellé
+inspector-inputs = Entrées
+inspector-outputs = Sorties
+inspector-role = Rôle
+inspector-values = Valeurs
+inspector-error = Erreur
+inspector-executions = Exécutions
+inspector-last-run = Dernière exécution
+inspector-last-duration = Dernière durée
+
+## Problems pane
+
+problems-none = Aucun problème trouvé
+
+## Differences pane
+
+differences-hint = Comparer le graphe à un graphe enregistré depuis le menu Fichier
+differences-compared-with = Comparé à { $name }
+differences-none = Aucune différence
+differences-added = Ajoutés
+differences-changed = Modifiés
+differences-removed = Supprimés
+differences-connected = Connectés
+differences-disconnected = Déconnectés
+differences-wire = Nœud { $from } sortie { $output } → nœud { $to } entrée { $input }
+
+## History pane
+
+history-snapshot-name = Nom de l'instantané
+history-take-snapshot = Prendre un instantané
+history-take-snapshot-hint = Garder une copie du graphe dans son fichier, pour y revenir plus tard
+history-none = Aucun instantané pris
+history-restore = Restaurer
+history-restore-hint = Remplacer le graphe par l'instantané, ce qui peut être annulé
+history-compare = Comparer
+history-compare-hint = Montrer comment le graphe a changé depuis l'instantané
+history-delete = Supprimer
+
+## Confirmation
+
+confirm-title = Êtes-vous sûr ?
+confirm-can-undo = Cela peut être annulé depuis le menu Édition.
+confirm-cannot-undo = Cela ne peut pas être annulé.
+confirm = Confirmer
+confirm-cancel = Annuler
+
+## Validation
+
+validation-input-not-connected = L'entrée { $input } ({ $type }) n'est pas connectée
+validation-input-may-be-needed = L'entrée { $input } ({ $type }) n'est pas connectée, mais pourrait être nécessaire
+validation-too-many-wires = L'entrée { $input } a { $count } liens mais n'en accepte qu'un
+validation-wrong-type = L'entrée { $input } attend { $expected } mais est reliée à { $found } du nœud { $node }
+validation-missing-output = L'entrée { $input } est reliée à une sortie qui n'existe plus
+validation-unconnected-node = N'est connecté à aucun autre nœud
+validation-unused-results = Ses résultats ne sont utilisés par aucun autre nœud
+validation-cycle = Fait partie d'un cycle entre les nœuds { $nodes }
+validation-nested-groups = Contient des groupes imbriqués sur plus de { $depth } niveaux, qui ne peuvent pas être évalués
+
+## Node categories
+
+category-values = Valeurs
+category-math = Mathématiques
+category-logic = Logique
+category-conversions = Conversions
+category-lists = Listes
+category-flow = Flux
+category-state = État
+category-graphics = Graphismes
+category-files = Fichiers
+category-tasks = Tâches
+category-groups = Groupes
+category-plugins = Extensions
+
+## Nodes
+
+node-number = Nombre
+node-sink = Puits
+node-plot = Courbe
+node-table = Tableau
+node-secret = Secret
+node-argument = Argument
+node-add = Addition
+node-expression = Expression
+node-script = Script
+node-if = Si
+node-greater-than = Supérieur à
+node-less-than = Inférieur à
+node-equals = Égal à
+node-and = Et
+node-or = Ou
+node-not = Non
+node-to-text = Vers texte
+node-parse-number = Lire un nombre
+node-range = Intervalle
+node-list = Liste
+node-sum = Somme
+node-length = Longueur
+node-map-add = Ajouter à chaque élément
+node-throttle = Limiteur
+node-deadband = Zone morte
+node-running-sum = Somme cumulée
+node-moving-average = Moyenne mobile
+node-counter = Compteur
+node-delay = Retard
+node-time = Temps
+node-make-colour = Créer une couleur
+node-make-vector = Créer un vecteur
+node-split-vector = Séparer un vecteur
+node-read-file = Lire un fichier
+node-write-file = Écrire un fichier
+node-csv-source = Source CSV
+node-http-request = Requête HTTP
+node-command = Commande
+node-group = Groupe
+node-group-inputs = Entrées du groupe
+node-group-outputs = Sorties du groupe
+node-comment = Commentaire
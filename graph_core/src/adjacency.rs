@@ -0,0 +1,217 @@
+//! Dependencies between the nodes of a graph, kept up to date edit by edit
+//! rather than rebuilt with [`as_petgraph`] each time they are needed
+//!
+//! After changing the wires of a node, or inserting or removing it,
+//! [`refresh`](Adjacency::refresh) the nodes involved:
+//!
+//! ```ignore
+//! snarl.connect(from, to);
+//! adjacency.refresh(&snarl, [from.node, to.node]);
+//! ```
+
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+use petgraph::Graph;
+
+use crate::node_graph::{as_petgraph, Node};
+
+/// Number of wires between pairs of nodes, by the node at either end
+type Wires = BTreeMap<NodeId, BTreeMap<NodeId, usize>>;
+
+/// The wires between nodes, matching the edges of [`as_petgraph`]. Every
+/// node has an entry in both directions, even without any wires.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Adjacency {
+    downstream: Wires,
+    upstream: Wires,
+}
+
+impl Adjacency {
+    pub fn new(snarl: &Snarl<Box<dyn Node>>) -> Self {
+        let mut adjacency = Self::default();
+        for (node, _) in snarl.node_ids() {
+            adjacency.downstream.entry(node).or_default();
+            adjacency.upstream.entry(node).or_default();
+        }
+        for (node, _) in snarl.node_ids() {
+            for to in outgoing(snarl, node) {
+                adjacency.add_wire(node, to);
+            }
+        }
+        adjacency
+    }
+
+    /// Brings the wires of `nodes` up to date with `snarl`, after they have
+    /// been inserted, removed, connected or disconnected. Only the wires of
+    /// those nodes are looked at, so both ends of a changed wire need
+    /// refreshing.
+    pub fn refresh(
+        &mut self,
+        snarl: &Snarl<Box<dyn Node>>,
+        nodes: impl IntoIterator<Item = NodeId>,
+    ) {
+        for node in nodes {
+            self.remove_node(node);
+            if snarl.get_node(node).is_none() {
+                continue;
+            }
+            self.downstream.entry(node).or_default();
+            self.upstream.entry(node).or_default();
+            for to in outgoing(snarl, node) {
+                self.add_wire(node, to);
+            }
+            // Wires from the node to itself were added above
+            for from in incoming(snarl, node).filter(|from| *from != node) {
+                self.add_wire(from, node);
+            }
+        }
+    }
+
+    fn add_wire(&mut self, from: NodeId, to: NodeId) {
+        *self
+            .downstream
+            .entry(from)
+            .or_default()
+            .entry(to)
+            .or_default() += 1;
+        *self
+            .upstream
+            .entry(to)
+            .or_default()
+            .entry(from)
+            .or_default() += 1;
+    }
+
+    /// Forgets the node and all of its wires
+    fn remove_node(&mut self, node: NodeId) {
+        for to in self
+            .downstream
+            .remove(&node)
+            .unwrap_or_default()
+            .into_keys()
+        {
+            if let Entry::Occupied(mut wires) = self.upstream.entry(to) {
+                wires.get_mut().remove(&node);
+            }
+        }
+        for from in self.upstream.remove(&node).unwrap_or_default().into_keys() {
+            if let Entry::Occupied(mut wires) = self.downstream.entry(from) {
+                wires.get_mut().remove(&node);
+            }
+        }
+    }
+
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.downstream.contains_key(&node)
+    }
+
+    /// Every node, in order of id
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.downstream.keys().copied()
+    }
+
+    /// Nodes wired to the outputs of `node`
+    pub fn downstream(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.downstream
+            .get(&node)
+            .into_iter()
+            .flat_map(|to| to.keys().copied())
+    }
+
+    /// Nodes wired to the inputs of `node`
+    pub fn upstream(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.upstream
+            .get(&node)
+            .into_iter()
+            .flat_map(|from| from.keys().copied())
+    }
+
+    /// `node` and every node downstream of it
+    pub fn reachable_from(&self, node: NodeId) -> BTreeSet<NodeId> {
+        let mut reached = BTreeSet::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if self.contains(node) && reached.insert(node) {
+                stack.extend(self.downstream(node));
+            }
+        }
+        reached
+    }
+
+    /// Whether `to` is `from` or downstream of it
+    pub fn reaches(&self, from: NodeId, to: NodeId) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![from];
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            if visited.insert(node) {
+                stack.extend(self.downstream(node));
+            }
+        }
+        false
+    }
+
+    /// Whether wiring an output of `from` to an input of `to` would make the
+    /// graph cyclic, only visiting the nodes downstream of `to`
+    pub fn would_create_cycle(&self, from: NodeId, to: NodeId) -> bool {
+        self.reaches(to, from)
+    }
+
+    /// The same graph as [`as_petgraph`], built without looking at the
+    /// snarl
+    pub fn to_petgraph(&self) -> Graph<NodeId, ()> {
+        let mut graph = Graph::new();
+        let indices = self
+            .nodes()
+            .map(|node| (node, graph.add_node(node)))
+            .collect::<BTreeMap<_, _>>();
+        for (from, wires) in &self.downstream {
+            for (to, count) in wires {
+                for _ in 0..*count {
+                    graph.add_edge(indices[from], indices[to], ());
+                }
+            }
+        }
+        graph
+    }
+
+    /// Checks, in debug builds, that the adjacency still matches `snarl` by
+    /// rebuilding it from scratch
+    pub fn debug_check(&self, snarl: &Snarl<Box<dyn Node>>) {
+        if cfg!(debug_assertions) {
+            let graph = as_petgraph(snarl);
+            let mut rebuilt = Self::default();
+            for node in graph.node_weights() {
+                rebuilt.downstream.entry(*node).or_default();
+                rebuilt.upstream.entry(*node).or_default();
+            }
+            for edge in graph.raw_edges() {
+                rebuilt.add_wire(graph[edge.source()], graph[edge.target()]);
+            }
+            assert_eq!(self, &rebuilt, "adjacency has drifted from the graph");
+        }
+    }
+}
+
+/// Nodes wired to each output of `node`, once per wire
+fn outgoing(snarl: &Snarl<Box<dyn Node>>, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    (0..snarl[node].outputs().len())
+        .flat_map(move |output| snarl.out_pin(OutPinId { node, output }).remotes)
+        .map(|to| to.node)
+}
+
+/// Nodes wired to each input of `node`, once per wire, leaving out wires
+/// from outputs their node no longer has as [`as_petgraph`] does
+fn incoming(snarl: &Snarl<Box<dyn Node>>, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    (0..snarl[node].inputs().len())
+        .flat_map(move |input| snarl.in_pin(InPinId { node, input }).remotes)
+        .filter(|from| {
+            snarl
+                .get_node(from.node)
+                .is_some_and(|node| from.output < node.outputs().len())
+        })
+        .map(|from| from.node)
+}
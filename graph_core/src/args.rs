@@ -0,0 +1,260 @@
+//! Values given to a graph when it is run, such as from the command line of
+//! the headless runner, so that one saved graph can be run with different
+//! parameters. Each value is a variable of the evaluation, see
+//! [`EvalContext::variables`](crate::context::EvalContext::variables), read
+//! by the [`ArgNode`] of the same name.
+
+use std::{collections::BTreeMap, fmt};
+
+use egui::{self, Ui};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_value, DataType, Node, TypedData},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgParseError {
+    /// An assignment without an `=`
+    MissingValue(String),
+    Json(String),
+    /// Piped JSON that isn't an object of names to values
+    NotAnObject,
+    /// A JSON value with no matching type, such as an object or null
+    Unsupported(String),
+}
+
+impl fmt::Display for ArgParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgParseError::MissingValue(source) => {
+                write!(f, "invalid argument {:?}, expected <name>=<value>", source)
+            }
+            ArgParseError::Json(err) => write!(f, "invalid JSON arguments: {}", err),
+            ArgParseError::NotAnObject => {
+                write!(f, "JSON arguments must be an object of names to values")
+            }
+            ArgParseError::Unsupported(name) => {
+                write!(f, "argument {:?} has a value of an unsupported type", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgParseError {}
+
+/// The value of a JSON number, string, boolean or array of them
+pub fn from_json(value: &serde_json::Value) -> Option<TypedData> {
+    match value {
+        serde_json::Value::Number(number) => number.as_f64().map(TypedData::Number),
+        serde_json::Value::String(text) => Some(TypedData::String(text.clone())),
+        serde_json::Value::Bool(value) => Some(TypedData::Bool(*value)),
+        serde_json::Value::Array(values) => values
+            .iter()
+            .map(from_json)
+            .collect::<Option<_>>()
+            .map(TypedData::List),
+        serde_json::Value::Null | serde_json::Value::Object(_) => None,
+    }
+}
+
+/// Parses `<name>=<value>`, such as `number1=4.2`. Values are read as JSON
+/// where they can be, so `4.2` is a number and `[1, 2]` a list, and are
+/// otherwise taken as text.
+pub fn parse_assignment(source: &str) -> Result<(String, TypedData), ArgParseError> {
+    let (name, value) = source
+        .split_once('=')
+        .ok_or_else(|| ArgParseError::MissingValue(source.to_owned()))?;
+    let value = serde_json::from_str(value)
+        .ok()
+        .as_ref()
+        .and_then(from_json)
+        .unwrap_or_else(|| TypedData::String(value.to_owned()));
+    Ok((name.trim().to_owned(), value))
+}
+
+/// Parses a JSON object of names to values, such as one piped to the
+/// headless runner
+pub fn parse_json(text: &str) -> Result<BTreeMap<String, TypedData>, ArgParseError> {
+    let value = serde_json::from_str::<serde_json::Value>(text)
+        .map_err(|err| ArgParseError::Json(err.to_string()))?;
+    let serde_json::Value::Object(object) = value else {
+        return Err(ArgParseError::NotAnObject);
+    };
+    object
+        .iter()
+        .map(|(name, value)| {
+            let value = from_json(value).ok_or_else(|| ArgParseError::Unsupported(name.clone()))?;
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+/// Types an [`ArgNode`] can give
+const KINDS: [DataType; 3] = [DataType::Number, DataType::String, DataType::Bool];
+
+/// Gives the value of the argument of the same name given when the graph
+/// was run, or a default when it wasn't given one
+#[derive(Debug, Clone)]
+pub struct ArgNode {
+    name: String,
+    /// Given when the graph is run without this argument
+    default: TypedData,
+    value: Option<TypedData>,
+    failure: Option<String>,
+    label: Option<String>,
+}
+
+impl Default for ArgNode {
+    fn default() -> Self {
+        Self {
+            name: "arg".to_owned(),
+            default: TypedData::Number(0.),
+            value: None,
+            failure: None,
+            label: None,
+        }
+    }
+}
+
+impl ArgNode {
+    fn kind(&self) -> DataType {
+        match self.default {
+            TypedData::String(_) => DataType::String,
+            TypedData::Bool(_) => DataType::Bool,
+            _ => DataType::Number,
+        }
+    }
+
+    /// Converts a given argument to the type of the default, reading text
+    /// as a number or boolean where it needs to be
+    fn convert(&self, value: &TypedData) -> Option<TypedData> {
+        match (&self.default, value) {
+            (TypedData::Number(_), TypedData::Number(_))
+            | (TypedData::Bool(_), TypedData::Bool(_))
+            | (TypedData::String(_), TypedData::String(_)) => Some(value.clone()),
+            (TypedData::Number(_), TypedData::String(text)) => {
+                text.trim().parse().ok().map(TypedData::Number)
+            }
+            (TypedData::Bool(_), TypedData::String(text)) => {
+                text.trim().parse().ok().map(TypedData::Bool)
+            }
+            (TypedData::String(_), value) => Some(TypedData::String(format_value(value))),
+            _ => None,
+        }
+    }
+}
+
+impl Node for ArgNode {
+    fn name(&self) -> String {
+        "Argument".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![self.kind()]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.value.clone()
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.add(egui::TextEdit::singleline(&mut self.name).desired_width(80.))
+            .on_hover_text("Name given to the argument when running the graph")
+            .changed()
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.vertical(|ui| {
+            let mut kind = self.kind();
+            egui::ComboBox::from_id_source(ui.id().with("kind"))
+                .selected_text(format!("{:?}", kind))
+                .show_ui(ui, |ui| {
+                    for option in KINDS {
+                        let text = format!("{:?}", option);
+                        ui.selectable_value(&mut kind, option, text);
+                    }
+                });
+            if kind != self.kind() {
+                self.default = match kind {
+                    DataType::String => TypedData::String(String::new()),
+                    DataType::Bool => TypedData::Bool(false),
+                    _ => TypedData::Number(0.),
+                };
+                changed = true;
+            }
+            ui.horizontal(|ui| {
+                ui.label("Default");
+                changed |= match &mut self.default {
+                    TypedData::Number(value) => ui.add(egui::DragValue::new(value)).changed(),
+                    TypedData::String(text) => ui
+                        .add(egui::TextEdit::singleline(text).desired_width(80.))
+                        .changed(),
+                    TypedData::Bool(value) => ui.checkbox(value, "").changed(),
+                    _ => false,
+                };
+            });
+        });
+        changed
+    }
+
+    fn update(&mut self, _inputs: &[TypedData], ctx: &mut UpdateContext) {
+        self.failure = None;
+        self.value = match ctx.variable(&self.name) {
+            Some(given) => {
+                let value = self.convert(given);
+                if value.is_none() {
+                    self.failure = Some(format!(
+                        "Argument {:?} is {}, not a {:?}",
+                        self.name,
+                        format_value(given),
+                        self.kind()
+                    ));
+                }
+                value
+            }
+            None => Some(self.default.clone()),
+        };
+    }
+
+    fn error(&self) -> Option<String> {
+        self.failure.clone()
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({ "name": self.name, "default": self.default })
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(name) = params["name"].as_str() {
+            self.name = name.to_owned();
+        }
+        if let Ok(default) = serde_json::from_value::<TypedData>(params["default"].clone()) {
+            if matches!(
+                default,
+                TypedData::Number(_) | TypedData::String(_) | TypedData::Bool(_)
+            ) {
+                self.default = default;
+            }
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! Evaluating a graph on a worker thread, so that slow nodes don't hold up
+//! the editor. The worker updates copies of the graph's pure nodes, see
+//! [`Node::is_pure`], and their outputs are applied to the graph once it
+//! has finished. Every other node may keep state or have effects outside the
+//! graph, so is updated on the calling thread once the copies' outputs are
+//! in place.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::mpsc,
+};
+
+use egui::Pos2;
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+use petgraph::visit::Walker;
+
+use crate::{
+    cache::NodeCache,
+    context::EvalContext,
+    node_graph::{self, DataType, Evaluation, Node, TypedData},
+    registry,
+    run_report::RunReport,
+};
+
+/// Worker number of the nodes updated on the worker thread, see
+/// [`NodeRun::worker`](crate::run_report::NodeRun::worker)
+const WORKER: usize = 1;
+
+/// Evaluations of a graph run one at a time on a worker thread. Changes
+/// made while the worker is busy are evaluated once it has finished.
+#[derive(Default)]
+pub struct BackgroundEvaluation {
+    running: Option<Run>,
+    /// Nodes changed since the running evaluation started
+    queued: BTreeSet<NodeId>,
+}
+
+impl BackgroundEvaluation {
+    /// Asks for a node that was changed and everything downstream of it to
+    /// be recalculated
+    pub fn request(&mut self, node: NodeId) {
+        self.queued.insert(node);
+    }
+
+    /// Whether an evaluation is running or waiting to run
+    pub fn is_busy(&self) -> bool {
+        self.running.is_some() || !self.queued.is_empty()
+    }
+
+    /// Nodes the worker is updating
+    pub fn computing(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.running
+            .iter()
+            .flat_map(|run| run.copied.keys().copied())
+    }
+
+    /// Applies the results of an evaluation the worker has finished, and
+    /// starts the next one, returning the nodes updated. The worker only
+    /// shares `ctx`'s cache, and `wake` is called from the worker thread
+    /// once it has finished.
+    pub fn poll(
+        &mut self,
+        snarl: &mut Snarl<Box<dyn Node>>,
+        ctx: &EvalContext,
+        wake: impl Fn() + Send + 'static,
+    ) -> Vec<RunReport> {
+        let mut reports = Vec::new();
+        if let Some(run) = &self.running {
+            let results = match run.results.try_recv() {
+                Ok(results) => Some(results),
+                Err(mpsc::TryRecvError::Empty) => return reports,
+                // The worker stopped without finishing, so its nodes are
+                // updated here instead
+                Err(mpsc::TryRecvError::Disconnected) => None,
+            };
+            let run = self.running.take().unwrap();
+            reports.push(run.apply(snarl, results, ctx));
+        }
+        if !self.queued.is_empty() {
+            let starts = std::mem::take(&mut self.queued);
+            match Run::start(snarl, &starts, ctx, wake) {
+                Ok(run) => self.running = Some(run),
+                Err(report) => reports.push(report),
+            }
+        }
+        reports
+    }
+}
+
+/// An evaluation the worker is running
+struct Run {
+    /// Nodes whose values may have changed
+    dirty: BTreeSet<NodeId>,
+    /// Nodes copied to the worker, with the name and parameters they were
+    /// copied with
+    copied: BTreeMap<NodeId, (String, serde_json::Value)>,
+    results: mpsc::Receiver<Results>,
+}
+
+/// What the worker sends back once it has finished
+struct Results {
+    outputs: BTreeMap<NodeId, Outputs>,
+    report: RunReport,
+}
+
+/// Outputs of a copied node after the worker updated it
+struct Outputs {
+    cached: Option<Vec<TypedData>>,
+    values: Vec<Option<TypedData>>,
+}
+
+/// A node of the graph, as sent to the worker
+enum Part {
+    /// A pure node to be updated on the worker, recreated from its
+    /// parameters
+    Copied {
+        kind: String,
+        params: serde_json::Value,
+        outputs: Option<Vec<TypedData>>,
+    },
+    /// A node upstream of the copies, which only needs to give its outputs
+    Frozen(Frozen),
+}
+
+impl Run {
+    /// Starts evaluating the nodes in `starts` and everything downstream of
+    /// them. If none of them can be updated on the worker, the graph is
+    /// evaluated on this thread instead and its report returned.
+    fn start(
+        snarl: &mut Snarl<Box<dyn Node>>,
+        starts: &BTreeSet<NodeId>,
+        ctx: &EvalContext,
+        wake: impl Fn() + Send + 'static,
+    ) -> Result<Self, RunReport> {
+        let graph = node_graph::as_petgraph(snarl);
+        let mut dirty = BTreeSet::new();
+        for idx in graph.node_indices() {
+            if starts.contains(&graph[idx]) {
+                let bfs = petgraph::visit::Bfs::new(&graph, idx);
+                dirty.extend(bfs.iter(&graph).map(|idx| graph[idx]));
+            }
+        }
+        let Ok(order) = petgraph::algo::toposort(&graph, None) else {
+            return Err(evaluate_here(snarl, &dirty, ctx));
+        };
+
+        // Pure nodes can be copied as long as nothing upstream of them still
+        // has to be updated here
+        let kinds = registry::node_kinds()
+            .into_iter()
+            .map(|kind| kind.name)
+            .collect::<BTreeSet<_>>();
+        let mut copied = BTreeMap::new();
+        for idx in order {
+            let id = graph[idx];
+            let node = snarl[id].as_ref();
+            let copyable = dirty.contains(&id)
+                && node.is_pure()
+                && kinds.contains(node.name().as_str())
+                && graph
+                    .neighbors_directed(idx, petgraph::Direction::Incoming)
+                    .all(|upstream| {
+                        !dirty.contains(&graph[upstream]) || copied.contains_key(&graph[upstream])
+                    });
+            if copyable {
+                copied.insert(id, (node.name(), node.save()));
+            }
+        }
+        if copied.is_empty() {
+            return Err(evaluate_here(snarl, &dirty, ctx));
+        }
+
+        let mut parts = BTreeMap::new();
+        let mut wires = Vec::new();
+        for &id in copied.keys() {
+            let node = snarl[id].as_ref();
+            parts.insert(
+                id,
+                Part::Copied {
+                    kind: node.name(),
+                    params: node.save(),
+                    outputs: node.cached_outputs(),
+                },
+            );
+            for input in 0..node.inputs().len() {
+                let to = InPinId { node: id, input };
+                for from in snarl.in_pin(to).remotes {
+                    if !copied.contains_key(&from.node) {
+                        parts.insert(
+                            from.node,
+                            Part::Frozen(Frozen::new(snarl[from.node].as_ref())),
+                        );
+                    }
+                    wires.push((from, to));
+                }
+            }
+        }
+
+        let cache = ctx.cache.clone();
+        let (sender, results) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("evaluation".to_owned())
+            .spawn(move || {
+                if sender.send(evaluate_copy(parts, wires, cache)).is_ok() {
+                    wake();
+                }
+            });
+        if spawned.is_err() {
+            return Err(evaluate_here(snarl, &dirty, ctx));
+        }
+        Ok(Self {
+            dirty,
+            copied,
+            results,
+        })
+    }
+
+    /// Gives the copied nodes their outputs from the worker, then updates
+    /// the rest of the dirty nodes
+    fn apply(
+        self,
+        snarl: &mut Snarl<Box<dyn Node>>,
+        results: Option<Results>,
+        ctx: &EvalContext,
+    ) -> RunReport {
+        let mut applied = BTreeSet::new();
+        let mut report = RunReport::new();
+        if let Some(results) = results {
+            for (id, outputs) in results.outputs {
+                let Some(node) = snarl.get_node_mut(id) else {
+                    continue;
+                };
+                // Nodes that failed are updated again here to find out why,
+                // and nodes edited while the worker was busy keep their
+                // outputs until they are evaluated again
+                let failed = results
+                    .report
+                    .failures
+                    .iter()
+                    .any(|failure| failure.node == id);
+                if failed || self.copied.get(&id) != Some(&(node.name(), node.save())) {
+                    continue;
+                }
+                if let Some(cached) = &outputs.cached {
+                    node.restore_outputs(cached);
+                }
+                if node_graph::output_values(node.as_ref()) == outputs.values {
+                    applied.insert(id);
+                }
+            }
+            report = results.report;
+            report.runs.retain(|run| applied.contains(&run.node));
+            report.failures.clear();
+            report
+                .messages
+                .retain(|message| applied.contains(&message.node));
+            report.changes.retain(|node, _| applied.contains(node));
+        }
+
+        let rest = self
+            .dirty
+            .difference(&applied)
+            .copied()
+            .filter(|id| snarl.get_node(*id).is_some())
+            .collect::<BTreeSet<_>>();
+        if !rest.is_empty() {
+            report.merge(evaluate_here(snarl, &rest, ctx));
+        }
+        report
+    }
+}
+
+/// Evaluates `nodes` and everything downstream of them on this thread
+fn evaluate_here(
+    snarl: &mut Snarl<Box<dyn Node>>,
+    nodes: &BTreeSet<NodeId>,
+    ctx: &EvalContext,
+) -> RunReport {
+    let mut evaluation = Evaluation::new(snarl, ctx.clone(), |id, _| nodes.contains(&id));
+    while evaluation.step(snarl).is_some() {}
+    evaluation.finish()
+}
+
+/// Builds the worker's graph and updates its copied nodes, reporting them
+/// under their ids in the original graph
+fn evaluate_copy(
+    parts: BTreeMap<NodeId, Part>,
+    wires: Vec<(OutPinId, InPinId)>,
+    cache: NodeCache,
+) -> Results {
+    let mut snarl = Snarl::<Box<dyn Node>>::new();
+    let mut ids = BTreeMap::new();
+    let mut originals = BTreeMap::new();
+    for (id, part) in parts {
+        let node: Box<dyn Node> = match part {
+            Part::Copied {
+                kind,
+                params,
+                outputs,
+            } => {
+                let Some(mut node) = registry::create_node(&kind) else {
+                    continue;
+                };
+                node.restore(&params);
+                if let Some(outputs) = outputs {
+                    node.restore_outputs(&outputs);
+                }
+                let copy = snarl.insert_node(Pos2::ZERO, node);
+                ids.insert(id, copy);
+                originals.insert(copy, id);
+                continue;
+            }
+            Part::Frozen(frozen) => Box::new(frozen),
+        };
+        ids.insert(id, snarl.insert_node(Pos2::ZERO, node));
+    }
+    for (from, to) in wires {
+        if let (Some(&from_node), Some(&to_node)) = (ids.get(&from.node), ids.get(&to.node)) {
+            snarl.connect(
+                OutPinId {
+                    node: from_node,
+                    output: from.output,
+                },
+                InPinId {
+                    node: to_node,
+                    input: to.input,
+                },
+            );
+        }
+    }
+
+    let ctx = EvalContext {
+        cache,
+        ..Default::default()
+    };
+    let mut evaluation = Evaluation::new(&snarl, ctx, |id, _| originals.contains_key(&id));
+    while evaluation.step(&mut snarl).is_some() {}
+    let mut report = evaluation.finish();
+
+    for run in &mut report.runs {
+        run.node = originals[&run.node];
+        run.worker = WORKER;
+    }
+    for failure in &mut report.failures {
+        failure.node = originals[&failure.node];
+    }
+    for message in &mut report.messages {
+        message.node = originals[&message.node];
+    }
+    report.changes = std::mem::take(&mut report.changes)
+        .into_iter()
+        .map(|(node, change)| (originals[&node], change))
+        .collect();
+    let outputs = originals
+        .iter()
+        .map(|(copy, id)| {
+            let node = snarl[*copy].as_ref();
+            let outputs = Outputs {
+                cached: node.cached_outputs(),
+                values: node_graph::output_values(node),
+            };
+            (*id, outputs)
+        })
+        .collect();
+    Results { outputs, report }
+}
+
+/// Stands in for a node on the worker, giving the outputs it had when the
+/// evaluation started
+struct Frozen {
+    name: String,
+    outputs: Vec<DataType>,
+    values: Vec<Option<TypedData>>,
+}
+
+impl Frozen {
+    fn new(node: &dyn Node) -> Self {
+        Self {
+            name: node.name(),
+            outputs: node.outputs(),
+            values: node_graph::output_values(node),
+        }
+    }
+}
+
+impl Node for Frozen {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.outputs.clone()
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        self.values.get(idx).cloned().flatten()
+    }
+}
@@ -0,0 +1,189 @@
+//! Building graphs in code rather than in the editor
+//!
+//! ```ignore
+//! let mut builder = GraphBuilder::new();
+//! let a = builder.add_node(NumberNode::new(3.0));
+//! let b = builder.add_node(NumberNode::new(4.0));
+//! let sum = builder.add_node(AddNode::default());
+//! builder.connect(a.out(0), sum.input(0))?;
+//! builder.connect(b.out(0), sum.input(1))?;
+//! let mut snarl = builder.build();
+//! node_graph::evaluate(&mut snarl, None);
+//! ```
+
+use std::fmt;
+
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+use petgraph::Graph;
+
+use crate::{
+    adjacency::Adjacency,
+    execution_engine::TaskDag,
+    node_graph::{DataType, Node},
+};
+
+/// Horizontal gap between nodes added by a [`GraphBuilder`], so that the
+/// graph is readable when opened in the editor
+const NODE_SPACING: f32 = 200.;
+
+/// A node added to a [`GraphBuilder`], used to name its pins when wiring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeHandle(pub NodeId);
+
+impl NodeHandle {
+    pub fn id(self) -> NodeId {
+        self.0
+    }
+
+    pub fn out(self, output: usize) -> OutPinId {
+        OutPinId {
+            node: self.0,
+            output,
+        }
+    }
+
+    pub fn input(self, input: usize) -> InPinId {
+        InPinId {
+            node: self.0,
+            input,
+        }
+    }
+}
+
+/// Why a [`GraphBuilder`] refused to add a wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectError {
+    NoSuchOutput(OutPinId),
+    NoSuchInput(InPinId),
+    TypeMismatch {
+        output: DataType,
+        input: DataType,
+    },
+    /// The input already has a wire and has no reducer to combine several
+    AlreadyConnected(InPinId),
+    /// The wire would make the graph cyclic
+    Cycle,
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::NoSuchOutput(pin) => {
+                write!(f, "node {} has no output {}", pin.node.0, pin.output)
+            }
+            ConnectError::NoSuchInput(pin) => {
+                write!(f, "node {} has no input {}", pin.node.0, pin.input)
+            }
+            ConnectError::TypeMismatch { output, input } => {
+                write!(f, "cannot wire {:?} output to {:?} input", output, input)
+            }
+            ConnectError::AlreadyConnected(pin) => write!(
+                f,
+                "input {} of node {} only accepts one wire",
+                pin.input, pin.node.0
+            ),
+            ConnectError::Cycle => write!(f, "wire would create a cycle"),
+        }
+    }
+}
+
+impl std::error::Error for ConnectError {}
+
+/// Builds a graph in code, checking each wire as it is added in the same
+/// way as the editor does
+pub struct GraphBuilder {
+    snarl: Snarl<Box<dyn Node>>,
+    /// Dependencies between the nodes, kept up to date with each wire so
+    /// that cycles are found without rebuilding the graph
+    adjacency: Adjacency,
+    nodes: usize,
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphBuilder {
+    pub fn new() -> Self {
+        Self {
+            snarl: Snarl::new(),
+            adjacency: Adjacency::default(),
+            nodes: 0,
+        }
+    }
+
+    pub fn add_node(&mut self, node: impl Node) -> NodeHandle {
+        self.add_boxed(Box::new(node))
+    }
+
+    /// Adds a node that has already been boxed, such as one from
+    /// [`registry::create_node`](crate::registry::create_node)
+    pub fn add_boxed(&mut self, node: Box<dyn Node>) -> NodeHandle {
+        let pos = egui::pos2(self.nodes as f32 * NODE_SPACING, 0.);
+        self.nodes += 1;
+        let id = self.snarl.insert_node(pos, node);
+        self.adjacency.refresh(&self.snarl, [id]);
+        NodeHandle(id)
+    }
+
+    /// Wires `from` to `to`, failing if either pin doesn't exist, their types
+    /// don't match, the input is already taken, or the graph would become
+    /// cyclic. Pins of different types are not joined through conversion
+    /// nodes, add those explicitly.
+    pub fn connect(&mut self, from: OutPinId, to: InPinId) -> Result<(), ConnectError> {
+        let output = self
+            .snarl
+            .get_node(from.node)
+            .and_then(|node| node.outputs().get(from.output).cloned())
+            .ok_or(ConnectError::NoSuchOutput(from))?;
+        let to_node = self
+            .snarl
+            .get_node(to.node)
+            .ok_or(ConnectError::NoSuchInput(to))?;
+        let input = to_node
+            .inputs()
+            .get(to.input)
+            .cloned()
+            .ok_or(ConnectError::NoSuchInput(to))?;
+        if !output.compatible_with(&input) {
+            return Err(ConnectError::TypeMismatch { output, input });
+        }
+        if to_node.input_reducer(to.input).is_none() && !self.snarl.in_pin(to).remotes.is_empty() {
+            return Err(ConnectError::AlreadyConnected(to));
+        }
+
+        if self.adjacency.would_create_cycle(from.node, to.node) {
+            return Err(ConnectError::Cycle);
+        }
+
+        self.snarl.connect(from, to);
+        self.adjacency.refresh(&self.snarl, [from.node, to.node]);
+        Ok(())
+    }
+
+    /// The graph built so far
+    pub fn snarl(&self) -> &Snarl<Box<dyn Node>> {
+        &self.snarl
+    }
+
+    pub fn build(self) -> Snarl<Box<dyn Node>> {
+        self.snarl
+    }
+
+    pub fn adjacency(&self) -> &Adjacency {
+        &self.adjacency
+    }
+
+    /// The dependencies between the nodes, see
+    /// [`as_petgraph`](crate::node_graph::as_petgraph)
+    pub fn petgraph(&self) -> Graph<NodeId, ()> {
+        self.adjacency.to_petgraph()
+    }
+
+    /// A DAG of the nodes to run as tasks
+    pub fn task_dag(&self) -> TaskDag {
+        TaskDag::new(&self.petgraph())
+    }
+}
@@ -0,0 +1,168 @@
+//! Disabling nodes without removing them, so that branches of a graph can be
+//! compared. A disabled node passes its input straight through when it has a
+//! single input and output of compatible types, and has no outputs otherwise,
+//! so the nodes downstream of it are left without values.
+
+use std::time::Duration;
+
+use egui::Ui;
+use egui_snarl::{NodeId, Snarl};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{DataType, Node, NodeRole, Reducer, SinkNode, TypedData},
+};
+
+/// A node that has been disabled, standing in for it in the graph. It shows
+/// and saves the node as usual, but never updates it.
+pub struct Bypassed {
+    node: Box<dyn Node>,
+    /// The input passed through by the last update
+    passed: Option<TypedData>,
+}
+
+impl Bypassed {
+    pub fn new(node: Box<dyn Node>) -> Self {
+        Self { node, passed: None }
+    }
+
+    /// Whether the node passes its input through, rather than having no
+    /// outputs
+    pub fn passes_through(&self) -> bool {
+        match (&self.node.inputs()[..], &self.node.outputs()[..]) {
+            ([input], [output]) => input.compatible_with(output),
+            _ => false,
+        }
+    }
+}
+
+pub fn is_disabled(node: &dyn Node) -> bool {
+    node.as_any().is::<Bypassed>()
+}
+
+/// Disables or re-enables a node, returning whether it changed. The graph
+/// needs to be evaluated from the node afterwards.
+pub fn set_disabled(snarl: &mut Snarl<Box<dyn Node>>, id: NodeId, disabled: bool) -> bool {
+    let Some(slot) = snarl.get_node_mut(id) else {
+        return false;
+    };
+    if is_disabled(slot.as_ref()) == disabled {
+        return false;
+    }
+    let mut node = std::mem::replace(slot, placeholder());
+    *slot = match node.as_any_mut().downcast_mut::<Bypassed>() {
+        Some(bypassed) => std::mem::replace(&mut bypassed.node, placeholder()),
+        None => Box::new(Bypassed::new(node)),
+    };
+    true
+}
+
+/// Stands in for a node while it is wrapped or unwrapped
+fn placeholder() -> Box<dyn Node> {
+    Box::<SinkNode>::default()
+}
+
+impl Node for Bypassed {
+    fn name(&self) -> String {
+        self.node.name()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.node.label()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.node.set_label(label);
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        self.node.inputs()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.node.outputs()
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        if idx == 0 && self.passes_through() {
+            self.passed.clone()
+        } else {
+            None
+        }
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.passed = self
+            .passes_through()
+            .then(|| inputs.first().cloned())
+            .flatten()
+            .filter(|value| !matches!(value, TypedData::Unknown));
+    }
+
+    /// Only the input passed through is needed, so that a disabled node
+    /// doesn't fail over inputs it would never use
+    fn required_inputs(&self, _inputs: &[Option<TypedData>]) -> Vec<usize> {
+        if self.passes_through() {
+            vec![0]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Errors are passed through like any other value
+    fn accepts_errors(&self) -> bool {
+        true
+    }
+
+    fn input_default(&self, idx: usize) -> Option<TypedData> {
+        self.node.input_default(idx)
+    }
+
+    fn input_reducer(&self, idx: usize) -> Option<Reducer> {
+        self.node.input_reducer(idx)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        self.node.show_input(idx, remote, ui)
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        self.node.show_output(idx, ui)
+    }
+
+    fn has_body(&self) -> bool {
+        self.node.has_body()
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        self.node.show_body(ui)
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.node.save()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        self.node.restore(params);
+    }
+
+    fn estimated_cost(&self) -> Option<Duration> {
+        Some(Duration::ZERO)
+    }
+
+    fn reset(&mut self) {
+        self.node.reset();
+    }
+
+    fn role(&self) -> NodeRole {
+        self.node.role()
+    }
+
+    fn subgraph(&self) -> Option<&Snarl<Box<dyn Node>>> {
+        self.node.subgraph()
+    }
+
+    fn subgraph_mut(&mut self) -> Option<&mut Snarl<Box<dyn Node>>> {
+        self.node.subgraph_mut()
+    }
+}
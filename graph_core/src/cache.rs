@@ -0,0 +1,101 @@
+//! Outputs of pure nodes kept between evaluations, so that a node whose
+//! inputs and parameters are the same as in an earlier update isn't updated
+//! again. Entries are keyed by a hash of the node's type, parameters and
+//! input values, so they are shared by every node of the same type.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use crate::node_graph::{Node, TypedData};
+
+/// Most outputs kept at once. The oldest are dropped to make room for new
+/// ones.
+pub const MAX_ENTRIES: usize = 4096;
+
+/// How well the cache has been working since it was last cleared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Updates skipped because their outputs were in the cache
+    pub hits: u64,
+    /// Updates of pure nodes whose outputs weren't in the cache
+    pub misses: u64,
+    pub entries: usize,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    outputs: HashMap<u64, Vec<TypedData>>,
+    /// Keys in the order they were added, oldest first
+    order: VecDeque<u64>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Cache of node outputs, which can be shared between evaluations by
+/// cloning it into their [`EvalContext`](crate::context::EvalContext)s.
+/// Each context starts with its own empty cache.
+#[derive(Debug, Clone, Default)]
+pub struct NodeCache(Arc<Mutex<Entries>>);
+
+impl NodeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The key of a node's outputs given its inputs, or `None` if the node
+    /// isn't [pure](Node::is_pure) and so can't be cached. Nodes without
+    /// inputs aren't cached either, as their outputs come straight from
+    /// their parameters.
+    pub fn key(node: &dyn Node, inputs: &[TypedData]) -> Option<u64> {
+        if !node.is_pure() || inputs.is_empty() {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        node.name().hash(&mut hasher);
+        // Values are hashed in their saved form, as numbers can't be hashed
+        // directly
+        serde_json::to_string(&node.save()).ok()?.hash(&mut hasher);
+        serde_json::to_string(inputs).ok()?.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    /// Outputs stored under `key`, counting whether they were found
+    pub fn get(&self, key: u64) -> Option<Vec<TypedData>> {
+        let mut entries = self.0.lock().unwrap();
+        let outputs = entries.outputs.get(&key).cloned();
+        match outputs {
+            Some(_) => entries.hits += 1,
+            None => entries.misses += 1,
+        }
+        outputs
+    }
+
+    pub fn insert(&self, key: u64, outputs: Vec<TypedData>) {
+        let mut entries = self.0.lock().unwrap();
+        if entries.outputs.insert(key, outputs).is_none() {
+            entries.order.push_back(key);
+        }
+        while entries.order.len() > MAX_ENTRIES {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.outputs.remove(&oldest);
+            }
+        }
+    }
+
+    /// Forgets every output, and resets the statistics
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = Entries::default();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.0.lock().unwrap();
+        CacheStats {
+            hits: entries.hits,
+            misses: entries.misses,
+            entries: entries.outputs.len(),
+        }
+    }
+}
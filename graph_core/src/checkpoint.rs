@@ -0,0 +1,125 @@
+//! Checkpoints of a partly completed [`TaskDag`], so that a long run can be
+//! resumed after the editor is restarted without repeating the tasks that
+//! had already finished
+
+use std::collections::HashMap;
+
+use egui_snarl::{NodeId, Snarl};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    execution_engine::TaskDag,
+    node_graph::{as_petgraph, Node, TypedData},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub completed: Vec<CompletedTask>,
+}
+
+/// A task that had been completed when the checkpoint was saved, along with
+/// what identifies the node that ran it. Node ids are reused once a node is
+/// removed, so the id alone could match a different node in a graph that
+/// has since been edited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletedTask {
+    pub id: usize,
+    /// Name of the node's type in the registry
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub params: serde_json::Value,
+    /// Ids of the tasks it depended on
+    #[serde(default)]
+    pub depends_on: Vec<usize>,
+    /// Outputs of the nodes that support caching them, see
+    /// [`Node::cached_outputs`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<TypedData>>,
+}
+
+impl Checkpoint {
+    /// Records the completed tasks of a DAG calculated for `snarl`
+    pub fn new(dag: &TaskDag, snarl: &Snarl<Box<dyn Node>>) -> Self {
+        let dependencies = dependencies(snarl);
+        let mut completed = dag
+            .completed_tasks()
+            .filter_map(|id| {
+                let node = snarl.get_node(id)?;
+                Some(CompletedTask {
+                    id: id.0,
+                    kind: node.name(),
+                    params: node.save(),
+                    depends_on: dependencies.get(&id).cloned().unwrap_or_default(),
+                    outputs: node.cached_outputs(),
+                })
+            })
+            .collect::<Vec<_>>();
+        completed.sort_by_key(|task| task.id);
+        Self { completed }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("checkpoints can always be serialised")
+    }
+
+    pub fn from_json(contents: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(contents)
+    }
+
+    /// Calculates the DAG of `snarl` with the tasks of the checkpoint already
+    /// completed, restoring the outputs they cached.
+    ///
+    /// A task is only skipped if its node has the same type, parameters and
+    /// dependencies as when the checkpoint was saved, and every task it
+    /// depends on was skipped too, as otherwise its inputs may have changed.
+    pub fn resume(&self, snarl: &mut Snarl<Box<dyn Node>>) -> TaskDag {
+        let dependencies = dependencies(snarl);
+        let completed = self
+            .completed
+            .iter()
+            .map(|task| (NodeId(task.id), task))
+            .collect::<HashMap<_, _>>();
+        let mut dag = TaskDag::new(&as_petgraph(snarl));
+        loop {
+            let resumed = dag
+                .ready_tasks()
+                .filter(|id| {
+                    let (Some(task), Some(node)) = (completed.get(id), snarl.get_node(*id)) else {
+                        return false;
+                    };
+                    task.kind == node.name()
+                        && task.params == node.save()
+                        && Some(&task.depends_on) == dependencies.get(id)
+                })
+                .collect::<Vec<_>>();
+            if resumed.is_empty() {
+                return dag;
+            }
+            for id in resumed {
+                if let Some(outputs) = &completed[&id].outputs {
+                    snarl[id].restore_outputs(outputs);
+                }
+                dag.complete_task(id)
+                    .expect("only ready tasks are completed");
+            }
+        }
+    }
+}
+
+/// Sorted ids of the nodes wired into each node of a graph
+fn dependencies(snarl: &Snarl<Box<dyn Node>>) -> HashMap<NodeId, Vec<usize>> {
+    let graph = as_petgraph(snarl);
+    graph
+        .node_indices()
+        .map(|idx| {
+            let mut depends_on = graph
+                .neighbors_directed(idx, petgraph::Direction::Incoming)
+                .map(|dep| graph[dep].0)
+                .collect::<Vec<_>>();
+            depends_on.sort_unstable();
+            depends_on.dedup();
+            (graph[idx], depends_on)
+        })
+        .collect()
+}
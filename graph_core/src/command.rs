@@ -0,0 +1,289 @@
+use std::process::{Command, Output};
+
+use egui::{self, Ui};
+
+use crate::node_graph::{show_value, DataType, Node, Reducer, TypedData};
+
+/// What a command's process wrote and how it exited
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandOutput {
+    /// `None` if the process was killed by a signal
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.status == Some(0)
+    }
+}
+
+/// An external process, run through the shell when the node's task becomes
+/// ready in a [`TaskDag`](crate::execution_engine::TaskDag). Evaluating the
+/// graph passes on the output of the last run rather than running it again.
+///
+/// Its input only orders it after the nodes wired to it, so any number of
+/// tasks can be wired to it.
+#[derive(Debug, Clone, Default)]
+pub struct CommandNode {
+    command: String,
+    /// Directory the command is run in, or the editor's if empty
+    working_dir: String,
+    /// Variables set for the command, on top of those of the editor
+    env: Vec<(String, String)>,
+    /// Result of the last run, or why the process couldn't be run
+    output: Option<Result<CommandOutput, String>>,
+    label: Option<String>,
+}
+
+impl CommandNode {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_owned(),
+            ..Self::default()
+        }
+    }
+
+    pub fn output(&self) -> Option<&CommandOutput> {
+        self.output.as_ref()?.as_ref().ok()
+    }
+}
+
+impl Node for CommandNode {
+    fn name(&self) -> String {
+        "Command".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Unknown]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number, DataType::String, DataType::String]
+    }
+
+    /// Outputs are only available if the command succeeded, so that the
+    /// tasks after it aren't run on the output of a failed one
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        let output = self.output().filter(|output| output.success())?;
+        match idx {
+            0 => output.status.map(|status| TypedData::Number(status as f64)),
+            1 => Some(TypedData::String(output.stdout.clone())),
+            2 => Some(TypedData::String(output.stderr.clone())),
+            _ => None,
+        }
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.label("After");
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        ui.label(["Exit code", "Stdout", "Stderr"][idx]);
+        false
+    }
+
+    fn input_default(&self, _idx: usize) -> Option<TypedData> {
+        Some(TypedData::Unknown)
+    }
+
+    fn input_reducer(&self, _idx: usize) -> Option<Reducer> {
+        Some(Reducer::Collect)
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.vertical(|ui| {
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.command)
+                        .code_editor()
+                        .hint_text("Command")
+                        .desired_width(180.),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.working_dir)
+                        .hint_text("Working directory")
+                        .desired_width(180.),
+                )
+                .changed();
+            ui.collapsing("Environment", |ui| {
+                let mut removed = None;
+                for (idx, (name, value)) in self.env.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        changed |= ui
+                            .add(egui::TextEdit::singleline(name).desired_width(60.))
+                            .changed();
+                        ui.label("=");
+                        changed |= ui
+                            .add(egui::TextEdit::singleline(value).desired_width(80.))
+                            .changed();
+                        if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                            removed = Some(idx);
+                        }
+                    });
+                }
+                if let Some(idx) = removed {
+                    self.env.remove(idx);
+                    changed = true;
+                }
+                if ui.small_button("+").on_hover_text("Add").clicked() {
+                    self.env.push(Default::default());
+                    changed = true;
+                }
+            });
+
+            match &self.output {
+                Some(Ok(output)) => {
+                    let status = match output.status {
+                        Some(status) => format!("Exited with status {}", status),
+                        None => "Killed".to_owned(),
+                    };
+                    if output.success() {
+                        ui.label(status);
+                    } else {
+                        ui.colored_label(ui.visuals().error_fg_color, status);
+                    }
+                    for (name, text) in [("Stdout", &output.stdout), ("Stderr", &output.stderr)] {
+                        if !text.is_empty() {
+                            ui.collapsing(name, |ui| {
+                                egui::ScrollArea::vertical()
+                                    .id_source(name)
+                                    .max_height(120.)
+                                    .show(ui, |ui| ui.monospace(text));
+                            });
+                        }
+                    }
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                None => {
+                    ui.weak("Not run yet");
+                }
+            }
+        });
+        if changed {
+            // The last run was of a different command, so it no longer
+            // stands for this one's output
+            self.output = None;
+        }
+        changed
+    }
+
+    fn process(&self) -> Option<Command> {
+        if self.command.trim().is_empty() {
+            return None;
+        }
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.args(["/C", &self.command]);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.args(["-c", &self.command]);
+            command
+        };
+        if !self.working_dir.is_empty() {
+            command.current_dir(&self.working_dir);
+        }
+        command.envs(
+            self.env
+                .iter()
+                .filter(|(name, _)| !name.is_empty())
+                .cloned(),
+        );
+        Some(command)
+    }
+
+    fn process_finished(&mut self, output: std::io::Result<Output>) {
+        self.output = Some(
+            output
+                .map(|output| CommandOutput {
+                    status: output.status.code(),
+                    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                })
+                .map_err(|err| format!("Couldn't run {:?}: {}", self.command, err)),
+        );
+    }
+
+    fn error(&self) -> Option<String> {
+        match &self.output {
+            None => Some("The command hasn't been run yet".to_owned()),
+            Some(Err(err)) => Some(err.clone()),
+            Some(Ok(output)) if !output.success() => {
+                let mut message = match output.status {
+                    Some(status) => format!("The command exited with status {}", status),
+                    None => "The command was killed".to_owned(),
+                };
+                if let Some(line) = output.stderr.lines().rfind(|line| !line.trim().is_empty()) {
+                    message = format!("{}: {}", message, line.trim());
+                }
+                Some(message)
+            }
+            Some(Ok(_)) => None,
+        }
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        let output = self.output().filter(|output| output.success())?;
+        Some(vec![
+            TypedData::Number(0.),
+            TypedData::String(output.stdout.clone()),
+            TypedData::String(output.stderr.clone()),
+        ])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(status), TypedData::String(stdout), TypedData::String(stderr)] =
+            outputs
+        {
+            self.output = Some(Ok(CommandOutput {
+                status: Some(*status as i32),
+                stdout: stdout.clone(),
+                stderr: stderr.clone(),
+            }));
+        }
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({
+            "command": self.command,
+            "working_dir": self.working_dir,
+            "env": self.env,
+        })
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(command) = params["command"].as_str() {
+            self.command = command.to_owned();
+        }
+        if let Some(working_dir) = params["working_dir"].as_str() {
+            self.working_dir = working_dir.to_owned();
+        }
+        if let Ok(env) = serde_json::from_value(params["env"].clone()) {
+            self.env = env;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// A titled note drawn behind the nodes of a graph, for explaining what a
+/// part of it does. Nodes inside a comment move along with it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// Top left corner, in graph coordinates
+    pub pos: [f32; 2],
+    pub size: [f32; 2],
+    /// RGB colour of the frame
+    pub colour: [u8; 3],
+}
+
+impl Comment {
+    pub const DEFAULT_COLOUR: [u8; 3] = [0x50, 0x80, 0xc0];
+
+    pub fn new(pos: [f32; 2], size: [f32; 2]) -> Self {
+        Self {
+            title: "Comment".to_owned(),
+            text: String::new(),
+            pos,
+            size,
+            colour: Self::DEFAULT_COLOUR,
+        }
+    }
+
+    /// Whether a point on the graph lies within the comment's frame
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        (0..2).all(|axis| {
+            point[axis] >= self.pos[axis] && point[axis] <= self.pos[axis] + self.size[axis]
+        })
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use egui_snarl::NodeId;
+use web_time::SystemTime;
+
+use crate::{
+    cache::NodeCache,
+    node_graph::{ErrorValue, TypedData},
+    observer::Observers,
+    run_report::LogLevel,
+};
+
+/// Flag for stopping an evaluation before every node has been updated,
+/// which can be set from another thread while the graph is evaluated
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the evaluation once the node being updated has finished. Long
+    /// running nodes can check [`UpdateContext::is_cancelled`] to stop
+    /// sooner.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far through its update the node currently being updated is, which
+/// can be read from another thread while the graph is evaluated
+#[derive(Debug, Clone, Default)]
+pub struct Progress(Arc<Mutex<Option<(NodeId, f32)>>>);
+
+impl Progress {
+    /// The node being updated and its progress, from 0 to 1, if it has
+    /// reported any
+    pub fn get(&self) -> Option<(NodeId, f32)> {
+        *self.0.lock().unwrap()
+    }
+
+    fn set(&self, progress: Option<(NodeId, f32)>) {
+        *self.0.lock().unwrap() = progress;
+    }
+}
+
+/// How deeply groups can be nested inside each other before evaluating them
+/// fails, unless an evaluation sets its own limit
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// Settings shared by every node updated in an evaluation
+#[derive(Debug, Clone)]
+pub struct EvalContext {
+    /// Values that any node in the graph can read by name
+    pub variables: BTreeMap<String, TypedData>,
+    /// Seed for nodes that produce random values, so that evaluations can be
+    /// repeated exactly
+    pub seed: u64,
+    /// Number of the evaluation, counting up each time the graph is run
+    pub tick: u64,
+    pub progress: Progress,
+    pub cancellation: CancellationToken,
+    /// How many groups the graph being evaluated is nested inside
+    pub depth: usize,
+    /// Groups nested deeper than this fail rather than being evaluated
+    pub max_depth: usize,
+    /// Told about each node as it is updated
+    pub observers: Observers,
+    /// Outputs of earlier updates of pure nodes, reused rather than updating
+    /// them again
+    pub cache: NodeCache,
+}
+
+impl Default for EvalContext {
+    fn default() -> Self {
+        Self {
+            variables: BTreeMap::new(),
+            seed: 0,
+            tick: 0,
+            progress: Progress::default(),
+            cancellation: CancellationToken::default(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            observers: Observers::default(),
+            cache: NodeCache::default(),
+        }
+    }
+}
+
+impl EvalContext {
+    /// The settings for evaluating a graph nested one level deeper. Its
+    /// nodes aren't reported to the observers, as their ids only make sense
+    /// within the group.
+    pub fn nested(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            observers: Observers::default(),
+            ..self.clone()
+        }
+    }
+}
+
+/// What a node can see of the evaluation it is being updated in, passed to
+/// [`Node::update`](crate::node_graph::Node::update)
+pub struct UpdateContext<'a> {
+    eval: &'a EvalContext,
+    node: NodeId,
+    messages: Vec<(LogLevel, SystemTime, String)>,
+}
+
+impl<'a> UpdateContext<'a> {
+    pub fn new(eval: &'a EvalContext, node: NodeId) -> Self {
+        eval.progress.set(None);
+        Self {
+            eval,
+            node,
+            messages: Vec::new(),
+        }
+    }
+
+    /// The evaluation's settings, for passing on to nested graphs
+    pub fn eval(&self) -> &'a EvalContext {
+        self.eval
+    }
+
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&'a TypedData> {
+        self.eval.variables.get(name)
+    }
+
+    /// Seed for this node's random values. Each node gets a different seed
+    /// derived from the evaluation's.
+    pub fn seed(&self) -> u64 {
+        // SplitMix64, so that neighbouring ids give unrelated seeds
+        let mut z = (self.eval.seed ^ self.node.0 as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.eval.tick
+    }
+
+    /// An error coming from this node, to give in place of an output that
+    /// couldn't be calculated
+    pub fn error(&self, message: impl Into<String>) -> ErrorValue {
+        ErrorValue {
+            message: message.into(),
+            origin: self.node.0,
+        }
+    }
+
+    /// Records a message in the evaluation's report
+    pub fn log(&mut self, level: LogLevel, message: impl Into<String>) {
+        let message = message.into();
+        self.eval
+            .observers
+            .notify(|observer| observer.message(self.node, &message));
+        self.messages.push((level, SystemTime::now(), message));
+    }
+
+    /// Reports how far through its update the node is, from 0 to 1
+    pub fn set_progress(&self, fraction: f32) {
+        let fraction = fraction.clamp(0., 1.);
+        self.eval.progress.set(Some((self.node, fraction)));
+        self.eval
+            .observers
+            .notify(|observer| observer.task_progress(self.node, fraction));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.eval.cancellation.is_cancelled()
+    }
+
+    /// The messages logged by the node, with their levels and when they
+    /// were logged
+    pub fn into_messages(self) -> Vec<(LogLevel, SystemTime, String)> {
+        self.messages
+    }
+}
@@ -0,0 +1,164 @@
+use egui::{self, Ui};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, ErrorValue, Node, TypedData},
+};
+
+/// Writes a number as text
+#[derive(Debug, Clone, Default)]
+pub struct ToTextNode {
+    cached_result: Option<String>,
+    label: Option<String>,
+}
+
+impl Node for ToTextNode {
+    fn name(&self) -> String {
+        "To Text".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::String]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.clone().map(TypedData::String)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(text) = &self.cached_result {
+            ui.label(text);
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::String(value)] = outputs {
+            self.cached_result = Some(value.clone());
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = match inputs[0] {
+            TypedData::Number(value) => Some(format_float(value)),
+            _ => None,
+        };
+    }
+}
+
+/// Reads a number from text, failing if the text isn't one
+#[derive(Debug, Clone, Default)]
+pub struct ParseNumberNode {
+    cached_result: Option<f64>,
+    /// Given in place of the number when the text couldn't be parsed
+    failure: Option<ErrorValue>,
+    label: Option<String>,
+}
+
+impl Node for ParseNumberNode {
+    fn name(&self) -> String {
+        "Parse Number".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::String]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result
+            .map(TypedData::Number)
+            .or_else(|| self.failure.clone().map(TypedData::Error))
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(value) = self.cached_result {
+            ui.label(format_float(value));
+        }
+        false
+    }
+
+    fn error(&self) -> Option<String> {
+        self.failure.as_ref().map(|failure| failure.message.clone())
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.cached_result
+            .map(|value| vec![TypedData::Number(value)])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], ctx: &mut UpdateContext) {
+        self.failure = None;
+        self.cached_result = match &inputs[0] {
+            TypedData::String(text) => match text.trim().parse::<f64>() {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    self.failure = Some(ctx.error(format!("{:?} is not a number", text)));
+                    None
+                }
+            },
+            _ => None,
+        };
+    }
+}
@@ -0,0 +1,190 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use web_time::SystemTime;
+
+/// A cron-style schedule with the usual five fields: minute, hour, day of
+/// month, month and day of week.
+///
+/// Each field is `*` or a comma separated list of values, ranges (`1-5`) and
+/// steps (`*/15`, `0-30/10`). Times are in UTC. As with cron, when both the
+/// day of month and day of week are restricted, a day matching either fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronError(String);
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid cron schedule: {}", self.0)
+    }
+}
+
+impl std::error::Error for CronError {}
+
+/// Parses one field into a bitmask of the values it matches
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, CronError> {
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| CronError(format!("bad step in {:?}", part)))?,
+            ),
+            None => (part, 1),
+        };
+        let parse = |val: &str| {
+            val.parse::<u32>()
+                .ok()
+                .filter(|val| (min..=max).contains(val))
+                .ok_or_else(|| CronError(format!("{:?} is not between {} and {}", val, min, max)))
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (parse(start)?, parse(end)?),
+                None => {
+                    let val = parse(range)?;
+                    // `5/10` means every 10 starting from 5
+                    (val, if step > 1 { max } else { val })
+                }
+            },
+        };
+        for val in (start..=end).step_by(step as usize) {
+            mask |= 1 << val;
+        }
+    }
+    Ok(mask)
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let fields = source.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(CronError(format!(
+                "expected 5 fields but found {}",
+                fields.len()
+            )));
+        };
+        let mut weekday_mask = parse_field(weekdays, 0, 7)?;
+        // Both 0 and 7 are Sunday
+        if weekday_mask & (1 << 7) != 0 {
+            weekday_mask |= 1;
+        }
+        Ok(Self {
+            source: source.to_owned(),
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days: parse_field(days, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            weekdays: weekday_mask,
+            days_restricted: days != "*",
+            weekdays_restricted: weekdays != "*",
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Converts days since the unix epoch into a (year, month, day) date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl CronSchedule {
+    fn day_matches(&self, days_since_epoch: i64) -> bool {
+        let (_, month, day) = civil_from_days(days_since_epoch);
+        // The epoch was a Thursday
+        let weekday = (days_since_epoch + 4).rem_euclid(7);
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        let day_match = self.days & (1 << day) != 0;
+        let weekday_match = self.weekdays & (1 << weekday) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day_match || weekday_match,
+            (true, false) => day_match,
+            (false, true) => weekday_match,
+            (false, false) => true,
+        }
+    }
+
+    /// The first time strictly after `time` that the schedule fires, or
+    /// `None` if it never fires within the next few years
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        const MAX_DAYS: i64 = 4 * 366;
+
+        let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+        let next_minute = since_epoch.as_secs() / 60 + 1;
+        let first_day = (next_minute / (24 * 60)) as i64;
+        for day in first_day..first_day + MAX_DAYS {
+            if !self.day_matches(day) {
+                continue;
+            }
+            let day_start = day as u64 * 24 * 60;
+            for minute_of_day in 0..24 * 60 {
+                let minute = day_start + minute_of_day;
+                if minute < next_minute {
+                    continue;
+                }
+                let (hour, minute_of_hour) = (minute_of_day / 60, minute_of_day % 60);
+                if self.hours & (1 << hour) != 0 && self.minutes & (1 << minute_of_hour) != 0 {
+                    return Some(SystemTime::UNIX_EPOCH + Duration::from_secs(minute * 60));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Serialize for CronSchedule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronSchedule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Evaluates a graph whenever its schedule fires
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trigger {
+    pub schedule: CronSchedule,
+    /// Node to start a partial evaluation from, or `None` to evaluate the
+    /// whole graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+}
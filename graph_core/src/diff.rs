@@ -0,0 +1,200 @@
+//! Differences between two versions of a graph, such as the graph being
+//! edited and a saved copy of it. Nodes are matched by the ids kept in graph
+//! files, so a node is the same across versions as long as it keeps its id
+//! and type. A node whose id was reused for a node of another type counts as
+//! removed and added.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::graph_file::{SavedGraph, SavedNode};
+
+/// Longest value shown in a description of a change, in characters
+const MAX_VALUE_LENGTH: usize = 40;
+
+/// How a node of the newer graph differs from the older one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeDiff {
+    Added,
+    /// Its label, parameters or connections have changed, or it has been
+    /// disabled or enabled
+    Changed,
+}
+
+/// A node in both graphs that differs between them
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeChange {
+    pub id: usize,
+    /// Name of the node's type
+    pub kind: String,
+    /// What has changed, such as "threshold: 1 → 2"
+    pub changes: Vec<String>,
+}
+
+/// A wire, as the node and output it comes from and the node and input it
+/// goes to
+pub type Wire = ((usize, usize), (usize, usize));
+
+/// What has changed going from an older graph to a newer one
+#[derive(Debug, Clone, Default)]
+pub struct GraphDiff {
+    /// Nodes only in the newer graph
+    pub added: Vec<SavedNode>,
+    /// Nodes only in the older graph
+    pub removed: Vec<SavedNode>,
+    pub changed: Vec<NodeChange>,
+    /// Wires only in the newer graph
+    pub connected: Vec<Wire>,
+    /// Wires only in the older graph
+    pub disconnected: Vec<Wire>,
+}
+
+impl GraphDiff {
+    /// Compares the nodes and wires of two graphs. Positions are left out,
+    /// as moving a node doesn't change what the graph does.
+    pub fn between(old: &SavedGraph, new: &SavedGraph) -> Self {
+        let old_nodes = nodes_by_id(old);
+        let new_nodes = nodes_by_id(new);
+        // Whether the id belongs to the same node in both graphs
+        let same = |id: &usize| match (old_nodes.get(id), new_nodes.get(id)) {
+            (Some(old), Some(new)) => old.kind == new.kind,
+            _ => false,
+        };
+
+        let mut diff = Self::default();
+        for (id, node) in &new_nodes {
+            if !same(id) {
+                diff.added.push((*node).clone());
+            }
+        }
+        for (id, node) in &old_nodes {
+            if !same(id) {
+                diff.removed.push((*node).clone());
+            }
+        }
+        for (id, new_node) in &new_nodes {
+            let Some(old_node) = old_nodes.get(id).filter(|_| same(id)) else {
+                continue;
+            };
+            let changes = node_changes(old_node, new_node);
+            if !changes.is_empty() {
+                diff.changed.push(NodeChange {
+                    id: *id,
+                    kind: new_node.kind.clone(),
+                    changes,
+                });
+            }
+        }
+
+        let old_wires = wires(old);
+        let new_wires = wires(new);
+        diff.connected = new_wires.difference(&old_wires).copied().collect();
+        diff.disconnected = old_wires.difference(&new_wires).copied().collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.connected.is_empty()
+            && self.disconnected.is_empty()
+    }
+
+    /// How each node of the newer graph differs, leaving out those that
+    /// haven't changed. Nodes whose inputs were connected or disconnected
+    /// count as changed.
+    pub fn node_diffs(&self) -> BTreeMap<usize, NodeDiff> {
+        let mut diffs = BTreeMap::new();
+        for change in &self.changed {
+            diffs.insert(change.id, NodeDiff::Changed);
+        }
+        let removed = self
+            .removed
+            .iter()
+            .map(|node| node.id)
+            .collect::<BTreeSet<_>>();
+        for (_, (to, _)) in self.connected.iter().chain(&self.disconnected) {
+            // A removed node's id may have been reused by an added one
+            if !removed.contains(to) || self.added.iter().any(|node| node.id == *to) {
+                diffs.insert(*to, NodeDiff::Changed);
+            }
+        }
+        for node in &self.added {
+            diffs.insert(node.id, NodeDiff::Added);
+        }
+        diffs
+    }
+}
+
+fn nodes_by_id(graph: &SavedGraph) -> BTreeMap<usize, &SavedNode> {
+    graph.nodes.iter().map(|node| (node.id, node)).collect()
+}
+
+fn wires(graph: &SavedGraph) -> BTreeSet<Wire> {
+    graph
+        .wires
+        .iter()
+        .map(|wire| (wire.from, wire.to))
+        .collect()
+}
+
+/// Describes how a node in both graphs has changed
+fn node_changes(old: &SavedNode, new: &SavedNode) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.label != new.label {
+        changes.push(format!(
+            "label: {} → {}",
+            old.label.as_deref().unwrap_or("none"),
+            new.label.as_deref().unwrap_or("none")
+        ));
+    }
+    if old.disabled != new.disabled {
+        changes.push(if new.disabled { "disabled" } else { "enabled" }.to_owned());
+    }
+    param_changes(&old.params, &new.params, "parameters", &mut changes);
+    changes
+}
+
+/// Describes the differences between two sets of parameters, going into
+/// objects so that only the fields that changed are listed
+fn param_changes(
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    path: &str,
+    changes: &mut Vec<String>,
+) {
+    match (old, new) {
+        _ if old == new => {}
+        (serde_json::Value::Object(old), serde_json::Value::Object(new)) => {
+            let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+            let null = serde_json::Value::Null;
+            for key in keys {
+                param_changes(
+                    old.get(key).unwrap_or(&null),
+                    new.get(key).unwrap_or(&null),
+                    key,
+                    changes,
+                );
+            }
+        }
+        _ => changes.push(format!(
+            "{}: {} → {}",
+            path,
+            format_param(old),
+            format_param(new)
+        )),
+    }
+}
+
+fn format_param(value: &serde_json::Value) -> String {
+    if value.is_null() {
+        return "none".to_owned();
+    }
+    let text = value.to_string();
+    if text.chars().count() > MAX_VALUE_LENGTH {
+        let shortened = text.chars().take(MAX_VALUE_LENGTH).collect::<String>();
+        format!("{}…", shortened)
+    } else {
+        text
+    }
+}
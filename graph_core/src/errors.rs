@@ -0,0 +1,156 @@
+//! Error values passed down the graph in place of values that couldn't be
+//! calculated. A node can give an error on any output, such as a number that
+//! failed to parse. Nodes downstream of it show the error rather than being
+//! updated, unless they [accept errors](Node::accepts_errors), so sinks end
+//! up reporting the node the error came from.
+
+use std::time::Duration;
+
+use egui::Ui;
+use egui_snarl::{NodeId, Snarl};
+
+use crate::node_graph::{
+    show_value, DataType, ErrorValue, Node, NodeRole, Reducer, SinkNode, TypedData,
+};
+
+/// A node that was given an error, standing in for it in the graph until it
+/// is next updated without one. It shows and saves the node as usual, and
+/// gives the error on every output.
+pub struct Errored {
+    node: Box<dyn Node>,
+    error: ErrorValue,
+}
+
+/// The error a node is passing on in place of its outputs
+pub fn passed_error(node: &dyn Node) -> Option<&ErrorValue> {
+    node.as_any()
+        .downcast_ref::<Errored>()
+        .map(|errored| &errored.error)
+}
+
+/// Makes a node give `error` on its outputs instead of updating it
+pub fn pass_on(snarl: &mut Snarl<Box<dyn Node>>, id: NodeId, error: ErrorValue) {
+    let Some(slot) = snarl.get_node_mut(id) else {
+        return;
+    };
+    if let Some(errored) = slot.as_any_mut().downcast_mut::<Errored>() {
+        errored.error = error;
+        return;
+    }
+    let node = std::mem::replace(slot, placeholder());
+    *slot = Box::new(Errored { node, error });
+}
+
+/// Puts a node back in place of an [`Errored`] standing in for it, so that
+/// it can be updated again
+pub fn recover(snarl: &mut Snarl<Box<dyn Node>>, id: NodeId) {
+    let Some(slot) = snarl.get_node_mut(id) else {
+        return;
+    };
+    if let Some(errored) = slot.as_any_mut().downcast_mut::<Errored>() {
+        *slot = std::mem::replace(&mut errored.node, placeholder());
+    }
+}
+
+/// Stands in for a node while it is wrapped or unwrapped
+fn placeholder() -> Box<dyn Node> {
+    Box::<SinkNode>::default()
+}
+
+impl Node for Errored {
+    fn name(&self) -> String {
+        self.node.name()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.node.label()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.node.set_label(label);
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        self.node.inputs()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.node.outputs()
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        (idx < self.node.outputs().len()).then(|| TypedData::Error(self.error.clone()))
+    }
+
+    fn required_inputs(&self, inputs: &[Option<TypedData>]) -> Vec<usize> {
+        self.node.required_inputs(inputs)
+    }
+
+    fn input_default(&self, idx: usize) -> Option<TypedData> {
+        self.node.input_default(idx)
+    }
+
+    fn input_reducer(&self, idx: usize) -> Option<Reducer> {
+        self.node.input_reducer(idx)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        self.node.show_input(idx, remote, ui)
+    }
+
+    /// Shows the error after the node's own output, which is left over from
+    /// its last update
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        let changed = self.node.show_output(idx, ui);
+        show_value(&TypedData::Error(self.error.clone()), ui);
+        changed
+    }
+
+    fn has_body(&self) -> bool {
+        self.node.has_body()
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        self.node.show_body(ui)
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.node.save()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        self.node.restore(params);
+    }
+
+    fn estimated_cost(&self) -> Option<Duration> {
+        self.node.estimated_cost()
+    }
+
+    fn reset(&mut self) {
+        self.node.reset();
+    }
+
+    fn on_graph_run_start(&mut self) -> bool {
+        self.node.on_graph_run_start()
+    }
+
+    fn error(&self) -> Option<String> {
+        Some(self.error.to_string())
+    }
+
+    fn needs_continuous_update(&self) -> bool {
+        self.node.needs_continuous_update()
+    }
+
+    fn role(&self) -> NodeRole {
+        self.node.role()
+    }
+
+    fn subgraph(&self) -> Option<&Snarl<Box<dyn Node>>> {
+        self.node.subgraph()
+    }
+
+    fn subgraph_mut(&mut self) -> Option<&mut Snarl<Box<dyn Node>>> {
+        self.node.subgraph_mut()
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, io,
+    process::Output,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+use egui_snarl::{NodeId, Snarl};
+use serde::Serialize;
+
+use crate::{
+    node_graph::{as_petgraph, Node},
+    schedule::Priority,
+    stages::Stages,
+};
+
+/// The chain of dependent tasks that takes the longest to run, which bounds
+/// how quickly the DAG can be completed however many workers there are
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CriticalPath {
+    /// Tasks in the order they run
+    pub tasks: Vec<NodeId>,
+    /// Time to complete every task with unlimited workers
+    pub makespan: Duration,
+}
+
+/// The tasks of a DAG split into stages that run one after another. Tasks
+/// in a stage only depend on tasks in earlier stages, so each stage's tasks
+/// can all run in parallel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionPlan {
+    pub stages: Vec<Vec<NodeId>>,
+    /// Tasks that can never run, as they depend on a cycle
+    pub blocked: Vec<NodeId>,
+}
+
+/// An [`ExecutionPlan`] as written for other tools, with the names and
+/// dependencies of its tasks
+#[derive(Serialize)]
+struct PlanExport {
+    stages: Vec<Vec<PlannedTask>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    blocked: Vec<PlannedTask>,
+}
+
+#[derive(Serialize)]
+struct PlannedTask {
+    id: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    /// Named stage the task was tagged with, see [`Stages`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stage: Option<String>,
+    /// Tasks wired into this one, and those it waits for at a stage barrier
+    depends_on: Vec<usize>,
+}
+
+impl ExecutionPlan {
+    /// Plans the evaluation of every node in a graph, with the barriers
+    /// between its named stages
+    pub fn for_graph(snarl: &Snarl<Box<dyn Node>>, stages: &Stages) -> Self {
+        TaskDag::new(&as_petgraph(snarl)).with_stages(stages).plan()
+    }
+
+    pub fn to_json(&self, snarl: &Snarl<Box<dyn Node>>, stages: &Stages) -> String {
+        serde_json::to_string_pretty(&self.export(snarl, stages))
+            .expect("plans can always be serialised")
+    }
+
+    pub fn to_yaml(&self, snarl: &Snarl<Box<dyn Node>>, stages: &Stages) -> String {
+        serde_yaml::to_string(&self.export(snarl, stages)).expect("plans can always be serialised")
+    }
+
+    fn export(&self, snarl: &Snarl<Box<dyn Node>>, stages: &Stages) -> PlanExport {
+        let graph = as_petgraph(snarl);
+        let barriers = stages.barrier_dependencies();
+        let task = |id: NodeId| {
+            let mut depends_on = graph
+                .node_indices()
+                .find(|idx| graph[*idx] == id)
+                .into_iter()
+                .flat_map(|idx| graph.neighbors_directed(idx, petgraph::Direction::Incoming))
+                .map(|idx| graph[idx])
+                .chain(barriers.get(&id).into_iter().flatten().copied())
+                .map(|dep| dep.0)
+                .collect::<Vec<_>>();
+            depends_on.sort_unstable();
+            depends_on.dedup();
+            let node = snarl.get_node(id);
+            PlannedTask {
+                id: id.0,
+                kind: node.map_or_else(String::new, |node| node.name()),
+                label: node.and_then(|node| node.label()).map(str::to_owned),
+                stage: stages.name_of(id).map(str::to_owned),
+                depends_on,
+            }
+        };
+        PlanExport {
+            stages: self
+                .stages
+                .iter()
+                .map(|stage| stage.iter().copied().map(task).collect())
+                .collect(),
+            blocked: self.blocked.iter().copied().map(task).collect(),
+        }
+    }
+}
+
+/// Why a task of a [`TaskDag`] couldn't be completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    /// The task isn't part of the DAG
+    Unknown(NodeId),
+    AlreadyCompleted(NodeId),
+    /// The task is still waiting on the given dependencies
+    Blocked(NodeId, Vec<NodeId>),
+}
+
+impl TaskError {
+    pub fn task(&self) -> NodeId {
+        match self {
+            TaskError::Unknown(task)
+            | TaskError::AlreadyCompleted(task)
+            | TaskError::Blocked(task, _) => *task,
+        }
+    }
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::Unknown(task) => write!(f, "Task {} is not part of the DAG", task.0),
+            TaskError::AlreadyCompleted(task) => {
+                write!(f, "Task {} has already been completed", task.0)
+            }
+            TaskError::Blocked(task, waiting_on) => write!(
+                f,
+                "Task {} is still waiting on {}",
+                task.0,
+                waiting_on
+                    .iter()
+                    .map(|dep| dep.0.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+pub struct TaskDag {
+    outstanding: HashMap<NodeId, HashSet<NodeId>>,
+    completed: HashSet<NodeId>,
+    /// Priorities given to tasks by the user, see [`TaskDag::with_priorities`]
+    priorities: HashMap<NodeId, i32>,
+}
+
+impl TaskDag {
+    pub fn new(graph: &petgraph::Graph<NodeId, ()>) -> Self {
+        let outstanding = graph
+            .node_indices()
+            .map(|idx| {
+                let node_deps = graph
+                    .neighbors_directed(idx, petgraph::Direction::Incoming)
+                    .map(|idx| graph[idx])
+                    .collect::<HashSet<_>>();
+                (graph[idx], node_deps)
+            })
+            .collect();
+
+        Self {
+            outstanding,
+            completed: HashSet::new(),
+            priorities: HashMap::new(),
+        }
+    }
+
+    /// Gives tasks priorities, used to order them by
+    /// [`Priority::Assigned`]. Tasks without a priority have priority 0.
+    pub fn with_priorities(mut self, priorities: HashMap<NodeId, i32>) -> Self {
+        self.priorities = priorities;
+        self
+    }
+
+    /// Adds the barriers between named stages, so that no task of a stage
+    /// becomes ready until every task of the stage before it is completed.
+    /// Tasks that aren't part of the DAG, or are already completed, are
+    /// left out.
+    pub fn with_stages(mut self, stages: &Stages) -> Self {
+        let outstanding = self.outstanding.keys().copied().collect::<HashSet<_>>();
+        for (task, barrier) in stages.barrier_dependencies() {
+            if let Some(dependencies) = self.outstanding.get_mut(&task) {
+                dependencies.extend(barrier.intersection(&outstanding));
+            }
+        }
+        self
+    }
+
+    pub fn priority(&self, task: NodeId) -> i32 {
+        self.priorities.get(&task).copied().unwrap_or_default()
+    }
+
+    /// List of tasks that have no outstanding dependencies
+    pub fn ready_tasks(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.outstanding
+            .iter()
+            .filter(|(_task, pending_deps)| pending_deps.is_empty())
+            .map(|(task, _)| *task)
+    }
+
+    /// Tasks that have no outstanding dependencies, in the order `priority`
+    /// would start them
+    pub fn ready_tasks_by(
+        &self,
+        priority: Priority,
+        durations: &HashMap<NodeId, Duration>,
+    ) -> Vec<NodeId> {
+        let rank = priority.ranking(self, durations);
+        let mut ready = self.ready_tasks().collect::<Vec<_>>();
+        ready.sort_by_cached_key(|task| Reverse(rank(task)));
+        ready
+    }
+
+    /// Returns the list of tasks that are now ready to be started. Only
+    /// ready tasks can be completed.
+    pub fn complete_task(&mut self, task: NodeId) -> Result<HashSet<NodeId>, TaskError> {
+        let waiting_on = self.pending_dependencies(task)?;
+        if !waiting_on.is_empty() {
+            return Err(TaskError::Blocked(task, waiting_on));
+        }
+        self.outstanding.remove(&task);
+        self.completed.insert(task);
+
+        // Remove the completed task from all dependents' lists
+        let mut new_ready_tasks = HashSet::new();
+        self.outstanding.iter_mut().for_each(|(id, dependencies)| {
+            if dependencies.remove(&task) && dependencies.is_empty() {
+                new_ready_tasks.insert(*id);
+            }
+        });
+
+        Ok(new_ready_tasks)
+    }
+
+    /// Sorted dependencies of an outstanding task that haven't been
+    /// completed yet
+    pub fn pending_dependencies(&self, task: NodeId) -> Result<Vec<NodeId>, TaskError> {
+        match self.outstanding.get(&task) {
+            Some(dependencies) => {
+                let mut dependencies = dependencies.iter().copied().collect::<Vec<_>>();
+                dependencies.sort_unstable();
+                Ok(dependencies)
+            }
+            None if self.completed.contains(&task) => Err(TaskError::AlreadyCompleted(task)),
+            None => Err(TaskError::Unknown(task)),
+        }
+    }
+
+    /// Sorted tasks that would become ready if `task` were completed
+    pub fn unblocked_by(&self, task: NodeId) -> Vec<NodeId> {
+        let mut unblocked = self
+            .outstanding
+            .iter()
+            .filter(|(_, dependencies)| dependencies.len() == 1 && dependencies.contains(&task))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        unblocked.sort_unstable();
+        unblocked
+    }
+
+    pub fn is_completed(&self, task: NodeId) -> bool {
+        self.completed.contains(&task)
+    }
+
+    /// Finds the longest chain of outstanding tasks, weighted by their
+    /// durations. Tasks without a duration estimate are assumed to take no
+    /// time.
+    pub fn critical_path(&self, durations: &HashMap<NodeId, Duration>) -> CriticalPath {
+        // When each task finishes with unlimited workers, and the dependency
+        // that it had to wait for
+        let mut finish = BTreeMap::<NodeId, (Duration, Option<NodeId>)>::new();
+        let mut remaining = self
+            .outstanding
+            .iter()
+            .map(|(task, deps)| (*task, deps))
+            .collect::<BTreeMap<_, _>>();
+        while !remaining.is_empty() {
+            let ready = remaining
+                .iter()
+                .filter(|(_, deps)| deps.iter().all(|dep| finish.contains_key(dep)))
+                .map(|(task, _)| *task)
+                .collect::<Vec<_>>();
+            if ready.is_empty() {
+                // Only possible if the tasks form a cycle
+                break;
+            }
+            for task in ready {
+                let deps = remaining.remove(&task).unwrap();
+                let (start, waited_for) = deps
+                    .iter()
+                    .map(|dep| (finish[dep].0, Some(*dep)))
+                    .max()
+                    .unwrap_or_default();
+                let duration = durations.get(&task).copied().unwrap_or_default();
+                finish.insert(task, (start + duration, waited_for));
+            }
+        }
+
+        let Some((&last, &(makespan, _))) = finish.iter().max_by_key(|(_, (end, _))| *end) else {
+            return CriticalPath::default();
+        };
+        let mut tasks = vec![last];
+        while let Some(previous) = finish[tasks.last().unwrap()].1 {
+            tasks.push(previous);
+        }
+        tasks.reverse();
+        CriticalPath { tasks, makespan }
+    }
+
+    /// Time from the start of each outstanding task until every task that
+    /// depends on it, directly or not, could be finished. Tasks with the
+    /// largest values hold up the rest of the DAG the most.
+    pub fn remaining_times(
+        &self,
+        durations: &HashMap<NodeId, Duration>,
+    ) -> HashMap<NodeId, Duration> {
+        let mut dependents = HashMap::<NodeId, Vec<NodeId>>::new();
+        for (task, deps) in &self.outstanding {
+            for dep in deps {
+                dependents.entry(*dep).or_default().push(*task);
+            }
+        }
+
+        // Kahn's algorithm backwards, from the tasks nothing depends on
+        let mut unvisited = self
+            .outstanding
+            .keys()
+            .map(|task| (*task, dependents.get(task).map_or(0, Vec::len)))
+            .collect::<HashMap<_, _>>();
+        let mut queue = unvisited
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(task, _)| *task)
+            .collect::<Vec<_>>();
+        let duration = |task: &NodeId| durations.get(task).copied().unwrap_or_default();
+        let mut times = HashMap::new();
+        while let Some(task) = queue.pop() {
+            let after = dependents
+                .get(&task)
+                .into_iter()
+                .flatten()
+                .map(|dependent| times[dependent])
+                .max()
+                .unwrap_or_default();
+            times.insert(task, duration(&task) + after);
+            for dep in &self.outstanding[&task] {
+                if let Some(count) = unvisited.get_mut(dep) {
+                    *count -= 1;
+                    if *count == 0 {
+                        queue.push(*dep);
+                    }
+                }
+            }
+        }
+        // Tasks in a cycle, or leading into one, are left at their own
+        // duration, as what comes after them can never run
+        for task in self.outstanding.keys() {
+            times.entry(*task).or_insert_with(|| duration(task));
+        }
+        times
+    }
+
+    /// Splits the outstanding tasks into stages, each made up of the tasks
+    /// whose dependencies are all in earlier stages
+    pub fn plan(&self) -> ExecutionPlan {
+        let mut remaining = self
+            .outstanding
+            .iter()
+            .map(|(task, deps)| (*task, deps))
+            .collect::<BTreeMap<_, _>>();
+        let mut planned = HashSet::new();
+        let mut stages = Vec::new();
+        loop {
+            let stage = remaining
+                .iter()
+                .filter(|(_, deps)| deps.iter().all(|dep| planned.contains(dep)))
+                .map(|(task, _)| *task)
+                .collect::<Vec<_>>();
+            if stage.is_empty() {
+                break;
+            }
+            for task in &stage {
+                remaining.remove(task);
+            }
+            planned.extend(stage.iter().copied());
+            stages.push(stage);
+        }
+        ExecutionPlan {
+            stages,
+            blocked: remaining.into_keys().collect(),
+        }
+    }
+
+    /// Tasks that have been completed, see [`Checkpoint`](crate::checkpoint::Checkpoint)
+    pub fn completed_tasks(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.completed.iter().copied()
+    }
+
+    pub fn blocked_tasks(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.outstanding
+            .iter()
+            .filter(|(_task, pending_deps)| !pending_deps.is_empty())
+            .map(|(task, _)| *task)
+    }
+}
+
+/// Runs the processes of a [`TaskDag`]'s tasks, see [`Node::process`], in
+/// the background as the tasks become ready. Tasks are completed once their
+/// process exits successfully, so that the tasks depending on them become
+/// ready in turn. Tasks without a process are left to be completed by the
+/// caller.
+#[derive(Debug, Default)]
+pub struct ProcessExecutor {
+    running: HashMap<NodeId, Receiver<io::Result<Output>>>,
+    /// Tasks whose process failed, which aren't run again
+    failed: HashSet<NodeId>,
+}
+
+impl ProcessExecutor {
+    pub fn is_running(&self, task: NodeId) -> bool {
+        self.running.contains_key(&task)
+    }
+
+    pub fn has_failed(&self, task: NodeId) -> bool {
+        self.failed.contains(&task)
+    }
+
+    /// Whether any process is still running
+    pub fn is_busy(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Hands the output of every process that has exited to its node, then
+    /// starts the processes of the tasks that are ready. Returns the tasks
+    /// whose processes exited, whether or not they succeeded.
+    pub fn poll(&mut self, dag: &mut TaskDag, snarl: &mut Snarl<Box<dyn Node>>) -> Vec<NodeId> {
+        let mut exited = Vec::new();
+        self.running.retain(|task, receiver| {
+            let output = match receiver.try_recv() {
+                Ok(output) => output,
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => {
+                    Err(io::Error::other("the thread waiting for it stopped"))
+                }
+            };
+            exited.push((*task, output));
+            false
+        });
+
+        let mut finished = Vec::new();
+        for (task, output) in exited {
+            let success = output.as_ref().is_ok_and(|output| output.status.success());
+            if let Some(node) = snarl.get_node_mut(task) {
+                node.process_finished(output);
+            }
+            if success {
+                // The DAG may have been replaced while the process ran
+                let _ = dag.complete_task(task);
+            } else {
+                self.failed.insert(task);
+            }
+            finished.push(task);
+        }
+
+        let ready = dag
+            .ready_tasks()
+            .filter(|task| !self.running.contains_key(task) && !self.failed.contains(task))
+            .collect::<Vec<_>>();
+        for task in ready {
+            let Some(mut command) = snarl.get_node(task).and_then(|node| node.process()) else {
+                continue;
+            };
+            let (sender, receiver) = mpsc::channel();
+            let spawned = std::thread::Builder::new()
+                .name(format!("task {}", task.0))
+                .spawn(move || {
+                    let _ = sender.send(command.output());
+                });
+            if let Err(err) = spawned {
+                snarl[task].process_finished(Err(err));
+                self.failed.insert(task);
+                finished.push(task);
+                continue;
+            }
+            self.running.insert(task, receiver);
+        }
+        finished
+    }
+}
@@ -0,0 +1,302 @@
+//! Writing graphs in formats read by other graph tools. Each format is an
+//! [`ExportFormat`], written from the same [`ExportGraph`], so adding one
+//! only needs the code that lays out its text.
+
+use std::fmt::Write;
+
+use egui_snarl::{InPinId, NodeId, Snarl};
+
+use crate::{
+    node_graph::{DataType, Node},
+    run_report::RunReport,
+};
+
+/// How a node fared in the evaluation the graph was exported after
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Updated,
+    Failed(String),
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Updated => "updated",
+            Status::Failed(_) => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportNode {
+    pub id: NodeId,
+    /// Name of the node's type
+    pub kind: String,
+    /// The node's label, or its type and id if it hasn't been given one
+    pub label: String,
+    /// `None` if the node wasn't updated, or no evaluation was given
+    pub status: Option<Status>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportEdge {
+    pub from: NodeId,
+    pub output: usize,
+    pub to: NodeId,
+    pub input: usize,
+    /// Type of the values carried along the wire
+    pub data_type: DataType,
+}
+
+/// What every format is written from
+#[derive(Debug, Clone, Default)]
+pub struct ExportGraph {
+    pub nodes: Vec<ExportNode>,
+    pub edges: Vec<ExportEdge>,
+}
+
+impl ExportGraph {
+    /// Collects a graph's nodes and wires, along with how each node fared
+    /// in `report`, if given
+    pub fn new(snarl: &Snarl<Box<dyn Node>>, report: Option<&RunReport>) -> Self {
+        let status = |id: NodeId| {
+            let report = report?;
+            if let Some(failure) = report.failures.iter().find(|failure| failure.node == id) {
+                return Some(Status::Failed(failure.message.clone()));
+            }
+            report
+                .runs
+                .iter()
+                .any(|run| run.node == id)
+                .then_some(Status::Updated)
+        };
+
+        let mut nodes = snarl
+            .node_ids()
+            .map(|(id, node)| ExportNode {
+                id,
+                kind: node.name(),
+                label: node
+                    .label()
+                    .map_or_else(|| format!("{} {}", node.name(), id.0), str::to_owned),
+                status: status(id),
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|node| node.id);
+
+        let mut edges = Vec::new();
+        for node in &nodes {
+            for input in 0..snarl[node.id].inputs().len() {
+                let pin = snarl.in_pin(InPinId {
+                    node: node.id,
+                    input,
+                });
+                for remote in pin.remotes {
+                    let data_type = snarl
+                        .get_node(remote.node)
+                        .and_then(|from| from.outputs().get(remote.output).cloned())
+                        .unwrap_or(DataType::Unknown);
+                    edges.push(ExportEdge {
+                        from: remote.node,
+                        output: remote.output,
+                        to: node.id,
+                        input,
+                        data_type,
+                    });
+                }
+            }
+        }
+        edges.sort_by_key(|edge| (edge.from, edge.output, edge.to, edge.input));
+
+        Self { nodes, edges }
+    }
+}
+
+/// A text format that graphs can be exported to
+pub trait ExportFormat {
+    /// Name shown to the user
+    fn name(&self) -> &'static str;
+    /// File extension, without the dot
+    fn extension(&self) -> &'static str;
+    fn write(&self, graph: &ExportGraph) -> String;
+}
+
+/// Every format graphs can be exported to
+pub fn formats() -> Vec<Box<dyn ExportFormat>> {
+    vec![Box::new(Dot), Box::new(GraphMl), Box::new(Mermaid)]
+}
+
+fn type_name(data_type: &DataType) -> String {
+    format!("{:?}", data_type)
+}
+
+/// Graphviz DOT. Nodes that failed are drawn in red, and those that were
+/// updated in green.
+pub struct Dot;
+
+impl ExportFormat for Dot {
+    fn name(&self) -> &'static str {
+        "DOT"
+    }
+
+    fn extension(&self) -> &'static str {
+        "dot"
+    }
+
+    fn write(&self, graph: &ExportGraph) -> String {
+        let mut out = String::from("digraph {\n");
+        for node in &graph.nodes {
+            let colour = match &node.status {
+                Some(Status::Updated) => ", color = green",
+                Some(Status::Failed(_)) => ", color = red",
+                None => "",
+            };
+            let _ = writeln!(
+                out,
+                "    {} [ label = {:?}{} ]",
+                node.id.0, node.label, colour
+            );
+        }
+        for edge in &graph.edges {
+            let _ = writeln!(
+                out,
+                "    {} -> {} [ label = {:?} ]",
+                edge.from.0,
+                edge.to.0,
+                type_name(&edge.data_type)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// GraphML, as read by yEd and Gephi, with the nodes' labels, types and
+/// statuses and the edges' data types as attributes
+pub struct GraphMl;
+
+impl ExportFormat for GraphMl {
+    fn name(&self) -> &'static str {
+        "GraphML"
+    }
+
+    fn extension(&self) -> &'static str {
+        "graphml"
+    }
+
+    fn write(&self, graph: &ExportGraph) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n",
+            "  <key id=\"status\" for=\"node\" attr.name=\"status\" attr.type=\"string\"/>\n",
+            "  <key id=\"error\" for=\"node\" attr.name=\"error\" attr.type=\"string\"/>\n",
+            "  <key id=\"data_type\" for=\"edge\" attr.name=\"data_type\" attr.type=\"string\"/>\n",
+            "  <graph id=\"G\" edgedefault=\"directed\">\n",
+        ));
+        for node in &graph.nodes {
+            let _ = writeln!(out, "    <node id=\"n{}\">", node.id.0);
+            let _ = writeln!(
+                out,
+                "      <data key=\"label\">{}</data>",
+                xml_escape(&node.label)
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"type\">{}</data>",
+                xml_escape(&node.kind)
+            );
+            if let Some(status) = &node.status {
+                let _ = writeln!(out, "      <data key=\"status\">{}</data>", status.name());
+            }
+            if let Some(Status::Failed(message)) = &node.status {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"error\">{}</data>",
+                    xml_escape(message)
+                );
+            }
+            out.push_str("    </node>\n");
+        }
+        for edge in &graph.edges {
+            let _ = writeln!(
+                out,
+                "    <edge source=\"n{}\" target=\"n{}\">",
+                edge.from.0, edge.to.0
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"data_type\">{}</data>",
+                xml_escape(&type_name(&edge.data_type))
+            );
+            out.push_str("    </edge>\n");
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+/// Escapes text for use in XML, as both content and attribute values
+pub fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A Mermaid flowchart, for embedding in Markdown. Nodes that failed or were
+/// updated are given the `failed` and `updated` classes.
+pub struct Mermaid;
+
+impl ExportFormat for Mermaid {
+    fn name(&self) -> &'static str {
+        "Mermaid"
+    }
+
+    fn extension(&self) -> &'static str {
+        "mmd"
+    }
+
+    fn write(&self, graph: &ExportGraph) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for node in &graph.nodes {
+            let _ = writeln!(
+                out,
+                "    n{}[\"{}\"]",
+                node.id.0,
+                mermaid_escape(&node.label)
+            );
+        }
+        for edge in &graph.edges {
+            let _ = writeln!(
+                out,
+                "    n{} -->|\"{}\"| n{}",
+                edge.from.0,
+                mermaid_escape(&type_name(&edge.data_type)),
+                edge.to.0
+            );
+        }
+        for (class, style) in [
+            ("updated", "stroke:#2a2"),
+            ("failed", "stroke:#d22,stroke-width:2px"),
+        ] {
+            let nodes = graph
+                .nodes
+                .iter()
+                .filter(|node| node.status.as_ref().map(Status::name) == Some(class))
+                .map(|node| format!("n{}", node.id.0))
+                .collect::<Vec<_>>();
+            if !nodes.is_empty() {
+                let _ = writeln!(out, "    classDef {} {}", class, style);
+                let _ = writeln!(out, "    class {} {}", nodes.join(","), class);
+            }
+        }
+        out
+    }
+}
+
+/// Mermaid has no escape for quotes inside quoted text, only HTML entities
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
@@ -0,0 +1,633 @@
+use std::fmt;
+
+use egui::{self, Ui};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, ErrorValue, Node, TypedData},
+};
+
+/// A parsed arithmetic formula, e.g. `(a + b) * sin(c)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(String),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Sqrt,
+    Abs,
+    Exp,
+    Ln,
+    Floor,
+    Ceil,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, val: f64) -> f64 {
+        match self {
+            Function::Sin => val.sin(),
+            Function::Cos => val.cos(),
+            Function::Tan => val.tan(),
+            Function::Sqrt => val.sqrt(),
+            Function::Abs => val.abs(),
+            Function::Exp => val.exp(),
+            Function::Ln => val.ln(),
+            Function::Floor => val.floor(),
+            Function::Ceil => val.ceil(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset into the formula
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why a formula that parsed couldn't be evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// The variable has no value
+    Missing(String),
+    DivisionByZero,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Missing(name) => write!(f, "{} needs a number", name),
+            EvalError::DivisionByZero => write!(f, "Division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Symbol(char),
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(idx, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = idx + c.len_utf8();
+                chars.next();
+            }
+            // An exponent, as in `1e-3`, when digits follow the `e`
+            if let Some(&(idx, 'e' | 'E')) = chars.peek() {
+                let exponent = &source[idx + 1..];
+                let sign = usize::from(exponent.starts_with(['+', '-']));
+                let digits = exponent[sign..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(exponent.len() - sign);
+                if digits > 0 {
+                    end = idx + 1 + sign + digits;
+                    while chars.peek().is_some_and(|&(idx, _)| idx < end) {
+                        chars.next();
+                    }
+                }
+            }
+            let value = source[start..end].parse().map_err(|_| ParseError {
+                position: start,
+                message: format!("invalid number {:?}", &source[start..end]),
+            })?;
+            tokens.push((start, Token::Number(value)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(idx, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = idx + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((start, Token::Ident(source[start..end].to_owned())));
+        } else if "+-*/^()".contains(c) {
+            tokens.push((start, Token::Symbol(c)));
+            chars.next();
+        } else {
+            return Err(ParseError {
+                position: start,
+                message: format!("unexpected character {:?}", c),
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens of a formula
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// Length of the source, used when reporting errors at the end
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(pos, _)| *pos)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            position: self.position(),
+            message: message.into(),
+        }
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ParseError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", symbol)))
+        }
+    }
+
+    /// `term (('+' | '-') term)*`
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                BinaryOp::Add
+            } else if self.eat('-') {
+                BinaryOp::Sub
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    /// `unary (('*' | '/') unary)*`
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinaryOp::Mul
+            } else if self.eat('/') {
+                BinaryOp::Div
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    /// `'-' unary | atom ('^' unary)?`
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.eat('^') {
+            // Right associative, so `a ^ b ^ c` is `a ^ (b ^ c)`
+            return Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    /// A number, variable, constant, function call or parenthesised expression
+    fn atom(&mut self) -> Result<Expr, ParseError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("unexpected end of formula"));
+        };
+        match token {
+            Token::Number(value) => {
+                self.pos += 1;
+                Ok(Expr::Number(value))
+            }
+            Token::Ident(name) => {
+                let start = self.position();
+                self.pos += 1;
+                if self.eat('(') {
+                    let function = Function::from_name(&name).ok_or(ParseError {
+                        position: start,
+                        message: format!("unknown function {:?}", name),
+                    })?;
+                    let arg = self.expr()?;
+                    self.expect(')')?;
+                    return Ok(Expr::Call(function, Box::new(arg)));
+                }
+                Ok(match name.as_str() {
+                    "pi" => Expr::Number(std::f64::consts::PI),
+                    "e" => Expr::Number(std::f64::consts::E),
+                    _ => Expr::Variable(name),
+                })
+            }
+            Token::Symbol('(') => {
+                self.pos += 1;
+                let inner = self.expr()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Token::Symbol(c) => Err(self.error(format!("unexpected '{}'", c))),
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+            end: source.len(),
+        };
+        let expr = parser.expr()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(expr)
+    }
+
+    /// Free variables, in the order they first appear
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables = Vec::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables(&self, variables: &mut Vec<String>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Variable(name) => {
+                if !variables.contains(name) {
+                    variables.push(name.clone());
+                }
+            }
+            Expr::Neg(inner) | Expr::Call(_, inner) => inner.collect_variables(variables),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.collect_variables(variables);
+                rhs.collect_variables(variables);
+            }
+        }
+    }
+
+    /// Evaluates the formula, failing if a variable has no value. Dividing
+    /// by zero gives an infinite result, or fails if `strict` is set.
+    pub fn eval(
+        &self,
+        lookup: &impl Fn(&str) -> Option<f64>,
+        strict: bool,
+    ) -> Result<f64, EvalError> {
+        Ok(match self {
+            Expr::Number(value) => *value,
+            Expr::Variable(name) => lookup(name).ok_or_else(|| EvalError::Missing(name.clone()))?,
+            Expr::Neg(inner) => -inner.eval(lookup, strict)?,
+            Expr::Call(function, arg) => function.apply(arg.eval(lookup, strict)?),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(lookup, strict)?, rhs.eval(lookup, strict)?);
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::Div if strict && rhs == 0. => return Err(EvalError::DivisionByZero),
+                    BinaryOp::Div => lhs / rhs,
+                    BinaryOp::Pow => lhs.powf(rhs),
+                }
+            }
+        })
+    }
+}
+
+/// Evaluates a formula typed by the user, with one input per variable
+pub struct ExpressionNode {
+    source: String,
+    parsed: Result<Expr, ParseError>,
+    /// Variables of the last formula that parsed, so that inputs are kept
+    /// while the formula is being edited
+    variables: Vec<String>,
+    cached_result: Option<f64>,
+    /// Why the formula could not be evaluated when the node last updated
+    failure: Option<ErrorValue>,
+    /// Whether failures are passed on through a second output
+    error_output: bool,
+    /// Whether dividing by zero fails, and failures are given as error
+    /// values rather than leaving the result without a value
+    strict: bool,
+    label: Option<String>,
+}
+
+impl ExpressionNode {
+    pub fn new(source: &str) -> Self {
+        let mut node = Self {
+            source: String::new(),
+            parsed: Ok(Expr::Number(0.)),
+            variables: Vec::new(),
+            cached_result: None,
+            failure: None,
+            error_output: false,
+            strict: false,
+            label: None,
+        };
+        node.set_source(source.to_owned());
+        node
+    }
+
+    fn set_source(&mut self, source: String) {
+        self.parsed = Expr::parse(&source);
+        if let Ok(expr) = &self.parsed {
+            self.variables = expr.variables();
+        }
+        self.source = source;
+    }
+}
+
+impl Default for ExpressionNode {
+    fn default() -> Self {
+        Self::new("a + b")
+    }
+}
+
+impl Node for ExpressionNode {
+    fn name(&self) -> String {
+        "Expression".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number; self.variables.len()]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        if self.error_output {
+            vec![DataType::Number, DataType::String]
+        } else {
+            vec![DataType::Number]
+        }
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        match idx {
+            0 => self.cached_result.map(TypedData::Number).or_else(|| {
+                self.failure
+                    .clone()
+                    .filter(|_| self.strict)
+                    .map(TypedData::Error)
+            }),
+            1 => self
+                .failure
+                .as_ref()
+                .map(|failure| TypedData::String(failure.message.clone())),
+            _ => None,
+        }
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        ui.label(&self.variables[idx]);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        if idx == 1 {
+            ui.label("Error");
+            return false;
+        }
+        let mut changed = false;
+        ui.vertical(|ui| {
+            let mut source = self.source.clone();
+            if ui
+                .add(egui::TextEdit::singleline(&mut source).desired_width(120.))
+                .changed()
+            {
+                self.set_source(source);
+                changed = true;
+            }
+            match (&self.parsed, self.cached_result) {
+                (Err(err), _) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+                }
+                (Ok(_), Some(res)) => {
+                    ui.label(format_float(res));
+                }
+                (Ok(_), None) => {}
+            }
+            changed |= ui
+                .checkbox(&mut self.error_output, "Error output")
+                .changed();
+            changed |= ui
+                .checkbox(&mut self.strict, "Strict")
+                .on_hover_text(
+                    "Fail when dividing by zero, and pass failures on as errors \
+                     rather than leaving the result without a value",
+                )
+                .changed();
+        });
+        changed
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.cached_result
+            .map(|value| vec![TypedData::Number(value)])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], ctx: &mut UpdateContext) {
+        let lookup = |name: &str| {
+            let idx = self.variables.iter().position(|var| var == name)?;
+            match inputs.get(idx)? {
+                TypedData::Number(val) => Some(*val),
+                _ => None,
+            }
+        };
+        let result = match &self.parsed {
+            Ok(expr) => expr
+                .eval(&lookup, self.strict)
+                .map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        (self.cached_result, self.failure) = match result {
+            Ok(value) => (Some(value), None),
+            Err(err) => (None, Some(ctx.error(err))),
+        };
+    }
+
+    fn error(&self) -> Option<String> {
+        self.failure.as_ref().map(|failure| failure.message.clone())
+    }
+
+    fn error_output(&self) -> Option<usize> {
+        self.error_output.then_some(1)
+    }
+
+    fn save(&self) -> serde_json::Value {
+        if self.error_output || self.strict {
+            serde_json::json!({
+                "source": self.source,
+                "error_output": self.error_output,
+                "strict": self.strict,
+            })
+        } else {
+            self.source.clone().into()
+        }
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        // Expressions without any options are saved as just their source
+        if let Some(source) = params.as_str().or(params["source"].as_str()) {
+            self.set_source(source.to_owned());
+        }
+        self.error_output = params["error_output"].as_bool().unwrap_or(false);
+        self.strict = params["strict"].as_bool().unwrap_or(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str) -> Result<f64, EvalError> {
+        let lookup = |name: &str| (name == "x").then_some(2.);
+        Expr::parse(source).unwrap().eval(&lookup, true)
+    }
+
+    #[test]
+    fn operators_follow_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.));
+        assert_eq!(eval("10 - 4 - 3"), Ok(3.));
+        assert_eq!(eval("12 / 3 / 2"), Ok(2.));
+        assert_eq!(eval("2 * 3 ^ 2"), Ok(18.));
+        // Powers are right associative
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(512.));
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-x"), Ok(-2.));
+        assert_eq!(eval("--x"), Ok(2.));
+        assert_eq!(eval("3 - -x"), Ok(5.));
+        assert_eq!(eval("-x ^ 2"), Ok(-4.));
+        assert_eq!(eval("-(1 + 2) * 2"), Ok(-6.));
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.));
+        assert_eq!(eval("((x))"), Ok(2.));
+        assert_eq!(eval("sqrt(4 * (x + 2))"), Ok(4.));
+        assert!(Expr::parse("(1 + 2").is_err());
+        assert!(Expr::parse("1 + 2)").is_err());
+    }
+
+    #[test]
+    fn numbers_with_exponents() {
+        assert_eq!(eval("1e-3"), Ok(0.001));
+        assert_eq!(eval("2.5E2"), Ok(250.));
+        assert_eq!(eval("1e+2 * x"), Ok(200.));
+        // Without digits the `e` is Euler's number, which needs an operator
+        assert_eq!(eval("2 * e"), Ok(2. * std::f64::consts::E));
+        assert!(Expr::parse("2e").is_err());
+    }
+
+    #[test]
+    fn unknown_variables_are_inputs_without_values() {
+        let expr = Expr::parse("x + y * x").unwrap();
+        assert_eq!(expr.variables(), ["x", "y"]);
+        assert_eq!(eval("x + y"), Err(EvalError::Missing("y".to_owned())));
+    }
+
+    #[test]
+    fn trailing_input_is_rejected() {
+        let err = Expr::parse("1 + 2 3").unwrap_err();
+        assert_eq!(err.position, 6);
+        assert!(Expr::parse("x y").is_err());
+        assert!(Expr::parse("1 +").is_err());
+        assert!(Expr::parse("1 $ 2").is_err());
+        assert!(Expr::parse("nope(1)").is_err());
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(eval("1 / (x - 2)"), Err(EvalError::DivisionByZero));
+        let lenient = Expr::parse("1 / 0").unwrap().eval(&|_| None, false);
+        assert_eq!(lenient, Ok(f64::INFINITY));
+    }
+}
@@ -0,0 +1,329 @@
+//! Nodes that read and write files, and a watcher that re-evaluates the
+//! nodes reading files that change on disk
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use egui::{self, Ui};
+use egui_snarl::{NodeId, Snarl};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_value, show_value, DataType, Node, TypedData},
+};
+
+/// Reads a file, as text or as a list of bytes
+#[derive(Debug, Clone, Default)]
+pub struct ReadFileNode {
+    /// Whether the file is read as bytes rather than text
+    binary: bool,
+    /// Whether the node is re-evaluated when the file changes, see
+    /// [`FileWatcher`]
+    watch: bool,
+    /// Path the file was last read from
+    path: Option<PathBuf>,
+    /// File read in place of the path given as input, see
+    /// [`Node::set_data_file`]
+    file: Option<PathBuf>,
+    cached_result: Option<TypedData>,
+    failure: Option<String>,
+    label: Option<String>,
+}
+
+impl Node for ReadFileNode {
+    fn name(&self) -> String {
+        "Read File".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::String]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        if self.binary {
+            vec![DataType::List(Box::new(DataType::Number))]
+        } else {
+            vec![DataType::String]
+        }
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.clone()
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.label("Path");
+        if let Some(file) = &self.file {
+            ui.weak(file.display().to_string())
+                .on_hover_text("Read from the package the graph was opened from");
+        } else if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        match &self.cached_result {
+            Some(TypedData::String(text)) => ui.label(format!("{} characters", text.len())),
+            Some(TypedData::List(bytes)) => ui.label(format!("{} bytes", bytes.len())),
+            _ => ui.label(""),
+        };
+        false
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.vertical(|ui| {
+            changed |= ui.checkbox(&mut self.binary, "Binary").changed();
+            changed |= ui
+                .checkbox(&mut self.watch, "Watch")
+                .on_hover_text("Read the file again whenever it changes")
+                .changed();
+        });
+        changed
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = None;
+        self.failure = None;
+        let path = match (&self.file, &inputs[0]) {
+            (Some(file), _) => file.clone(),
+            (None, TypedData::String(path)) => PathBuf::from(path),
+            (None, _) => return,
+        };
+        let contents = if self.binary {
+            std::fs::read(&path).map(|bytes| {
+                TypedData::List(
+                    bytes
+                        .into_iter()
+                        .map(|byte| TypedData::Number(byte as f64))
+                        .collect(),
+                )
+            })
+        } else {
+            std::fs::read_to_string(&path).map(TypedData::String)
+        };
+        match contents {
+            Ok(contents) => self.cached_result = Some(contents),
+            Err(err) => self.failure = Some(format!("{}: {}", path.display(), err)),
+        }
+        self.path = Some(path);
+    }
+
+    fn error(&self) -> Option<String> {
+        self.failure.clone()
+    }
+
+    fn watched_path(&self) -> Option<PathBuf> {
+        self.path.clone().filter(|_| self.watch)
+    }
+
+    fn data_file(&self) -> Option<PathBuf> {
+        self.path.clone()
+    }
+
+    fn set_data_file(&mut self, path: PathBuf) {
+        self.file = Some(path);
+    }
+
+    fn save(&self) -> serde_json::Value {
+        let mut params = serde_json::json!({ "binary": self.binary, "watch": self.watch });
+        if let Some(file) = &self.file {
+            params["file"] = file.display().to_string().into();
+        }
+        params
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        self.binary = params["binary"].as_bool().unwrap_or(false);
+        self.watch = params["watch"].as_bool().unwrap_or(false);
+        self.file = params["file"].as_str().map(PathBuf::from);
+    }
+}
+
+/// Writes its input to a file each time it is evaluated. Strings are written
+/// as they are, and lists of numbers from 0 to 255 as bytes, so that files
+/// read by a [`ReadFileNode`] can be written back unchanged. Anything else is
+/// written as it is displayed.
+#[derive(Debug, Clone, Default)]
+pub struct WriteFileNode {
+    /// Number of bytes last written
+    written: Option<usize>,
+    failure: Option<String>,
+    label: Option<String>,
+}
+
+/// The bytes written to a file for a value
+fn file_contents(value: &TypedData) -> Vec<u8> {
+    match value {
+        TypedData::String(text) => text.clone().into_bytes(),
+        TypedData::List(values) => values
+            .iter()
+            .map(|value| match value {
+                TypedData::Number(byte) if byte.fract() == 0. && (0. ..=255.).contains(byte) => {
+                    Some(*byte as u8)
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_else(|| format_value(value).into_bytes()),
+        value => format_value(value).into_bytes(),
+    }
+}
+
+impl Node for WriteFileNode {
+    fn name(&self) -> String {
+        "Write File".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::String, DataType::Unknown]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![]
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        ui.label(["Path", "Contents"][idx]);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        if idx == 1 {
+            if let Some(written) = self.written {
+                ui.weak(format!("Wrote {} bytes", written));
+            }
+        }
+        false
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.written = None;
+        self.failure = None;
+        let (TypedData::String(path), contents) = (&inputs[0], &inputs[1]) else {
+            return;
+        };
+        let contents = file_contents(contents);
+        match std::fs::write(path, &contents) {
+            Ok(()) => self.written = Some(contents.len()),
+            Err(err) => self.failure = Some(format!("{}: {}", path, err)),
+        }
+    }
+
+    fn error(&self) -> Option<String> {
+        self.failure.clone()
+    }
+}
+
+/// Watches the files of nodes with a [`Node::watched_path`] on a background
+/// thread, checking when each was last modified every `interval`. The nodes
+/// whose files changed are collected with [`FileWatcher::changed`], to be
+/// evaluated again.
+pub struct FileWatcher {
+    watched: Arc<Mutex<HashMap<NodeId, PathBuf>>>,
+    changed: Receiver<NodeId>,
+    stop: Arc<AtomicBool>,
+}
+
+impl FileWatcher {
+    /// Starts watching. `wake` is called from the background thread when a
+    /// file changes, such as to repaint the editor so that it collects the
+    /// change.
+    pub fn new(interval: Duration, wake: impl Fn() + Send + 'static) -> std::io::Result<Self> {
+        let watched = Arc::new(Mutex::new(HashMap::<NodeId, PathBuf>::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, changed) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("file watcher".to_owned())
+            .spawn({
+                let watched = watched.clone();
+                let stop = stop.clone();
+                move || {
+                    let mut modified = HashMap::<PathBuf, Option<SystemTime>>::new();
+                    while !stop.load(Ordering::Relaxed) {
+                        let paths = watched.lock().unwrap().clone();
+                        let mut woken = false;
+                        for (node, path) in paths {
+                            let now = std::fs::metadata(&path)
+                                .and_then(|metadata| metadata.modified())
+                                .ok();
+                            // Files aren't reported when first seen, as the
+                            // node has only just read them
+                            match modified.insert(path, now) {
+                                Some(before) if before != now => {
+                                    if sender.send(node).is_err() {
+                                        return;
+                                    }
+                                    woken = true;
+                                }
+                                _ => {}
+                            }
+                        }
+                        if woken {
+                            wake();
+                        }
+                        std::thread::sleep(interval);
+                    }
+                }
+            })?;
+        Ok(Self {
+            watched,
+            changed,
+            stop,
+        })
+    }
+
+    /// Updates which files are watched from the nodes of a graph
+    pub fn watch(&self, snarl: &Snarl<Box<dyn Node>>) {
+        let watched = snarl
+            .node_ids()
+            .filter_map(|(id, node)| Some((id, node.watched_path()?)))
+            .collect();
+        *self.watched.lock().unwrap() = watched;
+    }
+
+    /// Nodes whose files have changed since this was last called
+    pub fn changed(&self) -> Vec<NodeId> {
+        let mut changed = self.changed.try_iter().collect::<Vec<_>>();
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
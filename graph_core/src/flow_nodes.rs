@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use egui::{self, Ui};
+use web_time::Instant;
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, Node, TypedData},
+};
+
+/// Passes its input on at most once per interval, holding the last value
+/// passed on in between so that frequent updates don't reach the rest of the
+/// graph
+#[derive(Debug, Clone)]
+pub struct ThrottleNode {
+    interval: Duration,
+    value: Option<f64>,
+    passed_at: Option<Instant>,
+    label: Option<String>,
+}
+
+impl Default for ThrottleNode {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            value: None,
+            passed_at: None,
+            label: None,
+        }
+    }
+}
+
+impl Node for ThrottleNode {
+    fn name(&self) -> String {
+        "Throttle".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.value.map(TypedData::Number)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        let mut secs = self.interval.as_secs_f64();
+        let changed = ui
+            .horizontal(|ui| {
+                ui.label("Every");
+                let response = ui.add(
+                    egui::DragValue::new(&mut secs)
+                        .speed(0.1)
+                        .clamp_range(0.0..=86_400.)
+                        .suffix(" s"),
+                );
+                if let Some(value) = self.value {
+                    ui.label(format_float(value));
+                }
+                response.changed()
+            })
+            .inner;
+        if changed {
+            self.interval = Duration::from_secs_f64(secs);
+        }
+        changed
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let TypedData::Number(value) = inputs[0] else {
+            self.value = None;
+            return;
+        };
+        let now = Instant::now();
+        if self
+            .passed_at
+            .is_none_or(|passed_at| now - passed_at >= self.interval)
+        {
+            self.value = Some(value);
+            self.passed_at = Some(now);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+        self.passed_at = None;
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.interval.as_secs_f64().into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(secs) = params.as_f64() {
+            self.interval = Duration::from_secs_f64(secs.max(0.));
+        }
+    }
+}
+
+/// Only passes its input on when it differs from the last value passed on
+/// by more than a threshold, ignoring small changes such as noise
+#[derive(Debug, Clone)]
+pub struct DeadbandNode {
+    threshold: f64,
+    value: Option<f64>,
+    label: Option<String>,
+}
+
+impl Default for DeadbandNode {
+    fn default() -> Self {
+        Self {
+            threshold: 1.,
+            value: None,
+            label: None,
+        }
+    }
+}
+
+impl Node for DeadbandNode {
+    fn name(&self) -> String {
+        "Deadband".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.value.map(TypedData::Number)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.horizontal(|ui| {
+            ui.label("±");
+            let response = ui.add(
+                egui::DragValue::new(&mut self.threshold)
+                    .speed(0.1)
+                    .clamp_range(0.0..=f64::MAX),
+            );
+            if let Some(value) = self.value {
+                ui.label(format_float(value));
+            }
+            response.changed()
+        })
+        .inner
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(value)] = outputs {
+            self.value = Some(*value);
+        }
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let TypedData::Number(value) = inputs[0] else {
+            self.value = None;
+            return;
+        };
+        if self
+            .value
+            .is_none_or(|last| (value - last).abs() > self.threshold)
+        {
+            self.value = Some(value);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.threshold.into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(threshold) = params.as_f64() {
+            self.threshold = threshold.max(0.);
+        }
+    }
+}
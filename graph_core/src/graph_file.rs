@@ -0,0 +1,280 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::Path,
+};
+
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bypass::{self, Bypassed},
+    comment::Comment,
+    cron::Trigger,
+    hooks::Hooks,
+    node_graph::{Node, SinkNode, TypedData},
+    profile::Profile,
+    registry,
+    snapshot::Snapshot,
+    stages::SavedStage,
+};
+
+/// Serialisable form of a graph, as stored in a graph file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedGraph {
+    pub nodes: Vec<SavedNode>,
+    pub wires: Vec<SavedWire>,
+    /// Named configurations of the graph, only stored for the root graph
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Profile>,
+    /// Schedules for evaluating the graph when run as an engine, only stored
+    /// for the root graph
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<Trigger>,
+    /// Scripts run by the engine around each evaluation, only stored for the
+    /// root graph
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+    /// Notes drawn behind the nodes, only stored for the root graph
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+    /// Earlier versions of the graph kept by the user, oldest first, only
+    /// stored for the root graph
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshots: Vec<Snapshot>,
+    /// Priorities the user gave nodes, by node id, used to order ready
+    /// tasks. Only stored for the root graph, and only for nodes whose
+    /// priority isn't 0.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub priorities: BTreeMap<usize, i32>,
+    /// Named stages nodes are tagged with, in the order they run, see
+    /// [`Stages`](crate::stages::Stages). Only stored for the root graph.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<SavedStage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedNode {
+    /// Node ids are preserved when the graph is loaded
+    pub id: usize,
+    /// Name of the node's type in the registry
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub pos: [f32; 2],
+    /// Name given to the node by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub params: serde_json::Value,
+    /// Whether the node is bypassed during evaluation, see [`bypass`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+    /// Outputs from when the graph was saved, restored so that they are
+    /// available without recalculating the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<TypedData>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SavedWire {
+    /// Node id and output index
+    pub from: (usize, usize),
+    /// Node id and input index
+    pub to: (usize, usize),
+}
+
+/// How many more ids than nodes a graph file can use. Ids left unused by
+/// removed nodes are filled in while loading, so this bounds that work for
+/// corrupt files.
+pub const MAX_UNUSED_IDS: usize = 4096;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    UnknownNode(String),
+    DuplicateNode(usize),
+    /// A node id too far beyond the number of nodes, see [`MAX_UNUSED_IDS`]
+    NodeIdTooLarge(usize),
+    InvalidWire(SavedWire),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "could not read graph file: {}", err),
+            LoadError::Parse(err) => write!(f, "invalid graph file: {}", err),
+            LoadError::UnknownNode(kind) => write!(f, "unknown node type {:?}", kind),
+            LoadError::DuplicateNode(id) => write!(f, "node id {} is used more than once", id),
+            LoadError::NodeIdTooLarge(id) => write!(
+                f,
+                "node id {} is more than {} past the number of nodes",
+                id, MAX_UNUSED_IDS
+            ),
+            LoadError::InvalidWire(wire) => write!(
+                f,
+                "wire from {:?} to {:?} does not connect existing pins",
+                wire.from, wire.to
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(err: std::io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        LoadError::Parse(err)
+    }
+}
+
+impl SavedGraph {
+    pub fn from_snarl(snarl: &Snarl<Box<dyn Node>>) -> Self {
+        let mut nodes = snarl
+            .nodes_pos_ids()
+            .map(|(id, pos, node)| SavedNode {
+                id: id.0,
+                kind: node.name(),
+                pos: [pos.x, pos.y],
+                label: node.label().map(str::to_owned),
+                params: node.save(),
+                disabled: bypass::is_disabled(node.as_ref()),
+                outputs: None,
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|node| node.id);
+
+        let mut wires = Vec::new();
+        for (id, node) in snarl.node_ids() {
+            for output in 0..node.outputs().len() {
+                let outpin = snarl.out_pin(OutPinId { node: id, output });
+                for remote in outpin.remotes {
+                    wires.push(SavedWire {
+                        from: (id.0, output),
+                        to: (remote.node.0, remote.input),
+                    });
+                }
+            }
+        }
+
+        Self {
+            nodes,
+            wires,
+            profiles: Vec::new(),
+            triggers: Vec::new(),
+            hooks: Hooks::default(),
+            comments: Vec::new(),
+            snapshots: Vec::new(),
+            priorities: BTreeMap::new(),
+            stages: Vec::new(),
+        }
+    }
+
+    /// Stores the current outputs of the nodes that support caching them, so
+    /// that they can be restored when the graph is loaded
+    pub fn cache_outputs(&mut self, snarl: &Snarl<Box<dyn Node>>) {
+        for saved in &mut self.nodes {
+            saved.outputs = snarl
+                .get_node(NodeId(saved.id))
+                .and_then(|node| node.cached_outputs());
+        }
+    }
+
+    /// Priorities of the graph's tasks, see
+    /// [`TaskDag::with_priorities`](crate::execution_engine::TaskDag::with_priorities)
+    pub fn task_priorities(&self) -> HashMap<NodeId, i32> {
+        self.priorities
+            .iter()
+            .map(|(id, priority)| (NodeId(*id), *priority))
+            .collect()
+    }
+
+    /// Recreates the graph, keeping the saved node ids. Nodes saved with
+    /// cached outputs have them restored, see
+    /// [`node_graph::evaluate_stale`](crate::node_graph::evaluate_stale).
+    pub fn to_snarl(&self) -> Result<Snarl<Box<dyn Node>>, LoadError> {
+        let mut nodes = BTreeMap::new();
+        for saved in &self.nodes {
+            if saved.id >= self.nodes.len() + MAX_UNUSED_IDS {
+                return Err(LoadError::NodeIdTooLarge(saved.id));
+            }
+            let mut node = registry::create_node(&saved.kind)
+                .ok_or_else(|| LoadError::UnknownNode(saved.kind.clone()))?;
+            node.restore(&saved.params);
+            node.set_label(saved.label.clone());
+            if let Some(outputs) = &saved.outputs {
+                node.restore_outputs(outputs);
+            }
+            if saved.disabled {
+                node = Box::new(Bypassed::new(node));
+            }
+            if nodes
+                .insert(saved.id, (egui::pos2(saved.pos[0], saved.pos[1]), node))
+                .is_some()
+            {
+                return Err(LoadError::DuplicateNode(saved.id));
+            }
+        }
+
+        // Nodes are inserted in id order, with placeholders filling any gaps
+        // so that every node ends up with the same id it was saved with
+        let mut snarl = Snarl::<Box<dyn Node>>::new();
+        let mut placeholders = Vec::new();
+        let mut next_id = 0;
+        for (id, (pos, node)) in nodes {
+            for _ in next_id..id {
+                placeholders.push(snarl.insert_node(egui::Pos2::ZERO, Box::<SinkNode>::default()));
+            }
+            let inserted = snarl.insert_node(pos, node);
+            debug_assert_eq!(inserted, NodeId(id));
+            next_id = id + 1;
+        }
+        for placeholder in placeholders {
+            snarl.remove_node(placeholder);
+        }
+
+        for wire in &self.wires {
+            let (from, output) = wire.from;
+            let (to, input) = wire.to;
+            let valid = matches!(
+                (snarl.get_node(NodeId(from)), snarl.get_node(NodeId(to))),
+                (Some(from), Some(to)) if output < from.outputs().len() && input < to.inputs().len()
+            );
+            if !valid {
+                return Err(LoadError::InvalidWire(*wire));
+            }
+            snarl.connect(
+                OutPinId {
+                    node: NodeId(from),
+                    output,
+                },
+                InPinId {
+                    node: NodeId(to),
+                    input,
+                },
+            );
+        }
+
+        Ok(snarl)
+    }
+}
+
+pub fn save_to_file(snarl: &Snarl<Box<dyn Node>>, path: &Path) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&SavedGraph::from_snarl(snarl))?;
+    std::fs::write(path, json)
+}
+
+/// Reads a graph file without recreating the graph
+pub fn read_file(path: &Path) -> Result<SavedGraph, LoadError> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+pub fn load_from_file(path: &Path) -> Result<Snarl<Box<dyn Node>>, LoadError> {
+    read_file(path)?.to_snarl()
+}
@@ -0,0 +1,342 @@
+//! Nodes for building and taking apart colours and vectors
+
+use egui::{self, Ui};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, Node, TypedData},
+};
+
+const CHANNELS: [&str; 4] = ["r", "g", "b", "a"];
+const COMPONENTS: [&str; 3] = ["x", "y", "z"];
+
+/// Makes a colour from its red, green, blue and alpha channels, each between
+/// 0 and 1
+#[derive(Debug, Clone, Default)]
+pub struct MakeColorNode {
+    cached_result: Option<[u8; 4]>,
+    label: Option<String>,
+}
+
+impl Node for MakeColorNode {
+    fn name(&self) -> String {
+        "Make Colour".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number; CHANNELS.len()]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Color]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(TypedData::Color)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        ui.label(CHANNELS[idx]);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(value) = self.output_value(0) {
+            show_value(&value, ui);
+        }
+        false
+    }
+
+    /// Unconnected channels are off, apart from alpha which is opaque
+    fn input_default(&self, idx: usize) -> Option<TypedData> {
+        Some(TypedData::Number(if idx == 3 { 1. } else { 0. }))
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Color(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let mut color = [0; 4];
+        for (channel, input) in color.iter_mut().zip(inputs) {
+            let TypedData::Number(value) = input else {
+                self.cached_result = None;
+                return;
+            };
+            *channel = (value.clamp(0., 1.) * 255.).round() as u8;
+        }
+        self.cached_result = Some(color);
+    }
+}
+
+/// Makes a 2D or 3D vector from its components
+#[derive(Debug, Clone)]
+pub struct MakeVecNode {
+    dimensions: usize,
+    cached_result: Option<TypedData>,
+    label: Option<String>,
+}
+
+impl Default for MakeVecNode {
+    fn default() -> Self {
+        Self {
+            dimensions: 2,
+            cached_result: None,
+            label: None,
+        }
+    }
+}
+
+impl Node for MakeVecNode {
+    fn name(&self) -> String {
+        "Make Vector".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number; self.dimensions]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![vector_type(self.dimensions)]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.clone()
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        ui.label(COMPONENTS[idx]);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(value) = &self.cached_result {
+            show_value(value, ui);
+        }
+        false
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        show_dimensions(&mut self.dimensions, ui)
+    }
+
+    /// Unconnected components are zero
+    fn input_default(&self, _idx: usize) -> Option<TypedData> {
+        Some(TypedData::Number(0.))
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.dimensions.into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(dimensions) = restore_dimensions(params) {
+            self.dimensions = dimensions;
+        }
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.cached_result.clone().map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [value @ (TypedData::Vec2(_) | TypedData::Vec3(_))] = outputs {
+            self.cached_result = Some(value.clone());
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let components = inputs
+            .iter()
+            .map(|input| match input {
+                TypedData::Number(value) => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        self.cached_result = match components.as_deref() {
+            Some(&[x, y]) => Some(TypedData::Vec2([x, y])),
+            Some(&[x, y, z]) => Some(TypedData::Vec3([x, y, z])),
+            _ => None,
+        };
+    }
+}
+
+/// Splits a 2D or 3D vector into its components
+#[derive(Debug, Clone)]
+pub struct SplitVecNode {
+    dimensions: usize,
+    cached_result: Option<Vec<f64>>,
+    label: Option<String>,
+}
+
+impl Default for SplitVecNode {
+    fn default() -> Self {
+        Self {
+            dimensions: 2,
+            cached_result: None,
+            label: None,
+        }
+    }
+}
+
+impl Node for SplitVecNode {
+    fn name(&self) -> String {
+        "Split Vector".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![vector_type(self.dimensions)]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number; self.dimensions]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        self.cached_result
+            .as_ref()
+            .and_then(|components| components.get(idx))
+            .copied()
+            .map(TypedData::Number)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        if let Some(TypedData::Number(value)) = self.output_value(idx) {
+            ui.label(format_float(value));
+        }
+        ui.label(COMPONENTS[idx]);
+        false
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        show_dimensions(&mut self.dimensions, ui)
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.dimensions.into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(dimensions) = restore_dimensions(params) {
+            self.dimensions = dimensions;
+        }
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.cached_result
+            .as_ref()
+            .map(|components| components.iter().copied().map(TypedData::Number).collect())
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        self.cached_result = outputs
+            .iter()
+            .map(|output| match output {
+                TypedData::Number(value) => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|components| components.len() == self.dimensions);
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = match &inputs[0] {
+            TypedData::Vec2(vector) => Some(vector.to_vec()),
+            TypedData::Vec3(vector) => Some(vector.to_vec()),
+            _ => None,
+        };
+    }
+}
+
+/// Type of a vector with `dimensions` components
+fn vector_type(dimensions: usize) -> DataType {
+    if dimensions == 3 {
+        DataType::Vec3
+    } else {
+        DataType::Vec2
+    }
+}
+
+/// Lets a node switch between 2D and 3D vectors, returning whether it did
+fn show_dimensions(dimensions: &mut usize, ui: &mut Ui) -> bool {
+    ui.horizontal(|ui| {
+        ui.selectable_value(dimensions, 2, "2D").changed()
+            | ui.selectable_value(dimensions, 3, "3D").changed()
+    })
+    .inner
+}
+
+fn restore_dimensions(params: &serde_json::Value) -> Option<usize> {
+    params
+        .as_u64()
+        .filter(|dimensions| (2..=3).contains(dimensions))
+        .map(|dimensions| dimensions as usize)
+}
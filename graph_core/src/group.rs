@@ -0,0 +1,552 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    time::Duration,
+};
+
+use egui::{self, Ui};
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+
+use crate::{
+    context::UpdateContext,
+    graph_file::SavedGraph,
+    node_graph::{self, show_value, DataType, Node, TypedData},
+};
+
+/// A node wrapping a nested graph.
+///
+/// The group's inputs are provided to the nested graph through a
+/// [`GroupInputNode`], and its outputs are read from whatever is connected to
+/// the nested [`GroupOutputNode`]. Updating the group evaluates the whole
+/// nested graph, so values flow through it exactly as if it were flattened
+/// into the parent graph.
+pub struct GroupNode {
+    snarl: Snarl<Box<dyn Node>>,
+    input_node: NodeId,
+    output_node: NodeId,
+    /// Disabled groups skip evaluating their nested graph and produce no
+    /// outputs
+    enabled: bool,
+    /// Why the nested graph couldn't be evaluated, such as being nested too
+    /// deeply
+    failure: Option<String>,
+    label: Option<String>,
+}
+
+impl Default for GroupNode {
+    /// An empty group with no inputs or outputs
+    fn default() -> Self {
+        let mut snarl = Snarl::<Box<dyn Node>>::new();
+        let input_node = snarl.insert_node(egui::pos2(-200., 0.), Box::<GroupInputNode>::default());
+        let output_node =
+            snarl.insert_node(egui::pos2(200., 0.), Box::<GroupOutputNode>::default());
+        Self {
+            snarl,
+            input_node,
+            output_node,
+            enabled: true,
+            failure: None,
+            label: None,
+        }
+    }
+}
+
+impl GroupNode {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// The node inside the group that passes on its inputs, which is
+    /// missing if the user has removed it
+    fn input_proxy(&mut self) -> Option<&mut GroupInputNode> {
+        self.snarl
+            .get_node_mut(self.input_node)?
+            .as_any_mut()
+            .downcast_mut()
+    }
+}
+
+impl Node for GroupNode {
+    fn name(&self) -> String {
+        "Group".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        self.snarl
+            .get_node(self.input_node)
+            .map_or_else(Vec::new, |node| node.outputs())
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.snarl
+            .get_node(self.output_node)
+            .map_or_else(Vec::new, |node| node.inputs())
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        if !self.enabled || self.failure.is_some() {
+            return None;
+        }
+        let inpin = self.snarl.in_pin(InPinId {
+            node: self.output_node,
+            input: idx,
+        });
+        let remote = inpin.remotes.first()?;
+        self.snarl[remote.node].output_value(remote.output)
+    }
+
+    fn reset(&mut self) {
+        node_graph::reset_state(&mut self.snarl);
+    }
+
+    /// Errors are passed into the nested graph, and out through the outputs
+    /// that depend on them
+    fn accepts_errors(&self) -> bool {
+        true
+    }
+
+    /// Nodes are updated one at a time, so the group takes as long as all
+    /// of them together
+    fn estimated_cost(&self) -> Option<Duration> {
+        self.snarl
+            .node_ids()
+            .filter_map(|(_, node)| node.estimated_cost())
+            .reduce(|total, cost| total + cost)
+    }
+
+    fn update(&mut self, inputs: &[TypedData], ctx: &mut UpdateContext) {
+        if !self.enabled {
+            return;
+        }
+        // Stop before the stack runs out, however the groups were nested
+        let eval = ctx.eval();
+        if eval.depth >= eval.max_depth {
+            self.failure = Some(format!(
+                "Groups are nested more than {} deep",
+                eval.max_depth
+            ));
+            return;
+        }
+        let Some(input_proxy) = self.input_proxy() else {
+            self.failure = Some("The group's inputs node has been removed".to_owned());
+            return;
+        };
+        input_proxy.values = inputs.iter().cloned().map(Some).collect();
+        self.failure = None;
+        // Every nested node is evaluated, not only those fed by the inputs,
+        // so that nodes edited or added inside the group are brought up to
+        // date along with it
+        let report = node_graph::evaluate_with(&mut self.snarl, None, &eval.nested());
+        for message in report.messages {
+            ctx.log(
+                message.level,
+                format!("node {}: {}", message.node.0, message.message),
+            );
+        }
+    }
+
+    fn show_input(&mut self, _idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        if let Some(value) = self.output_value(idx) {
+            show_value(&value, ui);
+        }
+        false
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({
+            "graph": SavedGraph::from_snarl(&self.snarl),
+            "input_node": self.input_node.0,
+            "output_node": self.output_node.0,
+            "enabled": self.enabled,
+        })
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        let Ok(graph) = serde_json::from_value::<SavedGraph>(params["graph"].clone()) else {
+            return;
+        };
+        let (Some(input_node), Some(output_node)) = (
+            params["input_node"].as_u64(),
+            params["output_node"].as_u64(),
+        ) else {
+            return;
+        };
+        let (input_node, output_node) = (NodeId(input_node as usize), NodeId(output_node as usize));
+        if let Ok(snarl) = graph.to_snarl() {
+            // Groups can only be evaluated through their own inputs and
+            // outputs nodes, so a file that names other nodes is ignored
+            let valid = snarl
+                .get_node(input_node)
+                .is_some_and(|node| node.as_any().is::<GroupInputNode>())
+                && snarl
+                    .get_node(output_node)
+                    .is_some_and(|node| node.as_any().is::<GroupOutputNode>());
+            if valid {
+                self.snarl = snarl;
+                self.input_node = input_node;
+                self.output_node = output_node;
+            }
+        }
+        self.enabled = params["enabled"].as_bool().unwrap_or(true);
+    }
+
+    fn error(&self) -> Option<String> {
+        if !self.enabled {
+            return Some("Group is disabled".to_owned());
+        }
+        self.failure.clone()
+    }
+
+    fn subgraph(&self) -> Option<&Snarl<Box<dyn Node>>> {
+        Some(&self.snarl)
+    }
+
+    fn subgraph_mut(&mut self) -> Option<&mut Snarl<Box<dyn Node>>> {
+        Some(&mut self.snarl)
+    }
+}
+
+/// Exposes the inputs of the enclosing group inside its nested graph
+pub struct GroupInputNode {
+    types: Vec<DataType>,
+    values: Vec<Option<TypedData>>,
+    label: Option<String>,
+}
+
+impl GroupInputNode {
+    fn new(types: Vec<DataType>) -> Self {
+        Self {
+            values: vec![None; types.len()],
+            types,
+            label: None,
+        }
+    }
+}
+
+impl Default for GroupInputNode {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Node for GroupInputNode {
+    fn name(&self) -> String {
+        "Group Inputs".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.types.clone()
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        self.values.get(idx).cloned().flatten()
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        if let Some(value) = self.output_value(idx) {
+            show_value(&value, ui);
+        }
+        false
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.types).unwrap_or_default()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Ok(types) = serde_json::from_value::<Vec<DataType>>(params.clone()) {
+            self.values = vec![None; types.len()];
+            self.types = types;
+        }
+    }
+}
+
+/// Collects the outputs of the enclosing group inside its nested graph
+#[derive(Default)]
+pub struct GroupOutputNode {
+    types: Vec<DataType>,
+    label: Option<String>,
+}
+
+impl Node for GroupOutputNode {
+    fn name(&self) -> String {
+        "Group Outputs".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        self.types.clone()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    /// The group gives errors on its outputs, rather than failing inside
+    fn accepts_errors(&self) -> bool {
+        true
+    }
+
+    fn show_input(&mut self, _idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.types).unwrap_or_default()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Ok(types) = serde_json::from_value(params.clone()) {
+            self.types = types;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollapseError {
+    NothingSelected,
+    /// A path from one selected node to another leaves the selection through
+    /// this node, so the group would both feed it and be fed by it
+    WouldCreateCycle(NodeId),
+}
+
+impl fmt::Display for CollapseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollapseError::NothingSelected => write!(f, "no nodes are selected"),
+            CollapseError::WouldCreateCycle(node) => write!(
+                f,
+                "can't group the selection, as it is wired through node {} outside of it",
+                node.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CollapseError {}
+
+/// A node outside of `selection` that is on a path between two of its
+/// nodes. Grouping the selection would make a cycle through it.
+fn path_outside(snarl: &Snarl<Box<dyn Node>>, selection: &BTreeSet<NodeId>) -> Option<NodeId> {
+    let dependents = |node: NodeId| {
+        (0..snarl[node].outputs().len())
+            .flat_map(move |output| snarl.out_pin(OutPinId { node, output }).remotes)
+            .map(|remote| remote.node)
+    };
+    // Outside nodes reachable from the selection without passing through it
+    let mut reached = BTreeSet::new();
+    let mut stack = selection
+        .iter()
+        .flat_map(|node| dependents(*node))
+        .filter(|node| !selection.contains(node))
+        .collect::<Vec<_>>();
+    while let Some(node) = stack.pop() {
+        if !reached.insert(node) {
+            continue;
+        }
+        for next in dependents(node) {
+            if selection.contains(&next) {
+                return Some(node);
+            }
+            stack.push(next);
+        }
+    }
+    None
+}
+
+/// Moves the given nodes into a new group node, returning the group's id.
+///
+/// Every distinct outside output feeding the selection becomes one group
+/// input, and every distinct selected output feeding the outside becomes one
+/// group output. The graph is left unchanged if grouping the selection would
+/// make a cycle.
+pub fn collapse_into_group(
+    snarl: &mut Snarl<Box<dyn Node>>,
+    selection: &BTreeSet<NodeId>,
+) -> Result<NodeId, CollapseError> {
+    if selection.is_empty() {
+        return Err(CollapseError::NothingSelected);
+    }
+    if let Some(node) = path_outside(snarl, selection) {
+        return Err(CollapseError::WouldCreateCycle(node));
+    }
+
+    let positions = snarl
+        .nodes_pos_ids()
+        .filter(|(id, _, _)| selection.contains(id))
+        .map(|(id, pos, _)| (id, pos))
+        .collect::<BTreeMap<_, _>>();
+
+    // Classify every wire touching the selection
+    let mut internal = Vec::new();
+    let mut incoming = Vec::new();
+    let mut outgoing = Vec::new();
+    for &node in selection {
+        for input in 0..snarl[node].inputs().len() {
+            let inpin = snarl.in_pin(InPinId { node, input });
+            for remote in inpin.remotes {
+                if selection.contains(&remote.node) {
+                    internal.push((remote, inpin.id));
+                } else {
+                    incoming.push((remote, inpin.id));
+                }
+            }
+        }
+        for output in 0..snarl[node].outputs().len() {
+            let outpin = snarl.out_pin(OutPinId { node, output });
+            for remote in outpin.remotes {
+                if !selection.contains(&remote.node) {
+                    outgoing.push((outpin.id, remote));
+                }
+            }
+        }
+    }
+
+    let mut group_inputs = Vec::<OutPinId>::new();
+    for (source, _) in &incoming {
+        if !group_inputs.contains(source) {
+            group_inputs.push(*source);
+        }
+    }
+    let mut group_outputs = Vec::<OutPinId>::new();
+    for (source, _) in &outgoing {
+        if !group_outputs.contains(source) {
+            group_outputs.push(*source);
+        }
+    }
+    let input_types = group_inputs
+        .iter()
+        .map(|pin| snarl[pin.node].outputs()[pin.output].clone())
+        .collect::<Vec<_>>();
+    let output_types = group_outputs
+        .iter()
+        .map(|pin| snarl[pin.node].outputs()[pin.output].clone())
+        .collect::<Vec<_>>();
+
+    // Move the selected nodes into the nested graph
+    let mut inner = Snarl::new();
+    let mut id_map = BTreeMap::new();
+    for (&node, &pos) in &positions {
+        let value = snarl.remove_node(node);
+        id_map.insert(node, inner.insert_node(pos, value));
+    }
+    for (from, to) in internal {
+        inner.connect(
+            OutPinId {
+                node: id_map[&from.node],
+                output: from.output,
+            },
+            InPinId {
+                node: id_map[&to.node],
+                input: to.input,
+            },
+        );
+    }
+
+    let bounds = egui::Rect::from_points(&positions.values().copied().collect::<Vec<_>>());
+    let input_node = inner.insert_node(
+        bounds.left_center() - egui::vec2(200., 0.),
+        Box::new(GroupInputNode::new(input_types)),
+    );
+    let output_node = inner.insert_node(
+        bounds.right_center() + egui::vec2(200., 0.),
+        Box::new(GroupOutputNode {
+            types: output_types,
+            label: None,
+        }),
+    );
+    for (source, target) in &incoming {
+        let output = group_inputs.iter().position(|pin| pin == source).unwrap();
+        inner.connect(
+            OutPinId {
+                node: input_node,
+                output,
+            },
+            InPinId {
+                node: id_map[&target.node],
+                input: target.input,
+            },
+        );
+    }
+    for (input, source) in group_outputs.iter().enumerate() {
+        inner.connect(
+            OutPinId {
+                node: id_map[&source.node],
+                output: source.output,
+            },
+            InPinId {
+                node: output_node,
+                input,
+            },
+        );
+    }
+
+    // Replace the selection with the group in the outer graph
+    let group = snarl.insert_node(
+        bounds.center(),
+        Box::new(GroupNode {
+            snarl: inner,
+            input_node,
+            output_node,
+            enabled: true,
+            failure: None,
+            label: None,
+        }),
+    );
+    for (input, source) in group_inputs.iter().enumerate() {
+        snarl.connect(*source, InPinId { node: group, input });
+    }
+    for (source, target) in outgoing {
+        let output = group_outputs.iter().position(|pin| *pin == source).unwrap();
+        snarl.connect(
+            OutPinId {
+                node: group,
+                output,
+            },
+            target,
+        );
+    }
+
+    Ok(group)
+}
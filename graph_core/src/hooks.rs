@@ -0,0 +1,201 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use egui_snarl::{InPinId, NodeId, Snarl};
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    node_graph::{self, Node, TypedData},
+    run_report::RunReport,
+};
+
+/// Upper bound on the work a hook can do, so that a runaway script can't
+/// stall the engine forever
+const MAX_OPERATIONS: u64 = 100_000_000;
+
+/// [Rhai](https://rhai.rs) scripts run by the engine before and after each
+/// evaluation of a graph.
+///
+/// Every hook can read `params`, a map from node id to the node's saved
+/// parameters. Changes the before-run hook makes to `params` are applied to
+/// the graph before it is evaluated. The after-run hook can also read
+/// `outputs`, mapping node ids to arrays of output values, `sinks`, mapping
+/// the ids of sinks to the values reaching their inputs, and `failures`,
+/// mapping the ids of failed nodes to their errors.
+///
+/// Scripts can call `read_file(path)` and `write_file(path, text)`, and
+/// anything they `print` is returned to be logged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before_run: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_run: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct HookError {
+    pub hook: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hook failed: {}", self.hook, self.message)
+    }
+}
+
+impl std::error::Error for HookError {}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.before_run.is_none() && self.after_run.is_none()
+    }
+
+    /// Runs the before-run hook, if there is one, returning what it printed
+    pub fn run_before(&self, snarl: &mut Snarl<Box<dyn Node>>) -> Result<Vec<String>, HookError> {
+        let Some(script) = &self.before_run else {
+            return Ok(Vec::new());
+        };
+        let error = |message: String| HookError {
+            hook: "before-run",
+            message,
+        };
+
+        let mut scope = Scope::new();
+        scope.push(
+            "params",
+            params(snarl).map_err(|err| error(err.to_string()))?,
+        );
+        let printed = run(script, &mut scope).map_err(|err| error(err.to_string()))?;
+
+        let params = scope.get_value::<Map>("params").unwrap_or_default();
+        for (id, value) in params {
+            let Some(node) = id
+                .parse()
+                .ok()
+                .and_then(|id| snarl.get_node_mut(NodeId(id)))
+            else {
+                return Err(error(format!("there is no node {}", id)));
+            };
+            let value = rhai::serde::from_dynamic::<serde_json::Value>(&value)
+                .map_err(|err| error(err.to_string()))?;
+            if value != node.save() {
+                node.restore(&value);
+            }
+        }
+        Ok(printed)
+    }
+
+    /// Runs the after-run hook, if there is one, returning what it printed
+    pub fn run_after(
+        &self,
+        snarl: &Snarl<Box<dyn Node>>,
+        report: &RunReport,
+    ) -> Result<Vec<String>, HookError> {
+        let Some(script) = &self.after_run else {
+            return Ok(Vec::new());
+        };
+        let error = |message: String| HookError {
+            hook: "after-run",
+            message,
+        };
+
+        let mut outputs = Map::new();
+        let mut sinks = Map::new();
+        for (id, node) in snarl.node_ids() {
+            let values = (0..node.outputs().len())
+                .map(|idx| {
+                    node.output_value(idx)
+                        .map_or(Dynamic::UNIT, |v| to_dynamic(&v))
+                })
+                .collect::<Vec<_>>();
+            if !values.is_empty() {
+                outputs.insert(id.0.to_string().into(), values.into());
+                continue;
+            }
+            let inputs = (0..node.inputs().len())
+                .map(|input| {
+                    node_graph::input_value(snarl, InPinId { node: id, input })
+                        .map_or(Dynamic::UNIT, |v| to_dynamic(&v))
+                })
+                .collect::<Vec<_>>();
+            sinks.insert(id.0.to_string().into(), inputs.into());
+        }
+        let failures = report
+            .failures
+            .iter()
+            .map(|failure| {
+                (
+                    failure.node.0.to_string().into(),
+                    failure.message.clone().into(),
+                )
+            })
+            .collect::<Map>();
+
+        let mut scope = Scope::new();
+        scope.push(
+            "params",
+            params(snarl).map_err(|err| error(err.to_string()))?,
+        );
+        scope.push("outputs", outputs);
+        scope.push("sinks", sinks);
+        scope.push("failures", failures);
+        run(script, &mut scope).map_err(|err| error(err.to_string()))
+    }
+}
+
+/// The saved parameters of every node, keyed by node id
+fn params(snarl: &Snarl<Box<dyn Node>>) -> Result<Map, Box<EvalAltResult>> {
+    snarl
+        .node_ids()
+        .map(|(id, node)| {
+            Ok((
+                id.0.to_string().into(),
+                rhai::serde::to_dynamic(node.save())?,
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn to_dynamic(value: &TypedData) -> Dynamic {
+    match value {
+        TypedData::Number(val) => (*val).into(),
+        TypedData::String(val) => val.clone().into(),
+        TypedData::Bool(val) => (*val).into(),
+        TypedData::Color(color) => color
+            .iter()
+            .map(|channel| Dynamic::from_float(*channel as f64 / 255.))
+            .collect::<Vec<_>>()
+            .into(),
+        TypedData::Vec2(vector) => vector.map(Dynamic::from_float).to_vec().into(),
+        TypedData::Vec3(vector) => vector.map(Dynamic::from_float).to_vec().into(),
+        TypedData::List(values) => values.iter().map(to_dynamic).collect::<Vec<_>>().into(),
+        TypedData::Unknown => Dynamic::UNIT,
+        TypedData::Error(error) => error.to_string().into(),
+    }
+}
+
+/// Runs a script, returning the lines it printed
+fn run(script: &str, scope: &mut Scope) -> Result<Vec<String>, Box<EvalAltResult>> {
+    let printed = Rc::new(RefCell::new(Vec::new()));
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print({
+        let printed = printed.clone();
+        move |line| printed.borrow_mut().push(line.to_owned())
+    });
+    engine.register_fn("read_file", read_file);
+    engine.register_fn("write_file", write_file);
+
+    engine.run_with_scope(scope, script)?;
+    Ok(printed.take())
+}
+
+fn read_file(path: &str) -> Result<String, Box<EvalAltResult>> {
+    std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err).into())
+}
+
+fn write_file(path: &str, contents: &str) -> Result<(), Box<EvalAltResult>> {
+    std::fs::write(path, contents).map_err(|err| format!("{}: {}", path, err).into())
+}
@@ -0,0 +1,395 @@
+//! Making HTTP requests from a graph. Requests are sent on a background
+//! thread, so that evaluating the graph doesn't wait for the response, and
+//! the node is evaluated again once it arrives.
+
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+use egui::{self, Ui};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, Node, TypedData},
+};
+
+/// How long to wait to connect, and then for each read of the response
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest response read, headers included, so that a large or endless
+/// response can't use up the editor's memory
+pub const MAX_RESPONSE_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub method: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// Sends the request and waits for the response. Only plain `http://`
+    /// URLs are supported.
+    pub fn send(&self) -> Result<HttpResponse, String> {
+        let (authority, path) = split_url(&self.url)?;
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| format!("Invalid port {:?}", port))?,
+            ),
+            None => (authority, 80),
+        };
+
+        let error = |err: std::io::Error| format!("{}: {}", self.url, err);
+        let address = (host, port)
+            .to_socket_addrs()
+            .map_err(error)?
+            .next()
+            .ok_or_else(|| format!("{}: host not found", self.url))?;
+        let mut stream = TcpStream::connect_timeout(&address, TIMEOUT).map_err(error)?;
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(error)?;
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            self.method.to_uppercase(),
+            path,
+            authority,
+            self.body.len(),
+            self.body
+        )
+        .map_err(error)?;
+        let mut response = Vec::new();
+        stream
+            .take(MAX_RESPONSE_SIZE + 1)
+            .read_to_end(&mut response)
+            .map_err(error)?;
+        if response.len() as u64 > MAX_RESPONSE_SIZE {
+            return Err(format!(
+                "{}: response is larger than {} bytes",
+                self.url, MAX_RESPONSE_SIZE
+            ));
+        }
+        parse_response(&response).ok_or_else(|| format!("{}: malformed response", self.url))
+    }
+}
+
+/// Splits a URL into the host and port to connect to, and the path and
+/// query to request from it
+fn split_url(url: &str) -> Result<(&str, String), String> {
+    let rest = match url.split_once("://") {
+        Some(("http", rest)) => rest,
+        Some((scheme, _)) => return Err(format!("{} URLs aren't supported", scheme)),
+        None => url,
+    };
+    let (authority, path) = match rest.find(['/', '?', '#']) {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+    // Fragments are never sent to the server
+    let path = path.split('#').next().unwrap_or_default();
+    let path = if path.starts_with('/') {
+        path.to_owned()
+    } else {
+        format!("/{}", path)
+    };
+    Ok((authority, path))
+}
+
+fn parse_response(response: &[u8]) -> Option<HttpResponse> {
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&response[..split]).ok()?;
+    let mut body = &response[split + 4..];
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+
+    let mut chunked = false;
+    for line in lines {
+        // Lines that aren't headers, such as obsolete folded ones, are
+        // skipped rather than failing the whole response
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            let length = value.parse::<usize>().ok()?;
+            body = body.get(..length)?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.eq_ignore_ascii_case("chunked");
+        }
+    }
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_vec()
+    };
+    Some(HttpResponse {
+        status,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// Joins the chunks of a body sent with `Transfer-Encoding: chunked`
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut joined = Vec::new();
+    loop {
+        let end = body.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..end]).ok()?;
+        // Chunk sizes can be followed by extensions, which are ignored
+        let size = size.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        if size == 0 {
+            return Some(joined);
+        }
+        let chunk = body.get(end + 2..end + 2 + size)?;
+        joined.extend_from_slice(chunk);
+        body = body.get(end + 4 + size..)?;
+    }
+}
+
+/// Sends an HTTP request and outputs the response's body and status. The
+/// request is sent in the background when the node's inputs change, and
+/// the node fails until the response arrives.
+///
+/// Only plain `http://` URLs can be requested, as there is no TLS support,
+/// so `https://` URLs fail with an error. Responses larger than
+/// [`MAX_RESPONSE_SIZE`] fail too.
+#[derive(Debug, Default)]
+pub struct HttpRequestNode {
+    /// Request being waited on
+    pending: Option<(HttpRequest, Receiver<Result<HttpResponse, String>>)>,
+    /// Last request to get a response, and the response
+    response: Option<(HttpRequest, Result<HttpResponse, String>)>,
+    label: Option<String>,
+}
+
+impl HttpRequestNode {
+    fn response(&self) -> Option<&HttpResponse> {
+        self.response.as_ref()?.1.as_ref().ok()
+    }
+
+    fn start(&mut self, request: HttpRequest) {
+        let (sender, receiver) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("http request".to_owned())
+            .spawn({
+                let request = request.clone();
+                move || {
+                    let _ = sender.send(request.send());
+                }
+            });
+        match spawned {
+            Ok(_) => self.pending = Some((request, receiver)),
+            Err(err) => self.response = Some((request, Err(err.to_string()))),
+        }
+    }
+}
+
+impl Node for HttpRequestNode {
+    fn name(&self) -> String {
+        "HTTP Request".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::String, DataType::String, DataType::String]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::String, DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        let response = self.response()?;
+        match idx {
+            0 => Some(TypedData::String(response.body.clone())),
+            1 => Some(TypedData::Number(response.status as f64)),
+            _ => None,
+        }
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        let name = ui.label(["URL", "Method", "Body"][idx]);
+        if idx == 0 {
+            name.on_hover_text("Only http:// URLs are supported, not https://");
+        }
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        match (idx, self.response()) {
+            (0, Some(response)) => {
+                ui.label(format!("{} bytes", response.body.len()));
+            }
+            (1, Some(response)) => {
+                ui.label(format_float(response.status as f64));
+            }
+            _ => {}
+        }
+        ui.label(["Body", "Status"][idx]);
+        false
+    }
+
+    fn input_default(&self, idx: usize) -> Option<TypedData> {
+        match idx {
+            1 => Some(TypedData::String("GET".to_owned())),
+            2 => Some(TypedData::String(String::new())),
+            _ => None,
+        }
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        if self.pending.is_some() {
+            ui.spinner();
+            return false;
+        }
+        let refresh = ui
+            .add_enabled(self.response.is_some(), egui::Button::new("⟳"))
+            .on_hover_text("Send the request again")
+            .clicked();
+        if refresh {
+            self.response = None;
+        }
+        refresh
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let [TypedData::String(url), TypedData::String(method), TypedData::String(body)] = inputs
+        else {
+            self.response = None;
+            return;
+        };
+        let request = HttpRequest {
+            url: url.clone(),
+            method: method.clone(),
+            body: body.clone(),
+        };
+        let answered = self
+            .response
+            .as_ref()
+            .is_some_and(|(answered, _)| *answered == request);
+        let waiting = self
+            .pending
+            .as_ref()
+            .is_some_and(|(pending, _)| *pending == request);
+        if !answered && !waiting {
+            self.response = None;
+            self.start(request);
+        }
+    }
+
+    fn error(&self) -> Option<String> {
+        if self.pending.is_some() {
+            return Some("Waiting for the response".to_owned());
+        }
+        match &self.response {
+            Some((_, Err(err))) => Some(err.clone()),
+            _ => None,
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn poll_pending(&mut self) -> bool {
+        let Some((request, receiver)) = &self.pending else {
+            return false;
+        };
+        let response = match receiver.try_recv() {
+            Ok(response) => response,
+            Err(TryRecvError::Empty) => return false,
+            Err(TryRecvError::Disconnected) => Err("The request was lost".to_owned()),
+        };
+        self.response = Some((request.clone(), response));
+        self.pending = None;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(body: &str) -> Option<HttpResponse> {
+        Some(HttpResponse {
+            status: 200,
+            body: body.to_owned(),
+        })
+    }
+
+    #[test]
+    fn bodies_are_cut_to_their_content_length() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello, world";
+        assert_eq!(parse_response(response), ok("hello"));
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 50\r\n\r\nhello";
+        assert_eq!(parse_response(response), None);
+    }
+
+    #[test]
+    fn chunked_bodies_are_joined() {
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+            5;ext=1\r\nhello\r\n7\r\n, world\r\n0\r\n\r\n";
+        assert_eq!(parse_response(response), ok("hello, world"));
+        let truncated = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel";
+        assert_eq!(parse_response(truncated), None);
+    }
+
+    #[test]
+    fn lines_that_arent_headers_are_skipped() {
+        let response = b"HTTP/1.1 200 OK\r\nServer: test\r\n folded\r\n\r\nhello";
+        assert_eq!(parse_response(response), ok("hello"));
+    }
+
+    #[test]
+    fn malformed_responses_are_refused() {
+        for response in [
+            &b""[..],
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5",
+            b"HTTP/1.1\r\n\r\n",
+            b"HTTP/1.1 OK 200\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: five\r\n\r\nhello",
+        ] {
+            assert_eq!(parse_response(response), None, "{:?}", response);
+        }
+    }
+
+    #[test]
+    fn urls_are_split_into_authority_and_target() {
+        for (url, authority, target) in [
+            ("http://host", "host", "/"),
+            ("http://host:8080/a/b?x=1", "host:8080", "/a/b?x=1"),
+            ("http://host?x=1", "host", "/?x=1"),
+            ("http://host#top", "host", "/"),
+            ("host/a#top", "host", "/a"),
+        ] {
+            assert_eq!(split_url(url), Ok((authority, target.to_owned())));
+        }
+        assert!(split_url("https://host").is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! Translations of the text shown to the user: node names, pane titles, menu
+//! entries and validation messages.
+//!
+//! Each language is a catalogue of messages written in a subset of the
+//! [Fluent](https://projectfluent.org) syntax, one `id = text` per line with
+//! `#` comments, where `{ $name }` is replaced by an argument of the same
+//! name. The catalogues are built into the application, in `locales/`. A
+//! message missing from a catalogue is shown in English, and one missing
+//! from that too is shown as its id.
+//!
+//! Nodes are still saved and looked up by [`Node::name`](crate::node_graph::Node::name),
+//! which stays in English; [`node_name`] translates it for display.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{OnceLock, RwLock},
+};
+
+/// A language the application has been translated into
+#[derive(Debug)]
+pub struct Language {
+    /// Language code, such as `de`
+    pub code: &'static str,
+    /// Name of the language in that language
+    pub name: &'static str,
+    source: &'static str,
+}
+
+/// Every language with a built in catalogue. English comes first, and is
+/// used for messages missing from the others.
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        name: "English",
+        source: include_str!("../locales/en.ftl"),
+    },
+    Language {
+        code: "de",
+        name: "Deutsch",
+        source: include_str!("../locales/de.ftl"),
+    },
+    Language {
+        code: "fr",
+        name: "Français",
+        source: include_str!("../locales/fr.ftl"),
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogueError {
+    /// A line that is neither a message, a comment nor blank
+    Syntax { line: usize },
+    /// A message defined twice
+    Duplicate { line: usize, id: String },
+}
+
+impl fmt::Display for CatalogueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogueError::Syntax { line } => {
+                write!(f, "line {}: expected <id> = <text>", line)
+            }
+            CatalogueError::Duplicate { line, id } => {
+                write!(f, "line {}: message {:?} is already defined", line, id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CatalogueError {}
+
+/// The messages of one language, by id
+#[derive(Debug, Clone, Default)]
+pub struct Catalogue {
+    messages: BTreeMap<String, String>,
+}
+
+impl Catalogue {
+    pub fn parse(source: &str) -> Result<Self, CatalogueError> {
+        let mut messages = BTreeMap::new();
+        for (idx, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (id, text) = line
+                .split_once('=')
+                .ok_or(CatalogueError::Syntax { line: idx + 1 })?;
+            let id = id.trim();
+            let valid_id = id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if id.is_empty() || !valid_id {
+                return Err(CatalogueError::Syntax { line: idx + 1 });
+            }
+            if messages
+                .insert(id.to_owned(), text.trim().to_owned())
+                .is_some()
+            {
+                return Err(CatalogueError::Duplicate {
+                    line: idx + 1,
+                    id: id.to_owned(),
+                });
+            }
+        }
+        Ok(Self { messages })
+    }
+
+    /// The text of a message, with its placeholders left in
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.messages.get(id).map(String::as_str)
+    }
+
+    /// The text of a message, with each `{ $name }` replaced by the argument
+    /// of that name. Placeholders without an argument are left as they are.
+    pub fn format(&self, id: &str, args: &[(&str, &dyn fmt::Display)]) -> Option<String> {
+        Some(substitute(self.get(id)?, args))
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+}
+
+fn substitute(text: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeholder = &rest[start..start + end + 1];
+        let name = placeholder[1..placeholder.len() - 1].trim();
+        match name
+            .strip_prefix('$')
+            .and_then(|name| args.iter().find(|(arg, _)| *arg == name))
+        {
+            Some((_, value)) => result.push_str(&value.to_string()),
+            None => result.push_str(placeholder),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+struct Translations {
+    language: &'static Language,
+    catalogue: Catalogue,
+    english: Catalogue,
+}
+
+impl Translations {
+    fn new(language: &'static Language) -> Self {
+        Self {
+            language,
+            catalogue: load(language),
+            english: load(&LANGUAGES[0]),
+        }
+    }
+
+    fn format(&self, id: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        self.catalogue
+            .format(id, args)
+            .or_else(|| self.english.format(id, args))
+            .unwrap_or_else(|| id.to_owned())
+    }
+}
+
+/// A built in catalogue, which is left empty if it fails to parse so that
+/// its messages fall back to English
+fn load(language: &Language) -> Catalogue {
+    Catalogue::parse(language.source).unwrap_or_default()
+}
+
+fn find(code: &str) -> Option<&'static Language> {
+    LANGUAGES
+        .iter()
+        .find(|language| language.code.eq_ignore_ascii_case(code))
+}
+
+/// `GRAPH_GUI_LANG` if set, otherwise the language of `LANG`, such as `de`
+/// for `de_DE.UTF-8`, if there is a catalogue for it
+fn default_language() -> &'static Language {
+    ["GRAPH_GUI_LANG", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find_map(|value| {
+            let code = value.split(['_', '.', '-']).next().unwrap_or_default();
+            find(code)
+        })
+        .unwrap_or(&LANGUAGES[0])
+}
+
+fn global() -> &'static RwLock<Translations> {
+    static TRANSLATIONS: OnceLock<RwLock<Translations>> = OnceLock::new();
+    TRANSLATIONS.get_or_init(|| RwLock::new(Translations::new(default_language())))
+}
+
+/// The language the application is shown in
+pub fn language() -> &'static Language {
+    global().read().unwrap().language
+}
+
+/// Shows the application in another language, returning false if there is
+/// no catalogue for `code`
+pub fn set_language(code: &str) -> bool {
+    let Some(language) = find(code) else {
+        return false;
+    };
+    let mut translations = global().write().unwrap();
+    if translations.language.code != language.code {
+        *translations = Translations::new(language);
+    }
+    true
+}
+
+/// A message in the current language
+pub fn tr(id: &str) -> String {
+    tr_args(id, &[])
+}
+
+/// A message in the current language, with its placeholders filled in
+pub fn tr_args(id: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    global().read().unwrap().format(id, args)
+}
+
+/// Id of the message for a name such as `Make Colour`, which is
+/// `<prefix>-make-colour`
+fn message_id(prefix: &str, name: &str) -> String {
+    let mut id = prefix.to_owned();
+    for word in name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        id.push('-');
+        id.push_str(&word.to_lowercase());
+    }
+    id
+}
+
+/// Looks up a message made from a name, showing the name itself rather than
+/// the id when it hasn't been translated, as for the nodes of plugins
+fn translate_name(prefix: &str, name: &str) -> String {
+    let id = message_id(prefix, name);
+    let translations = global().read().unwrap();
+    translations
+        .catalogue
+        .get(&id)
+        .or_else(|| translations.english.get(&id))
+        .map_or_else(|| name.to_owned(), str::to_owned)
+}
+
+/// The name of a type of node, from [`Node::name`](crate::node_graph::Node::name),
+/// in the current language
+pub fn node_name(name: &str) -> String {
+    translate_name("node", name)
+}
+
+/// The name of a category of nodes, from
+/// [`NodeKind::category`](crate::registry::NodeKind::category), in the
+/// current language
+pub fn category_name(category: &str) -> String {
+    translate_name("category", category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_language_has_every_english_message() {
+        let english = Catalogue::parse(LANGUAGES[0].source).unwrap();
+        for language in &LANGUAGES[1..] {
+            let catalogue = Catalogue::parse(language.source).unwrap();
+            let missing = english
+                .ids()
+                .filter(|id| catalogue.get(id).is_none())
+                .collect::<Vec<_>>();
+            assert!(
+                missing.is_empty(),
+                "{} is missing {:?}",
+                language.code,
+                missing
+            );
+        }
+    }
+}
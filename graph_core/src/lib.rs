@@ -0,0 +1,49 @@
+//! Node graph model and execution engine, usable without the editor GUI
+
+pub mod adjacency;
+pub mod args;
+pub mod background;
+pub mod builder;
+pub mod bypass;
+pub mod cache;
+pub mod checkpoint;
+pub mod command;
+pub mod comment;
+pub mod context;
+pub mod convert_nodes;
+pub mod cron;
+pub mod diff;
+pub mod errors;
+pub mod execution_engine;
+pub mod export;
+pub mod expression;
+pub mod file_nodes;
+pub mod flow_nodes;
+pub mod graph_file;
+pub mod graphics_nodes;
+pub mod group;
+pub mod hooks;
+pub mod http;
+pub mod i18n;
+pub mod list_nodes;
+pub mod node_graph;
+pub mod observer;
+pub mod package;
+pub mod pipeline;
+pub mod plot;
+pub mod plugins;
+pub mod profile;
+pub mod profiler;
+pub mod registry;
+pub mod run_report;
+pub mod schedule;
+pub mod script;
+pub mod secrets;
+pub mod snapshot;
+pub mod stages;
+pub mod state_nodes;
+pub mod sweep;
+pub mod table;
+pub mod template;
+pub mod trace;
+pub mod validation;
@@ -0,0 +1,411 @@
+use egui::{self, Ui};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, Node, TypedData},
+};
+
+/// Largest list a [`RangeNode`] will produce
+const MAX_RANGE_LEN: usize = 100_000;
+
+fn number_list() -> DataType {
+    DataType::List(Box::new(DataType::Number))
+}
+
+fn numbers(value: &TypedData) -> Option<Vec<f64>> {
+    let TypedData::List(values) = value else {
+        return None;
+    };
+    values
+        .iter()
+        .map(|value| match value {
+            TypedData::Number(val) => Some(*val),
+            _ => None,
+        })
+        .collect()
+}
+
+fn to_list(values: impl IntoIterator<Item = f64>) -> TypedData {
+    TypedData::List(values.into_iter().map(TypedData::Number).collect())
+}
+
+/// Numbers from `start` up to (but not including) `end`
+#[derive(Debug, Clone, Default)]
+pub struct RangeNode {
+    cached_result: Option<Vec<f64>>,
+    label: Option<String>,
+}
+
+impl Node for RangeNode {
+    fn name(&self) -> String {
+        "Range".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number, DataType::Number, DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![number_list()]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.clone().map(to_list)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert!(idx < 3);
+        ui.label(["start", "end", "step"][idx]);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(value) = self.output_value(idx) {
+            show_value(&value, ui);
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [list] = outputs {
+            self.cached_result = numbers(list);
+        }
+    }
+
+    /// Counts up in ones from zero unless told otherwise
+    fn input_default(&self, idx: usize) -> Option<TypedData> {
+        match idx {
+            0 => Some(TypedData::Number(0.)),
+            2 => Some(TypedData::Number(1.)),
+            _ => None,
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let [TypedData::Number(start), TypedData::Number(end), TypedData::Number(step)] = inputs
+        else {
+            self.cached_result = None;
+            return;
+        };
+        if *step <= 0. || !step.is_finite() {
+            self.cached_result = None;
+            return;
+        }
+        self.cached_result = Some(
+            (0..MAX_RANGE_LEN)
+                .map(|i| start + i as f64 * step)
+                .take_while(|val| val < end)
+                .collect(),
+        );
+    }
+}
+
+/// A list of numbers entered by hand
+#[derive(Debug, Clone, Default)]
+pub struct ListLiteralNode {
+    values: Vec<f64>,
+    label: Option<String>,
+}
+
+impl Node for ListLiteralNode {
+    fn name(&self) -> String {
+        "List".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![number_list()]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        Some(to_list(self.values.iter().copied()))
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        let mut changed = false;
+        ui.vertical(|ui| {
+            let mut removed = None;
+            for (i, value) in self.values.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::DragValue::new(value)).changed();
+                    if ui.small_button("x").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = removed {
+                self.values.remove(i);
+                changed = true;
+            }
+            if ui.small_button("+").clicked() {
+                self.values.push(0.);
+                changed = true;
+            }
+        });
+        changed
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.values.clone().into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Ok(values) = serde_json::from_value(params.clone()) {
+            self.values = values;
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+/// Adds up every number in a list
+#[derive(Debug, Clone, Default)]
+pub struct SumNode {
+    cached_result: Option<f64>,
+    label: Option<String>,
+}
+
+impl Node for SumNode {
+    fn name(&self) -> String {
+        "Sum".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![number_list()]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(TypedData::Number)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(res) = self.cached_result {
+            ui.label(format_float(res));
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = numbers(&inputs[0]).map(|values| values.iter().sum());
+    }
+}
+
+/// Number of elements in a list of any type
+#[derive(Debug, Clone, Default)]
+pub struct LengthNode {
+    cached_result: Option<usize>,
+    label: Option<String>,
+}
+
+impl Node for LengthNode {
+    fn name(&self) -> String {
+        "Length".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::List(Box::new(DataType::Unknown))]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(|len| TypedData::Number(len as f64))
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(len) = self.cached_result {
+            ui.label(len.to_string());
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(value)] = outputs {
+            self.cached_result = Some(*value as usize);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = match &inputs[0] {
+            TypedData::List(values) => Some(values.len()),
+            _ => None,
+        };
+    }
+}
+
+/// Adds a number to every element of a list
+#[derive(Debug, Clone, Default)]
+pub struct MapAddNode {
+    cached_result: Option<Vec<f64>>,
+    label: Option<String>,
+}
+
+impl Node for MapAddNode {
+    fn name(&self) -> String {
+        "Map Add".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![number_list(), DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![number_list()]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.clone().map(to_list)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert!(idx < 2);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(value) = self.output_value(idx) {
+            show_value(&value, ui);
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [list] = outputs {
+            self.cached_result = numbers(list);
+        }
+    }
+
+    fn input_default(&self, idx: usize) -> Option<TypedData> {
+        (idx == 1).then_some(TypedData::Number(0.))
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = match (numbers(&inputs[0]), &inputs[1]) {
+            (Some(values), TypedData::Number(offset)) => {
+                Some(values.into_iter().map(|val| val + offset).collect())
+            }
+            _ => None,
+        };
+    }
+}
@@ -0,0 +1,1617 @@
+use std::{
+    any::Any,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    time::Duration,
+};
+
+use egui::{Color32, Ui};
+use egui_snarl::{ui::PinInfo, InPinId, NodeId, OutPinId, Snarl};
+use petgraph::Graph;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    adjacency::Adjacency,
+    cache::NodeCache,
+    context::{EvalContext, UpdateContext},
+    errors,
+    observer::TaskState,
+    profiler,
+    run_report::{NodeFailure, NodeMessage, NodeRun, OutputChange, RunReport},
+};
+
+const STRING_COLOR: Color32 = Color32::from_rgb(0x00, 0xb0, 0x00);
+const NUMBER_COLOR: Color32 = Color32::from_rgb(0xb0, 0x00, 0x00);
+const BOOL_COLOR: Color32 = Color32::from_rgb(0x00, 0x60, 0xd0);
+const COLOR_COLOR: Color32 = Color32::from_rgb(0xd0, 0xa0, 0x00);
+const VECTOR_COLOR: Color32 = Color32::from_rgb(0x90, 0x40, 0xc0);
+const UNTYPED_COLOR: Color32 = Color32::from_rgb(0xb0, 0xb0, 0xb0);
+
+/// Colours of the pins and wires carrying each type of data. Lists take the
+/// colour of their elements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypeColours {
+    pub number: Color32,
+    pub string: Color32,
+    pub bool: Color32,
+    pub color: Color32,
+    /// Colour of both 2D and 3D vectors
+    pub vector: Color32,
+    pub unknown: Color32,
+}
+
+impl Default for TypeColours {
+    fn default() -> Self {
+        Self {
+            number: NUMBER_COLOR,
+            string: STRING_COLOR,
+            bool: BOOL_COLOR,
+            color: COLOR_COLOR,
+            vector: VECTOR_COLOR,
+            unknown: UNTYPED_COLOR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TypedData {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    /// Red, green, blue and alpha, not premultiplied
+    Color([u8; 4]),
+    Vec2([f64; 2]),
+    Vec3([f64; 3]),
+    List(Vec<TypedData>),
+    Unknown,
+    /// Given in place of a value that couldn't be calculated, see
+    /// [`errors`](crate::errors)
+    Error(ErrorValue),
+}
+
+impl TypedData {
+    pub fn as_error(&self) -> Option<&ErrorValue> {
+        match self {
+            TypedData::Error(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Why a value couldn't be calculated, passed down the graph in its place
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorValue {
+    pub message: String,
+    /// Id of the node the error came from
+    pub origin: usize,
+}
+
+impl fmt::Display for ErrorValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (from node {})", self.message, self.origin)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataType {
+    Number,
+    String,
+    Bool,
+    Color,
+    Vec2,
+    Vec3,
+    /// A list whose elements are all of the given type
+    List(Box<DataType>),
+    Unknown,
+}
+
+impl DataType {
+    pub fn colour(&self, colours: &TypeColours) -> Color32 {
+        match self {
+            DataType::Number => colours.number,
+            DataType::String => colours.string,
+            DataType::Bool => colours.bool,
+            DataType::Color => colours.color,
+            DataType::Vec2 | DataType::Vec3 => colours.vector,
+            DataType::List(element) => element.colour(colours),
+            DataType::Unknown => colours.unknown,
+        }
+    }
+
+    pub fn pin_info(&self, colours: &TypeColours) -> PinInfo {
+        let info = match self {
+            DataType::Number => PinInfo::square(),
+            DataType::String => PinInfo::triangle(),
+            DataType::Bool => PinInfo::custom(|painter, rect, fill, stroke| {
+                let points = vec![
+                    rect.center_top(),
+                    rect.right_center(),
+                    rect.center_bottom(),
+                    rect.left_center(),
+                ];
+                painter.add(egui::Shape::convex_polygon(points, fill, stroke));
+            }),
+            DataType::Color => PinInfo::custom(|painter, rect, fill, stroke| {
+                let radius = rect.width().min(rect.height()) / 2.;
+                let points = (0..6)
+                    .map(|corner| {
+                        let angle = corner as f32 * std::f32::consts::TAU / 6.;
+                        rect.center() + radius * egui::Vec2::angled(angle)
+                    })
+                    .collect();
+                painter.add(egui::Shape::convex_polygon(points, fill, stroke));
+            }),
+            // A bar for each component
+            DataType::Vec2 => PinInfo::custom(|painter, rect, fill, stroke| {
+                vector_pin(painter, rect, 2, fill, stroke);
+            }),
+            DataType::Vec3 => PinInfo::custom(|painter, rect, fill, stroke| {
+                vector_pin(painter, rect, 3, fill, stroke);
+            }),
+            DataType::List(_) => PinInfo::custom(|painter, rect, fill, _stroke| {
+                painter.rect_stroke(rect.shrink(1.), 0., egui::Stroke::new(2., fill));
+            }),
+            DataType::Unknown => PinInfo::circle(),
+        };
+        info.with_fill(self.colour(colours))
+    }
+
+    /// Whether a value of this type can be passed to an input of the
+    /// `destination` type
+    pub fn compatible_with(&self, destination: &DataType) -> bool {
+        match (self, destination) {
+            (source, destination) if source == destination => true,
+            (_, DataType::Unknown) => true,
+            (DataType::List(source), DataType::List(destination)) => {
+                source.compatible_with(destination)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Draws a vector's pin as a stack of `components` bars
+fn vector_pin(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    components: usize,
+    fill: Color32,
+    stroke: egui::Stroke,
+) {
+    let height = rect.height() / components as f32;
+    for component in 0..components {
+        let top = rect.top() + height * component as f32;
+        let bar = egui::Rect::from_x_y_ranges(rect.x_range(), top..=top + height).shrink(0.5);
+        painter.rect(bar, 0., fill, stroke);
+    }
+}
+
+/// Allows concrete node types to be recovered from a `Box<dyn Node>`
+pub trait AsAny: Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Combines the values of every wire connected to an input into one. Values
+/// are combined in order of the id of the node they come from, then the
+/// index of its output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reducer {
+    /// Adds up numbers
+    Sum,
+    Min,
+    Max,
+    /// Whether every boolean is true
+    All,
+    /// Whether any boolean is true
+    Any,
+    /// Makes a list of the values
+    Collect,
+}
+
+impl Reducer {
+    /// Combines `values`, or returns `None` if they are the wrong type
+    pub fn reduce(self, values: Vec<TypedData>) -> Option<TypedData> {
+        let numbers = || {
+            values
+                .iter()
+                .map(|value| match value {
+                    TypedData::Number(value) => Some(*value),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+        let bools = || {
+            values
+                .iter()
+                .map(|value| match value {
+                    TypedData::Bool(value) => Some(*value),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+        match self {
+            Reducer::Sum => Some(TypedData::Number(numbers()?.into_iter().sum())),
+            Reducer::Min => numbers()?
+                .into_iter()
+                .reduce(f64::min)
+                .map(TypedData::Number),
+            Reducer::Max => numbers()?
+                .into_iter()
+                .reduce(f64::max)
+                .map(TypedData::Number),
+            Reducer::All => Some(TypedData::Bool(bools()?.into_iter().all(|value| value))),
+            Reducer::Any => Some(TypedData::Bool(bools()?.into_iter().any(|value| value))),
+            Reducer::Collect => Some(TypedData::List(values)),
+        }
+    }
+}
+
+/// The part a node plays in evaluating its graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+    /// Produces values without needing inputs, and can be triggered on its
+    /// own to re-evaluate everything downstream of it
+    Source,
+    /// Calculates its outputs from its inputs
+    Transform,
+    /// Consumes the graph's results. A run is complete once every sink it
+    /// reached has been updated.
+    Sink,
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeRole::Source => write!(f, "Source"),
+            NodeRole::Transform => write!(f, "Transform"),
+            NodeRole::Sink => write!(f, "Sink"),
+        }
+    }
+}
+
+pub trait Node: AsAny {
+    fn name(&self) -> String;
+    /// Name given to the node by the user, shown in place of its id
+    fn label(&self) -> Option<&str> {
+        None
+    }
+    fn set_label(&mut self, label: Option<String>) {
+        let _ = label;
+    }
+    fn inputs(&self) -> Vec<DataType>;
+    fn outputs(&self) -> Vec<DataType>;
+    /// Returns none if the value is not available yet
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        let _ = idx;
+        None
+    }
+    /// Recalculates the node's outputs from its inputs. `ctx` gives access
+    /// to the rest of the evaluation, such as the graph's variables.
+    fn update(&mut self, inputs: &[TypedData], ctx: &mut UpdateContext) {
+        let _ = (inputs, ctx);
+    }
+    /// Indices of the inputs needed to update this node, given the inputs
+    /// that have been evaluated so far. Inputs that are never requested are
+    /// passed to [`Node::update`] as [`TypedData::Unknown`].
+    fn required_inputs(&self, inputs: &[Option<TypedData>]) -> Vec<usize> {
+        (0..inputs.len()).collect()
+    }
+    /// Value used for an input while nothing is connected to it. Inputs
+    /// without a default are required, and the node fails when one of them
+    /// is needed but not connected.
+    fn input_default(&self, idx: usize) -> Option<TypedData> {
+        let _ = idx;
+        None
+    }
+    /// How the values of several wires connected to the one input are
+    /// combined. Inputs without a reducer only accept a single wire.
+    fn input_reducer(&self, idx: usize) -> Option<Reducer> {
+        let _ = idx;
+        None
+    }
+    /// Return true if the node should be recalculated
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        let _ = (idx, remote, ui);
+        false
+    }
+    /// Return true if the node should be recalculated
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        let _ = (idx, ui);
+        false
+    }
+    /// Whether the node has anything to show between its inputs and outputs
+    fn has_body(&self) -> bool {
+        false
+    }
+    /// Shows the node's body, if [`Node::has_body`]. Return true if the node
+    /// should be recalculated
+    fn show_body(&mut self, _ui: &mut Ui) -> bool {
+        false
+    }
+    /// External process that does the node's work, run when the node's task
+    /// becomes ready in a [`TaskDag`](crate::execution_engine::TaskDag) by a
+    /// [`ProcessExecutor`](crate::execution_engine::ProcessExecutor)
+    fn process(&self) -> Option<std::process::Command> {
+        None
+    }
+    /// Receives the output of the process from [`Node::process`] once it has
+    /// exited, or why it couldn't be run
+    fn process_finished(&mut self, output: std::io::Result<std::process::Output>) {
+        let _ = output;
+    }
+    /// Whether the node is waiting on work it started in the background,
+    /// such as a network request, rather than having finished updating
+    fn is_pending(&self) -> bool {
+        false
+    }
+    /// Checks on the node's background work, returning true once it has
+    /// finished and the node should be evaluated again to pass on its result
+    fn poll_pending(&mut self) -> bool {
+        false
+    }
+    /// File the node reads, whose changes should cause the node to be
+    /// evaluated again, see [`FileWatcher`](crate::file_nodes::FileWatcher)
+    fn watched_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+    /// File the node last read its data from, bundled with the graph when it
+    /// is packaged, see [`Package`](crate::package::Package)
+    fn data_file(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+    /// Has the node read its data from `path` rather than where it was
+    /// told to, such as from a package's copy of its [`Node::data_file`]
+    fn set_data_file(&mut self, path: std::path::PathBuf) {
+        let _ = path;
+    }
+    /// Parameters needed to recreate this node when a saved graph is loaded.
+    /// Cached results are saved separately, see [`Node::cached_outputs`].
+    fn save(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+    /// Restores parameters previously returned by [`Node::save`]
+    fn restore(&mut self, params: &serde_json::Value) {
+        let _ = params;
+    }
+    /// Outputs from the last update to save along with the graph, so that
+    /// they can be shown as soon as it is loaded rather than recalculated.
+    /// Nodes that return `None` are recalculated after loading.
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        None
+    }
+    /// Restores outputs previously returned by [`Node::cached_outputs`]
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        let _ = outputs;
+    }
+    /// Whether the node is updated when one of its inputs is an error value.
+    /// Other nodes pass the first error on through every output instead,
+    /// see [`errors`](crate::errors).
+    fn accepts_errors(&self) -> bool {
+        false
+    }
+    /// Whether the node's outputs depend only on its parameters and inputs,
+    /// so that a copy made from [`Node::save`] gives the same results. Pure
+    /// nodes can be updated on another thread, see
+    /// [`BackgroundEvaluation`](crate::background::BackgroundEvaluation),
+    /// and need to restore the outputs given by [`Node::cached_outputs`].
+    fn is_pure(&self) -> bool {
+        false
+    }
+    /// Whether the node's outputs change on their own, such as a clock's, so
+    /// that the editor keeps evaluating the graph from it, see
+    /// [`evaluate_continuous`]
+    fn needs_continuous_update(&self) -> bool {
+        false
+    }
+    /// Rough time the node takes to update, used to plan evaluations before
+    /// the node has been timed
+    fn estimated_cost(&self) -> Option<Duration> {
+        None
+    }
+    /// Forgets any state kept from earlier evaluations, such as running
+    /// totals, so that the graph can be restarted
+    fn reset(&mut self) {}
+    /// Called on every node of a graph as an evaluation of it starts, before
+    /// any node is updated and whether or not this one will be. Return true
+    /// if this changed the node's outputs, such as a delay moving on to the
+    /// value it was holding, so that the nodes downstream are evaluated too.
+    fn on_graph_run_start(&mut self) -> bool {
+        false
+    }
+    /// Explanation of why the node could not produce its outputs
+    fn error(&self) -> Option<String> {
+        None
+    }
+    /// Index of an output that carries the node's error as a string when it
+    /// fails, and has no value otherwise. Nodes connected to it are still
+    /// evaluated when the node fails, so that failures can be handled within
+    /// the graph.
+    fn error_output(&self) -> Option<usize> {
+        None
+    }
+    /// The part the node plays in evaluations. By default nodes without
+    /// inputs are sources and nodes without outputs are sinks.
+    fn role(&self) -> NodeRole {
+        if self.inputs().is_empty() {
+            NodeRole::Source
+        } else if self.outputs().is_empty() {
+            NodeRole::Sink
+        } else {
+            NodeRole::Transform
+        }
+    }
+    /// The nested graph of this node, if it is a group
+    fn subgraph(&self) -> Option<&Snarl<Box<dyn Node>>> {
+        None
+    }
+    fn subgraph_mut(&mut self) -> Option<&mut Snarl<Box<dyn Node>>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NumberNode {
+    value: f64,
+    label: Option<String>,
+}
+
+impl NumberNode {
+    pub fn new(value: f64) -> Self {
+        Self { value, label: None }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value;
+    }
+}
+
+impl Node for NumberNode {
+    fn name(&self) -> String {
+        "Number".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        Some(TypedData::Number(self.value))
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if ui.add(egui::DragValue::new(&mut self.value)).changed() {
+            // TODO: Evaluate and propagate
+            return true;
+        }
+        false
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.value.into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(value) = params.as_f64() {
+            self.value = value;
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AddNode {
+    cached_result: Option<f64>,
+    label: Option<String>,
+}
+
+impl Node for AddNode {
+    fn name(&self) -> String {
+        "Add".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number, DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(TypedData::Number)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert!(idx < 2);
+        let Some(remote) = remote else {
+            return false;
+        };
+        show_value(&remote, ui);
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(res) = self.cached_result {
+            ui.label(format_float(res));
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    /// Unconnected inputs add nothing
+    fn input_default(&self, _idx: usize) -> Option<TypedData> {
+        Some(TypedData::Number(0.))
+    }
+
+    /// Each input adds up everything wired to it
+    fn input_reducer(&self, _idx: usize) -> Option<Reducer> {
+        Some(Reducer::Sum)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = Some(
+            inputs
+                .iter()
+                .filter_map(|v| {
+                    if let TypedData::Number(v) = v {
+                        Some(*v)
+                    } else {
+                        None
+                    }
+                })
+                .sum(),
+        );
+    }
+}
+
+/// Selects between two values, only evaluating the chosen one
+#[derive(Debug, Clone, Default)]
+pub struct IfNode {
+    cached_result: Option<f64>,
+    label: Option<String>,
+}
+
+impl Node for IfNode {
+    fn name(&self) -> String {
+        "If".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Bool, DataType::Number, DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(TypedData::Number)
+    }
+
+    fn required_inputs(&self, inputs: &[Option<TypedData>]) -> Vec<usize> {
+        match inputs[0] {
+            Some(TypedData::Bool(true)) => vec![0, 1],
+            Some(_) => vec![0, 2],
+            None => vec![0],
+        }
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert!(idx < 3);
+        match idx {
+            0 => ui.label("if"),
+            1 => ui.label("then"),
+            _ => ui.label("else"),
+        };
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(res) = self.cached_result {
+            ui.label(format_float(res));
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Number(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let TypedData::Bool(condition) = inputs[0] else {
+            self.cached_result = None;
+            return;
+        };
+        let selected = if condition { &inputs[1] } else { &inputs[2] };
+        self.cached_result = match selected {
+            TypedData::Number(val) => Some(*val),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    GreaterThan,
+    LessThan,
+    Equals,
+}
+
+/// Compares two numbers
+#[derive(Debug, Clone)]
+pub struct CompareNode {
+    op: CompareOp,
+    cached_result: Option<bool>,
+    label: Option<String>,
+}
+
+impl CompareNode {
+    pub fn new(op: CompareOp) -> Self {
+        Self {
+            op,
+            cached_result: None,
+            label: None,
+        }
+    }
+}
+
+impl Node for CompareNode {
+    fn name(&self) -> String {
+        match self.op {
+            CompareOp::GreaterThan => "Greater Than",
+            CompareOp::LessThan => "Less Than",
+            CompareOp::Equals => "Equals",
+        }
+        .to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number, DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Bool]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(TypedData::Bool)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert!(idx < 2);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(res) = self.cached_result {
+            ui.label(res.to_string());
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Bool(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = match (&inputs[0], &inputs[1]) {
+            (TypedData::Number(a), TypedData::Number(b)) => Some(match self.op {
+                CompareOp::GreaterThan => a > b,
+                CompareOp::LessThan => a < b,
+                CompareOp::Equals => a == b,
+            }),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicOp {
+    And,
+    Or,
+}
+
+/// Combines two booleans
+#[derive(Debug, Clone)]
+pub struct LogicNode {
+    op: LogicOp,
+    cached_result: Option<bool>,
+    label: Option<String>,
+}
+
+impl LogicNode {
+    pub fn new(op: LogicOp) -> Self {
+        Self {
+            op,
+            cached_result: None,
+            label: None,
+        }
+    }
+}
+
+impl Node for LogicNode {
+    fn name(&self) -> String {
+        match self.op {
+            LogicOp::And => "And",
+            LogicOp::Or => "Or",
+        }
+        .to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Bool, DataType::Bool]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Bool]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(TypedData::Bool)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert!(idx < 2);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(res) = self.cached_result {
+            ui.label(res.to_string());
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Bool(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    /// Wires into the same input are combined with the node's own operation
+    fn input_reducer(&self, _idx: usize) -> Option<Reducer> {
+        Some(match self.op {
+            LogicOp::And => Reducer::All,
+            LogicOp::Or => Reducer::Any,
+        })
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = match (&inputs[0], &inputs[1]) {
+            (TypedData::Bool(a), TypedData::Bool(b)) => Some(match self.op {
+                LogicOp::And => *a && *b,
+                LogicOp::Or => *a || *b,
+            }),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NotNode {
+    cached_result: Option<bool>,
+    label: Option<String>,
+}
+
+impl Node for NotNode {
+    fn name(&self) -> String {
+        "Not".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Bool]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Bool]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.cached_result.map(TypedData::Bool)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(res) = self.cached_result {
+            ui.label(res.to_string());
+        }
+        false
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.output_value(0).map(|value| vec![value])
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if let [TypedData::Bool(value)] = outputs {
+            self.cached_result = Some(*value);
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.cached_result = match inputs[0] {
+            TypedData::Bool(val) => Some(!val),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SinkNode {
+    label: Option<String>,
+}
+
+impl Node for SinkNode {
+    fn name(&self) -> String {
+        "Sink".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        let Some(remote) = remote else {
+            return false;
+        };
+        show_value(&remote, ui);
+        false
+    }
+}
+
+/// The value currently reaching an input, combining the outputs of every
+/// wire connected to it as the node would when updated
+pub fn input_value(snarl: &Snarl<Box<dyn Node>>, pin: InPinId) -> Option<TypedData> {
+    let mut remotes = snarl.in_pin(pin).remotes;
+    remotes.sort();
+    let values = remotes
+        .iter()
+        .map(|remote| snarl[remote.node].output_value(remote.output))
+        .collect::<Option<Vec<_>>>()?;
+    match snarl[pin.node].input_reducer(pin.input) {
+        Some(reducer) if values.len() > 1 => reducer.reduce(values),
+        _ => values.into_iter().next(),
+    }
+}
+
+/// The nodes of a graph playing `role`
+pub fn nodes_with_role(snarl: &Snarl<Box<dyn Node>>, role: NodeRole) -> Vec<NodeId> {
+    snarl
+        .node_ids()
+        .filter(|(_, node)| node.role() == role)
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Resets the state of every node, including those within groups
+pub fn reset_state(snarl: &mut Snarl<Box<dyn Node>>) {
+    let ids = snarl.node_ids().map(|(id, _)| id).collect::<Vec<_>>();
+    for id in ids {
+        snarl[id].reset();
+    }
+}
+
+/// Tells every node of the graph that an evaluation is starting, see
+/// [`Node::on_graph_run_start`]. Returns the nodes whose outputs changed,
+/// which need evaluating along with everything downstream of them.
+pub fn start_run(snarl: &mut Snarl<Box<dyn Node>>) -> BTreeSet<NodeId> {
+    snarl
+        .nodes_ids_mut()
+        .filter_map(|(id, node)| node.on_graph_run_start().then_some(id))
+        .collect()
+}
+
+pub fn as_petgraph(snarl: &Snarl<Box<dyn Node>>) -> Graph<NodeId, ()> {
+    let _scope = profiler::scope("as_petgraph");
+    let mut graph = petgraph::Graph::<NodeId, ()>::new();
+
+    let mut nodeid_to_idx = BTreeMap::new();
+
+    // Add nodes to graph
+    for (node_id, _node) in snarl.node_ids() {
+        let idx = graph.add_node(node_id);
+        nodeid_to_idx.insert(node_id, idx);
+    }
+
+    // Add edges
+    for (node_id, node) in snarl.node_ids() {
+        let downstream_nodeids = (0..node.outputs().len())
+            .map(|i| {
+                snarl.out_pin(OutPinId {
+                    node: node_id,
+                    output: i,
+                })
+            })
+            .flat_map(|output| output.remotes)
+            .map(|inpin| inpin.node);
+
+        for downstream in downstream_nodeids {
+            graph.add_edge(nodeid_to_idx[&node_id], nodeid_to_idx[&downstream], ());
+        }
+    }
+
+    graph
+}
+
+/// Recalculates the graph by pulling values from its sinks.
+///
+/// Only the inputs a node reports as required are evaluated, so nodes
+/// such as [`IfNode`] only compute the branch that is actually selected.
+/// If `start` is given, only that node and those downstream of it are
+/// recalculated, the rest of the graph reuses its existing outputs. Nodes
+/// whose outputs [change as the run starts](Node::on_graph_run_start) are
+/// always recalculated from.
+pub fn evaluate(snarl: &mut Snarl<Box<dyn Node>>, start: Option<NodeId>) -> RunReport {
+    evaluate_with(snarl, start, &EvalContext::default())
+}
+
+/// Like [`evaluate`], giving the nodes access to `ctx`
+pub fn evaluate_with(
+    snarl: &mut Snarl<Box<dyn Node>>,
+    start: Option<NodeId>,
+    ctx: &EvalContext,
+) -> RunReport {
+    match start {
+        Some(start) => evaluate_from(snarl, ctx, |id, _| id == start),
+        None => evaluate_from(snarl, ctx, |_, _| true),
+    }
+}
+
+/// Like [`evaluate_with`], using the dependencies between nodes kept in
+/// `adjacency` rather than working them out from the graph again
+pub fn evaluate_in(
+    snarl: &mut Snarl<Box<dyn Node>>,
+    adjacency: &Adjacency,
+    start: Option<NodeId>,
+    ctx: &EvalContext,
+) -> RunReport {
+    let _scope = profiler::scope("evaluate");
+    let changed = start_run(snarl);
+    let mut evaluation = Evaluation::with_adjacency(snarl, adjacency, ctx.clone(), |id, _| {
+        changed.contains(&id) || start.is_none_or(|start| id == start)
+    });
+    while evaluation.step(snarl).is_some() {}
+    evaluation.finish()
+}
+
+/// Recalculates only the nodes that are missing outputs, such as those
+/// without cached results after loading a graph, and everything downstream
+/// of them
+pub fn evaluate_stale(snarl: &mut Snarl<Box<dyn Node>>) -> RunReport {
+    evaluate_from(snarl, &EvalContext::default(), |_, node| {
+        node.outputs().is_empty() || !missing_outputs(node).is_empty()
+    })
+}
+
+/// Recalculates the nodes that [need continuous updates](Node::needs_continuous_update)
+/// and everything downstream of them
+pub fn evaluate_continuous(snarl: &mut Snarl<Box<dyn Node>>, ctx: &EvalContext) -> RunReport {
+    evaluate_from(snarl, ctx, |_, node| node.needs_continuous_update())
+}
+
+/// Checks on every node waiting on background work, returning those whose
+/// work has finished, which need to be evaluated again. See
+/// [`Node::poll_pending`].
+pub fn poll_pending(snarl: &mut Snarl<Box<dyn Node>>) -> Vec<NodeId> {
+    snarl
+        .nodes_ids_mut()
+        .filter_map(|(id, node)| (node.is_pending() && node.poll_pending()).then_some(id))
+        .collect()
+}
+
+/// Recalculates the nodes matching `start` and everything downstream of them
+fn evaluate_from(
+    snarl: &mut Snarl<Box<dyn Node>>,
+    ctx: &EvalContext,
+    start: impl Fn(NodeId, &dyn Node) -> bool,
+) -> RunReport {
+    let _scope = profiler::scope("evaluate");
+    // Nodes whose outputs moved on as the run started are evaluated from
+    // too, even when they wouldn't be otherwise
+    let changed = start_run(snarl);
+    let mut evaluation = Evaluation::new(snarl, ctx.clone(), |id, node| {
+        changed.contains(&id) || start(id, node)
+    });
+    while evaluation.step(snarl).is_some() {}
+    evaluation.finish()
+}
+
+/// A node waiting for its inputs to be evaluated
+#[derive(Debug)]
+struct Frame {
+    node: NodeId,
+    inputs: Vec<Option<TypedData>>,
+}
+
+/// An evaluation that can be paused between node updates, for stepping
+/// through a graph one node at a time. Nodes are updated in the same order
+/// as [`evaluate`], with each node's required inputs updated before it.
+#[derive(Debug)]
+pub struct Evaluation {
+    ctx: EvalContext,
+    /// Nodes whose values may have changed
+    dirty: BTreeSet<NodeId>,
+    /// Nodes that have been updated, or are waiting on their inputs
+    visited: BTreeSet<NodeId>,
+    /// Nodes with nothing downstream still to be pulled, last first
+    sinks: Vec<NodeId>,
+    /// Nodes waiting on their inputs, the innermost last
+    stack: Vec<Frame>,
+    report: RunReport,
+}
+
+impl Evaluation {
+    /// Starts an evaluation of the nodes matching `start` and everything
+    /// downstream of them, without updating any nodes yet
+    pub fn new(
+        snarl: &Snarl<Box<dyn Node>>,
+        ctx: EvalContext,
+        start: impl Fn(NodeId, &dyn Node) -> bool,
+    ) -> Self {
+        Self::with_adjacency(snarl, &Adjacency::new(snarl), ctx, start)
+    }
+
+    /// Like [`Evaluation::new`], using the dependencies between nodes kept
+    /// in `adjacency`, which must be up to date with `snarl`
+    pub fn with_adjacency(
+        snarl: &Snarl<Box<dyn Node>>,
+        adjacency: &Adjacency,
+        ctx: EvalContext,
+        start: impl Fn(NodeId, &dyn Node) -> bool,
+    ) -> Self {
+        adjacency.debug_check(snarl);
+
+        // Find the nodes whose values may have changed, which are all the
+        // nodes downstream of the starting ones
+        let mut dirty = BTreeSet::new();
+        for node in adjacency.nodes() {
+            if !dirty.contains(&node)
+                && snarl
+                    .get_node(node)
+                    .is_some_and(|value| start(node, value.as_ref()))
+            {
+                dirty.extend(adjacency.reachable_from(node));
+            }
+        }
+
+        // Demand values from every dirty sink, and every other dirty node
+        // that has nothing downstream
+        let sinks = dirty
+            .iter()
+            .rev()
+            .filter(|node| {
+                snarl
+                    .get_node(**node)
+                    .is_some_and(|value| value.role() == NodeRole::Sink)
+                    || adjacency.downstream(**node).next().is_none()
+            })
+            .copied()
+            .collect();
+        ctx.observers
+            .notify(|observer| observer.started(dirty.len()));
+        Self {
+            ctx,
+            dirty,
+            visited: BTreeSet::new(),
+            sinks,
+            stack: Vec::new(),
+            report: RunReport::new(),
+        }
+    }
+
+    /// Works out which node will be updated next, without updating it.
+    /// Returns `None` once the evaluation has finished or been cancelled.
+    pub fn next(&mut self, snarl: &Snarl<Box<dyn Node>>) -> Option<NodeId> {
+        loop {
+            if self.ctx.cancellation.is_cancelled() {
+                return None;
+            }
+            let Some(frame) = self.stack.last() else {
+                let sink = self.sinks.pop()?;
+                self.push(snarl, sink);
+                continue;
+            };
+            // The graph may have been edited while the evaluation was paused
+            let Some(node) = snarl.get_node(frame.node) else {
+                self.stack.pop();
+                continue;
+            };
+
+            let Some(idx) = node
+                .required_inputs(&frame.inputs)
+                .into_iter()
+                .find(|idx| frame.inputs.get(*idx).is_some_and(Option::is_none))
+            else {
+                return Some(frame.node);
+            };
+            let node_id = frame.node;
+            let mut remotes = snarl
+                .in_pin(InPinId {
+                    node: node_id,
+                    input: idx,
+                })
+                .remotes;
+            if remotes.is_empty() {
+                if let Some(default) = node.input_default(idx) {
+                    if let Some(frame) = self.stack.last_mut() {
+                        frame.inputs[idx] = Some(default);
+                    }
+                    continue;
+                }
+                self.fail(node_id, format!("Input {} is not connected", idx));
+                continue;
+            }
+            let reducer = node.input_reducer(idx);
+            if remotes.len() > 1 && reducer.is_none() {
+                self.fail(
+                    node_id,
+                    format!(
+                        "Input {} has {} wires but only accepts one",
+                        idx,
+                        remotes.len()
+                    ),
+                );
+                continue;
+            }
+
+            // Values are combined in order of the nodes they come from
+            remotes.sort();
+            if remotes.iter().any(|remote| self.push(snarl, remote.node)) {
+                continue;
+            }
+            let Some(mut values) = remotes
+                .iter()
+                .map(|remote| snarl[remote.node].output_value(remote.output))
+                .collect::<Option<Vec<_>>>()
+            else {
+                // An upstream node has already been reported as failing
+                self.stack.pop();
+                continue;
+            };
+            let value = match reducer {
+                Some(reducer) if values.len() > 1 => reducer.reduce(values),
+                _ => values.pop(),
+            };
+            match value {
+                Some(value) => {
+                    if let Some(frame) = self.stack.last_mut() {
+                        frame.inputs[idx] = Some(value);
+                    }
+                }
+                None => self.fail(
+                    node_id,
+                    format!("The values wired to input {} can't be combined", idx),
+                ),
+            }
+        }
+    }
+
+    /// Records that the innermost waiting node can't be updated, and stops
+    /// waiting on it
+    fn fail(&mut self, node: NodeId, message: String) {
+        let state = TaskState::Failed(message.clone());
+        self.ctx
+            .observers
+            .notify(|observer| observer.task_changed(node, &state));
+        self.report.failures.push(NodeFailure { node, message });
+        self.stack.pop();
+    }
+
+    /// Queues a node to be updated once its inputs have been, returning
+    /// whether it was queued. Clean nodes keep their current outputs, and
+    /// each node is only updated once per evaluation.
+    fn push(&mut self, snarl: &Snarl<Box<dyn Node>>, node: NodeId) -> bool {
+        if !self.dirty.contains(&node) || !self.visited.insert(node) {
+            return false;
+        }
+        self.stack.push(Frame {
+            node,
+            inputs: vec![None; snarl[node].inputs().len()],
+        });
+        true
+    }
+
+    /// Updates the next node, returning it, or `None` once the evaluation
+    /// has finished or been cancelled
+    pub fn step(&mut self, snarl: &mut Snarl<Box<dyn Node>>) -> Option<NodeId> {
+        let node = self.next(snarl)?;
+        let frame = self.stack.pop()?;
+
+        // Inputs that were not required are left unevaluated, as are any
+        // added while the evaluation was paused
+        let mut inputs = frame
+            .inputs
+            .into_iter()
+            .map(|value| value.unwrap_or(TypedData::Unknown))
+            .collect::<Vec<_>>();
+        inputs.resize(snarl[node].inputs().len(), TypedData::Unknown);
+        let before = output_values(snarl[node].as_ref());
+        self.ctx
+            .observers
+            .notify(|observer| observer.task_changed(node, &TaskState::Running));
+        let started = web_time::Instant::now();
+        let mut update_ctx = UpdateContext::new(&self.ctx, node);
+        // Nodes that don't handle errors pass on the first error among their
+        // inputs rather than being updated
+        let input_error = inputs
+            .iter()
+            .find_map(TypedData::as_error)
+            .filter(|_| !snarl[node].accepts_errors())
+            .cloned();
+        if let Some(error) = input_error {
+            errors::pass_on(snarl, node, error);
+        } else {
+            errors::recover(snarl, node);
+            // Pure nodes take their outputs from the cache if they have been
+            // updated with the same parameters and inputs before
+            let key = NodeCache::key(snarl[node].as_ref(), &inputs);
+            let cached = key
+                .and_then(|key| self.ctx.cache.get(key))
+                .is_some_and(|outputs| {
+                    snarl[node].restore_outputs(&outputs);
+                    missing_outputs(snarl[node].as_ref()).is_empty()
+                        && snarl[node].error().is_none()
+                });
+            if !cached {
+                let scope = profiler::scope_with(|| display_name(snarl[node].as_ref(), node));
+                snarl[node].update(&inputs, &mut update_ctx);
+                drop(scope);
+                let succeeded = missing_outputs(snarl[node].as_ref()).is_empty()
+                    && snarl[node].error().is_none();
+                if let Some((key, outputs)) =
+                    key.filter(|_| succeeded).zip(snarl[node].cached_outputs())
+                {
+                    self.ctx.cache.insert(key, outputs);
+                }
+            }
+        }
+        let duration = started.elapsed();
+        self.report.changes.insert(
+            node,
+            OutputChange {
+                before,
+                after: output_values(snarl[node].as_ref()),
+            },
+        );
+        self.report.runs.push(NodeRun {
+            node,
+            finished: web_time::SystemTime::now(),
+            duration,
+            worker: 0,
+        });
+        self.report
+            .messages
+            .extend(
+                update_ctx
+                    .into_messages()
+                    .into_iter()
+                    .map(|(level, time, message)| NodeMessage {
+                        node,
+                        level,
+                        time,
+                        message,
+                    }),
+            );
+
+        // Sinks have no outputs to go missing, so they fail by reporting an
+        // error instead
+        let sink_failed = snarl[node].outputs().is_empty() && snarl[node].error().is_some();
+        // Nodes giving an error value have failed too
+        let output_error = output_values(snarl[node].as_ref())
+            .iter()
+            .flatten()
+            .find_map(TypedData::as_error)
+            .map(ErrorValue::to_string);
+        let state = if missing_outputs(snarl[node].as_ref()).is_empty()
+            && !sink_failed
+            && output_error.is_none()
+        {
+            TaskState::Completed
+        } else {
+            let message = snarl[node]
+                .error()
+                .or(output_error)
+                .unwrap_or_else(|| "Produced no value".to_owned());
+            self.report.failures.push(NodeFailure {
+                node,
+                message: message.clone(),
+            });
+            TaskState::Failed(message)
+        };
+        let fraction = self.report.runs.len() as f32 / self.dirty.len() as f32;
+        self.ctx.observers.notify(|observer| {
+            observer.task_changed(node, &state);
+            observer.progress(fraction);
+        });
+        Some(node)
+    }
+
+    /// Updates nodes until the next one to be updated matches `stop`,
+    /// returning it, or `None` once the evaluation has finished
+    pub fn run_until(
+        &mut self,
+        snarl: &mut Snarl<Box<dyn Node>>,
+        stop: impl Fn(NodeId) -> bool,
+    ) -> Option<NodeId> {
+        loop {
+            let next = self.next(snarl)?;
+            if stop(next) {
+                return Some(next);
+            }
+            self.step(snarl);
+        }
+    }
+
+    /// Stops the evaluation before the next node is updated
+    pub fn cancel(&self) {
+        self.ctx.cancellation.cancel();
+    }
+
+    /// The nodes updated so far
+    pub fn report(&self) -> &RunReport {
+        &self.report
+    }
+
+    /// Stops the evaluation, leaving any nodes not yet updated with their
+    /// current outputs
+    pub fn finish(mut self) -> RunReport {
+        self.report.cancelled = self.ctx.cancellation.is_cancelled();
+        self.ctx
+            .observers
+            .notify(|observer| observer.finished(&self.report));
+        self.report
+    }
+}
+
+pub(crate) fn output_values(node: &dyn Node) -> Vec<Option<TypedData>> {
+    (0..node.outputs().len())
+        .map(|idx| node.output_value(idx))
+        .collect()
+}
+
+/// Indices of the outputs that have no value, other than the error output
+fn missing_outputs(node: &dyn Node) -> Vec<usize> {
+    (0..node.outputs().len())
+        .filter(|idx| Some(*idx) != node.error_output() && node.output_value(*idx).is_none())
+        .collect()
+}
+
+/// How a node is referred to in the editor: its label if the user has given
+/// it one, or its id otherwise
+pub fn display_name(node: &dyn Node, id: NodeId) -> String {
+    node.label()
+        .map_or_else(|| format!("ID: {}", id.0), str::to_owned)
+}
+
+/// Displays a value in a compact form
+pub fn show_value(value: &TypedData, ui: &mut Ui) {
+    match value {
+        TypedData::Number(val) => ui.label(format_float(*val)),
+        TypedData::String(val) => ui.label(val),
+        TypedData::Bool(val) => ui.label(val.to_string()),
+        TypedData::Color(color) => {
+            ui.horizontal(|ui| {
+                show_swatch(*color, ui);
+                ui.label(format_color(*color))
+            })
+            .inner
+        }
+        TypedData::Vec2(_) | TypedData::Vec3(_) => ui.label(format_value(value)),
+        TypedData::List(values) => ui.label(format_list(values)),
+        TypedData::Unknown => ui.label("?"),
+        TypedData::Error(error) => ui.colored_label(ui.visuals().error_fg_color, error.to_string()),
+    };
+}
+
+/// Shows a colour as a small patch of it
+pub fn show_swatch(color: [u8; 4], ui: &mut Ui) -> egui::Response {
+    let [r, g, b, a] = color;
+    egui::color_picker::show_color(
+        ui,
+        Color32::from_rgba_unmultiplied(r, g, b, a),
+        egui::vec2(ui.spacing().interact_size.y, ui.spacing().interact_size.y) * 0.8,
+    )
+}
+
+/// Formats a colour as hex, leaving out the alpha when it is opaque
+pub fn format_color(color: [u8; 4]) -> String {
+    match color {
+        [r, g, b, 255] => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        [r, g, b, a] => format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+    }
+}
+
+/// Formats a list, only including the first few elements
+fn format_list(values: &[TypedData]) -> String {
+    const MAX_SHOWN: usize = 5;
+    let mut shown = values
+        .iter()
+        .take(MAX_SHOWN)
+        .map(format_value)
+        .collect::<Vec<_>>();
+    if values.len() > MAX_SHOWN {
+        shown.push(format!("… ({} total)", values.len()));
+    }
+    format!("[{}]", shown.join(", "))
+}
+
+/// Formats a value as text, with strings quoted
+pub fn format_value(value: &TypedData) -> String {
+    match value {
+        TypedData::Number(val) => format_float(*val),
+        TypedData::String(val) => format!("{:?}", val),
+        TypedData::Bool(val) => val.to_string(),
+        TypedData::Color(color) => format_color(*color),
+        TypedData::Vec2([x, y]) => format!("({}, {})", format_float(*x), format_float(*y)),
+        TypedData::Vec3([x, y, z]) => format!(
+            "({}, {}, {})",
+            format_float(*x),
+            format_float(*y),
+            format_float(*z)
+        ),
+        TypedData::List(values) => format_list(values),
+        TypedData::Unknown => "?".to_owned(),
+        TypedData::Error(error) => format!("Error: {}", error),
+    }
+}
+
+pub fn format_float(v: f64) -> String {
+    let v = (v * 1000.0).round() / 1000.0;
+    format!("{}", v)
+}
@@ -0,0 +1,77 @@
+//! Progress of an evaluation as it happens, for embedders that show it
+//! outside the editor, such as progress bars on the command line or in a web
+//! page
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use egui_snarl::NodeId;
+
+use crate::run_report::RunReport;
+
+/// Where a node has got to in an evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskState {
+    /// The node is being updated
+    Running,
+    /// The node was updated and produced its outputs
+    Completed,
+    /// The node couldn't be updated, or produced no value
+    Failed(String),
+}
+
+/// Receives the progress of evaluations it is registered with using
+/// [`Observers::add`]. Every method does nothing by default, so observers
+/// only need to implement those they are interested in.
+///
+/// Only the nodes of the graph being evaluated are reported, not those
+/// inside its groups.
+pub trait ExecutionObserver {
+    /// The evaluation is starting, and will update at most `total` nodes
+    fn started(&mut self, _total: usize) {}
+
+    fn task_changed(&mut self, _node: NodeId, _state: &TaskState) {}
+
+    /// The node being updated reported how far through its update it is,
+    /// from 0 to 1
+    fn task_progress(&mut self, _node: NodeId, _fraction: f32) {}
+
+    /// A node logged a message while being updated
+    fn message(&mut self, _node: NodeId, _message: &str) {}
+
+    /// How much of the evaluation is done, from 0 to 1, reported after each
+    /// node finishes. Nodes whose values turn out not to be needed are never
+    /// updated, so this can finish short of 1.
+    fn progress(&mut self, _fraction: f32) {}
+
+    fn finished(&mut self, _report: &RunReport) {}
+}
+
+/// Observers registered with an evaluation. They are shared, so that the
+/// embedder can read back what they have recorded.
+#[derive(Clone, Default)]
+pub struct Observers(Vec<Arc<Mutex<dyn ExecutionObserver + Send>>>);
+
+impl Observers {
+    pub fn add(&mut self, observer: Arc<Mutex<dyn ExecutionObserver + Send>>) {
+        self.0.push(observer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn notify(&self, event: impl Fn(&mut dyn ExecutionObserver)) {
+        for observer in &self.0 {
+            event(&mut *observer.lock().unwrap());
+        }
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
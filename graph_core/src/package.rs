@@ -0,0 +1,277 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    io::{self, Cursor, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use egui_snarl::{NodeId, Snarl};
+use serde::{Deserialize, Serialize};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+use crate::{
+    graph_file::{LoadError, SavedGraph},
+    node_graph::Node,
+};
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const GRAPH_ENTRY: &str = "graph.json";
+const DATA_DIR: &str = "data/";
+const FORMAT_VERSION: u32 = 1;
+
+/// Describes the contents of a package, so that other tools can find the
+/// graph without knowing the layout of the archive
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    graph: String,
+    #[serde(default)]
+    data: Vec<String>,
+    /// Data file read by each node, see [`Package::files`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    files: BTreeMap<usize, String>,
+}
+
+/// A graph bundled with the files it needs into a single zip archive, so it
+/// can be shared as one file.
+///
+/// Groups are stored inside the graph file, and the graph's profiles hold
+/// its sets of sample inputs. Any other files, such as data for the graph
+/// to run on, are kept under `data/`. The files read by nodes, such as a
+/// Read File or CSV Source node, are gathered into the package when it is
+/// made, and extracted again when it is opened.
+#[derive(Debug, Clone, Default)]
+pub struct Package {
+    pub graph: SavedGraph,
+    /// Contents of the files under `data/`, keyed by their path within it
+    pub data: BTreeMap<String, Vec<u8>>,
+    /// Data file read by each node of the root graph, by node id, naming
+    /// its entry in [`Package::data`]
+    pub files: BTreeMap<usize, String>,
+}
+
+/// Whether `contents` look like a zip archive rather than a graph file
+pub fn is_package(contents: &[u8]) -> bool {
+    contents.starts_with(b"PK\x03\x04")
+}
+
+/// Directory beside the package at `path` that its data files are extracted
+/// to, see [`Package::extract_files`]
+pub fn data_dir(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or(OsStr::new("package"));
+    let mut name = stem.to_os_string();
+    name.push("_data");
+    path.with_file_name(name)
+}
+
+/// Whether `name` is a file name alone, so that it can't be extracted
+/// outside of the data directory
+fn is_plain_name(name: &str) -> bool {
+    Path::new(name).file_name() == Some(OsStr::new(name))
+}
+
+impl Package {
+    /// Bundles `graph` with `data` and the files read by the nodes of
+    /// `snarl`, see [`Node::data_file`]. Nodes inside groups aren't
+    /// searched.
+    pub fn gather(
+        graph: SavedGraph,
+        snarl: &Snarl<Box<dyn Node>>,
+        data: BTreeMap<String, Vec<u8>>,
+    ) -> io::Result<Self> {
+        let mut package = Self {
+            graph,
+            data,
+            files: BTreeMap::new(),
+        };
+        // Files read by several nodes are only bundled once
+        let mut names = HashMap::<PathBuf, String>::new();
+        for (id, node) in snarl.node_ids() {
+            let Some(path) = node.data_file() else {
+                continue;
+            };
+            if let Some(name) = names.get(&path) {
+                package.files.insert(id.0, name.clone());
+                continue;
+            }
+            let contents = std::fs::read(&path).map_err(|err| {
+                io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+            })?;
+            let name = package.unused_name(&path);
+            package.data.insert(name.clone(), contents);
+            package.files.insert(id.0, name.clone());
+            names.insert(path, name);
+        }
+        Ok(package)
+    }
+
+    /// Name for the file at `path` that no other data file has
+    fn unused_name(&self, path: &Path) -> String {
+        let name = path.file_name().map_or_else(
+            || "data".to_owned(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        (1..)
+            .map(|n| match n {
+                1 => name.clone(),
+                n => format!("{}-{}", n, name),
+            })
+            .find(|candidate| !self.data.contains_key(candidate))
+            .unwrap()
+    }
+
+    /// Writes the data files read by nodes to `dir`, and has those nodes of
+    /// `snarl` read them from there. The files are taken out of
+    /// [`Package::data`], as they are gathered from the nodes again when the
+    /// graph is next packaged. The nodes are left as they were if any of
+    /// the files can't be written.
+    pub fn extract_files(
+        &mut self,
+        dir: &Path,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) -> io::Result<()> {
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(dir)?;
+        for name in self.files.values() {
+            std::fs::write(dir.join(name), &self.data[name])?;
+        }
+        for (id, name) in &self.files {
+            if let Some(node) = snarl.get_node_mut(NodeId(*id)) {
+                node.set_data_file(dir.join(name));
+            }
+        }
+        for name in self.files.values() {
+            self.data.remove(name);
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default();
+
+        let manifest = Manifest {
+            format: FORMAT_VERSION,
+            graph: GRAPH_ENTRY.to_owned(),
+            data: self.data.keys().cloned().collect(),
+            files: self.files.clone(),
+        };
+        zip.start_file(MANIFEST_ENTRY, options)?;
+        serde_json::to_writer_pretty(&mut zip, &manifest)?;
+
+        zip.start_file(GRAPH_ENTRY, options)?;
+        serde_json::to_writer_pretty(&mut zip, &self.graph)?;
+
+        for (name, contents) in &self.data {
+            zip.start_file(format!("{}{}", DATA_DIR, name), options)?;
+            zip.write_all(contents)?;
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+
+    pub fn from_bytes(contents: &[u8]) -> Result<Self, LoadError> {
+        let mut zip = ZipArchive::new(Cursor::new(contents)).map_err(io::Error::from)?;
+
+        let manifest: Manifest = match zip.by_name(MANIFEST_ENTRY) {
+            Ok(entry) => serde_json::from_reader(entry)?,
+            Err(err) => return Err(io::Error::from(err).into()),
+        };
+        if manifest.format > FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("package format {} is not supported", manifest.format),
+            )
+            .into());
+        }
+
+        let graph = match zip.by_name(&manifest.graph) {
+            Ok(entry) => serde_json::from_reader(entry)?,
+            Err(err) => return Err(io::Error::from(err).into()),
+        };
+
+        let mut data = BTreeMap::new();
+        for name in manifest.data {
+            let mut entry = zip
+                .by_name(&format!("{}{}", DATA_DIR, name))
+                .map_err(io::Error::from)?;
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            data.insert(name, contents);
+        }
+
+        if let Some(name) = manifest
+            .files
+            .values()
+            .find(|name| !is_plain_name(name) || !data.contains_key(*name))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("data file {} is missing or not a file name", name),
+            )
+            .into());
+        }
+
+        Ok(Self {
+            graph,
+            data,
+            files: manifest.files,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        args::ArgNode,
+        builder::GraphBuilder,
+        file_nodes::ReadFileNode,
+        node_graph::{self, TypedData},
+    };
+
+    #[test]
+    fn data_files_are_packaged_and_read_from_where_they_are_extracted() {
+        let dir = std::env::temp_dir().join(format!("graph_core_package_{}", std::process::id()));
+        let source = dir.join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        let input = source.join("input.txt");
+        std::fs::write(&input, "packaged").unwrap();
+
+        let mut path = ArgNode::default();
+        path.restore(&serde_json::json!({
+            "name": "path",
+            "default": TypedData::String(input.display().to_string()),
+        }));
+        let mut builder = GraphBuilder::new();
+        let path = builder.add_node(path);
+        let read = builder.add_node(ReadFileNode::default());
+        builder.connect(path.out(0), read.input(0)).unwrap();
+        let mut snarl = builder.build();
+        node_graph::evaluate(&mut snarl, None);
+
+        let package =
+            Package::gather(SavedGraph::from_snarl(&snarl), &snarl, BTreeMap::new()).unwrap();
+        let bytes = package.to_bytes().unwrap();
+        std::fs::remove_dir_all(&source).unwrap();
+
+        let mut package = Package::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            package.files,
+            BTreeMap::from([(read.id().0, "input.txt".to_owned())])
+        );
+        let mut snarl = package.graph.to_snarl().unwrap();
+        let extracted = data_dir(&dir.join("graph.zip"));
+        package.extract_files(&extracted, &mut snarl).unwrap();
+        assert!(package.data.is_empty());
+        node_graph::evaluate(&mut snarl, None);
+        let contents = snarl[read.id()].output_value(0);
+        let data_file = snarl[read.id()].data_file();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(contents, Some(TypedData::String("packaged".to_owned())));
+        assert_eq!(data_file, Some(extracted.join("input.txt")));
+    }
+}
@@ -0,0 +1,395 @@
+//! Pipelined evaluation, where a list coming out of a source node is split
+//! into chunks that pass through the nodes downstream of it one after
+//! another. Each of those nodes is updated on its own thread, joined to its
+//! neighbours by bounded channels, so that a node starts on the first chunk
+//! while the nodes before it are still working on later ones, rather than
+//! waiting for the whole list.
+//!
+//! Streamed nodes are updated as copies made from their parameters, so they
+//! must be pure, see [`Node::is_pure`], and the graph itself is left as it
+//! was. Inputs wired to nodes that aren't streamed get the same value for
+//! every chunk.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::mpsc::{self, Receiver, SyncSender},
+    time::Duration,
+};
+
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+use petgraph::visit::Walker;
+use web_time::Instant;
+
+use crate::{
+    context::{EvalContext, UpdateContext},
+    node_graph::{self, ErrorValue, Node, TypedData},
+    registry,
+    run_report::NodeFailure,
+};
+
+/// Settings for a pipelined evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pipeline {
+    /// Output giving the list to be streamed
+    pub source: OutPinId,
+    /// Number of list elements in each chunk
+    pub chunk_size: usize,
+    /// Number of chunks that can wait on each wire before the node sending
+    /// them is held up
+    pub capacity: usize,
+}
+
+impl Pipeline {
+    pub fn new(source: OutPinId) -> Self {
+        Self {
+            source,
+            chunk_size: 64,
+            capacity: 4,
+        }
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum PipelineError {
+    NoSuchOutput(OutPinId),
+    /// The source output doesn't have a list to stream
+    NotAList(OutPinId),
+    /// A node downstream of the source can't be copied to its own thread
+    NotPure(NodeId),
+    Cyclic,
+    Spawn(std::io::Error),
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::NoSuchOutput(pin) => {
+                write!(f, "node {} has no output {}", pin.node.0, pin.output)
+            }
+            PipelineError::NotAList(pin) => write!(
+                f,
+                "output {} of node {} doesn't have a list to stream",
+                pin.output, pin.node.0
+            ),
+            PipelineError::NotPure(node) => {
+                write!(f, "node {} can't be streamed as it isn't pure", node.0)
+            }
+            PipelineError::Cyclic => write!(f, "the graph has a cycle"),
+            PipelineError::Spawn(err) => write!(f, "could not start a pipeline stage: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// What came out of the end of a pipeline
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReport {
+    pub chunks: usize,
+    /// Values that reached each input of the streamed nodes that nothing
+    /// else is streamed to, one for each chunk in order
+    pub received: BTreeMap<InPinId, Vec<TypedData>>,
+    /// Time until the first chunk had passed through every node
+    pub first_chunk: Option<Duration>,
+    pub elapsed: Duration,
+    /// Nodes that failed to update a chunk, with the first reason each gave
+    pub failures: Vec<NodeFailure>,
+}
+
+/// Where an input of a streamed node gets its values from
+enum Feed {
+    Stream(Receiver<TypedData>),
+    Constant(Option<TypedData>),
+}
+
+/// A streamed node, with the channels joining it to its neighbours
+struct Stage {
+    id: NodeId,
+    kind: String,
+    params: serde_json::Value,
+    /// Feeds of each wire into each input, in the order they are reduced
+    inputs: Vec<Vec<Feed>>,
+    /// Channels to the inputs wired to each output
+    outputs: Vec<Vec<SyncSender<TypedData>>>,
+    /// Whether the node's inputs are recorded in the report, as no streamed
+    /// node comes after it
+    last: bool,
+}
+
+/// What a stage thread found once its input ran out
+struct StageResult {
+    id: NodeId,
+    received: Vec<Vec<TypedData>>,
+    first_chunk: Option<Instant>,
+    failure: Option<String>,
+}
+
+/// Streams the list from `pipeline.source` through the nodes downstream of
+/// it, see the [module documentation](self)
+pub fn run(
+    snarl: &Snarl<Box<dyn Node>>,
+    pipeline: Pipeline,
+    ctx: &EvalContext,
+) -> Result<PipelineReport, PipelineError> {
+    let source = pipeline.source;
+    let source_node = snarl
+        .get_node(source.node)
+        .filter(|node| source.output < node.outputs().len())
+        .ok_or(PipelineError::NoSuchOutput(source))?;
+    let Some(TypedData::List(values)) = source_node.output_value(source.output) else {
+        return Err(PipelineError::NotAList(source));
+    };
+
+    // Every node downstream of the wires leaving the source output
+    let graph = node_graph::as_petgraph(snarl);
+    let order = petgraph::algo::toposort(&graph, None).map_err(|_| PipelineError::Cyclic)?;
+    let consumers = snarl
+        .out_pin(source)
+        .remotes
+        .iter()
+        .map(|pin| pin.node)
+        .collect::<BTreeSet<_>>();
+    let mut streamed = BTreeSet::new();
+    for idx in graph.node_indices() {
+        if consumers.contains(&graph[idx]) {
+            let bfs = petgraph::visit::Bfs::new(&graph, idx);
+            streamed.extend(bfs.iter(&graph).map(|idx| graph[idx]));
+        }
+    }
+    let kinds = registry::node_kinds()
+        .into_iter()
+        .map(|kind| kind.name)
+        .collect::<BTreeSet<_>>();
+    for &id in &streamed {
+        let node = snarl[id].as_ref();
+        if !node.is_pure() || !kinds.contains(node.name().as_str()) {
+            return Err(PipelineError::NotPure(id));
+        }
+    }
+
+    // A bounded channel for every wire carrying chunks
+    let mut senders = BTreeMap::<OutPinId, Vec<SyncSender<TypedData>>>::new();
+    let mut receivers = BTreeMap::<(OutPinId, InPinId), Receiver<TypedData>>::new();
+    for &id in &streamed {
+        for input in 0..snarl[id].inputs().len() {
+            let to = InPinId { node: id, input };
+            for from in snarl.in_pin(to).remotes {
+                if from == source || streamed.contains(&from.node) {
+                    let (sender, receiver) = mpsc::sync_channel(pipeline.capacity);
+                    senders.entry(from).or_default().push(sender);
+                    receivers.insert((from, to), receiver);
+                }
+            }
+        }
+    }
+
+    let mut stages = Vec::new();
+    for idx in order {
+        let id = graph[idx];
+        if !streamed.contains(&id) {
+            continue;
+        }
+        let node = snarl[id].as_ref();
+        let inputs = (0..node.inputs().len())
+            .map(|input| {
+                let to = InPinId { node: id, input };
+                let mut remotes = snarl.in_pin(to).remotes;
+                remotes.sort();
+                if remotes.is_empty() {
+                    return vec![Feed::Constant(node.input_default(input))];
+                }
+                remotes
+                    .into_iter()
+                    .map(|from| match receivers.remove(&(from, to)) {
+                        Some(receiver) => Feed::Stream(receiver),
+                        None => Feed::Constant(snarl[from.node].output_value(from.output)),
+                    })
+                    .collect()
+            })
+            .collect();
+        let outputs = (0..node.outputs().len())
+            .map(|output| {
+                senders
+                    .remove(&OutPinId { node: id, output })
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        stages.push(Stage {
+            id,
+            kind: node.name(),
+            params: node.save(),
+            inputs,
+            last: outputs.iter().all(Vec::is_empty),
+            outputs,
+        });
+    }
+    let feed = senders.remove(&source).unwrap_or_default();
+
+    let ctx = EvalContext {
+        variables: ctx.variables.clone(),
+        seed: ctx.seed,
+        tick: ctx.tick,
+        cancellation: ctx.cancellation.clone(),
+        ..Default::default()
+    };
+    let chunks = values
+        .chunks(pipeline.chunk_size.max(1))
+        .map(|chunk| TypedData::List(chunk.to_vec()))
+        .collect::<Vec<_>>();
+    let chunk_count = chunks.len();
+    let started = Instant::now();
+
+    let results = std::thread::scope(|scope| {
+        let mut handles = Vec::new();
+        for stage in stages {
+            let ctx = ctx.clone();
+            let handle = std::thread::Builder::new()
+                .name(format!("pipeline {}", stage.id.0))
+                .spawn_scoped(scope, move || run_stage(stage, &ctx))
+                .map_err(PipelineError::Spawn)?;
+            handles.push(handle);
+        }
+        for chunk in chunks {
+            if ctx.cancellation.is_cancelled() {
+                break;
+            }
+            for sender in &feed {
+                let _ = sender.send(chunk.clone());
+            }
+        }
+        // Closing the source's channels lets each stage finish in turn
+        drop(feed);
+        Ok::<_, PipelineError>(
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .collect::<Vec<_>>(),
+        )
+    })?;
+
+    let mut report = PipelineReport {
+        chunks: chunk_count,
+        elapsed: started.elapsed(),
+        ..Default::default()
+    };
+    for result in results {
+        if let Some(message) = result.failure {
+            report.failures.push(NodeFailure {
+                node: result.id,
+                message,
+            });
+        }
+        if let Some(first) = result.first_chunk {
+            let latency = first.duration_since(started);
+            report.first_chunk = Some(report.first_chunk.map_or(latency, |max| max.max(latency)));
+        }
+        for (input, values) in result.received.into_iter().enumerate() {
+            report.received.insert(
+                InPinId {
+                    node: result.id,
+                    input,
+                },
+                values,
+            );
+        }
+    }
+    Ok(report)
+}
+
+/// Updates a copy of a stage's node with each chunk as it arrives, until its
+/// streamed inputs run out
+fn run_stage(stage: Stage, ctx: &EvalContext) -> StageResult {
+    let mut result = StageResult {
+        id: stage.id,
+        received: vec![Vec::new(); stage.inputs.len()],
+        first_chunk: None,
+        failure: None,
+    };
+    let Some(mut node) = registry::create_node(&stage.kind) else {
+        result.failure = Some(format!("unknown node type {:?}", stage.kind));
+        return result;
+    };
+    node.restore(&stage.params);
+    let reducers = (0..stage.inputs.len())
+        .map(|input| node.input_reducer(input))
+        .collect::<Vec<_>>();
+    let output_count = node.outputs().len();
+
+    'chunks: loop {
+        let mut inputs = Vec::with_capacity(stage.inputs.len());
+        for (feeds, reducer) in stage.inputs.iter().zip(&reducers) {
+            let mut values = Vec::with_capacity(feeds.len());
+            for feed in feeds {
+                let value = match feed {
+                    Feed::Stream(receiver) => match receiver.recv() {
+                        Ok(value) => Some(value),
+                        Err(_) => break 'chunks,
+                    },
+                    Feed::Constant(value) => value.clone(),
+                };
+                values.push(value.unwrap_or(TypedData::Unknown));
+            }
+            let value = match reducer {
+                Some(reducer) if values.len() > 1 => reducer
+                    .reduce(values)
+                    .unwrap_or_else(|| error(stage.id, "inputs can't be combined")),
+                _ => values.into_iter().next().unwrap_or(TypedData::Unknown),
+            };
+            inputs.push(value);
+        }
+        let outputs = match inputs.iter().find_map(TypedData::as_error) {
+            Some(err) if !node.accepts_errors() => {
+                vec![TypedData::Error(err.clone()); output_count]
+            }
+            _ => {
+                let mut update = UpdateContext::new(ctx, stage.id);
+                node.update(&inputs, &mut update);
+                let failure = node.error();
+                if result.failure.is_none() {
+                    result.failure.clone_from(&failure);
+                }
+                (0..output_count)
+                    .map(|idx| match (&failure, node.output_value(idx)) {
+                        (None, Some(value)) => value,
+                        (Some(message), _) => error(stage.id, message),
+                        (None, None) => error(stage.id, "no value was produced"),
+                    })
+                    .collect()
+            }
+        };
+
+        if stage.last {
+            result.first_chunk.get_or_insert_with(Instant::now);
+            for (received, value) in result.received.iter_mut().zip(inputs) {
+                received.push(value);
+            }
+        }
+        for (senders, value) in stage.outputs.iter().zip(outputs) {
+            for sender in senders {
+                let _ = sender.send(value.clone());
+            }
+        }
+    }
+    if !stage.last {
+        result.received.clear();
+    }
+    result
+}
+
+fn error(node: NodeId, message: &str) -> TypedData {
+    TypedData::Error(ErrorValue {
+        message: message.to_owned(),
+        origin: node.0,
+    })
+}
@@ -0,0 +1,260 @@
+//! A sink that charts the numbers reaching it, either as they arrive over
+//! successive evaluations or all at once from a list
+
+use std::collections::VecDeque;
+
+use egui::{self, epaint, pos2, vec2, Sense, Shape, Stroke, Ui};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, Node, TypedData},
+};
+
+/// Size of the chart drawn in the node's body
+const CHART_SIZE: egui::Vec2 = vec2(200., 100.);
+
+/// Charts the last few numbers it has received, or the numbers in a list
+#[derive(Debug, Clone)]
+pub struct PlotNode {
+    /// Number of values kept
+    window: usize,
+    /// Draws a point for each value rather than a line through them
+    scatter: bool,
+    /// Range of the vertical axis, or `None` to fit the values
+    range: Option<(f64, f64)>,
+    values: VecDeque<f64>,
+    label: Option<String>,
+}
+
+impl Default for PlotNode {
+    fn default() -> Self {
+        Self {
+            window: 100,
+            scatter: false,
+            range: None,
+            values: VecDeque::new(),
+            label: None,
+        }
+    }
+}
+
+impl PlotNode {
+    fn drop_old_values(&mut self) {
+        while self.values.len() > self.window {
+            self.values.pop_front();
+        }
+    }
+
+    /// Range of the vertical axis, widened when every value is the same so
+    /// that they can still be drawn
+    fn y_range(&self) -> (f64, f64) {
+        let (min, max) = self.range.unwrap_or_else(|| {
+            self.values
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                    (min.min(*value), max.max(*value))
+                })
+        });
+        if !min.is_finite() || !max.is_finite() {
+            (0., 1.)
+        } else if min >= max {
+            (min - 1., max + 1.)
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Shows how the values are charted. Changing these only changes how
+    /// the values are drawn, so doesn't need the node to be recalculated.
+    fn show_settings(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Last");
+            let response = ui.add(egui::DragValue::new(&mut self.window).clamp_range(1..=10_000));
+            // A smaller window applies straight away, without adding a value
+            if response.changed() {
+                self.drop_old_values();
+            }
+            ui.checkbox(&mut self.scatter, "Points");
+        });
+        ui.horizontal(|ui| {
+            let mut fixed = self.range.is_some();
+            if ui
+                .checkbox(&mut fixed, "Fixed range")
+                .on_hover_text("Keep the vertical axis between two values, rather than fitting it to the values shown")
+                .changed()
+            {
+                self.range = fixed.then(|| self.y_range());
+            }
+            if let Some((min, max)) = &mut self.range {
+                ui.add(egui::DragValue::new(min).speed(0.1));
+                ui.label("to");
+                ui.add(egui::DragValue::new(max).speed(0.1));
+            }
+        });
+    }
+
+    fn show_chart(&self, ui: &mut Ui) {
+        let (response, painter) = ui.allocate_painter(CHART_SIZE, Sense::hover());
+        let rect = response.rect;
+        let visuals = ui.visuals();
+        painter.rect(
+            rect,
+            2.,
+            visuals.extreme_bg_color,
+            visuals.widgets.noninteractive.bg_stroke,
+        );
+        if self.values.is_empty() {
+            return;
+        }
+
+        let (min, max) = self.y_range();
+        let plot_rect = rect.shrink(4.);
+        let last = (self.values.len() - 1).max(1) as f32;
+        let points = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                let x = plot_rect.left() + plot_rect.width() * idx as f32 / last;
+                let y = ((value - min) / (max - min)) as f32;
+                pos2(x, plot_rect.bottom() - plot_rect.height() * y.clamp(0., 1.))
+            })
+            .collect::<Vec<_>>();
+        let colour = visuals.selection.bg_fill;
+        if self.scatter || points.len() == 1 {
+            for point in &points {
+                painter.circle_filled(*point, 2., colour);
+            }
+        } else {
+            painter.add(Shape::line(points.clone(), Stroke::new(1.5, colour)));
+        }
+
+        let font = egui::FontId::monospace(9.);
+        let text_colour = visuals.weak_text_color();
+        painter.text(
+            rect.left_top() + vec2(2., 1.),
+            egui::Align2::LEFT_TOP,
+            format_float(max),
+            font.clone(),
+            text_colour,
+        );
+        painter.text(
+            rect.left_bottom() + vec2(2., -1.),
+            egui::Align2::LEFT_BOTTOM,
+            format_float(min),
+            font,
+            text_colour,
+        );
+
+        // Hovering shows the value nearest the pointer
+        if let Some(pointer) = response.hover_pos() {
+            let idx = (((pointer.x - plot_rect.left()) / plot_rect.width() * last).round()
+                as usize)
+                .min(self.values.len() - 1);
+            let point = points[idx];
+            painter.add(epaint::CircleShape::stroke(
+                point,
+                4.,
+                Stroke::new(1., visuals.strong_text_color()),
+            ));
+            painter.line_segment(
+                [
+                    pos2(point.x, plot_rect.top()),
+                    pos2(point.x, plot_rect.bottom()),
+                ],
+                Stroke::new(0.5, text_colour),
+            );
+            response.on_hover_text_at_pointer(format!(
+                "{}: {}",
+                idx,
+                format_float(self.values[idx])
+            ));
+        }
+    }
+}
+
+impl Node for PlotNode {
+    fn name(&self) -> String {
+        "Plot".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    /// Takes a number, which is added to the values charted, or a list of
+    /// numbers, which replaces them
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Unknown]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        ui.vertical(|ui| {
+            self.show_settings(ui);
+            self.show_chart(ui);
+        });
+        false
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        match &inputs[0] {
+            TypedData::Number(value) => self.values.push_back(*value),
+            TypedData::List(values) => {
+                self.values = values
+                    .iter()
+                    .filter_map(|value| match value {
+                        TypedData::Number(value) => Some(*value),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+        self.drop_old_values();
+    }
+
+    fn reset(&mut self) {
+        self.values.clear();
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({
+            "window": self.window,
+            "scatter": self.scatter,
+            "range": self.range.map(|(min, max)| [min, max]),
+        })
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(window) = params.get("window").and_then(|window| window.as_u64()) {
+            self.window = (window as usize).max(1);
+        }
+        if let Some(scatter) = params.get("scatter").and_then(|scatter| scatter.as_bool()) {
+            self.scatter = scatter;
+        }
+        self.range = params
+            .get("range")
+            .and_then(|range| range.as_array())
+            .and_then(|range| Some((range.first()?.as_f64()?, range.get(1)?.as_f64()?)));
+    }
+}
@@ -0,0 +1,454 @@
+//! Node packs shared by third parties, loaded from the plugins directory so
+//! that new node types can be added without rebuilding the application.
+//!
+//! Each pack is a JSON or YAML file describing its nodes, whose work is done
+//! by a [Rhai](https://rhai.rs) script as in a
+//! [`ScriptNode`](crate::script::ScriptNode):
+//!
+//! ```yaml
+//! name: Geometry
+//! version: "1.0"
+//! nodes:
+//!   - name: Distance
+//!     inputs: [{ name: a, data_type: Vec2 }, { name: b, data_type: Vec2 }]
+//!     outputs: [{ name: distance, data_type: Number }]
+//!     script: |
+//!       let dx = a[0] - b[0];
+//!       let dy = a[1] - b[1];
+//!       distance = sqrt(dx * dx + dy * dy);
+//! ```
+//!
+//! Scripts run in the same sandbox on every platform, so packs work the
+//! same natively and on the web.
+//!
+//! Packs only add node types. Their pins use the built-in [`DataType`]s, as
+//! packs can't yet declare data types of their own.
+//!
+//! Packs are reloaded when their files change: the file of each plugin node
+//! is its [`Node::watched_path`], so editing a pack re-evaluates the nodes
+//! made from it with the new script.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock, RwLock,
+    },
+    time::SystemTime,
+};
+
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{DataType, Node, TypedData},
+    registry::{self, NodeKind},
+    script::{Pin, ScriptNode},
+};
+
+/// A node type provided by a pack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginKind {
+    pub name: String,
+    /// Where the node is listed in the add-node menu, the name of its pack
+    /// if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default)]
+    pub inputs: Vec<Pin>,
+    #[serde(default)]
+    pub outputs: Vec<Pin>,
+    /// Rhai script given each input as a variable named after its pin,
+    /// which gives each output a value by assigning to its variable
+    pub script: String,
+}
+
+/// A set of node types shipped together in one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodePack {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub nodes: Vec<PluginKind>,
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Yaml(serde_yaml::Error),
+    /// A node type with the same name as a built-in one or one of another
+    /// pack
+    DuplicateName(String),
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Io(err) => write!(f, "{}", err),
+            PluginError::Json(err) => write!(f, "invalid JSON: {}", err),
+            PluginError::Yaml(err) => write!(f, "invalid YAML: {}", err),
+            PluginError::DuplicateName(name) => {
+                write!(f, "a node type named {:?} already exists", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+impl From<std::io::Error> for PluginError {
+    fn from(err: std::io::Error) -> Self {
+        PluginError::Io(err)
+    }
+}
+
+impl NodePack {
+    /// Reads a pack from a `.json`, `.yaml` or `.yml` file
+    pub fn load(path: &Path) -> Result<Self, PluginError> {
+        let contents = std::fs::read_to_string(path)?;
+        if is_yaml(path) {
+            serde_yaml::from_str(&contents).map_err(PluginError::Yaml)
+        } else {
+            serde_json::from_str(&contents).map_err(PluginError::Json)
+        }
+    }
+}
+
+fn is_yaml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "yaml" || extension == "yml")
+}
+
+fn is_pack(path: &Path) -> bool {
+    is_yaml(path)
+        || path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[derive(Debug)]
+struct LoadedPack {
+    path: PathBuf,
+    /// When the file was last modified as of loading it
+    modified: Option<SystemTime>,
+    pack: NodePack,
+}
+
+/// The packs found in the plugins directory
+#[derive(Debug, Default)]
+pub struct PluginLibrary {
+    dir: Option<PathBuf>,
+    packs: Vec<LoadedPack>,
+    /// Files that couldn't be loaded, and why
+    errors: Vec<(PathBuf, PluginError)>,
+}
+
+impl PluginLibrary {
+    /// `GRAPH_GUI_PLUGINS` if set, otherwise `~/.config/graph_gui/plugins`
+    pub fn default_dir() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("GRAPH_GUI_PLUGINS") {
+            return Some(path.into());
+        }
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join(".config/graph_gui/plugins"))
+    }
+
+    /// Loads every pack in a directory, which is treated as empty if it does
+    /// not exist. Packs that can't be loaded are left out and listed in
+    /// [`PluginLibrary::errors`].
+    pub fn load(dir: &Path) -> std::io::Result<Self> {
+        let mut library = Self {
+            dir: Some(dir.to_owned()),
+            ..Default::default()
+        };
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(library),
+            Err(err) => return Err(err),
+        };
+        let mut paths = entries
+            .map(|entry| Ok(entry?.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.retain(|path| is_pack(path));
+        paths.sort();
+        for path in paths {
+            library.add(path);
+        }
+        Ok(library)
+    }
+
+    fn add(&mut self, path: PathBuf) {
+        let modified = modified(&path);
+        match NodePack::load(&path).and_then(|pack| {
+            self.check_names(&pack)?;
+            Ok(pack)
+        }) {
+            Ok(pack) => self.packs.push(LoadedPack {
+                path,
+                modified,
+                pack,
+            }),
+            Err(err) => self.errors.push((path, err)),
+        }
+    }
+
+    fn check_names(&self, pack: &NodePack) -> Result<(), PluginError> {
+        let builtin = registry::builtin_kinds();
+        for (idx, kind) in pack.nodes.iter().enumerate() {
+            let taken = builtin.iter().any(|existing| existing.name == kind.name)
+                || self.find(&kind.name).is_some()
+                || pack.nodes[..idx]
+                    .iter()
+                    .any(|existing| existing.name == kind.name);
+            if taken {
+                return Err(PluginError::DuplicateName(kind.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    pub fn packs(&self) -> impl Iterator<Item = &NodePack> {
+        self.packs.iter().map(|loaded| &loaded.pack)
+    }
+
+    pub fn errors(&self) -> &[(PathBuf, PluginError)] {
+        &self.errors
+    }
+
+    /// A node type of one of the packs, along with its pack and the file it
+    /// came from
+    fn find(&self, name: &str) -> Option<(&LoadedPack, &PluginKind)> {
+        self.packs.iter().find_map(|loaded| {
+            let kind = loaded.pack.nodes.iter().find(|kind| kind.name == name)?;
+            Some((loaded, kind))
+        })
+    }
+
+    /// Whether a pack file was added, removed or modified since the packs
+    /// were loaded
+    fn is_stale(&self) -> bool {
+        let Some(dir) = &self.dir else {
+            return false;
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return !self.packs.is_empty() || !self.errors.is_empty();
+        };
+        let mut count = 0;
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if !is_pack(&path) {
+                continue;
+            }
+            count += 1;
+            let known = self
+                .packs
+                .iter()
+                .find(|loaded| loaded.path == path)
+                .map(|loaded| loaded.modified);
+            match known {
+                Some(before) if before == modified(&path) => {}
+                // Files that failed to load are retried when they change too
+                None if self.errors.iter().any(|(failed, _)| *failed == path) => {}
+                _ => return true,
+            }
+        }
+        count != self.packs.len() + self.errors.len()
+    }
+}
+
+/// Counts reloads of the packs, so that nodes can tell when theirs changed
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn global() -> &'static RwLock<PluginLibrary> {
+    static LIBRARY: OnceLock<RwLock<PluginLibrary>> = OnceLock::new();
+    LIBRARY.get_or_init(|| {
+        let library = PluginLibrary::default_dir()
+            .and_then(|dir| PluginLibrary::load(&dir).ok())
+            .unwrap_or_default();
+        RwLock::new(library)
+    })
+}
+
+/// Calls `f` with the application's plugins
+pub fn with_library<T>(f: impl FnOnce(&PluginLibrary) -> T) -> T {
+    f(&global().read().unwrap())
+}
+
+/// Loads the packs in the plugins directory again
+pub fn reload() -> std::io::Result<()> {
+    let mut library = global().write().unwrap();
+    if let Some(dir) = library.dir.clone().or_else(PluginLibrary::default_dir) {
+        *library = PluginLibrary::load(&dir)?;
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Reloads the packs if any of their files changed, returning whether they
+/// did
+pub fn refresh() -> bool {
+    if !with_library(PluginLibrary::is_stale) {
+        return false;
+    }
+    reload().is_ok()
+}
+
+/// The node types of the installed packs
+pub fn node_kinds() -> Vec<NodeKind> {
+    with_library(|library| {
+        library
+            .packs
+            .iter()
+            .flat_map(|loaded| {
+                loaded.pack.nodes.iter().map(|kind| {
+                    let name = kind.name.clone();
+                    let category = kind.category.as_ref().unwrap_or(&loaded.pack.name);
+                    NodeKind {
+                        name: Cow::Owned(kind.name.clone()),
+                        category: Cow::Owned(category.clone()),
+                        create: Arc::new(move || -> Box<dyn Node> {
+                            Box::new(PluginNode::new(&name))
+                        }),
+                    }
+                })
+            })
+            .collect()
+    })
+}
+
+/// A node whose type comes from a pack, running the script the pack gives
+/// it
+pub struct PluginNode {
+    kind: String,
+    /// File of the pack, which is watched for changes
+    path: Option<PathBuf>,
+    description: String,
+    script: ScriptNode,
+    /// [`GENERATION`] of the packs the script was taken from
+    generation: u64,
+    label: Option<String>,
+}
+
+impl PluginNode {
+    pub fn new(kind: &str) -> Self {
+        let mut node = Self {
+            kind: kind.to_owned(),
+            path: None,
+            description: String::new(),
+            script: ScriptNode::new("", Vec::new(), Vec::new()),
+            generation: 0,
+            label: None,
+        };
+        node.load();
+        node
+    }
+
+    /// Takes the script and pins of the node type from its pack
+    fn load(&mut self) {
+        self.generation = GENERATION.load(Ordering::Relaxed);
+        with_library(|library| {
+            if let Some((loaded, kind)) = library.find(&self.kind) {
+                self.path = Some(loaded.path.clone());
+                self.description = kind.description.clone();
+                self.script =
+                    ScriptNode::new(&kind.script, kind.inputs.clone(), kind.outputs.clone());
+            } else {
+                self.path = None;
+            }
+        });
+    }
+}
+
+impl Node for PluginNode {
+    fn name(&self) -> String {
+        self.kind.clone()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        self.script.inputs()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.script.outputs()
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        self.script.output_value(idx)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        self.script.show_input(idx, remote, ui)
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        self.script.show_output(idx, ui)
+    }
+
+    fn has_body(&self) -> bool {
+        !self.description.is_empty()
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        ui.weak(&self.description);
+        false
+    }
+
+    fn watched_path(&self) -> Option<PathBuf> {
+        self.path.clone()
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.script.cached_outputs()
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        self.script.restore_outputs(outputs);
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], ctx: &mut UpdateContext) {
+        refresh();
+        if self.generation != GENERATION.load(Ordering::Relaxed) {
+            self.load();
+        }
+        if self.path.is_some() {
+            self.script.update(inputs, ctx);
+        }
+    }
+
+    fn error(&self) -> Option<String> {
+        if self.path.is_none() {
+            return Some(format!(
+                "No installed plugin provides {:?}, see the plugins directory",
+                self.kind
+            ));
+        }
+        self.script.error()
+    }
+}
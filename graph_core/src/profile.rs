@@ -0,0 +1,86 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use egui_snarl::Snarl;
+use serde::{Deserialize, Serialize};
+
+use crate::{group::GroupNode, node_graph::Node};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EvaluationMode {
+    /// Recalculate the affected nodes after every edit
+    #[default]
+    OnChange,
+    /// Only recalculate when asked to
+    Manual,
+}
+
+fn default_workers() -> usize {
+    1
+}
+
+/// A named configuration of a graph, so that one graph can be run in several
+/// scenarios
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Parameters of the root graph's input nodes, keyed by node id
+    #[serde(default)]
+    pub inputs: BTreeMap<usize, serde_json::Value>,
+    #[serde(default)]
+    pub mode: EvaluationMode,
+    /// Number of workers used when scheduling the graph's tasks
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+    /// Ids of the root graph's groups that are disabled
+    #[serde(default)]
+    pub disabled_groups: BTreeSet<usize>,
+}
+
+impl Profile {
+    /// Captures the current input values and disabled groups of a graph.
+    ///
+    /// Input nodes are those with no inputs of their own and some saved
+    /// parameters, such as number and list nodes.
+    pub fn capture(
+        name: String,
+        snarl: &Snarl<Box<dyn Node>>,
+        mode: EvaluationMode,
+        workers: usize,
+    ) -> Self {
+        let mut inputs = BTreeMap::new();
+        let mut disabled_groups = BTreeSet::new();
+        for (id, node) in snarl.node_ids() {
+            if let Some(group) = node.as_any().downcast_ref::<GroupNode>() {
+                if !group.enabled() {
+                    disabled_groups.insert(id.0);
+                }
+            } else if node.inputs().is_empty() {
+                let params = node.save();
+                if !params.is_null() {
+                    inputs.insert(id.0, params);
+                }
+            }
+        }
+        Self {
+            name,
+            inputs,
+            mode,
+            workers,
+            disabled_groups,
+        }
+    }
+
+    /// Sets the graph's input values and groups to match the profile. The
+    /// graph needs to be evaluated afterwards.
+    pub fn apply(&self, snarl: &mut Snarl<Box<dyn Node>>) {
+        let ids = snarl.node_ids().map(|(id, _)| id).collect::<Vec<_>>();
+        for id in ids {
+            let node = &mut snarl[id];
+            if let Some(group) = node.as_any_mut().downcast_mut::<GroupNode>() {
+                group.set_enabled(!self.disabled_groups.contains(&id.0));
+            } else if let Some(params) = self.inputs.get(&id.0) {
+                node.restore(params);
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! A lightweight profiler timing named scopes, such as evaluations and
+//! rendering, so that slow frames can be broken down.
+//!
+//! Profiling is off by default and costs one atomic load per scope while
+//! off. Once [enabled](set_enabled), finished scopes are collected from
+//! every thread until they are [taken](take_scopes).
+//!
+//! ```ignore
+//! profiler::set_enabled(true);
+//! {
+//!     let _scope = profiler::scope("evaluate");
+//!     node_graph::evaluate(&mut snarl, None);
+//! }
+//! let scopes = profiler::take_scopes();
+//! ```
+
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use web_time::Instant;
+
+/// Most scopes held between calls to [`take_scopes`], so that memory stays
+/// bounded if nothing collects them
+const MAX_SCOPES: usize = 100_000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Scopes finished since they were last taken
+static SCOPES: Mutex<Vec<ScopeRecord>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// Number of scopes open on this thread
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A finished scope
+#[derive(Debug, Clone)]
+pub struct ScopeRecord {
+    pub name: Cow<'static, str>,
+    /// Name of the thread the scope ran on
+    pub thread: String,
+    /// Number of scopes it was nested in on its thread
+    pub depth: usize,
+    pub start: Instant,
+    pub duration: Duration,
+}
+
+impl ScopeRecord {
+    pub fn end(&self) -> Instant {
+        self.start + self.duration
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        SCOPES.lock().unwrap().clear();
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Times the code until the returned guard is dropped, if profiling is
+/// enabled
+#[must_use = "the scope ends when the guard is dropped"]
+pub fn scope(name: &'static str) -> Option<ScopeGuard> {
+    is_enabled().then(|| ScopeGuard::new(Cow::Borrowed(name)))
+}
+
+/// Like [`scope`], only building the name if profiling is enabled
+#[must_use = "the scope ends when the guard is dropped"]
+pub fn scope_with(name: impl FnOnce() -> String) -> Option<ScopeGuard> {
+    is_enabled().then(|| ScopeGuard::new(Cow::Owned(name())))
+}
+
+/// Takes the scopes that have finished since the last call, in the order
+/// they finished
+pub fn take_scopes() -> Vec<ScopeRecord> {
+    std::mem::take(&mut *SCOPES.lock().unwrap())
+}
+
+/// Total time spent in and number of calls to each scope, slowest first.
+/// Time in a scope nested in one of the same name is counted twice.
+pub fn totals(scopes: &[ScopeRecord]) -> Vec<(&str, Duration, usize)> {
+    let mut totals = HashMap::<&str, (Duration, usize)>::new();
+    for scope in scopes {
+        let total = totals.entry(&scope.name).or_default();
+        total.0 += scope.duration;
+        total.1 += 1;
+    }
+    let mut totals = totals
+        .into_iter()
+        .map(|(name, (duration, calls))| (name, duration, calls))
+        .collect::<Vec<_>>();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    totals
+}
+
+/// An open scope, recorded when dropped
+#[derive(Debug)]
+pub struct ScopeGuard {
+    name: Cow<'static, str>,
+    depth: usize,
+    start: Instant,
+}
+
+impl ScopeGuard {
+    fn new(name: Cow<'static, str>) -> Self {
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        Self {
+            name,
+            depth,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        DEPTH.with(|depth| depth.set(self.depth));
+        // Scopes still open when profiling was turned off are dropped
+        if !is_enabled() {
+            return;
+        }
+        let mut scopes = SCOPES.lock().unwrap();
+        if scopes.len() >= MAX_SCOPES {
+            return;
+        }
+        let thread = std::thread::current();
+        scopes.push(ScopeRecord {
+            name: std::mem::take(&mut self.name),
+            thread: thread.name().unwrap_or("worker").to_owned(),
+            depth: self.depth,
+            start: self.start,
+            duration,
+        });
+    }
+}
@@ -0,0 +1,175 @@
+use std::{borrow::Cow, sync::Arc};
+
+use crate::{
+    args::ArgNode,
+    command::CommandNode,
+    convert_nodes::{ParseNumberNode, ToTextNode},
+    expression::ExpressionNode,
+    file_nodes::{ReadFileNode, WriteFileNode},
+    flow_nodes::{DeadbandNode, ThrottleNode},
+    graphics_nodes::{MakeColorNode, MakeVecNode, SplitVecNode},
+    group::{GroupInputNode, GroupNode, GroupOutputNode},
+    http::HttpRequestNode,
+    list_nodes::{LengthNode, ListLiteralNode, MapAddNode, RangeNode, SumNode},
+    node_graph::{
+        AddNode, CompareNode, CompareOp, DataType, IfNode, LogicNode, LogicOp, Node, NotNode,
+        NumberNode, SinkNode,
+    },
+    plot::PlotNode,
+    plugins,
+    script::ScriptNode,
+    secrets::SecretNode,
+    state_nodes::{CounterNode, DelayNode, MovingAverageNode, RunningSumNode, TimeNode},
+    table::{CsvSourceNode, TableSinkNode},
+};
+
+/// A type of node that can be created by name
+#[derive(Clone)]
+pub struct NodeKind {
+    /// Matches [`Node::name`] for nodes created by `create`
+    pub name: Cow<'static, str>,
+    pub category: Cow<'static, str>,
+    pub create: Arc<dyn Fn() -> Box<dyn Node> + Send + Sync>,
+}
+
+impl NodeKind {
+    fn builtin(name: &'static str, category: &'static str, create: fn() -> Box<dyn Node>) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            category: Cow::Borrowed(category),
+            create: Arc::new(create),
+        }
+    }
+}
+
+/// Every node type known to the application, including those of the
+/// installed plugins
+pub fn node_kinds() -> Vec<NodeKind> {
+    let mut kinds = builtin_kinds();
+    kinds.extend(plugins::node_kinds());
+    kinds
+}
+
+/// Node types built into the application
+pub(crate) fn builtin_kinds() -> Vec<NodeKind> {
+    vec![
+        NodeKind::builtin("Number", "Values", || Box::new(NumberNode::new(0.))),
+        NodeKind::builtin("Sink", "Values", || Box::<SinkNode>::default()),
+        NodeKind::builtin("Plot", "Values", || Box::<PlotNode>::default()),
+        NodeKind::builtin("Table", "Values", || Box::<TableSinkNode>::default()),
+        NodeKind::builtin("Secret", "Values", || Box::<SecretNode>::default()),
+        NodeKind::builtin("Argument", "Values", || Box::<ArgNode>::default()),
+        NodeKind::builtin("Add", "Math", || Box::<AddNode>::default()),
+        NodeKind::builtin("Expression", "Math", || Box::<ExpressionNode>::default()),
+        NodeKind::builtin("Script", "Math", || Box::<ScriptNode>::default()),
+        NodeKind::builtin("If", "Logic", || Box::<IfNode>::default()),
+        NodeKind::builtin("Greater Than", "Logic", || {
+            Box::new(CompareNode::new(CompareOp::GreaterThan))
+        }),
+        NodeKind::builtin("Less Than", "Logic", || {
+            Box::new(CompareNode::new(CompareOp::LessThan))
+        }),
+        NodeKind::builtin("Equals", "Logic", || {
+            Box::new(CompareNode::new(CompareOp::Equals))
+        }),
+        NodeKind::builtin("And", "Logic", || Box::new(LogicNode::new(LogicOp::And))),
+        NodeKind::builtin("Or", "Logic", || Box::new(LogicNode::new(LogicOp::Or))),
+        NodeKind::builtin("Not", "Logic", || Box::<NotNode>::default()),
+        NodeKind::builtin("To Text", CONVERSIONS, || Box::<ToTextNode>::default()),
+        NodeKind::builtin("Parse Number", CONVERSIONS, || {
+            Box::<ParseNumberNode>::default()
+        }),
+        NodeKind::builtin("Range", "Lists", || Box::<RangeNode>::default()),
+        NodeKind::builtin("List", "Lists", || Box::<ListLiteralNode>::default()),
+        NodeKind::builtin("Sum", "Lists", || Box::<SumNode>::default()),
+        NodeKind::builtin("Length", "Lists", || Box::<LengthNode>::default()),
+        NodeKind::builtin("Map Add", "Lists", || Box::<MapAddNode>::default()),
+        NodeKind::builtin("Throttle", "Flow", || Box::<ThrottleNode>::default()),
+        NodeKind::builtin("Deadband", "Flow", || Box::<DeadbandNode>::default()),
+        NodeKind::builtin("Running Sum", "State", || Box::<RunningSumNode>::default()),
+        NodeKind::builtin("Moving Average", "State", || {
+            Box::<MovingAverageNode>::default()
+        }),
+        NodeKind::builtin("Counter", "State", || Box::<CounterNode>::default()),
+        NodeKind::builtin("Delay", "State", || Box::<DelayNode>::default()),
+        NodeKind::builtin("Time", "State", || Box::<TimeNode>::default()),
+        NodeKind::builtin("Make Colour", "Graphics", || {
+            Box::<MakeColorNode>::default()
+        }),
+        NodeKind::builtin("Make Vector", "Graphics", || Box::<MakeVecNode>::default()),
+        NodeKind::builtin("Split Vector", "Graphics", || {
+            Box::<SplitVecNode>::default()
+        }),
+        NodeKind::builtin("Read File", "Files", || Box::<ReadFileNode>::default()),
+        NodeKind::builtin("Write File", "Files", || Box::<WriteFileNode>::default()),
+        NodeKind::builtin("CSV Source", "Files", || Box::<CsvSourceNode>::default()),
+        NodeKind::builtin("HTTP Request", "Files", || {
+            Box::<HttpRequestNode>::default()
+        }),
+        NodeKind::builtin("Command", "Tasks", || Box::<CommandNode>::default()),
+        NodeKind::builtin("Group", "Groups", || Box::<GroupNode>::default()),
+        NodeKind::builtin("Group Inputs", "Groups", || {
+            Box::<GroupInputNode>::default()
+        }),
+        NodeKind::builtin("Group Outputs", "Groups", || {
+            Box::<GroupOutputNode>::default()
+        }),
+    ]
+}
+
+/// Creates a node with default parameters from its name
+pub fn create_node(name: &str) -> Option<Box<dyn Node>> {
+    node_kinds()
+        .into_iter()
+        .find(|kind| kind.name == name)
+        .map(|kind| (kind.create)())
+}
+
+/// Category of the node kinds used to convert between types that aren't
+/// otherwise compatible, including those of plugins
+pub const CONVERSIONS: &str = "Conversions";
+
+/// A conversion from one type to another that isn't otherwise compatible
+/// with it, made by a node placed between them
+pub struct Coercion {
+    pub from: DataType,
+    pub to: DataType,
+    /// Name of the [`NodeKind`] that converts between them, which takes
+    /// `from` as its only input and gives `to` as its only output
+    pub node: Cow<'static, str>,
+}
+
+/// Every conversion that is made automatically when an output is wired to an
+/// input of another type: one for each node kind in the [`CONVERSIONS`]
+/// category with a single input and output, by their types
+pub fn coercions() -> Vec<Coercion> {
+    node_kinds()
+        .into_iter()
+        .filter(|kind| kind.category == CONVERSIONS)
+        .filter_map(|kind| {
+            let node = (kind.create)();
+            let (inputs, outputs) = (node.inputs(), node.outputs());
+            let ([from], [to]) = (inputs.as_slice(), outputs.as_slice()) else {
+                return None;
+            };
+            (!from.compatible_with(to)).then(|| Coercion {
+                from: from.clone(),
+                to: to.clone(),
+                node: kind.name,
+            })
+        })
+        .collect()
+}
+
+/// Creates a node that converts `from` to `to`, if there is a coercion
+/// between them. The node's pins are checked against the wire's types, as
+/// plugin nodes can change them.
+pub fn create_coercion(from: &DataType, to: &DataType) -> Option<Box<dyn Node>> {
+    let coercion = coercions()
+        .into_iter()
+        .find(|coercion| coercion.from == *from && coercion.to == *to)?;
+    let node = create_node(&coercion.node)?;
+    let fits =
+        from.compatible_with(node.inputs().first()?) && node.outputs().first()?.compatible_with(to);
+    fits.then_some(node)
+}
@@ -0,0 +1,300 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
+    time::Duration,
+};
+
+use egui_snarl::{NodeId, Snarl};
+// `std::time::SystemTime::now` panics in the browser
+use web_time::SystemTime;
+
+use crate::node_graph::{Node, NodeRole, TypedData};
+
+/// A single node update performed during an evaluation
+#[derive(Debug, Clone)]
+pub struct NodeRun {
+    pub node: NodeId,
+    pub finished: SystemTime,
+    pub duration: Duration,
+    /// Worker that updated the node. Evaluations run every node on the
+    /// calling thread, which is worker 0, other than the nodes updated by a
+    /// [`BackgroundEvaluation`](crate::background::BackgroundEvaluation)'s
+    /// worker thread, which is worker 1.
+    pub worker: usize,
+}
+
+/// A node that could not produce its outputs during an evaluation
+#[derive(Debug, Clone)]
+pub struct NodeFailure {
+    pub node: NodeId,
+    pub message: String,
+}
+
+/// How important a logged message is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warning,
+        LogLevel::Error,
+    ];
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Debug => "Debug",
+            LogLevel::Info => "Info",
+            LogLevel::Warning => "Warning",
+            LogLevel::Error => "Error",
+        })
+    }
+}
+
+/// Something a node logged while it was updated
+#[derive(Debug, Clone)]
+pub struct NodeMessage {
+    pub node: NodeId,
+    pub level: LogLevel,
+    pub time: SystemTime,
+    pub message: String,
+}
+
+/// A node's outputs from before and after it was updated
+#[derive(Debug, Clone)]
+pub struct OutputChange {
+    pub before: Vec<Option<TypedData>>,
+    pub after: Vec<Option<TypedData>>,
+}
+
+impl OutputChange {
+    pub fn changed(&self) -> bool {
+        self.before != self.after
+    }
+
+    /// How much each output changed by, for outputs that were numbers both
+    /// before and after
+    pub fn deltas(&self) -> Vec<Option<f64>> {
+        self.after
+            .iter()
+            .enumerate()
+            .map(|(idx, after)| match (self.before.get(idx), after) {
+                (Some(Some(TypedData::Number(before))), Some(TypedData::Number(after))) => {
+                    Some(after - before)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Record of the nodes updated by one call to [`evaluate`](crate::node_graph::evaluate)
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub started: SystemTime,
+    pub runs: Vec<NodeRun>,
+    pub failures: Vec<NodeFailure>,
+    pub messages: Vec<NodeMessage>,
+    /// Outputs of each updated node from before and after its update
+    pub changes: BTreeMap<NodeId, OutputChange>,
+    /// Whether the evaluation was stopped before every node was updated
+    pub cancelled: bool,
+}
+
+impl RunReport {
+    pub fn new() -> Self {
+        Self {
+            started: SystemTime::now(),
+            runs: Vec::new(),
+            failures: Vec::new(),
+            messages: Vec::new(),
+            changes: BTreeMap::new(),
+            cancelled: false,
+        }
+    }
+
+    /// Adds the nodes updated by a later evaluation to this run. Nodes that
+    /// later evaluation reached only keep its update and failure.
+    pub fn merge(&mut self, other: RunReport) {
+        let reached = other
+            .runs
+            .iter()
+            .map(|run| run.node)
+            .chain(other.failures.iter().map(|failure| failure.node))
+            .collect::<HashSet<_>>();
+        self.runs.retain(|run| !reached.contains(&run.node));
+        self.failures
+            .retain(|failure| !reached.contains(&failure.node));
+        self.runs.extend(other.runs);
+        self.failures.extend(other.failures);
+        self.messages.extend(other.messages);
+        self.changes.extend(other.changes);
+        self.cancelled |= other.cancelled;
+    }
+
+    /// Number of sinks updated without failing, which is what a run
+    /// produces
+    pub fn sinks_produced(&self, snarl: &Snarl<Box<dyn Node>>) -> usize {
+        self.runs
+            .iter()
+            .filter(|run| {
+                snarl
+                    .get_node(run.node)
+                    .is_some_and(|node| node.role() == NodeRole::Sink)
+                    && !self.failures.iter().any(|failure| failure.node == run.node)
+            })
+            .count()
+    }
+
+    /// Writes the nodes updated, the failures and the messages of this run
+    pub fn write(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        writeln!(f, "Run started at {}", format_timestamp(self.started))?;
+        for run in &self.runs {
+            writeln!(f, "  Node {} updated in {:?}", run.node.0, run.duration)?;
+        }
+        for failure in &self.failures {
+            writeln!(f, "  Node {} failed: {}", failure.node.0, failure.message)?;
+        }
+        for message in &self.messages {
+            writeln!(
+                f,
+                "  Node {} {}: {}",
+                message.node.0, message.level, message.message
+            )?;
+        }
+        if self.cancelled {
+            writeln!(f, "  Cancelled")?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for RunReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRunStats {
+    pub count: u64,
+    pub last_run: SystemTime,
+    pub last_duration: Duration,
+}
+
+/// Most messages kept in [`ExecutionStats::log`], beyond which the oldest
+/// are dropped
+pub const MAX_LOG_ENTRIES: usize = 10_000;
+
+/// Execution statistics accumulated across evaluations of a graph
+#[derive(Debug, Default)]
+pub struct ExecutionStats {
+    pub nodes: BTreeMap<NodeId, NodeRunStats>,
+    pub last_report: Option<RunReport>,
+    /// Latest error of every node that failed the last time it was evaluated
+    pub failures: BTreeMap<NodeId, String>,
+    /// Messages logged by nodes across every evaluation, oldest first, with
+    /// failures logged as errors
+    pub log: VecDeque<NodeMessage>,
+}
+
+impl ExecutionStats {
+    pub fn record(&mut self, report: RunReport) {
+        for run in &report.runs {
+            let stats = self.nodes.entry(run.node).or_insert(NodeRunStats {
+                count: 0,
+                last_run: run.finished,
+                last_duration: run.duration,
+            });
+            stats.count += 1;
+            stats.last_run = run.finished;
+            stats.last_duration = run.duration;
+            self.failures.remove(&run.node);
+        }
+        for failure in &report.failures {
+            self.failures.insert(failure.node, failure.message.clone());
+        }
+        self.log.extend(report.messages.iter().cloned());
+        self.log.extend(report.failures.iter().map(|failure| {
+            NodeMessage {
+                node: failure.node,
+                level: LogLevel::Error,
+                time: report
+                    .runs
+                    .iter()
+                    .rev()
+                    .find(|run| run.node == failure.node)
+                    .map_or(report.started, |run| run.finished),
+                message: failure.message.clone(),
+            }
+        }));
+        let excess = self.log.len().saturating_sub(MAX_LOG_ENTRIES);
+        self.log.drain(..excess);
+        self.last_report = Some(report);
+    }
+
+    pub fn get(&self, node: NodeId) -> Option<&NodeRunStats> {
+        self.nodes.get(&node)
+    }
+
+    /// How long each node of the graph took when it last ran, or its
+    /// estimated cost if it hasn't run yet
+    pub fn durations(&self, snarl: &Snarl<Box<dyn Node>>) -> HashMap<NodeId, Duration> {
+        snarl
+            .node_ids()
+            .filter_map(|(id, node)| {
+                let duration = match self.get(id) {
+                    Some(stats) => stats.last_duration,
+                    None => node.estimated_cost()?,
+                };
+                Some((id, duration))
+            })
+            .collect()
+    }
+
+    /// Writes a report of the latest evaluation, along with the totals for
+    /// every node that has ever run
+    pub fn write_report(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        if let Some(report) = &self.last_report {
+            report.write(f)?;
+        } else {
+            writeln!(f, "No runs recorded")?;
+        }
+        writeln!(f, "Totals:")?;
+        for (node, stats) in &self.nodes {
+            writeln!(
+                f,
+                "  Node {}: {} run(s), last at {}",
+                node.0,
+                stats.count,
+                format_timestamp(stats.last_run)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a time as seconds since the unix epoch
+pub fn format_timestamp(time: SystemTime) -> String {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since_epoch) => format!("{:.3}", since_epoch.as_secs_f64()),
+        Err(_) => "unknown".to_owned(),
+    }
+}
+
+/// Formats how long ago a time was, e.g. "3.2s ago"
+pub fn format_elapsed(time: SystemTime) -> String {
+    match time.elapsed() {
+        Ok(elapsed) => format!("{:.1}s ago", elapsed.as_secs_f64()),
+        Err(_) => "just now".to_owned(),
+    }
+}
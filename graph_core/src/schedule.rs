@@ -0,0 +1,312 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+    fmt,
+    path::Path,
+    time::Duration,
+};
+
+use egui_snarl::NodeId;
+
+use crate::{
+    execution_engine::TaskDag,
+    graph_file::{self, LoadError},
+    node_graph,
+    stages::Stages,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledTask {
+    pub task: NodeId,
+    pub worker: usize,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+impl ScheduledTask {
+    pub fn end(&self) -> Duration {
+        self.start + self.duration
+    }
+}
+
+/// When and where each task of a DAG runs
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    /// Tasks in the order they are started
+    pub tasks: Vec<ScheduledTask>,
+    /// Time at which the last task finishes
+    pub makespan: Duration,
+    pub workers: usize,
+}
+
+/// Time a worker spends waiting between tasks, or after its last task
+/// until the whole schedule finishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleGap {
+    pub worker: usize,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+impl Schedule {
+    /// Total time `worker` spends running tasks
+    pub fn busy_time(&self, worker: usize) -> Duration {
+        self.tasks
+            .iter()
+            .filter(|task| task.worker == worker)
+            .map(|task| task.duration)
+            .sum()
+    }
+
+    /// Fraction of the makespan each worker spends running tasks
+    pub fn utilisation(&self) -> Vec<f64> {
+        let makespan = self.makespan.as_secs_f64();
+        (0..self.workers)
+            .map(|worker| {
+                if makespan > 0. {
+                    self.busy_time(worker).as_secs_f64() / makespan
+                } else {
+                    0.
+                }
+            })
+            .collect()
+    }
+
+    /// Times when a worker has nothing to run, longest first. Workers wait
+    /// when every ready task has been started, so long gaps show where the
+    /// DAG could do with more parallel work.
+    pub fn idle_gaps(&self) -> Vec<IdleGap> {
+        let mut gaps = Vec::new();
+        for worker in 0..self.workers {
+            let mut free_from = Duration::ZERO;
+            let tasks = self.tasks.iter().filter(|task| task.worker == worker);
+            let ends = tasks
+                .map(|task| (task.start, task.end()))
+                .chain([(self.makespan, self.makespan)]);
+            for (start, end) in ends {
+                if start > free_from {
+                    gaps.push(IdleGap {
+                        worker,
+                        start: free_from,
+                        duration: start - free_from,
+                    });
+                }
+                free_from = free_from.max(end);
+            }
+        }
+        gaps.sort_by(|a, b| {
+            b.duration
+                .cmp(&a.duration)
+                .then(a.worker.cmp(&b.worker))
+                .then(a.start.cmp(&b.start))
+        });
+        gaps
+    }
+
+    /// Draws a Gantt chart of the schedule, with a row for each worker
+    /// `width` characters wide followed by the tasks it ran
+    pub fn write_gantt(&self, f: &mut impl fmt::Write, width: usize) -> fmt::Result {
+        let scale = width as f64 / self.makespan.as_secs_f64().max(f64::MIN_POSITIVE);
+        let column = |time: Duration| ((time.as_secs_f64() * scale).round() as usize).min(width);
+        for worker in 0..self.workers {
+            let tasks = self
+                .tasks
+                .iter()
+                .filter(|task| task.worker == worker)
+                .collect::<Vec<_>>();
+            let mut bar = vec![' '; width];
+            for (idx, task) in tasks.iter().enumerate() {
+                // Alternate shading so that neighbouring tasks can be told apart
+                let shade = if idx % 2 == 0 { '█' } else { '▒' };
+                for cell in &mut bar[column(task.start)..column(task.end())] {
+                    *cell = shade;
+                }
+            }
+            write!(f, "{:>3} |{}|", worker, bar.into_iter().collect::<String>())?;
+            for task in tasks {
+                write!(f, " {} ({:?}–{:?})", task.task.0, task.start, task.end())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// How a [`Scheduler`] picks which ready task to start when a worker
+/// becomes free
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Priority {
+    /// The task with the lowest id
+    #[default]
+    LowestId,
+    /// The task with the longest chain of work after it, which tends to
+    /// finish the whole DAG soonest
+    CriticalPath,
+    /// The task that takes the longest
+    LongestFirst,
+    /// The task given the highest priority by the user, see
+    /// [`TaskDag::with_priorities`]
+    Assigned,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [
+        Priority::LowestId,
+        Priority::CriticalPath,
+        Priority::LongestFirst,
+        Priority::Assigned,
+    ];
+
+    /// Ranks the tasks of `dag`, with the task to start first ranked
+    /// highest and ties broken by lowest id
+    pub(crate) fn ranking<'a>(
+        self,
+        dag: &TaskDag,
+        durations: &'a HashMap<NodeId, Duration>,
+    ) -> impl Fn(&NodeId) -> (i32, Duration, Reverse<NodeId>) + 'a {
+        let remaining = match self {
+            Priority::CriticalPath => dag.remaining_times(durations),
+            _ => HashMap::new(),
+        };
+        let priorities = match self {
+            Priority::Assigned => dag
+                .ready_tasks()
+                .chain(dag.blocked_tasks())
+                .map(|task| (task, dag.priority(task)))
+                .collect(),
+            _ => HashMap::new(),
+        };
+        move |task| {
+            let duration = durations.get(task).copied().unwrap_or_default();
+            let (priority, key) = match self {
+                Priority::LowestId => (0, Duration::ZERO),
+                Priority::CriticalPath => (0, remaining.get(task).copied().unwrap_or_default()),
+                Priority::LongestFirst => (0, duration),
+                Priority::Assigned => (
+                    priorities.get(task).copied().unwrap_or_default(),
+                    Duration::ZERO,
+                ),
+            };
+            (priority, key, Reverse(*task))
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Priority::LowestId => "Lowest id",
+            Priority::CriticalPath => "Critical path",
+            Priority::LongestFirst => "Longest first",
+            Priority::Assigned => "Assigned priority",
+        })
+    }
+}
+
+/// Simulates running the tasks of a DAG on a fixed number of workers.
+///
+/// ```ignore
+/// let schedule = Scheduler::new(dag, 4)
+///     .priority(Priority::CriticalPath)
+///     .run(&durations);
+/// ```
+pub struct Scheduler {
+    dag: TaskDag,
+    workers: usize,
+    priority: Priority,
+}
+
+impl Scheduler {
+    pub fn new(dag: TaskDag, workers: usize) -> Self {
+        Self {
+            dag,
+            workers: workers.max(1),
+            priority: Priority::default(),
+        }
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Works out when and where each task runs. Tasks without a duration
+    /// estimate are assumed to take no time.
+    pub fn run(mut self, durations: &HashMap<NodeId, Duration>) -> Schedule {
+        let duration = |task: &NodeId| durations.get(task).copied().unwrap_or_default();
+        // Ready tasks are taken from the end
+        let rank = self.priority.ranking(&self.dag, durations);
+
+        let mut schedule = Schedule::default();
+        let mut time = Duration::ZERO;
+        let mut free_workers = (0..self.workers).collect::<BTreeSet<_>>();
+        let mut ready = self
+            .dag
+            .ready_tasks()
+            .map(|task| (rank(&task), task))
+            .collect::<BTreeSet<_>>();
+        let mut running = Vec::<ScheduledTask>::new();
+
+        loop {
+            // Start as many ready tasks as there are free workers
+            while !ready.is_empty() && !free_workers.is_empty() {
+                let (_, task) = ready.pop_last().unwrap();
+                let worker = free_workers.pop_first().unwrap();
+                let scheduled = ScheduledTask {
+                    task,
+                    worker,
+                    start: time,
+                    duration: duration(&task),
+                };
+                schedule.tasks.push(scheduled);
+                running.push(scheduled);
+            }
+
+            // Advance to the next task to finish
+            let Some(next) = running
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, task)| (task.end(), task.task))
+                .map(|(idx, _)| idx)
+            else {
+                break;
+            };
+            let finished = running.swap_remove(next);
+            time = finished.end();
+            free_workers.insert(finished.worker);
+            ready.extend(
+                self.dag
+                    .complete_task(finished.task)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|task| (rank(&task), task)),
+            );
+        }
+
+        schedule.workers = self.workers;
+        schedule.makespan = time;
+        schedule
+    }
+}
+
+/// Schedules the tasks of a DAG onto a fixed number of workers.
+///
+/// Whenever a worker is free it is given the ready task with the lowest id.
+/// Tasks without a duration estimate are assumed to take no time.
+pub fn schedule(dag: TaskDag, durations: &HashMap<NodeId, Duration>, workers: usize) -> Schedule {
+    Scheduler::new(dag, workers).run(durations)
+}
+
+/// Loads a saved graph and schedules its tasks, keeping to the barriers
+/// between its stages, without needing the GUI
+pub fn schedule_graph_file(
+    path: impl AsRef<Path>,
+    durations: &HashMap<NodeId, Duration>,
+    workers: usize,
+) -> Result<Schedule, LoadError> {
+    let saved = graph_file::read_file(path.as_ref())?;
+    let snarl = saved.to_snarl()?;
+    let graph = node_graph::as_petgraph(&snarl);
+    let dag = TaskDag::new(&graph).with_stages(&Stages::from_saved(&saved.stages));
+    Ok(schedule(dag, durations, workers))
+}
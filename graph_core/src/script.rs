@@ -0,0 +1,420 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{self, Ui};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::UpdateContext,
+    hooks::to_dynamic,
+    node_graph::{show_value, DataType, Node, TypedData},
+    run_report::LogLevel,
+};
+
+/// Upper bound on the work a script can do in one update, so that a
+/// runaway loop fails the node rather than stalling the evaluation
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Types that pins can be given, in the order they are offered
+const PIN_TYPES: [DataType; 7] = [
+    DataType::Number,
+    DataType::String,
+    DataType::Bool,
+    DataType::Color,
+    DataType::Vec2,
+    DataType::Vec3,
+    DataType::Unknown,
+];
+
+/// An input or output of a script, which the script sees as a variable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pin {
+    pub name: String,
+    pub data_type: DataType,
+}
+
+impl Pin {
+    fn new(name: &str, data_type: DataType) -> Self {
+        Self {
+            name: name.to_owned(),
+            data_type,
+        }
+    }
+}
+
+/// Runs a [Rhai](https://rhai.rs) script written by the user. Each input is
+/// a variable named after its pin, and the script gives each output a value
+/// by assigning to the variable of the same name, e.g. `sum = a + b`.
+/// Anything the script `print`s is logged, and anything it `debug`s is
+/// logged at the debug level.
+pub struct ScriptNode {
+    source: String,
+    compiled: Result<AST, String>,
+    inputs: Vec<Pin>,
+    outputs: Vec<Pin>,
+    cached_result: Option<Vec<TypedData>>,
+    failure: Option<String>,
+    label: Option<String>,
+}
+
+impl ScriptNode {
+    pub fn new(source: &str, inputs: Vec<Pin>, outputs: Vec<Pin>) -> Self {
+        let mut node = Self {
+            source: String::new(),
+            compiled: Err(String::new()),
+            inputs,
+            outputs,
+            cached_result: None,
+            failure: None,
+            label: None,
+        };
+        node.set_source(source.to_owned());
+        node
+    }
+
+    fn set_source(&mut self, source: String) {
+        self.compiled = Engine::new()
+            .compile(&source)
+            .map_err(|err| err.to_string());
+        self.source = source;
+    }
+
+    fn run(&self, inputs: &[TypedData], ctx: &mut UpdateContext) -> Result<Vec<TypedData>, String> {
+        let ast = self.compiled.as_ref().map_err(Clone::clone)?;
+        for pin in self.inputs.iter().chain(&self.outputs) {
+            if !is_identifier(&pin.name) {
+                return Err(format!("{:?} is not a valid variable name", pin.name));
+            }
+        }
+
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.on_print({
+            let printed = printed.clone();
+            move |line| printed.borrow_mut().push((LogLevel::Info, line.to_owned()))
+        });
+        engine.on_debug({
+            let printed = printed.clone();
+            move |line, _, _| {
+                printed
+                    .borrow_mut()
+                    .push((LogLevel::Debug, line.to_owned()))
+            }
+        });
+        let cancellation = ctx.eval().cancellation.clone();
+        engine.on_progress(move |_| cancellation.is_cancelled().then_some(Dynamic::UNIT));
+
+        let mut scope = Scope::new();
+        for (pin, value) in self.inputs.iter().zip(inputs) {
+            scope.push_dynamic(pin.name.as_str(), to_dynamic(value));
+        }
+        for pin in &self.outputs {
+            scope.push_dynamic(pin.name.as_str(), Dynamic::UNIT);
+        }
+        let result = engine.run_ast_with_scope(&mut scope, ast);
+        for (level, line) in printed.take() {
+            ctx.log(level, line);
+        }
+        result.map_err(|err| err.to_string())?;
+
+        self.outputs
+            .iter()
+            .map(|pin| {
+                let value = scope.get_value::<Dynamic>(&pin.name).unwrap_or_default();
+                if value.is_unit() {
+                    return Err(format!("The script didn't set {}", pin.name));
+                }
+                from_dynamic(value, &pin.data_type).map_err(|found| {
+                    format!(
+                        "{} should be {} but the script set it to {}",
+                        pin.name,
+                        type_name(&pin.data_type),
+                        found
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for ScriptNode {
+    fn default() -> Self {
+        Self::new(
+            "sum = a + b;",
+            vec![
+                Pin::new("a", DataType::Number),
+                Pin::new("b", DataType::Number),
+            ],
+            vec![Pin::new("sum", DataType::Number)],
+        )
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Converts a value set by a script to the type of its output. Integers are
+/// accepted as numbers, and outputs of an unknown type take whatever type
+/// the value has. Returns the name of the value's type if it can't be
+/// converted.
+fn from_dynamic(value: Dynamic, data_type: &DataType) -> Result<TypedData, String> {
+    let found = value.type_name().to_owned();
+    let converted = match data_type {
+        DataType::Number => value
+            .as_float()
+            .ok()
+            .or_else(|| value.as_int().ok().map(|val| val as f64))
+            .map(TypedData::Number),
+        DataType::String => value.into_string().ok().map(TypedData::String),
+        DataType::Bool => value.as_bool().ok().map(TypedData::Bool),
+        // Colours and vectors are arrays of numbers, with colours' channels
+        // between 0 and 1 and an optional alpha
+        DataType::Color => numbers(value).and_then(|channels| {
+            let channel = |idx: usize| (channels[idx].clamp(0., 1.) * 255.).round() as u8;
+            match channels.len() {
+                3 => Some(TypedData::Color([channel(0), channel(1), channel(2), 255])),
+                4 => Some(TypedData::Color([
+                    channel(0),
+                    channel(1),
+                    channel(2),
+                    channel(3),
+                ])),
+                _ => None,
+            }
+        }),
+        DataType::Vec2 => numbers(value)
+            .and_then(|components| components.try_into().ok())
+            .map(TypedData::Vec2),
+        DataType::Vec3 => numbers(value)
+            .and_then(|components| components.try_into().ok())
+            .map(TypedData::Vec3),
+        DataType::List(element) => value.into_array().ok().and_then(|values| {
+            values
+                .into_iter()
+                .map(|value| from_dynamic(value, element).ok())
+                .collect::<Option<_>>()
+                .map(TypedData::List)
+        }),
+        DataType::Unknown => {
+            let data_type = if value.is_float() || value.is_int() {
+                DataType::Number
+            } else if value.is_string() {
+                DataType::String
+            } else if value.is_bool() {
+                DataType::Bool
+            } else if value.is_array() {
+                DataType::List(Box::new(DataType::Unknown))
+            } else {
+                return Err(found);
+            };
+            return from_dynamic(value, &data_type);
+        }
+    };
+    converted.ok_or(found)
+}
+
+/// The numbers in an array set by a script
+fn numbers(value: Dynamic) -> Option<Vec<f64>> {
+    value
+        .into_array()
+        .ok()?
+        .into_iter()
+        .map(|value| {
+            value
+                .as_float()
+                .ok()
+                .or_else(|| value.as_int().ok().map(|val| val as f64))
+        })
+        .collect()
+}
+
+fn type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Number => "a number".to_owned(),
+        DataType::String => "a string".to_owned(),
+        DataType::Bool => "a bool".to_owned(),
+        DataType::Color => "a colour".to_owned(),
+        DataType::Vec2 => "a 2D vector".to_owned(),
+        DataType::Vec3 => "a 3D vector".to_owned(),
+        DataType::List(element) => format!("a list of {}", type_name(element)),
+        DataType::Unknown => "any value".to_owned(),
+    }
+}
+
+/// Name of a pin's type in the pin editor
+fn pin_type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Unknown => "Any".to_owned(),
+        data_type => format!("{:?}", data_type),
+    }
+}
+
+/// Edits the names and types of a script's inputs or outputs
+fn pins_ui(ui: &mut Ui, heading: &str, prefix: &str, pins: &mut Vec<Pin>) -> bool {
+    let mut changed = false;
+    ui.label(heading);
+    let mut removed = None;
+    for (idx, pin) in pins.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut pin.name).desired_width(60.))
+                .changed();
+            egui::ComboBox::from_id_source((heading, idx))
+                .selected_text(pin_type_name(&pin.data_type))
+                .show_ui(ui, |ui| {
+                    for data_type in PIN_TYPES {
+                        let text = pin_type_name(&data_type);
+                        changed |= ui
+                            .selectable_value(&mut pin.data_type, data_type, text)
+                            .changed();
+                    }
+                });
+            if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                removed = Some(idx);
+            }
+        });
+    }
+    if let Some(idx) = removed {
+        pins.remove(idx);
+        changed = true;
+    }
+    if ui.small_button("+").on_hover_text("Add").clicked() {
+        let name = (pins.len() + 1..)
+            .map(|n| format!("{}{}", prefix, n))
+            .find(|name| pins.iter().all(|pin| pin.name != *name))
+            .unwrap_or_default();
+        pins.push(Pin::new(&name, DataType::Number));
+        changed = true;
+    }
+    changed
+}
+
+impl Node for ScriptNode {
+    fn name(&self) -> String {
+        "Script".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        self.inputs
+            .iter()
+            .map(|pin| pin.data_type.clone())
+            .collect()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.outputs
+            .iter()
+            .map(|pin| pin.data_type.clone())
+            .collect()
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        self.cached_result.as_ref()?.get(idx).cloned()
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        ui.label(&self.inputs[idx].name);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        if let Some(value) = self.output_value(idx) {
+            show_value(&value, ui);
+        }
+        ui.label(&self.outputs[idx].name);
+        false
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.vertical(|ui| {
+            let mut source = self.source.clone();
+            if ui
+                .add(
+                    egui::TextEdit::multiline(&mut source)
+                        .code_editor()
+                        .desired_rows(3)
+                        .desired_width(180.),
+                )
+                .changed()
+            {
+                self.set_source(source);
+                changed = true;
+            }
+            if let Err(err) = &self.compiled {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            ui.collapsing("Pins", |ui| {
+                changed |= pins_ui(ui, "Inputs", "in", &mut self.inputs);
+                changed |= pins_ui(ui, "Outputs", "out", &mut self.outputs);
+            });
+        });
+        changed
+    }
+
+    fn cached_outputs(&self) -> Option<Vec<TypedData>> {
+        self.cached_result.clone()
+    }
+
+    fn restore_outputs(&mut self, outputs: &[TypedData]) {
+        if outputs.len() == self.outputs.len() {
+            self.cached_result = Some(outputs.to_vec());
+        }
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    fn update(&mut self, inputs: &[TypedData], ctx: &mut UpdateContext) {
+        (self.cached_result, self.failure) = match self.run(inputs, ctx) {
+            Ok(values) => (Some(values), None),
+            Err(err) => (None, Some(err)),
+        };
+    }
+
+    fn error(&self) -> Option<String> {
+        self.failure.clone()
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({
+            "source": self.source,
+            "inputs": self.inputs,
+            "outputs": self.outputs,
+        })
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Ok(inputs) = serde_json::from_value(params["inputs"].clone()) {
+            self.inputs = inputs;
+        }
+        if let Ok(outputs) = serde_json::from_value(params["outputs"].clone()) {
+            self.outputs = outputs;
+        }
+        if let Some(source) = params["source"].as_str() {
+            self.set_source(source.to_owned());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{OnceLock, RwLock},
+};
+
+use egui::Ui;
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{DataType, Node, TypedData},
+};
+
+/// Credentials such as API keys and tokens, which graphs refer to by name so
+/// that graph files can be shared without leaking them.
+///
+/// A secret is read from the `GRAPH_GUI_SECRET_<NAME>` environment variable
+/// if it is set, otherwise from the secrets file, which holds a JSON object
+/// mapping names to values.
+#[derive(Debug, Default)]
+pub struct SecretStore {
+    values: BTreeMap<String, String>,
+    path: Option<PathBuf>,
+}
+
+impl SecretStore {
+    /// `GRAPH_GUI_SECRETS` if set, otherwise `~/.config/graph_gui/secrets.json`
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("GRAPH_GUI_SECRETS") {
+            return Some(path.into());
+        }
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join(".config/graph_gui/secrets.json"))
+    }
+
+    /// Reads a secrets file, which is treated as empty if it does not exist
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let values = match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            values,
+            path: Some(path.to_owned()),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        std::env::var(env_var_name(name))
+            .ok()
+            .or_else(|| self.values.get(name).cloned())
+    }
+
+    /// Names of the secrets in the secrets file
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+fn env_var_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("GRAPH_GUI_SECRET_{}", name)
+}
+
+fn global() -> &'static RwLock<SecretStore> {
+    static STORE: OnceLock<RwLock<SecretStore>> = OnceLock::new();
+    STORE.get_or_init(|| {
+        let store = SecretStore::default_path()
+            .and_then(|path| SecretStore::load(&path).ok())
+            .unwrap_or_default();
+        RwLock::new(store)
+    })
+}
+
+/// Looks up a secret in the application's secret store
+pub fn lookup(name: &str) -> Option<String> {
+    global().read().unwrap().get(name)
+}
+
+/// Calls `f` with the application's secret store
+pub fn with_store<T>(f: impl FnOnce(&SecretStore) -> T) -> T {
+    f(&global().read().unwrap())
+}
+
+/// Reloads the application's secret store from its file
+pub fn reload() -> std::io::Result<()> {
+    let mut store = global().write().unwrap();
+    if let Some(path) = store.path.clone().or_else(SecretStore::default_path) {
+        *store = SecretStore::load(&path)?;
+    }
+    Ok(())
+}
+
+/// Outputs a secret from the secret store. Only the secret's name is saved
+/// with the graph.
+#[derive(Debug, Clone, Default)]
+pub struct SecretNode {
+    name: String,
+    value: Option<String>,
+    label: Option<String>,
+}
+
+impl Node for SecretNode {
+    fn name(&self) -> String {
+        "Secret".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::String]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.value.clone().map(TypedData::String)
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        let mut changed = false;
+        ui.vertical(|ui| {
+            changed = ui
+                .add(egui::TextEdit::singleline(&mut self.name).desired_width(100.))
+                .changed();
+            // The value itself is never displayed
+            match self.value {
+                Some(_) => ui.label("••••••"),
+                None => ui.colored_label(ui.visuals().error_fg_color, "not found"),
+            };
+        });
+        changed
+    }
+
+    fn update(&mut self, _inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.value = lookup(&self.name);
+    }
+
+    fn error(&self) -> Option<String> {
+        self.value
+            .is_none()
+            .then(|| format!("No secret named {:?}", self.name))
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.name.clone().into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(name) = params.as_str() {
+            self.name = name.to_owned();
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Named copies of a graph kept in its graph file, so that earlier versions
+//! of it can be gone back to after the undo history has been lost
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use web_time::SystemTime;
+
+use crate::graph_file::SavedGraph;
+
+/// The graph as it was when the user took the snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    /// When the snapshot was taken, in seconds since the Unix epoch
+    pub taken: u64,
+    /// The root graph and its settings, without its own snapshots
+    pub graph: SavedGraph,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of `graph` now, leaving out any snapshots it has
+    pub fn new(name: String, mut graph: SavedGraph) -> Self {
+        graph.snapshots.clear();
+        let taken = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        Self { name, taken, graph }
+    }
+
+    pub fn taken_at(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.taken)
+    }
+}
+
+/// Describes roughly how long ago a time was, e.g. "5 minutes ago"
+pub fn format_age(time: SystemTime) -> String {
+    let Ok(age) = time.elapsed() else {
+        return "just now".to_owned();
+    };
+    let (count, unit) = match age.as_secs() {
+        secs if secs < 60 => return "just now".to_owned(),
+        secs if secs < 60 * 60 => (secs / 60, "minute"),
+        secs if secs < 24 * 60 * 60 => (secs / (60 * 60), "hour"),
+        secs => (secs / (24 * 60 * 60), "day"),
+    };
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
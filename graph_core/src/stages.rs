@@ -0,0 +1,179 @@
+//! Named stages the nodes of a graph can be tagged with, such as "ingest",
+//! "analysis" and "report". Stages are barriers: every task of a stage has
+//! to be completed before any task of a later stage starts, as if each
+//! depended on all of them. Untagged nodes run whenever their own
+//! dependencies allow.
+//!
+//! These are not the stages of an
+//! [`ExecutionPlan`](crate::execution_engine::ExecutionPlan), which are
+//! worked out from the wires, though barriers are reflected in them.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use egui_snarl::{NodeId, Snarl};
+use petgraph::Graph;
+use serde::{Deserialize, Serialize};
+
+use crate::node_graph::Node;
+
+/// A stage as stored in a graph file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedStage {
+    pub name: String,
+    /// Ids of the nodes tagged with the stage
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stages {
+    /// Names of the stages, in the order they run
+    names: Vec<String>,
+    /// Stage each tagged node belongs to, by index into `names`
+    tags: BTreeMap<NodeId, usize>,
+}
+
+impl Stages {
+    pub fn from_saved(saved: &[SavedStage]) -> Self {
+        let mut stages = Self::default();
+        for (idx, stage) in saved.iter().enumerate() {
+            stages.names.push(stage.name.clone());
+            for node in &stage.nodes {
+                stages.tags.insert(NodeId(*node), idx);
+            }
+        }
+        stages
+    }
+
+    /// The stages to store in a graph file, leaving out nodes no longer in
+    /// the graph
+    pub fn to_saved(&self, snarl: &Snarl<Box<dyn Node>>) -> Vec<SavedStage> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| SavedStage {
+                name: name.clone(),
+                nodes: self
+                    .members(idx)
+                    .filter(|node| snarl.get_node(*node).is_some())
+                    .map(|node| node.0)
+                    .collect(),
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Names of the stages, in the order they run
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Adds a stage that runs after the others
+    pub fn push(&mut self, name: impl Into<String>) {
+        self.names.push(name.into());
+    }
+
+    pub fn rename(&mut self, idx: usize, name: impl Into<String>) {
+        self.names[idx] = name.into();
+    }
+
+    /// Removes a stage, leaving its nodes untagged
+    pub fn remove(&mut self, idx: usize) {
+        self.names.remove(idx);
+        self.tags.retain(|_, stage| *stage != idx);
+        for stage in self.tags.values_mut() {
+            if *stage > idx {
+                *stage -= 1;
+            }
+        }
+    }
+
+    /// Makes a stage run one place earlier
+    pub fn move_up(&mut self, idx: usize) {
+        if idx == 0 || idx >= self.names.len() {
+            return;
+        }
+        self.names.swap(idx - 1, idx);
+        for stage in self.tags.values_mut() {
+            if *stage == idx {
+                *stage = idx - 1;
+            } else if *stage == idx - 1 {
+                *stage = idx;
+            }
+        }
+    }
+
+    /// Index of the stage a node is tagged with
+    pub fn stage_of(&self, node: NodeId) -> Option<usize> {
+        self.tags.get(&node).copied()
+    }
+
+    pub fn name_of(&self, node: NodeId) -> Option<&str> {
+        self.stage_of(node).map(|idx| self.names[idx].as_str())
+    }
+
+    /// Tags a node with a stage, or untags it
+    pub fn set_stage(&mut self, node: NodeId, stage: Option<usize>) {
+        match stage.filter(|idx| *idx < self.names.len()) {
+            Some(idx) => self.tags.insert(node, idx),
+            None => self.tags.remove(&node),
+        };
+    }
+
+    /// Nodes tagged with a stage
+    pub fn members(&self, stage: usize) -> impl Iterator<Item = NodeId> + '_ {
+        self.tags
+            .iter()
+            .filter(move |(_, idx)| **idx == stage)
+            .map(|(node, _)| *node)
+    }
+
+    /// Untags nodes that are no longer in the graph
+    pub fn retain_nodes(&mut self, snarl: &Snarl<Box<dyn Node>>) {
+        self.tags.retain(|node, _| snarl.get_node(*node).is_some());
+    }
+
+    /// Dependencies added by the barriers: each tagged node depends on every
+    /// node of the closest earlier stage with any nodes, which in turn
+    /// waited for the stages before it
+    pub fn barrier_dependencies(&self) -> HashMap<NodeId, HashSet<NodeId>> {
+        let mut dependencies = HashMap::new();
+        let mut previous = HashSet::new();
+        for idx in 0..self.names.len() {
+            let members = self.members(idx).collect::<HashSet<_>>();
+            if members.is_empty() {
+                continue;
+            }
+            if !previous.is_empty() {
+                for node in &members {
+                    dependencies.insert(*node, previous.clone());
+                }
+            }
+            previous = members;
+        }
+        dependencies
+    }
+
+    /// Wires that go from a node of a later stage to a node of an earlier
+    /// one, as `(from, to)`. Barriers make these impossible to satisfy, so
+    /// the nodes after them are never run.
+    pub fn conflicts(&self, graph: &Graph<NodeId, ()>) -> Vec<(NodeId, NodeId)> {
+        let mut conflicts = graph
+            .raw_edges()
+            .iter()
+            .map(|edge| (graph[edge.source()], graph[edge.target()]))
+            .filter(|(from, to)| {
+                matches!(
+                    (self.stage_of(*from), self.stage_of(*to)),
+                    (Some(from), Some(to)) if from > to
+                )
+            })
+            .collect::<Vec<_>>();
+        conflicts.sort_unstable();
+        conflicts.dedup();
+        conflicts
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::VecDeque;
+
+use egui::{self, Ui};
+use web_time::Instant;
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_float, show_value, DataType, Node, TypedData},
+};
+
+/// Total of every value the node has received since it was last reset
+#[derive(Debug, Clone, Default)]
+pub struct RunningSumNode {
+    total: Option<f64>,
+    label: Option<String>,
+}
+
+impl Node for RunningSumNode {
+    fn name(&self) -> String {
+        "Running Sum".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.total.map(TypedData::Number)
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(total) = self.total {
+            ui.label(format_float(total));
+        }
+        false
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        if let TypedData::Number(value) = inputs[0] {
+            self.total = Some(self.total.unwrap_or(0.) + value);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.total = None;
+    }
+}
+
+/// Average of the last few values the node has received
+#[derive(Debug, Clone)]
+pub struct MovingAverageNode {
+    window: usize,
+    values: VecDeque<f64>,
+    label: Option<String>,
+}
+
+impl Default for MovingAverageNode {
+    fn default() -> Self {
+        Self {
+            window: 5,
+            values: VecDeque::new(),
+            label: None,
+        }
+    }
+}
+
+impl MovingAverageNode {
+    fn drop_old_values(&mut self) {
+        while self.values.len() > self.window {
+            self.values.pop_front();
+        }
+    }
+}
+
+impl Node for MovingAverageNode {
+    fn name(&self) -> String {
+        "Moving Average".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        if self.values.is_empty() {
+            return None;
+        }
+        let average = self.values.iter().sum::<f64>() / self.values.len() as f64;
+        Some(TypedData::Number(average))
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.horizontal(|ui| {
+            ui.label("Last");
+            let response = ui.add(egui::DragValue::new(&mut self.window).clamp_range(1..=1000));
+            if let Some(TypedData::Number(average)) = self.output_value(0) {
+                ui.label(format_float(average));
+            }
+            // A smaller window applies straight away, without adding a value
+            if response.changed() {
+                self.drop_old_values();
+            }
+            false
+        })
+        .inner
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        if let TypedData::Number(value) = inputs[0] {
+            self.values.push_back(value);
+            self.drop_old_values();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.values.clear();
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.window.into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(window) = params.as_u64() {
+            self.window = (window as usize).max(1);
+        }
+    }
+}
+
+/// Number of times the node has received a value since it was last reset
+#[derive(Debug, Clone, Default)]
+pub struct CounterNode {
+    count: usize,
+    label: Option<String>,
+}
+
+impl Node for CounterNode {
+    fn name(&self) -> String {
+        "Counter".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Unknown]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        Some(TypedData::Number(self.count as f64))
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.label(self.count.to_string());
+        false
+    }
+
+    /// Left unconnected, the counter counts the evaluations it is part of
+    fn input_default(&self, _idx: usize) -> Option<TypedData> {
+        Some(TypedData::Unknown)
+    }
+
+    fn update(&mut self, _inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.count += 1;
+    }
+
+    fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+/// Seconds since the node was first updated, or the number of times it has
+/// been updated. The editor keeps updating it, so that the graph downstream
+/// of it is animated.
+#[derive(Debug, Clone, Default)]
+pub struct TimeNode {
+    /// Counts updates rather than seconds
+    ticks: bool,
+    started: Option<Instant>,
+    count: u64,
+    value: Option<f64>,
+    label: Option<String>,
+}
+
+impl Node for TimeNode {
+    fn name(&self) -> String {
+        "Time".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        self.value.map(TypedData::Number)
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.horizontal(|ui| {
+            let changed = ui.checkbox(&mut self.ticks, "Ticks").changed();
+            if changed {
+                self.reset();
+            }
+            if let Some(value) = self.value {
+                ui.label(format_float(value));
+            }
+            changed
+        })
+        .inner
+    }
+
+    fn update(&mut self, _inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        let value = if self.ticks {
+            self.count += 1;
+            self.count as f64
+        } else {
+            self.started
+                .get_or_insert_with(Instant::now)
+                .elapsed()
+                .as_secs_f64()
+        };
+        self.value = Some(value);
+    }
+
+    fn needs_continuous_update(&self) -> bool {
+        true
+    }
+
+    fn reset(&mut self) {
+        self.started = None;
+        self.count = 0;
+        self.value = None;
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({ "ticks": self.ticks })
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(ticks) = params.get("ticks").and_then(|ticks| ticks.as_bool()) {
+            self.ticks = ticks;
+        }
+    }
+}
+
+/// The value its input had on the previous run of the graph, for carrying
+/// values from one run to the next, such as to feed a total back into
+/// itself without making the graph cyclic
+#[derive(Debug, Clone, Default)]
+pub struct DelayNode {
+    /// Output until a value has been delayed
+    initial: f64,
+    /// Input received during the current run
+    received: Option<f64>,
+    /// Input received during the previous run
+    value: Option<f64>,
+    label: Option<String>,
+}
+
+impl Node for DelayNode {
+    fn name(&self) -> String {
+        "Delay".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        vec![DataType::Number]
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        assert_eq!(idx, 0);
+        Some(TypedData::Number(self.value.unwrap_or(self.initial)))
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        match self.value {
+            Some(value) => {
+                ui.label(format_float(value));
+                false
+            }
+            // Until something has been delayed the initial value is passed on
+            None => ui
+                .add(egui::DragValue::new(&mut self.initial).prefix("Initial "))
+                .changed(),
+        }
+    }
+
+    /// Only holds on to the value, which is passed on when the next run
+    /// starts
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        if let TypedData::Number(value) = inputs[0] {
+            self.received = Some(value);
+        }
+    }
+
+    fn on_graph_run_start(&mut self) -> bool {
+        let changed = self.received.is_some() && self.received != self.value;
+        if changed {
+            self.value = self.received;
+        }
+        changed
+    }
+
+    fn reset(&mut self) {
+        self.received = None;
+        self.value = None;
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.initial.into()
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Some(initial) = params.as_f64() {
+            self.initial = initial;
+        }
+    }
+}
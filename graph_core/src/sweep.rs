@@ -0,0 +1,224 @@
+//! Evaluating a graph repeatedly while sweeping number nodes over ranges of
+//! values, collecting what reaches the sinks into a table
+
+use std::{fmt, str::FromStr};
+
+use egui_snarl::{InPinId, NodeId, Snarl};
+
+use crate::{
+    context::EvalContext,
+    node_graph::{self, format_value, Node, NodeRole, NumberNode, TypedData},
+};
+
+/// Values taken by a [`NumberNode`] during a sweep, spread evenly from
+/// `start` to `end` inclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sweep {
+    pub node: NodeId,
+    pub start: f64,
+    pub end: f64,
+    pub steps: usize,
+}
+
+impl Sweep {
+    pub fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.steps).map(|step| {
+            if self.steps == 1 {
+                self.start
+            } else {
+                self.start + (self.end - self.start) * step as f64 / (self.steps - 1) as f64
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepParseError(String);
+
+impl fmt::Display for SweepParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid sweep {:?}, expected <node id>=<start>:<end>:<steps>",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for SweepParseError {}
+
+/// Parses `<node id>=<start>:<end>:<steps>`, such as `3=0:1:11`
+impl FromStr for Sweep {
+    type Err = SweepParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let error = || SweepParseError(source.to_owned());
+        let (node, range) = source.split_once('=').ok_or_else(error)?;
+        let [start, end, steps] = range.split(':').collect::<Vec<_>>()[..] else {
+            return Err(error());
+        };
+        Ok(Self {
+            node: NodeId(node.trim().parse().map_err(|_| error())?),
+            start: start.trim().parse().map_err(|_| error())?,
+            end: end.trim().parse().map_err(|_| error())?,
+            steps: steps.trim().parse().map_err(|_| error())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepError {
+    /// A swept node doesn't exist or isn't a [`NumberNode`]
+    NotANumber(NodeId),
+    /// A sweep has no steps, so there would be nothing to evaluate
+    NoSteps(NodeId),
+}
+
+impl fmt::Display for SweepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepError::NotANumber(node) => write!(f, "node {} is not a Number node", node.0),
+            SweepError::NoSteps(node) => write!(f, "the sweep of node {} has no steps", node.0),
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+/// One row per evaluation of a sweep, holding the value of each swept node
+/// followed by the value reaching each input of the graph's sinks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SweepResults {
+    pub columns: Vec<String>,
+    /// Values are `None` where nothing reached a sink's input
+    pub rows: Vec<Vec<Option<TypedData>>>,
+    /// Number of columns at the start that hold the swept values
+    pub swept: usize,
+}
+
+impl SweepResults {
+    /// The table as comma separated values, with a header row. Strings are
+    /// written without quotes unless they need them.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let mut write_row = |fields: Vec<String>| {
+            let fields = fields
+                .into_iter()
+                .map(|field| csv_field(&field))
+                .collect::<Vec<_>>();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        };
+        write_row(self.columns.clone());
+        for row in &self.rows {
+            write_row(
+                row.iter()
+                    .map(|value| match value {
+                        Some(TypedData::String(text)) => text.clone(),
+                        Some(value) => format_value(value),
+                        None => String::new(),
+                    })
+                    .collect(),
+            );
+        }
+        csv
+    }
+
+    /// The numbers in a column, for plotting
+    pub fn numbers(&self, column: usize) -> Vec<Option<f64>> {
+        self.rows
+            .iter()
+            .map(|row| match row.get(column) {
+                Some(Some(TypedData::Number(value))) => Some(*value),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Evaluates the graph once for every combination of the swept values,
+/// without any UI. The swept nodes are given back their values, and the
+/// graph re-evaluated, once the sweep is done.
+pub fn run(
+    snarl: &mut Snarl<Box<dyn Node>>,
+    sweeps: &[Sweep],
+    ctx: &EvalContext,
+) -> Result<SweepResults, SweepError> {
+    let mut original = Vec::new();
+    for sweep in sweeps {
+        let number = snarl
+            .get_node(sweep.node)
+            .and_then(|node| node.as_any().downcast_ref::<NumberNode>())
+            .ok_or(SweepError::NotANumber(sweep.node))?;
+        if sweep.steps == 0 {
+            return Err(SweepError::NoSteps(sweep.node));
+        }
+        original.push((sweep.node, number.value()));
+    }
+
+    let sinks = node_graph::nodes_with_role(snarl, NodeRole::Sink)
+        .into_iter()
+        .flat_map(|id| (0..snarl[id].inputs().len()).map(move |input| InPinId { node: id, input }))
+        .collect::<Vec<_>>();
+    let mut columns = sweeps
+        .iter()
+        .map(|sweep| node_graph::display_name(snarl[sweep.node].as_ref(), sweep.node))
+        .collect::<Vec<_>>();
+    columns.extend(sinks.iter().map(|pin| {
+        let name = node_graph::display_name(snarl[pin.node].as_ref(), pin.node);
+        if snarl[pin.node].inputs().len() == 1 {
+            name
+        } else {
+            format!("{} input {}", name, pin.input)
+        }
+    }));
+
+    let mut rows = Vec::new();
+    // Counts through every combination of steps, the last sweep changing
+    // fastest
+    let mut steps = vec![0; sweeps.len()];
+    'combinations: loop {
+        let mut row = Vec::new();
+        for (sweep, step) in sweeps.iter().zip(&steps) {
+            let value = sweep.values().nth(*step).unwrap();
+            set_number(snarl, sweep.node, value);
+            row.push(Some(TypedData::Number(value)));
+        }
+        node_graph::evaluate_with(snarl, None, ctx);
+        row.extend(sinks.iter().map(|pin| node_graph::input_value(snarl, *pin)));
+        rows.push(row);
+
+        for (idx, sweep) in sweeps.iter().enumerate().rev() {
+            steps[idx] += 1;
+            if steps[idx] < sweep.steps {
+                continue 'combinations;
+            }
+            steps[idx] = 0;
+        }
+        break;
+    }
+
+    for (node, value) in original {
+        set_number(snarl, node, value);
+    }
+    node_graph::evaluate_with(snarl, None, ctx);
+    Ok(SweepResults {
+        columns,
+        rows,
+        swept: sweeps.len(),
+    })
+}
+
+fn set_number(snarl: &mut Snarl<Box<dyn Node>>, node: NodeId, value: f64) {
+    if let Some(number) = snarl[node].as_any_mut().downcast_mut::<NumberNode>() {
+        number.set_value(value);
+    }
+}
@@ -0,0 +1,521 @@
+//! Nodes for working with tables: a source reading columns from a CSV file
+//! and a sink showing the rows reaching it
+
+use std::path::PathBuf;
+
+use egui::{self, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::UpdateContext,
+    node_graph::{format_value, show_value, DataType, Node, TypedData},
+    sweep::csv_field,
+};
+
+/// Most rows shown in a table node, so that large tables stay responsive.
+/// Every row is still exported.
+const MAX_SHOWN_ROWS: usize = 1000;
+/// Height of the scrollable part of a table node
+const TABLE_HEIGHT: f32 = 200.;
+
+/// Splits comma separated values into rows of fields. Fields can be quoted
+/// to hold commas, line breaks or doubled quotes, and blank lines are
+/// skipped.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                if row.len() > 1 || !row[0].is_empty() {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// A column of a CSV file given as an output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CsvColumn {
+    name: String,
+    /// Whether every field parsed as a number, so the column is given as a
+    /// list of numbers rather than strings
+    numeric: bool,
+}
+
+/// Reads a CSV file, giving each selected column as a list
+#[derive(Debug, Clone)]
+pub struct CsvSourceNode {
+    /// Whether the first row names the columns
+    header: bool,
+    /// Whether the node is re-evaluated when the file changes, see
+    /// [`FileWatcher`](crate::file_nodes::FileWatcher)
+    watch: bool,
+    /// Names of every column in the file last read
+    available: Vec<String>,
+    /// Columns given as outputs, in the order they appear in the file
+    selected: Vec<CsvColumn>,
+    /// Values of the selected columns
+    values: Vec<TypedData>,
+    /// Number of rows read, not counting the header
+    rows: usize,
+    path: Option<PathBuf>,
+    /// File read in place of the path given as input, see
+    /// [`Node::set_data_file`]
+    file: Option<PathBuf>,
+    failure: Option<String>,
+    label: Option<String>,
+}
+
+impl Default for CsvSourceNode {
+    fn default() -> Self {
+        Self {
+            header: true,
+            watch: false,
+            available: Vec::new(),
+            selected: Vec::new(),
+            values: Vec::new(),
+            rows: 0,
+            path: None,
+            file: None,
+            failure: None,
+            label: None,
+        }
+    }
+}
+
+impl CsvSourceNode {
+    /// Splits `text` into columns, giving the selected ones as outputs. When
+    /// no columns are selected yet, such as for a file read for the first
+    /// time, every column is.
+    fn load(&mut self, text: &str) {
+        let mut rows = parse_csv(text).into_iter();
+        let width = |row: &Vec<String>| row.len();
+        let header = if self.header { rows.next() } else { None };
+        let rows = rows.collect::<Vec<_>>();
+        let columns = rows
+            .iter()
+            .map(width)
+            .chain(header.as_ref().map(width))
+            .max()
+            .unwrap_or(0);
+        self.available = (0..columns)
+            .map(|idx| {
+                header
+                    .as_ref()
+                    .and_then(|header| header.get(idx))
+                    .filter(|name| !name.is_empty())
+                    .cloned()
+                    .unwrap_or_else(|| format!("Column {}", idx + 1))
+            })
+            .collect();
+        if self.selected.is_empty() {
+            self.selected = self
+                .available
+                .iter()
+                .map(|name| CsvColumn {
+                    name: name.clone(),
+                    numeric: false,
+                })
+                .collect();
+        }
+
+        self.rows = rows.len();
+        self.values.clear();
+        for column in &mut self.selected {
+            let Some(idx) = self.available.iter().position(|name| *name == column.name) else {
+                // The column is no longer in the file, so it gives nothing
+                self.values.push(TypedData::List(Vec::new()));
+                continue;
+            };
+            let fields = rows
+                .iter()
+                .map(|row| row.get(idx).map_or("", |field| field.trim()))
+                .collect::<Vec<_>>();
+            let numbers = fields
+                .iter()
+                .map(|field| field.parse::<f64>().ok())
+                .collect::<Option<Vec<_>>>();
+            column.numeric = numbers.is_some() && !fields.is_empty();
+            self.values.push(TypedData::List(match numbers {
+                Some(numbers) if column.numeric => {
+                    numbers.into_iter().map(TypedData::Number).collect()
+                }
+                _ => fields
+                    .into_iter()
+                    .map(|field| TypedData::String(field.to_owned()))
+                    .collect(),
+            }));
+        }
+    }
+}
+
+impl Node for CsvSourceNode {
+    fn name(&self) -> String {
+        "CSV Source".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::String]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        self.selected
+            .iter()
+            .map(|column| {
+                let element = if column.numeric {
+                    DataType::Number
+                } else {
+                    DataType::String
+                };
+                DataType::List(Box::new(element))
+            })
+            .collect()
+    }
+
+    fn output_value(&self, idx: usize) -> Option<TypedData> {
+        self.values.get(idx).cloned()
+    }
+
+    fn show_input(&mut self, idx: usize, remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        assert_eq!(idx, 0);
+        ui.label("Path");
+        if let Some(file) = &self.file {
+            ui.weak(file.display().to_string())
+                .on_hover_text("Read from the package the graph was opened from");
+        } else if let Some(remote) = remote {
+            show_value(&remote, ui);
+        }
+        false
+    }
+
+    fn show_output(&mut self, idx: usize, ui: &mut Ui) -> bool {
+        if let Some(column) = self.selected.get(idx) {
+            ui.label(&column.name);
+        }
+        false
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.vertical(|ui| {
+            changed |= ui
+                .checkbox(&mut self.header, "Header row")
+                .on_hover_text("Take the column names from the first row")
+                .changed();
+            changed |= ui
+                .checkbox(&mut self.watch, "Watch")
+                .on_hover_text("Read the file again whenever it changes")
+                .changed();
+            if self.available.is_empty() {
+                return;
+            }
+            ui.weak(format!("{} rows", self.rows));
+            ui.collapsing("Columns", |ui| {
+                for name in &self.available {
+                    let mut shown = self.selected.iter().any(|column| column.name == *name);
+                    if ui.checkbox(&mut shown, name).changed() {
+                        changed = true;
+                        if !shown {
+                            self.selected.retain(|column| column.name != *name);
+                        }
+                        if shown {
+                            self.selected.push(CsvColumn {
+                                name: name.clone(),
+                                numeric: false,
+                            });
+                        }
+                    }
+                }
+                // Outputs follow the order of the columns in the file
+                let available = &self.available;
+                self.selected
+                    .sort_by_key(|column| available.iter().position(|name| *name == column.name));
+            });
+        });
+        changed
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.values.clear();
+        self.failure = None;
+        let path = match (&self.file, &inputs[0]) {
+            (Some(file), _) => file.clone(),
+            (None, TypedData::String(path)) => PathBuf::from(path),
+            (None, _) => return,
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => self.load(&text),
+            Err(err) => self.failure = Some(format!("{}: {}", path.display(), err)),
+        }
+        self.path = Some(path);
+    }
+
+    fn error(&self) -> Option<String> {
+        self.failure.clone()
+    }
+
+    fn watched_path(&self) -> Option<PathBuf> {
+        self.path.clone().filter(|_| self.watch)
+    }
+
+    fn data_file(&self) -> Option<PathBuf> {
+        self.path.clone()
+    }
+
+    fn set_data_file(&mut self, path: PathBuf) {
+        self.file = Some(path);
+    }
+
+    /// The selected columns are saved with their types, so that the node
+    /// has the same outputs when loaded, before the file is read again
+    fn save(&self) -> serde_json::Value {
+        let mut params = serde_json::json!({
+            "header": self.header,
+            "watch": self.watch,
+            "columns": self.selected,
+        });
+        if let Some(file) = &self.file {
+            params["file"] = file.display().to_string().into();
+        }
+        params
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        self.header = params["header"].as_bool().unwrap_or(true);
+        self.watch = params["watch"].as_bool().unwrap_or(false);
+        self.selected = serde_json::from_value(params["columns"].clone()).unwrap_or_default();
+        self.file = params["file"].as_str().map(PathBuf::from);
+    }
+}
+
+/// Shows the rows reaching it as a table, with a column for each input.
+/// Lists are spread down their column, and anything else fills one row.
+#[derive(Debug, Clone)]
+pub struct TableSinkNode {
+    /// Heading of each column, and so the number of inputs
+    names: Vec<String>,
+    /// Values received by each column in the last update
+    columns: Vec<Vec<TypedData>>,
+    /// Path typed in for exporting the table
+    export_path: String,
+    /// Result of the last export, shown under the button
+    exported: Option<Result<usize, String>>,
+    label: Option<String>,
+}
+
+impl Default for TableSinkNode {
+    fn default() -> Self {
+        Self {
+            names: vec!["A".to_owned(), "B".to_owned()],
+            columns: Vec::new(),
+            export_path: "table.csv".to_owned(),
+            exported: None,
+            label: None,
+        }
+    }
+}
+
+impl TableSinkNode {
+    pub fn row_count(&self) -> usize {
+        self.columns.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// The fields of a row, empty where a column is shorter than the others
+    pub fn row(&self, idx: usize) -> Vec<String> {
+        (0..self.names.len())
+            .map(
+                |column| match self.columns.get(column).and_then(|values| values.get(idx)) {
+                    Some(TypedData::String(text)) => text.clone(),
+                    Some(value) => format_value(value),
+                    None => String::new(),
+                },
+            )
+            .collect()
+    }
+
+    /// The table as comma separated values, with a header row
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let rows = std::iter::once(self.names.clone())
+            .chain((0..self.row_count()).map(|idx| self.row(idx)));
+        for row in rows {
+            let fields = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    fn show_table(&self, ui: &mut Ui) {
+        let rows = self.row_count();
+        if rows == 0 {
+            ui.weak("No rows");
+            return;
+        }
+        egui::ScrollArea::both()
+            .max_height(TABLE_HEIGHT)
+            .show(ui, |ui| {
+                egui::Grid::new(ui.id().with("table"))
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.weak("#");
+                        for name in &self.names {
+                            ui.strong(name);
+                        }
+                        ui.end_row();
+                        for idx in 0..rows.min(MAX_SHOWN_ROWS) {
+                            ui.weak(idx.to_string());
+                            for field in self.row(idx) {
+                                ui.label(field);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if rows > MAX_SHOWN_ROWS {
+                    ui.weak(format!("{} more rows", rows - MAX_SHOWN_ROWS));
+                }
+            });
+    }
+}
+
+impl Node for TableSinkNode {
+    fn name(&self) -> String {
+        "Table".to_owned()
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    fn inputs(&self) -> Vec<DataType> {
+        vec![DataType::Unknown; self.names.len()]
+    }
+
+    fn outputs(&self) -> Vec<DataType> {
+        Vec::new()
+    }
+
+    fn show_input(&mut self, idx: usize, _remote: Option<TypedData>, ui: &mut Ui) -> bool {
+        ui.add(egui::TextEdit::singleline(&mut self.names[idx]).desired_width(60.));
+        false
+    }
+
+    /// Columns left unconnected are empty
+    fn input_default(&self, _idx: usize) -> Option<TypedData> {
+        Some(TypedData::Unknown)
+    }
+
+    fn has_body(&self) -> bool {
+        true
+    }
+
+    fn show_body(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} columns", self.names.len()));
+                if ui.small_button("+").clicked() {
+                    let name = char::from(b'A' + (self.names.len() % 26) as u8).to_string();
+                    self.names.push(name);
+                    changed = true;
+                }
+                if self.names.len() > 1 && ui.small_button("−").clicked() {
+                    self.names.pop();
+                    changed = true;
+                }
+            });
+            self.show_table(ui);
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.export_path).desired_width(100.));
+                if ui
+                    .button("Export CSV")
+                    .on_hover_text("Write every row to the file")
+                    .clicked()
+                {
+                    let csv = self.to_csv();
+                    self.exported = Some(
+                        std::fs::write(&self.export_path, &csv)
+                            .map(|()| self.row_count())
+                            .map_err(|err| err.to_string()),
+                    );
+                }
+                if ui.button("Copy").clicked() {
+                    let csv = self.to_csv();
+                    ui.output_mut(|output| output.copied_text = csv);
+                }
+            });
+            match &self.exported {
+                Some(Ok(rows)) => {
+                    ui.weak(format!("Exported {} rows", rows));
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                None => {}
+            }
+        });
+        changed
+    }
+
+    fn update(&mut self, inputs: &[TypedData], _ctx: &mut UpdateContext) {
+        self.columns = inputs
+            .iter()
+            .map(|input| match input {
+                TypedData::List(values) => values.clone(),
+                TypedData::Unknown => Vec::new(),
+                value => vec![value.clone()],
+            })
+            .collect();
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::json!({ "columns": self.names, "export_path": self.export_path })
+    }
+
+    fn restore(&mut self, params: &serde_json::Value) {
+        if let Ok(names) = serde_json::from_value::<Vec<String>>(params["columns"].clone()) {
+            if !names.is_empty() {
+                self.names = names;
+            }
+        }
+        if let Some(path) = params["export_path"].as_str() {
+            self.export_path = path.to_owned();
+        }
+    }
+}
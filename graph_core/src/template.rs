@@ -0,0 +1,177 @@
+//! Configured nodes saved under a name, so that they can be added again with
+//! the same parameters from the add-node menu and the palette
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use egui::{Pos2, Vec2};
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bypass::{self, Bypassed},
+    graph_file::{LoadError, SavedNode, SavedWire},
+    node_graph::Node,
+    registry,
+};
+
+/// A node, or a few nodes wired together, with their parameters and labels.
+/// Positions are relative to the top left node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    pub nodes: Vec<SavedNode>,
+    /// Wires between the template's nodes. Wires to other nodes are left out.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wires: Vec<SavedWire>,
+}
+
+impl Template {
+    /// Saves the given nodes of a graph, along with the wires between them
+    pub fn capture(name: String, snarl: &Snarl<Box<dyn Node>>, nodes: &BTreeSet<NodeId>) -> Self {
+        let positions = snarl
+            .nodes_pos_ids()
+            .filter(|(id, _, _)| nodes.contains(id))
+            .map(|(id, pos, _)| (id, pos))
+            .collect::<BTreeMap<_, _>>();
+        let origin = positions
+            .values()
+            .fold(Pos2::new(f32::INFINITY, f32::INFINITY), |origin, pos| {
+                origin.min(*pos)
+            });
+
+        let mut saved = Vec::new();
+        let mut wires = Vec::new();
+        for (&id, &pos) in &positions {
+            let node = &snarl[id];
+            let offset = pos - origin;
+            saved.push(SavedNode {
+                id: id.0,
+                kind: node.name(),
+                pos: [offset.x, offset.y],
+                label: node.label().map(str::to_owned),
+                params: node.save(),
+                disabled: bypass::is_disabled(node.as_ref()),
+                outputs: None,
+            });
+            for output in 0..node.outputs().len() {
+                for remote in snarl.out_pin(OutPinId { node: id, output }).remotes {
+                    if positions.contains_key(&remote.node) {
+                        wires.push(SavedWire {
+                            from: (id.0, output),
+                            to: (remote.node.0, remote.input),
+                        });
+                    }
+                }
+            }
+        }
+        Self {
+            name,
+            nodes: saved,
+            wires,
+        }
+    }
+
+    /// Adds the template's nodes to a graph with their top left at `pos`,
+    /// returning the new nodes
+    pub fn insert(
+        &self,
+        snarl: &mut Snarl<Box<dyn Node>>,
+        pos: Pos2,
+    ) -> Result<Vec<NodeId>, LoadError> {
+        // Every node is created before any is inserted, so that a template
+        // with an unknown node type leaves the graph untouched
+        let mut created = Vec::new();
+        for saved in &self.nodes {
+            let mut node = registry::create_node(&saved.kind)
+                .ok_or_else(|| LoadError::UnknownNode(saved.kind.clone()))?;
+            node.restore(&saved.params);
+            node.set_label(saved.label.clone());
+            if saved.disabled {
+                node = Box::new(Bypassed::new(node));
+            }
+            created.push((saved.id, Vec2::new(saved.pos[0], saved.pos[1]), node));
+        }
+
+        let mut ids = BTreeMap::new();
+        for (saved_id, offset, node) in created {
+            ids.insert(saved_id, snarl.insert_node(pos + offset, node));
+        }
+        for wire in &self.wires {
+            let (Some(&from), Some(&to)) = (ids.get(&wire.from.0), ids.get(&wire.to.0)) else {
+                continue;
+            };
+            if wire.from.1 < snarl[from].outputs().len() && wire.to.1 < snarl[to].inputs().len() {
+                snarl.connect(
+                    OutPinId {
+                        node: from,
+                        output: wire.from.1,
+                    },
+                    InPinId {
+                        node: to,
+                        input: wire.to.1,
+                    },
+                );
+            }
+        }
+        Ok(ids.into_values().collect())
+    }
+}
+
+/// The user's templates, in the order they are listed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateLibrary {
+    pub templates: Vec<Template>,
+}
+
+impl TemplateLibrary {
+    /// `GRAPH_GUI_TEMPLATES` if set, otherwise
+    /// `~/.config/graph_gui/templates.json`
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("GRAPH_GUI_TEMPLATES") {
+            return Some(path.into());
+        }
+        let home = std::env::var_os("HOME")?;
+        Some(Path::new(&home).join(".config/graph_gui/templates.json"))
+    }
+
+    /// Reads a templates file, which is treated as empty if it does not exist
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the templates file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    /// Adds a template, replacing any with the same name
+    pub fn add(&mut self, template: Template) {
+        match self
+            .templates
+            .iter_mut()
+            .find(|existing| existing.name == template.name)
+        {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+    }
+
+    /// Moves a template to another place in the list
+    pub fn move_template(&mut self, from: usize, to: usize) {
+        if from < self.templates.len() && to < self.templates.len() {
+            let template = self.templates.remove(from);
+            self.templates.insert(to, template);
+        }
+    }
+}
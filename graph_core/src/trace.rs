@@ -0,0 +1,251 @@
+//! Execution traces in the Chrome trace event format, for analysing runs in
+//! `about://tracing` or Perfetto, and for loading them back into the editor
+
+use std::time::Duration;
+
+use egui_snarl::{NodeId, Snarl};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use web_time::SystemTime;
+
+use crate::{
+    node_graph::Node,
+    run_report::{LogLevel, NodeFailure, NodeMessage, NodeRun, RunReport},
+};
+
+/// Evaluations all run in the one process
+const PROCESS_ID: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace {
+    trace_events: Vec<TraceEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    display_time_unit: Option<String>,
+    #[serde(default)]
+    other_data: TraceInfo,
+}
+
+/// Details of the run that the trace format has no place for
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TraceInfo {
+    /// When the run started, in microseconds since the Unix epoch. Event
+    /// timestamps are relative to this.
+    #[serde(default)]
+    started_us: u64,
+    #[serde(default)]
+    cancelled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TraceEvent {
+    name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    cat: String,
+    /// Kind of event: `X` for a span, `i` for an instant and `M` for
+    /// metadata
+    ph: String,
+    /// Start of the event, in microseconds
+    #[serde(default)]
+    ts: f64,
+    /// Length of a span, in microseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
+    #[serde(default)]
+    pid: u32,
+    /// Worker the event happened on
+    #[serde(default)]
+    tid: usize,
+    /// Scope of an instant event, `t` for the thread it happened on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    s: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    args: serde_json::Value,
+}
+
+impl TraceEvent {
+    fn new(name: String, ph: &str, tid: usize, args: serde_json::Value) -> Self {
+        Self {
+            name,
+            cat: String::new(),
+            ph: ph.to_owned(),
+            ts: 0.,
+            dur: None,
+            pid: PROCESS_ID,
+            tid,
+            s: None,
+            args,
+        }
+    }
+
+    fn node(&self) -> Option<NodeId> {
+        Some(NodeId(self.args.get("node")?.as_u64()? as usize))
+    }
+}
+
+/// Writes the node updates, messages and failures of a run as a Chrome
+/// trace. Each update is a span on its worker's thread, with the size of the
+/// values it produced. Messages and failures are instant events at the end
+/// of the update they came from.
+pub fn to_chrome_trace(report: &RunReport, snarl: &Snarl<Box<dyn Node>>) -> String {
+    let micros = |time: SystemTime| {
+        time.duration_since(report.started)
+            .unwrap_or_default()
+            .as_secs_f64()
+            * 1e6
+    };
+    let name = |node: NodeId| {
+        let kind = snarl
+            .get_node(node)
+            .map_or_else(|| "Node".to_owned(), |node| node.name());
+        format!("{} {}", kind, node.0)
+    };
+    // Messages and failures are placed at the end of the node's update
+    let finished = |node: NodeId| {
+        report
+            .runs
+            .iter()
+            .find(|run| run.node == node)
+            .map_or((0., 0), |run| (micros(run.finished), run.worker))
+    };
+
+    let mut events = vec![TraceEvent::new(
+        "process_name".to_owned(),
+        "M",
+        0,
+        json!({ "name": "graph evaluation" }),
+    )];
+    let workers = report
+        .runs
+        .iter()
+        .map(|run| run.worker)
+        .max()
+        .map_or(0, |max| max + 1);
+    events.extend((0..workers).map(|worker| {
+        TraceEvent::new(
+            "thread_name".to_owned(),
+            "M",
+            worker,
+            json!({ "name": format!("Worker {}", worker) }),
+        )
+    }));
+
+    for run in &report.runs {
+        let result_size = report.changes.get(&run.node).map_or(0, |change| {
+            serde_json::to_vec(&change.after).map_or(0, |bytes| bytes.len())
+        });
+        let duration = run.duration.as_secs_f64() * 1e6;
+        events.push(TraceEvent {
+            cat: "node".to_owned(),
+            ts: (micros(run.finished) - duration).max(0.),
+            dur: Some(duration),
+            ..TraceEvent::new(
+                name(run.node),
+                "X",
+                run.worker,
+                json!({ "node": run.node.0, "result_size": result_size }),
+            )
+        });
+    }
+    for message in &report.messages {
+        let (_, worker) = finished(message.node);
+        events.push(TraceEvent {
+            cat: "message".to_owned(),
+            ts: micros(message.time),
+            s: Some("t".to_owned()),
+            ..TraceEvent::new(
+                name(message.node),
+                "i",
+                worker,
+                json!({
+                    "node": message.node.0,
+                    "level": message.level.to_string(),
+                    "message": message.message,
+                }),
+            )
+        });
+    }
+    for failure in &report.failures {
+        let (ts, worker) = finished(failure.node);
+        events.push(TraceEvent {
+            cat: "failure".to_owned(),
+            ts,
+            s: Some("t".to_owned()),
+            ..TraceEvent::new(
+                name(failure.node),
+                "i",
+                worker,
+                json!({ "node": failure.node.0, "message": failure.message }),
+            )
+        });
+    }
+
+    let started_us = report
+        .started
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+    let trace = Trace {
+        trace_events: events,
+        display_time_unit: Some("ms".to_owned()),
+        other_data: TraceInfo {
+            started_us,
+            cancelled: report.cancelled,
+        },
+    };
+    serde_json::to_string_pretty(&trace).expect("traces can always be serialised")
+}
+
+/// Reads back a run written by [`to_chrome_trace`]. Events that weren't
+/// written for a node, such as those added by other tools, are ignored.
+pub fn from_chrome_trace(contents: &[u8]) -> Result<RunReport, serde_json::Error> {
+    let trace = serde_json::from_slice::<Trace>(contents)?;
+    let mut report = RunReport::new();
+    report.started = SystemTime::UNIX_EPOCH + Duration::from_micros(trace.other_data.started_us);
+    report.cancelled = trace.other_data.cancelled;
+    let time = |micros: f64| report.started + Duration::from_secs_f64(micros.max(0.) / 1e6);
+
+    for event in &trace.trace_events {
+        let Some(node) = event.node() else {
+            continue;
+        };
+        let message = || {
+            event
+                .args
+                .get("message")
+                .and_then(|message| message.as_str())
+                .unwrap_or_default()
+                .to_owned()
+        };
+        match (event.ph.as_str(), event.cat.as_str()) {
+            ("X", _) => {
+                let duration = event.dur.unwrap_or_default();
+                report.runs.push(NodeRun {
+                    node,
+                    finished: time(event.ts + duration),
+                    duration: Duration::from_secs_f64(duration.max(0.) / 1e6),
+                    worker: event.tid,
+                });
+            }
+            ("i" | "I", "message") => {
+                let level = event.args.get("level").and_then(|level| level.as_str());
+                report.messages.push(NodeMessage {
+                    node,
+                    level: LogLevel::ALL
+                        .into_iter()
+                        .find(|known| Some(known.to_string().as_str()) == level)
+                        .unwrap_or_default(),
+                    time: time(event.ts),
+                    message: message(),
+                })
+            }
+            ("i" | "I", "failure") => report.failures.push(NodeFailure {
+                node,
+                message: message(),
+            }),
+            _ => {}
+        }
+    }
+    report.runs.sort_by_key(|run| run.finished);
+    Ok(report)
+}
@@ -0,0 +1,196 @@
+//! Checks for problems in a graph that can be found without evaluating it
+
+use std::fmt;
+
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+
+use crate::{
+    context::DEFAULT_MAX_DEPTH,
+    i18n::{tr, tr_args},
+    node_graph::{as_petgraph, Node},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The graph can't be evaluated as it is
+    Error,
+    /// The graph can be evaluated, but probably doesn't do what was meant
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem with one node of a graph
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub node: NodeId,
+    /// In the language the application is shown in, see [`i18n`](crate::i18n)
+    pub message: String,
+}
+
+/// Checks a graph for unconnected inputs without defaults, wires between incompatible pins,
+/// cycles, nodes whose results go nowhere, and groups nested more deeply
+/// than evaluations allow by default. Errors are listed before warnings.
+pub fn validate(snarl: &Snarl<Box<dyn Node>>) -> Vec<Diagnostic> {
+    validate_with(snarl, DEFAULT_MAX_DEPTH)
+}
+
+/// Checks a graph as [`validate`] does, for evaluations that allow groups to
+/// be nested `max_depth` deep
+pub fn validate_with(snarl: &Snarl<Box<dyn Node>>, max_depth: usize) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut report = |severity, node, message| {
+        diagnostics.push(Diagnostic {
+            severity,
+            node,
+            message,
+        })
+    };
+    let node_count = snarl.node_ids().count();
+
+    for (id, node) in snarl.node_ids() {
+        let inputs = node.inputs();
+        let outputs = node.outputs();
+        // Inputs needed whatever the values of the others
+        let always_required = node.required_inputs(&vec![None; inputs.len()]);
+        let mut connected = false;
+
+        for (idx, input) in inputs.iter().enumerate() {
+            let pin = snarl.in_pin(InPinId {
+                node: id,
+                input: idx,
+            });
+            if pin.remotes.is_empty() {
+                if node.input_default(idx).is_some() {
+                    continue;
+                }
+                let (severity, message) = if always_required.contains(&idx) {
+                    (Severity::Error, "validation-input-not-connected")
+                } else {
+                    (Severity::Warning, "validation-input-may-be-needed")
+                };
+                report(
+                    severity,
+                    id,
+                    tr_args(
+                        message,
+                        &[("input", &idx), ("type", &format!("{:?}", input))],
+                    ),
+                );
+                continue;
+            }
+            connected = true;
+            if pin.remotes.len() > 1 && node.input_reducer(idx).is_none() {
+                report(
+                    Severity::Error,
+                    id,
+                    tr_args(
+                        "validation-too-many-wires",
+                        &[("input", &idx), ("count", &pin.remotes.len())],
+                    ),
+                );
+            }
+            for remote in &pin.remotes {
+                let output = snarl
+                    .get_node(remote.node)
+                    .and_then(|remote_node| remote_node.outputs().get(remote.output).cloned());
+                match output {
+                    Some(output) if output.compatible_with(input) => {}
+                    Some(output) => report(
+                        Severity::Error,
+                        id,
+                        tr_args(
+                            "validation-wrong-type",
+                            &[
+                                ("input", &idx),
+                                ("expected", &format!("{:?}", input)),
+                                ("found", &format!("{:?}", output)),
+                                ("node", &remote.node.0),
+                            ],
+                        ),
+                    ),
+                    None => report(
+                        Severity::Error,
+                        id,
+                        tr_args("validation-missing-output", &[("input", &idx)]),
+                    ),
+                }
+            }
+        }
+
+        let used = (0..outputs.len()).any(|output| {
+            !snarl
+                .out_pin(OutPinId { node: id, output })
+                .remotes
+                .is_empty()
+        });
+        if !connected && !used && node_count > 1 {
+            report(Severity::Warning, id, tr("validation-unconnected-node"));
+        } else if !outputs.is_empty() && !used {
+            report(Severity::Warning, id, tr("validation-unused-results"));
+        }
+    }
+
+    let graph = as_petgraph(snarl);
+    for component in petgraph::algo::tarjan_scc(&graph) {
+        let looped = match component.as_slice() {
+            [idx] => graph.contains_edge(*idx, *idx),
+            _ => true,
+        };
+        if !looped {
+            continue;
+        }
+        let mut nodes = component.iter().map(|idx| graph[*idx]).collect::<Vec<_>>();
+        nodes.sort();
+        let ids = nodes
+            .iter()
+            .map(|node| node.0.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        for node in &nodes {
+            report(
+                Severity::Error,
+                *node,
+                tr_args("validation-cycle", &[("nodes", &ids)]),
+            );
+        }
+    }
+
+    for (id, node) in snarl.node_ids() {
+        let Some(subgraph) = node.subgraph() else {
+            continue;
+        };
+        if nesting_depth(subgraph, max_depth) >= max_depth {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                node: id,
+                message: tr_args("validation-nested-groups", &[("depth", &max_depth)]),
+            });
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.severity, diagnostic.node));
+    diagnostics
+}
+
+/// How many levels of groups are nested inside a graph, counting no further
+/// than `limit`
+fn nesting_depth(snarl: &Snarl<Box<dyn Node>>, limit: usize) -> usize {
+    if limit == 0 {
+        return 0;
+    }
+    snarl
+        .node_ids()
+        .filter_map(|(_, node)| node.subgraph())
+        .map(|subgraph| 1 + nesting_depth(subgraph, limit - 1))
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,51 @@
+//! Runs the processes of Command nodes through a `ProcessExecutor`, using
+//! the shell of Unix-like systems
+#![cfg(unix)]
+
+use std::time::{Duration, Instant};
+
+use graph_core::{
+    builder::GraphBuilder,
+    command::{CommandNode, CommandOutput},
+    execution_engine::{ProcessExecutor, TaskDag},
+    node_graph::{self, TypedData},
+};
+
+#[test]
+fn command_output_is_given_to_its_node() {
+    let mut builder = GraphBuilder::new();
+    let command = builder.add_node(CommandNode::new("echo hi"));
+    let mut snarl = builder.build();
+    let mut dag = TaskDag::new(&node_graph::as_petgraph(&snarl));
+    let mut processes = ProcessExecutor::default();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let finished = loop {
+        let finished = processes.poll(&mut dag, &mut snarl);
+        if !finished.is_empty() {
+            break finished;
+        }
+        assert!(Instant::now() < deadline, "the command didn't finish");
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    assert_eq!(finished, [command.id()]);
+    assert!(!processes.has_failed(command.id()));
+    assert!(dag.completed_tasks().any(|task| task == command.id()));
+    let node = snarl[command.id()]
+        .as_any()
+        .downcast_ref::<CommandNode>()
+        .unwrap();
+    assert_eq!(
+        node.output(),
+        Some(&CommandOutput {
+            status: Some(0),
+            stdout: "hi\n".to_owned(),
+            stderr: String::new(),
+        })
+    );
+    assert_eq!(
+        snarl[command.id()].output_value(0),
+        Some(TypedData::Number(0.))
+    );
+}
@@ -0,0 +1,31 @@
+//! Evaluates small graphs built in code and checks the values they give
+
+use graph_core::{
+    builder::GraphBuilder,
+    node_graph::{self, CompareNode, CompareOp, IfNode, NumberNode, TypedData},
+};
+
+#[test]
+fn if_node_picks_a_branch_by_its_condition() {
+    for (a, b, expected) in [(2., 1., 10.), (1., 2., 20.)] {
+        let mut builder = GraphBuilder::new();
+        let a = builder.add_node(NumberNode::new(a));
+        let b = builder.add_node(NumberNode::new(b));
+        let compare = builder.add_node(CompareNode::new(CompareOp::GreaterThan));
+        let then = builder.add_node(NumberNode::new(10.));
+        let otherwise = builder.add_node(NumberNode::new(20.));
+        let choice = builder.add_node(IfNode::default());
+        builder.connect(a.out(0), compare.input(0)).unwrap();
+        builder.connect(b.out(0), compare.input(1)).unwrap();
+        builder.connect(compare.out(0), choice.input(0)).unwrap();
+        builder.connect(then.out(0), choice.input(1)).unwrap();
+        builder.connect(otherwise.out(0), choice.input(2)).unwrap();
+
+        let mut snarl = builder.build();
+        node_graph::evaluate(&mut snarl, None);
+        assert_eq!(
+            snarl[choice.id()].output_value(0),
+            Some(TypedData::Number(expected))
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! Loading graph files, keeping the node ids they were saved with
+
+use egui_snarl::NodeId;
+use graph_core::graph_file::{LoadError, SavedGraph, MAX_UNUSED_IDS};
+
+fn graph_with_node(id: usize) -> SavedGraph {
+    serde_json::from_value(serde_json::json!({
+        "nodes": [{ "id": id, "type": "Number", "pos": [0.0, 0.0] }],
+        "wires": [],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn node_ids_are_kept_across_gaps() {
+    let snarl = graph_with_node(MAX_UNUSED_IDS).to_snarl().unwrap();
+    let ids = snarl.node_ids().map(|(id, _)| id).collect::<Vec<_>>();
+    assert_eq!(ids, [NodeId(MAX_UNUSED_IDS)]);
+}
+
+#[test]
+fn node_ids_far_past_the_node_count_are_refused() {
+    for id in [MAX_UNUSED_IDS + 1, 1_000_000_000] {
+        assert!(matches!(
+            graph_with_node(id).to_snarl(),
+            Err(LoadError::NodeIdTooLarge(refused)) if refused == id
+        ));
+    }
+}
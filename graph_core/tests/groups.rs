@@ -0,0 +1,70 @@
+//! Collapsing selections of a graph into group nodes
+
+use std::collections::BTreeSet;
+
+use graph_core::{
+    builder::GraphBuilder,
+    group::{self, CollapseError},
+    node_graph::{self, AddNode, NumberNode, SinkNode, TypedData},
+};
+
+#[test]
+fn grouping_around_an_outside_node_is_refused() {
+    // a → b → c, grouping a and c would make the group feed b and be fed by it
+    let mut builder = GraphBuilder::new();
+    let a = builder.add_node(NumberNode::new(1.));
+    let b = builder.add_node(AddNode::default());
+    let c = builder.add_node(AddNode::default());
+    builder.connect(a.out(0), b.input(0)).unwrap();
+    builder.connect(b.out(0), c.input(0)).unwrap();
+    let mut snarl = builder.build();
+
+    let selection = BTreeSet::from([a.id(), c.id()]);
+    assert_eq!(
+        group::collapse_into_group(&mut snarl, &selection),
+        Err(CollapseError::WouldCreateCycle(b.id()))
+    );
+    assert_eq!(snarl.node_ids().count(), 3);
+
+    let selection = BTreeSet::from([a.id(), b.id()]);
+    let group = group::collapse_into_group(&mut snarl, &selection).unwrap();
+    assert_eq!(snarl.node_ids().count(), 2);
+    assert!(snarl.get_node(group).is_some());
+}
+
+#[test]
+fn inner_nodes_not_fed_by_the_group_inputs_are_evaluated() {
+    // a → sum ← double ← b, sum → sink, with sum, double and b grouped so
+    // that only sum is fed by the group's inputs
+    let mut builder = GraphBuilder::new();
+    let a = builder.add_node(NumberNode::new(1.));
+    let b = builder.add_node(NumberNode::new(5.));
+    let double = builder.add_node(AddNode::default());
+    let sum = builder.add_node(AddNode::default());
+    let sink = builder.add_node(SinkNode::default());
+    builder.connect(sum.out(0), sink.input(0)).unwrap();
+    builder.connect(b.out(0), double.input(0)).unwrap();
+    builder.connect(b.out(0), double.input(1)).unwrap();
+    builder.connect(a.out(0), sum.input(0)).unwrap();
+    builder.connect(double.out(0), sum.input(1)).unwrap();
+    let mut snarl = builder.build();
+
+    let selection = BTreeSet::from([b.id(), double.id(), sum.id()]);
+    let group = group::collapse_into_group(&mut snarl, &selection).unwrap();
+    node_graph::evaluate(&mut snarl, None);
+    assert_eq!(snarl[group].output_value(0), Some(TypedData::Number(11.)));
+
+    // Edited without being evaluated, as when evaluation is left to the user
+    let inner = snarl[group].subgraph_mut().unwrap();
+    let (_, number) = inner
+        .nodes_ids_mut()
+        .find(|(_, node)| node.as_any().is::<NumberNode>())
+        .unwrap();
+    number
+        .as_any_mut()
+        .downcast_mut::<NumberNode>()
+        .unwrap()
+        .set_value(6.);
+    node_graph::evaluate(&mut snarl, Some(a.id()));
+    assert_eq!(snarl[group].output_value(0), Some(TypedData::Number(13.)));
+}
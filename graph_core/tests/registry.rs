@@ -0,0 +1,21 @@
+//! Node kinds known to the registry and the conversions made between them
+
+use graph_core::{node_graph::DataType, registry};
+
+#[test]
+fn coercions_come_from_conversion_nodes() {
+    let coercions = registry::coercions();
+    for (from, to, node) in [
+        (DataType::Number, DataType::String, "To Text"),
+        (DataType::String, DataType::Number, "Parse Number"),
+    ] {
+        assert!(coercions
+            .iter()
+            .any(|coercion| coercion.from == from && coercion.to == to && coercion.node == node));
+        let created = registry::create_coercion(&from, &to).unwrap();
+        assert_eq!(created.name(), node);
+        assert_eq!(created.inputs(), [from]);
+        assert_eq!(created.outputs(), [to]);
+    }
+    assert!(registry::create_coercion(&DataType::Bool, &DataType::Number).is_none());
+}
@@ -0,0 +1,429 @@
+//! Checks the invariants of `TaskDag` scheduling over many randomly
+//! generated DAGs
+
+use std::collections::{HashMap, HashSet};
+
+use egui_snarl::NodeId;
+use graph_core::{
+    execution_engine::{TaskDag, TaskError},
+    schedule::{self, Priority, Scheduler},
+    stages::Stages,
+};
+use petgraph::Graph;
+
+const CASES: u64 = 500;
+
+/// Small deterministic xorshift generator, so that a failing case can be
+/// reproduced from its seed
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n.max(1)
+    }
+}
+
+/// A random DAG of up to 30 tasks. Edges only go from lower to higher
+/// indices so the graph is acyclic, and task ids are shuffled so that they
+/// don't give away the topological order.
+fn random_dag(seed: u64) -> Graph<NodeId, ()> {
+    let mut rng = Rng::new(seed);
+    let count = rng.below(30) as usize + 1;
+    let density = rng.below(100);
+
+    let mut ids = (0..count).collect::<Vec<_>>();
+    for idx in (1..ids.len()).rev() {
+        ids.swap(idx, rng.below(idx as u64 + 1) as usize);
+    }
+
+    let mut graph = Graph::new();
+    let nodes = ids
+        .into_iter()
+        .map(|id| graph.add_node(NodeId(id)))
+        .collect::<Vec<_>>();
+    for to in 0..count {
+        for &from in &nodes[..to] {
+            if rng.below(100) < density / 4 {
+                graph.add_edge(from, nodes[to], ());
+            }
+        }
+    }
+    graph
+}
+
+fn dependencies(graph: &Graph<NodeId, ()>) -> HashMap<NodeId, HashSet<NodeId>> {
+    graph
+        .node_indices()
+        .map(|idx| {
+            let deps = graph
+                .neighbors_directed(idx, petgraph::Direction::Incoming)
+                .map(|dep| graph[dep])
+                .collect();
+            (graph[idx], deps)
+        })
+        .collect()
+}
+
+/// Whether `order` contains every task once, with each after its
+/// dependencies
+fn is_topological(order: &[NodeId], deps: &HashMap<NodeId, HashSet<NodeId>>) -> bool {
+    let position = order
+        .iter()
+        .enumerate()
+        .map(|(idx, task)| (*task, idx))
+        .collect::<HashMap<_, _>>();
+    position.len() == order.len()
+        && position.len() == deps.len()
+        && deps.iter().all(|(task, task_deps)| {
+            task_deps
+                .iter()
+                .all(|dep| position.get(dep) < position.get(task))
+        })
+}
+
+#[test]
+fn every_task_becomes_ready_exactly_once() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let deps = dependencies(&graph);
+        let mut dag = TaskDag::new(&graph);
+
+        let mut ready = dag.ready_tasks().collect::<Vec<_>>();
+        let mut seen = ready.iter().copied().collect::<HashSet<_>>();
+        let mut completed = HashSet::new();
+        while let Some(task) = ready.pop() {
+            for newly_ready in dag.complete_task(task).unwrap() {
+                assert!(
+                    seen.insert(newly_ready),
+                    "seed {seed}: task {} became ready twice",
+                    newly_ready.0
+                );
+                ready.push(newly_ready);
+            }
+            completed.insert(task);
+        }
+
+        assert_eq!(seen.len(), deps.len(), "seed {seed}");
+        assert_eq!(completed.len(), deps.len(), "seed {seed}");
+        assert_eq!(dag.blocked_tasks().count(), 0, "seed {seed}");
+    }
+}
+
+#[test]
+fn completed_tasks_only_release_tasks_with_met_dependencies() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let deps = dependencies(&graph);
+        let mut dag = TaskDag::new(&graph);
+        let mut rng = Rng::new(seed ^ 0xdead_beef);
+
+        let mut ready = dag.ready_tasks().collect::<Vec<_>>();
+        for task in &ready {
+            assert!(deps[task].is_empty(), "seed {seed}");
+        }
+        let mut completed = HashSet::new();
+        while !ready.is_empty() {
+            // Complete the ready tasks in a random order
+            let task = ready.swap_remove(rng.below(ready.len() as u64) as usize);
+            completed.insert(task);
+            for newly_ready in dag.complete_task(task).unwrap() {
+                assert!(
+                    deps[&newly_ready].is_subset(&completed),
+                    "seed {seed}: task {} released before its dependencies",
+                    newly_ready.0
+                );
+                ready.push(newly_ready);
+            }
+        }
+    }
+}
+
+#[test]
+fn completing_a_task_twice_is_an_error() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let mut dag = TaskDag::new(&graph);
+        let task = dag.ready_tasks().next().unwrap();
+        assert!(dag.complete_task(task).is_ok());
+        assert_eq!(
+            dag.complete_task(task),
+            Err(TaskError::AlreadyCompleted(task))
+        );
+    }
+}
+
+#[test]
+fn completing_an_unknown_task_is_an_error() {
+    let graph = random_dag(0);
+    let mut dag = TaskDag::new(&graph);
+    let unknown = NodeId(graph.node_count());
+    assert_eq!(dag.complete_task(unknown), Err(TaskError::Unknown(unknown)));
+}
+
+#[test]
+fn completing_a_blocked_task_is_an_error() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let deps = dependencies(&graph);
+        let mut dag = TaskDag::new(&graph);
+        for task in dag.blocked_tasks().collect::<Vec<_>>() {
+            let mut waiting_on = deps[&task].iter().copied().collect::<Vec<_>>();
+            waiting_on.sort_unstable();
+            assert_eq!(
+                dag.complete_task(task),
+                Err(TaskError::Blocked(task, waiting_on)),
+                "seed {seed}"
+            );
+        }
+        // Failed attempts leave the DAG as it was
+        assert_eq!(
+            dag.blocked_tasks().count() + dag.ready_tasks().count(),
+            deps.len(),
+            "seed {seed}"
+        );
+    }
+}
+
+#[test]
+fn unblocked_by_predicts_newly_ready_tasks() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let mut dag = TaskDag::new(&graph);
+        let mut ready = dag.ready_tasks().collect::<Vec<_>>();
+        while let Some(task) = ready.pop() {
+            let predicted = dag.unblocked_by(task);
+            let mut newly_ready = dag
+                .complete_task(task)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>();
+            newly_ready.sort_unstable();
+            assert_eq!(predicted, newly_ready, "seed {seed}");
+            assert!(dag.is_completed(task), "seed {seed}");
+            ready.extend(newly_ready);
+        }
+    }
+}
+
+#[test]
+fn plan_stages_linearise_to_a_topological_order() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let deps = dependencies(&graph);
+        let plan = TaskDag::new(&graph).plan();
+
+        assert!(plan.blocked.is_empty(), "seed {seed}");
+        let order = plan.stages.concat();
+        assert!(is_topological(&order, &deps), "seed {seed}: {order:?}");
+        for (idx, stage) in plan.stages.iter().enumerate() {
+            // Tasks within a stage are independent of each other
+            let earlier = plan.stages[..idx].concat();
+            for task in stage {
+                assert!(
+                    deps[task].iter().all(|dep| earlier.contains(dep)),
+                    "seed {seed}: task {} depends on its own stage",
+                    task.0
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn schedules_respect_dependencies_and_workers() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let deps = dependencies(&graph);
+        let mut rng = Rng::new(!seed);
+        let durations = deps
+            .keys()
+            .map(|task| (*task, std::time::Duration::from_millis(rng.below(50))))
+            .collect::<HashMap<_, _>>();
+        let workers = rng.below(4) as usize + 1;
+
+        for priority in Priority::ALL {
+            let schedule = Scheduler::new(TaskDag::new(&graph), workers)
+                .priority(priority)
+                .run(&durations);
+            let order = schedule
+                .tasks
+                .iter()
+                .map(|task| task.task)
+                .collect::<Vec<_>>();
+            assert!(is_topological(&order, &deps), "seed {seed}: {order:?}");
+
+            let end = schedule
+                .tasks
+                .iter()
+                .map(|task| (task.task, task.end()))
+                .collect::<HashMap<_, _>>();
+            for task in &schedule.tasks {
+                assert!(task.worker < workers, "seed {seed}");
+                for dep in &deps[&task.task] {
+                    assert!(end[dep] <= task.start, "seed {seed}");
+                }
+                // No worker runs two tasks at once
+                let overlapping = schedule.tasks.iter().any(|other| {
+                    other.task != task.task
+                        && other.worker == task.worker
+                        && other.start < task.end()
+                        && task.start < other.end()
+                });
+                assert!(!overlapping, "seed {seed}: task {} overlaps", task.task.0);
+            }
+        }
+
+        let default = schedule::schedule(TaskDag::new(&graph), &durations, workers);
+        assert_eq!(default.tasks.len(), deps.len(), "seed {seed}");
+    }
+}
+
+#[test]
+fn workers_are_busy_or_idle_for_the_whole_makespan() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let mut rng = Rng::new(seed ^ 0x5eed);
+        let durations = dependencies(&graph)
+            .into_keys()
+            .map(|task| (task, std::time::Duration::from_millis(rng.below(50))))
+            .collect::<HashMap<_, _>>();
+        let workers = rng.below(4) as usize + 1;
+        let schedule = schedule::schedule(TaskDag::new(&graph), &durations, workers);
+
+        let gaps = schedule.idle_gaps();
+        for (worker, utilisation) in schedule.utilisation().into_iter().enumerate() {
+            let idle = gaps
+                .iter()
+                .filter(|gap| gap.worker == worker)
+                .map(|gap| gap.duration)
+                .sum::<std::time::Duration>();
+            assert_eq!(
+                schedule.busy_time(worker) + idle,
+                schedule.makespan,
+                "seed {seed}: worker {worker}"
+            );
+            assert!((0. ..=1.).contains(&utilisation), "seed {seed}");
+        }
+        assert!(
+            gaps.windows(2)
+                .all(|pair| pair[0].duration >= pair[1].duration),
+            "seed {seed}"
+        );
+    }
+}
+
+#[test]
+fn assigned_priorities_order_ready_tasks() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let mut rng = Rng::new(seed.rotate_left(17));
+        let priorities = dependencies(&graph)
+            .into_keys()
+            .map(|task| (task, rng.below(7) as i32 - 3))
+            .collect::<HashMap<_, _>>();
+        let dag = TaskDag::new(&graph).with_priorities(priorities.clone());
+
+        let ready = dag.ready_tasks_by(Priority::Assigned, &HashMap::new());
+        assert_eq!(ready.len(), dag.ready_tasks().count(), "seed {seed}");
+        for pair in ready.windows(2) {
+            let key = |task: NodeId| (std::cmp::Reverse(priorities[&task]), task);
+            assert!(key(pair[0]) < key(pair[1]), "seed {seed}: {ready:?}");
+        }
+    }
+}
+
+#[test]
+fn stages_finish_before_later_stages_start() {
+    for seed in 0..CASES {
+        let graph = random_dag(seed);
+        let deps = dependencies(&graph);
+        let mut rng = Rng::new(seed.wrapping_add(0x57a6e));
+        // Tagging tasks in runs of their topological order keeps every wire
+        // going forwards between stages, leaving some tasks untagged
+        let mut stages = Stages::default();
+        for name in ["ingest", "analysis", "report"] {
+            stages.push(name);
+        }
+        let mut stage_of = HashMap::new();
+        let mut stage = 0;
+        for idx in graph.node_indices() {
+            if rng.below(4) == 0 {
+                stage = (stage + 1).min(2);
+            }
+            if rng.below(5) > 0 {
+                stages.set_stage(graph[idx], Some(stage));
+                stage_of.insert(graph[idx], stage);
+            }
+        }
+        assert!(stages.conflicts(&graph).is_empty(), "seed {seed}");
+
+        let durations = deps
+            .keys()
+            .map(|task| (*task, std::time::Duration::from_millis(rng.below(50))))
+            .collect::<HashMap<_, _>>();
+        let workers = rng.below(4) as usize + 1;
+        let schedule =
+            Scheduler::new(TaskDag::new(&graph).with_stages(&stages), workers).run(&durations);
+        assert_eq!(schedule.tasks.len(), deps.len(), "seed {seed}");
+
+        for task in &schedule.tasks {
+            let Some(stage) = stage_of.get(&task.task) else {
+                continue;
+            };
+            for earlier in &schedule.tasks {
+                if stage_of
+                    .get(&earlier.task)
+                    .is_some_and(|other| other < stage)
+                {
+                    assert!(
+                        earlier.end() <= task.start,
+                        "seed {seed}: task {} started before task {} finished",
+                        task.task.0,
+                        earlier.task.0
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn remaining_times_leave_cycles_at_their_own_duration() {
+    // 0 → 1 ⇄ 2 → 3, and 4 → 5 apart from the cycle
+    let mut graph = Graph::new();
+    let nodes = (0..6)
+        .map(|id| graph.add_node(NodeId(id)))
+        .collect::<Vec<_>>();
+    for (from, to) in [(0, 1), (1, 2), (2, 1), (2, 3), (4, 5)] {
+        graph.add_edge(nodes[from], nodes[to], ());
+    }
+    let dag = TaskDag::new(&graph);
+    let durations = (0..6)
+        .map(|id| (NodeId(id), std::time::Duration::from_millis(id as u64 + 1)))
+        .collect::<HashMap<_, _>>();
+
+    let times = dag.remaining_times(&durations);
+    assert_eq!(times.len(), 6);
+    for id in 0..4 {
+        assert_eq!(times[&NodeId(id)], durations[&NodeId(id)], "task {id}");
+    }
+    assert_eq!(
+        times[&NodeId(4)],
+        durations[&NodeId(4)] + durations[&NodeId(5)]
+    );
+    assert_eq!(
+        dag.ready_tasks_by(Priority::CriticalPath, &durations),
+        [NodeId(4), NodeId(0)]
+    );
+}
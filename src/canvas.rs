@@ -0,0 +1,495 @@
+//! Everything drawn on the node canvas beneath the nodes: the grid and the
+//! graph's comments. Also moves the canvas's view, for navigating large
+//! graphs.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+
+use eframe::egui::{self, emath::Rot2, vec2, Color32, Id, Pos2, Rect, Stroke, Ui, Vec2};
+use egui_snarl::{
+    ui::{BackgroundPattern, Grid, PinInfo, SnarlStyle, SnarlViewer, Viewport},
+    InPin, NodeId, OutPin, Snarl,
+};
+use graph_core::{comment::Comment, node_graph::Node};
+
+use crate::viewer;
+
+/// Height of a comment's title bar, in graph units
+const TITLE_HEIGHT: f32 = 24.;
+const MIN_COMMENT_SIZE: Vec2 = vec2(80., 48.);
+const DEFAULT_COMMENT_SIZE: Vec2 = vec2(240., 160.);
+/// Space left around nodes when a comment is made to fit them
+const COMMENT_PADDING: f32 = 16.;
+/// Size of the handle in the bottom right corner for resizing a comment, in
+/// screen units
+const RESIZE_HANDLE: f32 = 12.;
+/// Rough size of a node that hasn't been drawn yet
+const NODE_SIZE: Vec2 = vec2(120., 60.);
+/// Space left around the nodes fitted into view
+const VIEW_PADDING: f32 = 24.;
+/// Space the canvas leaves around the nodes when fitting them into view
+const FIT_MARGIN: f32 = 100.;
+
+/// A change to the part of the graph in view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    /// Fit every node into view
+    FitAll,
+    /// Fit the selected nodes into view
+    FitSelected,
+    /// Centre the view on a node, keeping the zoom
+    JumpTo(NodeId),
+}
+
+/// What the background needs to be drawn, shared with the callback the
+/// canvas calls to draw it
+#[derive(Default)]
+struct Background {
+    grid: Option<Grid>,
+    comments: Vec<Comment>,
+    /// How graph positions were mapped to the screen when last drawn
+    viewport: Option<(Rect, f32, Vec2)>,
+}
+
+/// The comments of the root graph, the canvas's background, and where the
+/// canvas is looking
+pub struct Canvas {
+    pub comments: Vec<Comment>,
+    /// Whether an overview of the whole graph is shown in a corner
+    pub minimap: bool,
+    /// Comment whose title and text are being edited
+    editing: Option<usize>,
+    background: Arc<Mutex<Background>>,
+    /// Changed to make the canvas forget its view, see [`Self::view_id`]
+    generation: u64,
+    /// Area of the graph to show from the next frame
+    view: Option<Rect>,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            comments: Vec::new(),
+            minimap: true,
+            editing: None,
+            background: Arc::default(),
+            generation: 0,
+            view: None,
+        }
+    }
+}
+
+impl Canvas {
+    /// Replaces the background of `style` with one that also draws comments,
+    /// keeping its grid
+    pub fn install(&self, style: &mut SnarlStyle) {
+        let background = self.background.clone();
+        let pattern = BackgroundPattern::custom(move |style, viewport, ui| {
+            let mut background = background.lock().unwrap();
+            background.viewport = Some((viewport.rect, viewport.scale, viewport.offset));
+            if let Some(grid) = background.grid {
+                draw_grid(&grid, style, viewport, ui);
+            }
+            for comment in &background.comments {
+                draw_comment(comment, viewport, ui);
+            }
+        });
+        match std::mem::replace(&mut style.bg_pattern, pattern) {
+            BackgroundPattern::Grid(grid) => self.background.lock().unwrap().grid = Some(grid),
+            BackgroundPattern::NoPattern => self.background.lock().unwrap().grid = None,
+            // Already installed
+            BackgroundPattern::Custom(_) => {}
+        }
+    }
+
+    fn viewport(&self) -> Option<Viewport> {
+        let (rect, scale, offset) = self.background.lock().unwrap().viewport?;
+        Some(Viewport {
+            rect,
+            scale,
+            offset,
+        })
+    }
+
+    /// Converts a position on screen into one on the graph, as it was drawn
+    /// last frame
+    pub fn screen_to_graph(&self, pos: Pos2, canvas: Rect) -> Pos2 {
+        match self.viewport() {
+            Some(viewport) => viewport.screen_pos_to_graph(pos),
+            // An empty graph starts out centred on the origin, unscaled
+            None => (pos - canvas.center()).to_pos2(),
+        }
+    }
+
+    /// Area of the graph covered by each node, using the screen area it was
+    /// drawn over this frame if it was drawn
+    pub fn node_areas(
+        &self,
+        snarl: &Snarl<Box<dyn Node>>,
+        drawn: impl Fn(NodeId) -> Option<Rect>,
+    ) -> BTreeMap<NodeId, Rect> {
+        let viewport = self.viewport();
+        snarl
+            .nodes_pos_ids()
+            .map(|(id, pos, _)| {
+                let area = match (drawn(id), &viewport) {
+                    (Some(rect), Some(viewport)) => Rect::from_min_max(
+                        viewport.screen_pos_to_graph(rect.min),
+                        viewport.screen_pos_to_graph(rect.max),
+                    ),
+                    _ => Rect::from_min_size(pos, NODE_SIZE),
+                };
+                (id, area)
+            })
+            .collect()
+    }
+
+    /// Moves the view from the next frame. `nodes` is the area covered by
+    /// each node, see [`Self::node_areas`].
+    pub fn navigate(
+        &mut self,
+        navigation: Navigation,
+        nodes: &BTreeMap<NodeId, Rect>,
+        selected: &BTreeSet<NodeId>,
+        canvas: Rect,
+    ) {
+        let area = match navigation {
+            Navigation::FitAll => nodes.values().copied().reduce(Rect::union),
+            Navigation::FitSelected => selected
+                .iter()
+                .filter_map(|id| nodes.get(id))
+                .copied()
+                .reduce(Rect::union),
+            Navigation::JumpTo(node) => {
+                if let Some(area) = nodes.get(&node) {
+                    self.centre_on(area.center(), canvas);
+                }
+                return;
+            }
+        };
+        match area {
+            Some(area) => self.show_area(area.expand(VIEW_PADDING)),
+            // Nothing to fit, so the view of an empty graph is shown
+            None if navigation == Navigation::FitAll => self.generation += 1,
+            None => {}
+        }
+    }
+
+    /// Moves the view from the next frame so that it is centred on a
+    /// position on the graph, keeping the zoom
+    pub fn centre_on(&mut self, pos: Pos2, canvas: Rect) {
+        let scale = self.viewport().map_or(1., |viewport| viewport.scale);
+        self.show_area(Rect::from_center_size(pos, canvas.size() / scale));
+    }
+
+    /// Moves the view from the next frame so that an area of the graph fits
+    /// the canvas
+    fn show_area(&mut self, area: Rect) {
+        self.view = Some(area);
+        self.generation += 1;
+    }
+
+    /// Id to show the graph with, based on `id`.
+    ///
+    /// The canvas only lets its view be moved by dragging and zooming, so
+    /// the view is moved by showing the graph under a new id. A graph shown
+    /// for the first time is fitted into view, which is done here for an
+    /// invisible graph with nodes at the corners of the area to show, so
+    /// that it is that area the graph is then shown with.
+    pub fn view_id(&mut self, ui: &mut Ui, id: Id, style: &SnarlStyle) -> Id {
+        let id = id.with(("view", self.generation));
+        let Some(area) = self.view.take() else {
+            return id;
+        };
+        let corners = Rect::from_center_size(
+            area.center(),
+            (area.size() - Vec2::splat(2. * FIT_MARGIN)).max(Vec2::splat(1.)),
+        );
+        let mut markers = Snarl::new();
+        markers.insert_node(corners.min, ());
+        markers.insert_node(corners.max, ());
+        let mut hidden = Ui::new(
+            ui.ctx().clone(),
+            ui.layer_id(),
+            ui.id(),
+            ui.max_rect(),
+            ui.clip_rect(),
+        );
+        hidden.set_visible(false);
+        let mut marker_style = SnarlStyle::new();
+        marker_style.min_scale = style.min_scale;
+        marker_style.max_scale = style.max_scale;
+        markers.show(&mut Markers, &marker_style, id, &mut hidden);
+        ui.ctx().request_repaint();
+        id
+    }
+
+    /// Adds a comment with its top left corner at a position on the graph,
+    /// and starts editing it
+    pub fn add_comment(&mut self, pos: Pos2) {
+        self.comments
+            .push(Comment::new(pos.into(), DEFAULT_COMMENT_SIZE.into()));
+        self.editing = Some(self.comments.len() - 1);
+    }
+
+    /// Adds a comment surrounding an area of the screen, and starts editing
+    /// it
+    pub fn add_comment_around(&mut self, area: Rect, canvas: Rect) {
+        let min = self.screen_to_graph(area.min, canvas);
+        let max = self.screen_to_graph(area.max, canvas);
+        let rect = Rect::from_min_max(min, max)
+            .expand(COMMENT_PADDING)
+            .with_min_y(min.y - COMMENT_PADDING - TITLE_HEIGHT);
+        self.comments
+            .push(Comment::new(rect.min.into(), rect.size().into()));
+        self.editing = Some(self.comments.len() - 1);
+    }
+
+    /// Handles moving and resizing comments, which has to be seen before the
+    /// nodes are drawn. Dragging a comment by its title moves the nodes
+    /// inside it too. Comments are only shown when `visible`.
+    pub fn before_nodes(&mut self, ui: &mut Ui, snarl: &mut Snarl<Box<dyn Node>>, visible: bool) {
+        let comments = if visible {
+            self.comments.clone()
+        } else {
+            Vec::new()
+        };
+        self.background.lock().unwrap().comments = comments;
+        let Some(viewport) = self.viewport().filter(|_| visible) else {
+            return;
+        };
+
+        for (idx, comment) in self.comments.iter_mut().enumerate() {
+            let rect = screen_rect(comment, &viewport);
+            let title = rect.with_max_y(rect.min.y + TITLE_HEIGHT * viewport.scale);
+            let response = ui
+                .interact(
+                    title,
+                    ui.id().with(("comment", idx)),
+                    egui::Sense::click_and_drag(),
+                )
+                .on_hover_cursor(egui::CursorIcon::Grab)
+                .on_hover_text("Drag to move, double-click to edit");
+            if response.double_clicked() {
+                self.editing = Some(idx);
+            }
+            let delta = viewport.screen_vec_to_graph(response.drag_delta());
+            if response.dragged() && delta != Vec2::ZERO {
+                let inside = snarl
+                    .nodes_pos_ids()
+                    .filter(|(_, pos, _)| comment.contains((*pos).into()))
+                    .map(|(id, _, _)| id)
+                    .collect::<Vec<_>>();
+                for id in inside {
+                    viewer::move_node(snarl, id, delta);
+                }
+                comment.pos = (Pos2::from(comment.pos) + delta).into();
+            }
+
+            let handle = Rect::from_min_max(rect.max - Vec2::splat(RESIZE_HANDLE), rect.max);
+            let response = ui
+                .interact(
+                    handle,
+                    ui.id().with(("comment_resize", idx)),
+                    egui::Sense::drag(),
+                )
+                .on_hover_cursor(egui::CursorIcon::ResizeNwSe);
+            if response.dragged() {
+                let size =
+                    Vec2::from(comment.size) + viewport.screen_vec_to_graph(response.drag_delta());
+                comment.size = size.max(MIN_COMMENT_SIZE).into();
+            }
+        }
+    }
+
+    /// Shows the window for editing a comment, if one is open
+    pub fn edit_window(&mut self, ctx: &egui::Context) {
+        let Some(idx) = self.editing.filter(|idx| *idx < self.comments.len()) else {
+            self.editing = None;
+            return;
+        };
+        let comment = &mut self.comments[idx];
+        let mut open = true;
+        let mut remove = false;
+        egui::Window::new("Comment")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.text_edit_singleline(&mut comment.title);
+                ui.add(egui::TextEdit::multiline(&mut comment.text).hint_text("Notes…"));
+                ui.horizontal(|ui| {
+                    ui.label("Colour");
+                    ui.color_edit_button_srgb(&mut comment.colour);
+                });
+                remove = ui.button("Remove comment").clicked();
+            });
+        if remove {
+            self.comments.remove(idx);
+        }
+        if !open || remove {
+            self.editing = None;
+        }
+    }
+
+    /// Stops editing, such as when the comments are replaced
+    pub fn reset(&mut self) {
+        self.editing = None;
+    }
+}
+
+/// Shows the nodes that mark out the area of the graph to fit into view,
+/// which have nothing to draw
+struct Markers;
+
+impl SnarlViewer<()> for Markers {
+    fn title(&mut self, _node: &()) -> String {
+        String::new()
+    }
+
+    fn outputs(&mut self, _node: &()) -> usize {
+        0
+    }
+
+    fn inputs(&mut self, _node: &()) -> usize {
+        0
+    }
+
+    fn show_input(
+        &mut self,
+        _pin: &InPin,
+        _ui: &mut Ui,
+        _scale: f32,
+        _snarl: &mut Snarl<()>,
+    ) -> PinInfo {
+        PinInfo::default()
+    }
+
+    fn show_output(
+        &mut self,
+        _pin: &OutPin,
+        _ui: &mut Ui,
+        _scale: f32,
+        _snarl: &mut Snarl<()>,
+    ) -> PinInfo {
+        PinInfo::default()
+    }
+
+    fn input_color(
+        &mut self,
+        _pin: &InPin,
+        _style: &egui::Style,
+        _snarl: &mut Snarl<()>,
+    ) -> Color32 {
+        Color32::TRANSPARENT
+    }
+
+    fn output_color(
+        &mut self,
+        _pin: &OutPin,
+        _style: &egui::Style,
+        _snarl: &mut Snarl<()>,
+    ) -> Color32 {
+        Color32::TRANSPARENT
+    }
+}
+
+fn screen_rect(comment: &Comment, viewport: &Viewport) -> Rect {
+    Rect::from_min_size(
+        viewport.graph_pos_to_screen(comment.pos.into()),
+        viewport.graph_vec_to_screen(comment.size.into()),
+    )
+}
+
+fn draw_comment(comment: &Comment, viewport: &Viewport, ui: &Ui) {
+    let scale = viewport.scale;
+    let rect = screen_rect(comment, viewport);
+    if !rect.intersects(viewport.rect) {
+        return;
+    }
+    let [r, g, b] = comment.colour;
+    let colour = Color32::from_rgb(r, g, b);
+    let rounding = 4. * scale;
+    let painter = ui.painter().with_clip_rect(viewport.rect);
+    painter.rect(
+        rect,
+        rounding,
+        Color32::from_rgba_unmultiplied(r, g, b, 40),
+        Stroke::new(1.5 * scale, colour),
+    );
+    let title = rect.with_max_y(rect.min.y + TITLE_HEIGHT * scale);
+    painter.rect_filled(
+        title,
+        egui::Rounding {
+            nw: rounding,
+            ne: rounding,
+            ..Default::default()
+        },
+        Color32::from_rgba_unmultiplied(r, g, b, 160),
+    );
+
+    // Keep the text inside the frame
+    let painter = painter.with_clip_rect(rect.intersect(viewport.rect));
+    let margin = 6. * scale;
+    painter.text(
+        title.left_center() + vec2(margin, 0.),
+        egui::Align2::LEFT_CENTER,
+        &comment.title,
+        egui::FontId::proportional(14. * scale),
+        ui.visuals().strong_text_color(),
+    );
+    if !comment.text.is_empty() {
+        let galley = painter.layout(
+            comment.text.clone(),
+            egui::FontId::proportional(12. * scale),
+            ui.visuals().text_color(),
+            rect.width() - 2. * margin,
+        );
+        painter.galley(
+            title.left_bottom() + Vec2::splat(margin),
+            galley,
+            ui.visuals().text_color(),
+        );
+    }
+}
+
+/// Draws a grid in the same way as the canvas's own background
+fn draw_grid(grid: &Grid, style: &SnarlStyle, viewport: &Viewport, ui: &Ui) {
+    let bg_stroke = style
+        .background_pattern_stroke
+        .unwrap_or_else(|| ui.visuals().widgets.noninteractive.bg_stroke);
+    let stroke = Stroke::new(
+        bg_stroke.width * viewport.scale.max(1.),
+        bg_stroke.color.gamma_multiply(viewport.scale.min(1.)),
+    );
+    let spacing = ui.spacing().icon_width * grid.spacing;
+    let rot = Rot2::from_angle(grid.angle);
+
+    let bounds = Rect::from_min_max(
+        viewport.screen_pos_to_graph(viewport.rect.min),
+        viewport.screen_pos_to_graph(viewport.rect.max),
+    )
+    .rotate_bb(rot.inverse());
+    let line = |from: Vec2, to: Vec2| {
+        ui.painter().line_segment(
+            [
+                viewport.graph_pos_to_screen((rot * from).to_pos2()),
+                viewport.graph_pos_to_screen((rot * to).to_pos2()),
+            ],
+            stroke,
+        );
+    };
+
+    let mut x = (bounds.min.x / spacing.x).ceil() * spacing.x;
+    while x <= bounds.max.x {
+        line(vec2(x, bounds.min.y), vec2(x, bounds.max.y));
+        x += spacing.x;
+    }
+    let mut y = (bounds.min.y / spacing.y).ceil() * spacing.y;
+    while y <= bounds.max.y {
+        line(vec2(bounds.min.x, y), vec2(bounds.max.x, y));
+        y += spacing.y;
+    }
+}
@@ -0,0 +1,150 @@
+//! Several graphs open at once, each in a tab with its own editor, so with
+//! its own panes, task DAG and undo history
+
+use eframe::egui;
+
+use crate::{
+    editor::EditorState,
+    file_io::{self, PickedFile},
+};
+
+/// The open graphs, of which one is shown at a time
+pub struct Documents {
+    editors: Vec<EditorState>,
+    active: usize,
+    /// Tab the user has asked to close, waiting for them to confirm
+    closing: Option<usize>,
+}
+
+impl Default for Documents {
+    fn default() -> Self {
+        Self {
+            editors: vec![EditorState::default()],
+            active: 0,
+            closing: None,
+        }
+    }
+}
+
+impl Documents {
+    /// Editor of the graph being shown
+    pub fn active(&mut self) -> &mut EditorState {
+        &mut self.editors[self.active]
+    }
+
+    /// Opens a new tab with an empty graph
+    pub fn new_graph(&mut self) {
+        self.editors.push(EditorState::default());
+        self.active = self.editors.len() - 1;
+    }
+
+    /// Opens a graph file in a new tab, or in the current one if it's blank
+    pub fn open(&mut self, file: PickedFile) {
+        if !self.active().is_blank() {
+            self.new_graph();
+        }
+        self.active().open_file(file);
+    }
+
+    /// Opens the graph files dropped onto the window, and shows where they
+    /// can be dropped while they are dragged over it
+    pub fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                egui::Id::new("dropped_files"),
+            ));
+            let screen = ctx.screen_rect();
+            painter.rect_filled(screen, 0., egui::Color32::from_black_alpha(160));
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop to open",
+                egui::FontId::proportional(24.),
+                egui::Color32::WHITE,
+            );
+        }
+        for file in file_io::dropped_files(ctx) {
+            match file {
+                Ok(file) => self.open(file),
+                Err(err) => self.active().notifications().error(err.to_string()),
+            }
+        }
+    }
+
+    /// Shows a tab for each open graph, for switching between them
+    pub fn tabs_ui(&mut self, ui: &mut egui::Ui) {
+        let mut close = None;
+        ui.horizontal(|ui| {
+            let closable = self.editors.len() > 1;
+            for (idx, editor) in self.editors.iter().enumerate() {
+                if ui
+                    .selectable_label(idx == self.active, editor.title())
+                    .clicked()
+                {
+                    self.active = idx;
+                }
+                if closable
+                    && ui
+                        .small_button("×")
+                        .on_hover_text("Close the graph")
+                        .clicked()
+                {
+                    close = Some(idx);
+                }
+                ui.separator();
+            }
+            if ui.small_button("+").on_hover_text("New graph").clicked() {
+                self.new_graph();
+            }
+        });
+        // Graphs with nothing in them are closed without asking
+        match close {
+            Some(idx) if self.editors[idx].is_blank() => self.close(idx),
+            Some(idx) => self.closing = Some(idx),
+            None => {}
+        }
+        self.show_close_confirmation(ui.ctx());
+    }
+
+    fn close(&mut self, idx: usize) {
+        if self.editors.len() == 1 {
+            return;
+        }
+        self.editors.remove(idx);
+        if self.active > idx || self.active == self.editors.len() {
+            self.active -= 1;
+        }
+    }
+
+    fn show_close_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(idx) = self.closing else {
+            return;
+        };
+        let mut confirmed = None;
+        egui::Window::new("Are you sure?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("Close {}?", self.editors[idx].title()));
+                ui.label("Changes since it was last saved will be lost.");
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        confirmed = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        confirmed = Some(false);
+                    }
+                });
+            });
+        match confirmed {
+            Some(true) => {
+                self.closing = None;
+                self.close(idx);
+            }
+            Some(false) => self.closing = None,
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,2784 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use eframe::{
+    egui::{self, Label, Layout},
+    epaint::{Color32, Rounding},
+};
+use egui_snarl::{ui::SnarlStyle, InPinId, NodeId, OutPinId, Snarl};
+use egui_tiles::{Container, Linear, LinearDir, Tile};
+
+use graph_core::{
+    checkpoint::Checkpoint,
+    comment::Comment,
+    cron::Trigger,
+    diff::GraphDiff,
+    execution_engine::{ExecutionPlan, ProcessExecutor, TaskDag},
+    export::{self, ExportFormat, ExportGraph},
+    graph_file::{LoadError, SavedGraph},
+    group::GroupNode,
+    hooks::Hooks,
+    i18n,
+    node_graph::{self, Evaluation, Node, NodeRole},
+    package::{self, Package},
+    plugins,
+    profile::{EvaluationMode, Profile},
+    profiler,
+    run_report::{format_elapsed, format_timestamp, ExecutionStats, RunReport},
+    schedule::{Priority, Scheduler},
+    secrets,
+    snapshot::{self, Snapshot},
+    stages::Stages,
+    template::TemplateLibrary,
+    trace,
+    validation::{self, Diagnostic, Severity},
+};
+
+use crate::{
+    canvas::{Canvas, Navigation},
+    file_io::{self, FileOpener, PickedFile},
+    image_export::{self, ImageFormat, ImageOptions},
+    log_console::LogConsole,
+    minimap,
+    notifications::Notifications,
+    palette::{NodePalette, Picked},
+    settings::Settings,
+    simulation::SimulationPanel,
+    sweep::SweepPanel,
+    thumbnail,
+    timeline::Timeline,
+    undo::{DestructiveAction, UndoEntry, UndoHistory},
+    viewer::{DemoViewer, NewComment},
+};
+
+/// Number of graph files listed under Open Recent
+const MAX_RECENT: usize = 8;
+/// Width that thumbnails are shown at under Open Recent
+const THUMBNAIL_WIDTH: f32 = 120.;
+/// How often nodes that [need continuous updates](Node::needs_continuous_update)
+/// are updated
+const CONTINUOUS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+/// How often watched files are checked for changes
+#[cfg(not(target_arch = "wasm32"))]
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+pub enum Pane {
+    Config,
+    Nodes,
+    Statistics,
+    Inspector,
+    Timeline,
+    Sweep,
+    Simulation,
+    Log,
+    Problems,
+    Differences,
+    History,
+}
+
+/// A saved graph the graph being edited is compared with
+struct Comparison {
+    /// Name of the file the graph was read from
+    name: String,
+    saved: SavedGraph,
+    /// Changes from the saved graph to the graph being edited, kept up to
+    /// date with each edit
+    diff: GraphDiff,
+}
+
+struct TreeBehavior<'a> {
+    reports: &'a mut Vec<RunReport>,
+    actions: &'a mut Vec<DestructiveAction>,
+    /// Id of the editor's tiles, which the ids of its panes are made from
+    id: egui::Id,
+    snarl: &'a mut Snarl<Box<dyn Node>>,
+    viewer: &'a mut DemoViewer,
+    group_path: &'a mut Vec<NodeId>,
+    style: &'a SnarlStyle,
+    task_execution: &'a mut Option<TaskDag>,
+    processes: &'a mut ProcessExecutor,
+    stats: &'a ExecutionStats,
+    config: &'a mut ConfigState,
+    settings: Option<&'a mut Settings>,
+    canvas: &'a mut Canvas,
+    timeline: &'a mut Timeline,
+    sweep: &'a mut SweepPanel,
+    simulation: &'a mut SimulationPanel,
+    log: &'a mut LogConsole,
+    /// Problems found in the root graph
+    diagnostics: &'a [Diagnostic],
+    /// Evaluation being stepped through, paused at a node
+    debug: &'a mut Option<Evaluation>,
+    comparison: &'a mut Option<Comparison>,
+    notifications: &'a mut Notifications,
+}
+
+/// Named profiles of the graph, and the settings they control that are not
+/// stored in the graph itself
+struct ConfigState {
+    profiles: Vec<Profile>,
+    active_profile: Option<usize>,
+    workers: usize,
+    /// How tasks are ordered when estimating the run time
+    priority: Priority,
+    /// Name entered for the next profile to be saved
+    profile_name: String,
+    /// Kept so that they are saved along with the graph
+    triggers: Vec<Trigger>,
+    hooks: Hooks,
+    snapshots: Vec<Snapshot>,
+    /// Name entered for the next snapshot to be taken
+    snapshot_name: String,
+    /// Whether a thumbnail is saved next to each graph file
+    save_thumbnails: bool,
+    /// Whether the state of the task DAG is served to other programs, see
+    /// [`StateServer`](crate::state_server::StateServer)
+    serve_state: bool,
+    serve_port: u16,
+    /// Origin of the web page allowed to read the served state, such as
+    /// `http://localhost:3000`. Other web pages are refused when empty.
+    serve_origin: String,
+    /// Whether edits are recalculated on a worker thread, rather than while
+    /// the graph is being drawn
+    background: bool,
+}
+
+impl Default for ConfigState {
+    fn default() -> Self {
+        Self {
+            profiles: Vec::new(),
+            active_profile: None,
+            workers: 1,
+            priority: Priority::default(),
+            profile_name: String::new(),
+            triggers: Vec::new(),
+            hooks: Hooks::default(),
+            snapshots: Vec::new(),
+            snapshot_name: String::new(),
+            save_thumbnails: false,
+            serve_state: false,
+            serve_port: 8787,
+            serve_origin: String::new(),
+            background: cfg!(not(target_arch = "wasm32")),
+        }
+    }
+}
+
+/// The graph currently being displayed, either the root graph or a group
+/// nested within it
+fn current_snarl<'s>(
+    snarl: &'s mut Snarl<Box<dyn Node>>,
+    group_path: &mut Vec<NodeId>,
+) -> &'s mut Snarl<Box<dyn Node>> {
+    // Drop any part of the path that no longer refers to a group
+    let mut depth = 0;
+    let mut current: &Snarl<_> = snarl;
+    for id in group_path.iter() {
+        match current.get_node(*id).and_then(|node| node.subgraph()) {
+            Some(subgraph) => current = subgraph,
+            None => break,
+        }
+        depth += 1;
+    }
+    group_path.truncate(depth);
+
+    let mut current = snarl;
+    for id in group_path.iter() {
+        current = current[*id].subgraph_mut().unwrap();
+    }
+    current
+}
+
+/// The root graph along with the settings saved with it, leaving out cached
+/// outputs and snapshots
+fn graph_with_settings(
+    snarl: &Snarl<Box<dyn Node>>,
+    config: &ConfigState,
+    comments: &[Comment],
+    priorities: &BTreeMap<NodeId, i32>,
+    stages: &Stages,
+) -> SavedGraph {
+    let mut saved = SavedGraph::from_snarl(snarl);
+    saved.profiles = config.profiles.clone();
+    saved.triggers = config.triggers.clone();
+    saved.hooks = config.hooks.clone();
+    saved.comments = comments.to_vec();
+    saved.priorities = saved_priorities(snarl, priorities);
+    saved.stages = stages.to_saved(snarl);
+    saved
+}
+
+/// Priorities of the nodes still in the graph, as saved in a graph file
+fn saved_priorities(
+    snarl: &Snarl<Box<dyn Node>>,
+    priorities: &BTreeMap<NodeId, i32>,
+) -> BTreeMap<usize, i32> {
+    priorities
+        .iter()
+        .filter(|(id, priority)| **priority != 0 && snarl.get_node(**id).is_some())
+        .map(|(id, priority)| (id.0, *priority))
+        .collect()
+}
+
+/// Priorities read from a graph file, by node
+fn loaded_priorities(saved: &SavedGraph) -> BTreeMap<NodeId, i32> {
+    saved
+        .priorities
+        .iter()
+        .map(|(id, priority)| (NodeId(*id), *priority))
+        .collect()
+}
+
+/// Reads a graph file or package, a graph file giving a package without
+/// any data files
+fn read_graph(contents: &[u8]) -> Result<Package, LoadError> {
+    if package::is_package(contents) {
+        Package::from_bytes(contents)
+    } else {
+        Ok(Package {
+            graph: serde_json::from_slice(contents)?,
+            ..Default::default()
+        })
+    }
+}
+
+impl<'a> TreeBehavior<'a> {
+    /// Recalculates the root graph, unless evaluation has been left to the
+    /// user
+    fn evaluate_if_automatic(&mut self, start: Option<NodeId>) {
+        if !self.viewer.manual {
+            self.reports
+                .push(self.viewer.evaluate_root(self.snarl, start));
+        }
+    }
+
+    /// Controls for stepping through an evaluation of the root graph, pausing
+    /// before each node with a breakpoint
+    fn debug_ui(&mut self, ui: &mut egui::Ui) {
+        let breakpoints = &self.viewer.breakpoints;
+        let Some(evaluation) = self.debug.as_mut() else {
+            if ui
+                .button(i18n::tr("debug"))
+                .on_hover_text(i18n::tr("debug-hint"))
+                .clicked()
+            {
+                node_graph::start_run(self.snarl);
+                let mut evaluation = Evaluation::new(self.snarl, Default::default(), |_, _| true);
+                evaluation.run_until(self.snarl, |node| breakpoints.contains(&node));
+                *self.debug = Some(evaluation);
+            }
+            return;
+        };
+
+        let paused_at = evaluation.next(self.snarl);
+        ui.horizontal(|ui| {
+            if ui
+                .button(i18n::tr("debug-step"))
+                .on_hover_text(i18n::tr("debug-step-hint"))
+                .clicked()
+            {
+                evaluation.step(self.snarl);
+            }
+            if ui
+                .button(i18n::tr("debug-continue"))
+                .on_hover_text(i18n::tr("debug-continue-hint"))
+                .clicked()
+            {
+                evaluation.step(self.snarl);
+                evaluation.run_until(self.snarl, |node| breakpoints.contains(&node));
+            }
+            if ui.button(i18n::tr("debug-stop")).clicked() {
+                evaluation.cancel();
+            }
+        });
+        if let Some((node, paused)) =
+            paused_at.and_then(|node| Some((node, self.snarl.get_node(node)?)))
+        {
+            ui.label(i18n::tr_args(
+                "debug-paused",
+                &[
+                    ("node", &i18n::node_name(&paused.name())),
+                    ("id", &node.0),
+                    ("count", &evaluation.report().runs.len()),
+                ],
+            ));
+        }
+
+        if evaluation.next(self.snarl).is_none() {
+            if let Some(evaluation) = self.debug.take() {
+                self.reports.push(evaluation.finish());
+            }
+        }
+    }
+
+    /// Lists the problems found in the root graph. Clicking one selects,
+    /// inspects and moves the view to its node, leaving any open group.
+    fn problems_ui(&mut self, ui: &mut egui::Ui) {
+        if self.diagnostics.is_empty() {
+            ui.label(i18n::tr("problems-none"));
+            return;
+        }
+        for diagnostic in self.diagnostics {
+            let (icon, colour) = match diagnostic.severity {
+                Severity::Error => ("⛔", ui.visuals().error_fg_color),
+                Severity::Warning => ("⚠", ui.visuals().warn_fg_color),
+            };
+            let name = self
+                .snarl
+                .get_node(diagnostic.node)
+                .map_or_else(|| i18n::tr("node"), |node| i18n::node_name(&node.name()));
+            let response = ui
+                .horizontal(|ui| {
+                    ui.colored_label(colour, icon)
+                        .on_hover_text(diagnostic.severity.to_string());
+                    ui.add(
+                        egui::Label::new(format!(
+                            "{} {}: {}",
+                            name, diagnostic.node.0, diagnostic.message
+                        ))
+                        .sense(egui::Sense::click()),
+                    )
+                })
+                .inner
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text(i18n::tr("go-to-node-hint"));
+            if response.hovered() {
+                self.viewer.highlighted = Some(diagnostic.node);
+            }
+            if response.clicked() {
+                self.group_path.clear();
+                self.viewer.selected = [diagnostic.node].into();
+                self.viewer.inspected = Some(diagnostic.node);
+                self.viewer.navigation = Some(Navigation::JumpTo(diagnostic.node));
+            }
+        }
+    }
+
+    /// Lists how the graph differs from the graph it is being compared
+    /// with. The nodes listed can be clicked to go to them.
+    fn differences_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(comparison) = &*self.comparison else {
+            ui.label(i18n::tr("differences-hint"));
+            return;
+        };
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.label(i18n::tr_args(
+                "differences-compared-with",
+                &[("name", &comparison.name)],
+            ));
+            close = ui.small_button(i18n::tr("close")).clicked();
+        });
+        let diff = &comparison.diff;
+        if diff.is_empty() {
+            ui.label(i18n::tr("differences-none"));
+        }
+
+        // Rows for nodes in the graph being edited, which can be gone to
+        let mut row = |ui: &mut egui::Ui, id: usize, text: String| {
+            let response = ui
+                .add(egui::Label::new(text).sense(egui::Sense::click()))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text(i18n::tr("go-to-node-hint"));
+            let node = NodeId(id);
+            if response.hovered() {
+                self.viewer.highlighted = Some(node);
+            }
+            if response.clicked() {
+                self.group_path.clear();
+                self.viewer.selected = [node].into();
+                self.viewer.inspected = Some(node);
+                self.viewer.navigation = Some(Navigation::JumpTo(node));
+            }
+        };
+        if !diff.added.is_empty() {
+            ui.strong(i18n::tr("differences-added"));
+            for node in &diff.added {
+                row(ui, node.id, format!("{} {}", node.kind, node.id));
+            }
+        }
+        if !diff.changed.is_empty() {
+            ui.strong(i18n::tr("differences-changed"));
+            for change in &diff.changed {
+                row(ui, change.id, format!("{} {}", change.kind, change.id));
+                ui.indent(change.id, |ui| {
+                    for line in &change.changes {
+                        ui.weak(line);
+                    }
+                });
+            }
+        }
+        if !diff.removed.is_empty() {
+            ui.strong(i18n::tr("differences-removed"));
+            for node in &diff.removed {
+                ui.label(format!("{} {}", node.kind, node.id));
+            }
+        }
+        for (heading, wires) in [
+            ("differences-connected", &diff.connected),
+            ("differences-disconnected", &diff.disconnected),
+        ] {
+            if wires.is_empty() {
+                continue;
+            }
+            ui.strong(i18n::tr(heading));
+            for ((from, output), (to, input)) in wires {
+                ui.label(i18n::tr_args(
+                    "differences-wire",
+                    &[
+                        ("from", from),
+                        ("output", output),
+                        ("to", to),
+                        ("input", input),
+                    ],
+                ));
+            }
+        }
+        if close {
+            *self.comparison = None;
+        }
+    }
+
+    /// Lists the graph's snapshots, newest first, and takes new ones
+    fn history_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.config.snapshot_name)
+                    .hint_text(i18n::tr("history-snapshot-name"))
+                    .desired_width(120.),
+            );
+            let name = self.config.snapshot_name.trim().to_owned();
+            if ui
+                .add_enabled(
+                    !name.is_empty(),
+                    egui::Button::new(i18n::tr("history-take-snapshot")),
+                )
+                .on_hover_text(i18n::tr("history-take-snapshot-hint"))
+                .clicked()
+            {
+                let graph = graph_with_settings(
+                    self.snarl,
+                    self.config,
+                    &self.canvas.comments,
+                    &self.viewer.priorities,
+                    &self.viewer.stages,
+                );
+                self.config.snapshots.push(Snapshot::new(name, graph));
+                self.config.snapshot_name.clear();
+            }
+        });
+        if self.config.snapshots.is_empty() {
+            ui.label(i18n::tr("history-none"));
+            return;
+        }
+        ui.separator();
+        for (index, snapshot) in self.config.snapshots.iter().enumerate().rev() {
+            ui.horizontal(|ui| {
+                ui.strong(&snapshot.name);
+                ui.weak(snapshot::format_age(snapshot.taken_at()))
+                    .on_hover_text(format_timestamp(snapshot.taken_at()));
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .small_button(i18n::tr("history-restore"))
+                    .on_hover_text(i18n::tr("history-restore-hint"))
+                    .clicked()
+                {
+                    self.actions.push(DestructiveAction::RestoreSnapshot {
+                        index,
+                        name: snapshot.name.clone(),
+                    });
+                }
+                if ui
+                    .small_button(i18n::tr("history-compare"))
+                    .on_hover_text(i18n::tr("history-compare-hint"))
+                    .clicked()
+                {
+                    *self.comparison = Some(Comparison {
+                        name: format!("snapshot {:?}", snapshot.name),
+                        saved: snapshot.graph.clone(),
+                        diff: GraphDiff::default(),
+                    });
+                }
+                if ui.small_button(i18n::tr("history-delete")).clicked() {
+                    self.actions.push(DestructiveAction::DeleteSnapshot {
+                        index,
+                        name: snapshot.name.clone(),
+                    });
+                }
+            });
+        }
+    }
+
+    /// Stages of the root graph, which nodes are tagged with from their menu
+    fn stages_ui(&mut self, ui: &mut egui::Ui) {
+        ui.separator();
+        ui.label(i18n::tr("stages"))
+            .on_hover_text(i18n::tr("stages-hint"));
+        let stages = &mut self.viewer.stages;
+        let mut moved = None;
+        let mut removed = None;
+        for idx in 0..stages.names().len() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(idx > 0, egui::Button::new("⏶").small())
+                    .on_hover_text(i18n::tr("stages-run-earlier"))
+                    .clicked()
+                {
+                    moved = Some(idx);
+                }
+                if ui
+                    .small_button("✖")
+                    .on_hover_text(i18n::tr("remove"))
+                    .clicked()
+                {
+                    removed = Some(idx);
+                }
+                let mut name = stages.names()[idx].clone();
+                if ui
+                    .add(egui::TextEdit::singleline(&mut name).desired_width(100.))
+                    .changed()
+                {
+                    stages.rename(idx, name);
+                }
+                ui.weak(i18n::tr_args(
+                    "node-count",
+                    &[("count", &stages.members(idx).count())],
+                ));
+            });
+        }
+        if let Some(idx) = moved {
+            stages.move_up(idx);
+        }
+        if let Some(idx) = removed {
+            stages.remove(idx);
+        }
+        if ui.button(i18n::tr("stages-add")).clicked() {
+            stages.push(i18n::tr_args(
+                "stages-new-name",
+                &[("number", &(stages.names().len() + 1))],
+            ));
+        }
+
+        if stages.is_empty() {
+            return;
+        }
+        let graph = node_graph::as_petgraph(self.snarl);
+        for (from, to) in stages.conflicts(&graph) {
+            let name = |id| match self.snarl.get_node(id) {
+                Some(node) => node_graph::display_name(node.as_ref(), id),
+                None => i18n::tr_args("node-id", &[("id", &id.0)]),
+            };
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "⚠ {} in {} can never run, as it needs {} from the later stage {}",
+                    name(to),
+                    stages.name_of(to).unwrap_or_default(),
+                    name(from),
+                    stages.name_of(from).unwrap_or_default(),
+                ),
+            );
+        }
+    }
+
+    fn config_ui(&mut self, ui: &mut egui::Ui) {
+        let config = &mut *self.config;
+
+        ui.label(i18n::tr("config-profile"));
+        let selected_text = config
+            .active_profile
+            .map_or_else(|| i18n::tr("none"), |idx| config.profiles[idx].name.clone());
+        let mut selected = None;
+        egui::ComboBox::from_id_source("profile")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for (idx, profile) in config.profiles.iter().enumerate() {
+                    if ui
+                        .selectable_label(config.active_profile == Some(idx), &profile.name)
+                        .clicked()
+                    {
+                        selected = Some(idx);
+                    }
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut config.profile_name).desired_width(100.));
+            let name = config.profile_name.trim().to_owned();
+            if ui
+                .add_enabled(
+                    !name.is_empty(),
+                    egui::Button::new(i18n::tr("config-save-profile")),
+                )
+                .clicked()
+            {
+                let mode = if self.viewer.manual {
+                    EvaluationMode::Manual
+                } else {
+                    EvaluationMode::OnChange
+                };
+                let profile = Profile::capture(name, self.snarl, mode, config.workers);
+                match config
+                    .profiles
+                    .iter()
+                    .position(|existing| existing.name == profile.name)
+                {
+                    Some(idx) => {
+                        config.profiles[idx] = profile;
+                        config.active_profile = Some(idx);
+                    }
+                    None => {
+                        config.profiles.push(profile);
+                        config.active_profile = Some(config.profiles.len() - 1);
+                    }
+                }
+                config.profile_name.clear();
+            }
+        });
+        if let Some(idx) = config.active_profile {
+            if ui.button(i18n::tr("config-delete-profile")).clicked() {
+                config.profiles.remove(idx);
+                config.active_profile = None;
+            }
+        }
+
+        if let Some(idx) = selected {
+            let profile = &config.profiles[idx];
+            profile.apply(self.snarl);
+            // Parameters can change which pins nodes have
+            self.viewer.adjacency = None;
+            self.viewer.manual = profile.mode == EvaluationMode::Manual;
+            config.workers = profile.workers;
+            config.active_profile = Some(idx);
+            // Switching profiles always recalculates, even in manual mode
+            self.reports
+                .push(self.viewer.evaluate_root(self.snarl, None));
+        }
+
+        ui.separator();
+        ui.label(i18n::tr("config-evaluation"));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.viewer.manual, false, i18n::tr("config-on-change"));
+            ui.radio_value(&mut self.viewer.manual, true, i18n::tr("config-manual"));
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.add_enabled(
+            !self.viewer.manual,
+            egui::Checkbox::new(&mut config.background, i18n::tr("config-background")),
+        )
+        .on_hover_text(i18n::tr("config-background-hint"));
+        ui.horizontal(|ui| {
+            if self.viewer.manual && ui.button(i18n::tr("config-evaluate-now")).clicked() {
+                self.reports
+                    .push(self.viewer.evaluate_root(self.snarl, None));
+            }
+            if ui
+                .button(i18n::tr("config-reset-state"))
+                .on_hover_text(i18n::tr("menu-reset-state-hint"))
+                .clicked()
+            {
+                node_graph::reset_state(self.snarl);
+                self.evaluate_if_automatic(None);
+            }
+        });
+        ui.checkbox(
+            &mut self.viewer.show_changes,
+            i18n::tr("config-highlight-changes"),
+        )
+        .on_hover_text(i18n::tr("config-highlight-changes-hint"));
+        self.debug_ui(ui);
+        ui.horizontal(|ui| {
+            ui.label(i18n::tr("config-workers"));
+            ui.add(egui::DragValue::new(&mut self.config.workers).clamp_range(1..=64));
+        });
+        self.stages_ui(ui);
+
+        let sources = node_graph::nodes_with_role(self.snarl, NodeRole::Source);
+        if !sources.is_empty() {
+            ui.separator();
+            ui.label(i18n::tr("config-sources"))
+                .on_hover_text(i18n::tr("config-sources-hint"));
+        }
+        for id in sources {
+            ui.horizontal(|ui| {
+                let name = i18n::node_name(&self.snarl[id].name());
+                ui.label(format!("{} {}", name, id.0));
+                if ui.small_button(i18n::tr("config-trigger")).clicked() {
+                    self.reports
+                        .push(self.viewer.evaluate_root(self.snarl, Some(id)));
+                }
+            });
+        }
+
+        let groups = self
+            .snarl
+            .node_ids()
+            .filter_map(|(id, node)| {
+                let group = node.as_any().downcast_ref::<GroupNode>()?;
+                Some((id, group.enabled()))
+            })
+            .collect::<Vec<_>>();
+        if !groups.is_empty() {
+            ui.separator();
+            ui.label(i18n::tr("config-groups"));
+        }
+        for (id, mut enabled) in groups {
+            if ui
+                .checkbox(
+                    &mut enabled,
+                    i18n::tr_args("config-group", &[("id", &id.0)]),
+                )
+                .changed()
+            {
+                if let Some(group) = self.snarl[id].as_any_mut().downcast_mut::<GroupNode>() {
+                    group.set_enabled(enabled);
+                }
+                self.evaluate_if_automatic(Some(id));
+            }
+        }
+
+        ui.separator();
+        ui.label(i18n::tr("config-secrets"));
+        secrets::with_store(|store| {
+            match store.path() {
+                Some(path) => ui.small(path.display().to_string()),
+                None => ui.small(i18n::tr("config-no-secrets-file")),
+            };
+            for name in store.names() {
+                ui.label(format!("• {}", name));
+            }
+        });
+        if ui.button(i18n::tr("config-reload-secrets")).clicked() {
+            if let Err(err) = secrets::reload() {
+                self.notifications
+                    .error(format!("Failed to reload secrets: {}", err));
+            }
+            self.evaluate_if_automatic(None);
+        }
+
+        ui.separator();
+        ui.label(i18n::tr("config-plugins"))
+            .on_hover_text(i18n::tr("config-plugins-hint"));
+        plugins::with_library(|library| {
+            match library.dir() {
+                Some(dir) => ui.small(dir.display().to_string()),
+                None => ui.small(i18n::tr("config-no-plugins-directory")),
+            };
+            for pack in library.packs() {
+                let version = pack.version.as_deref().unwrap_or_default();
+                ui.label(format!("• {} {}", pack.name, version))
+                    .on_hover_text(i18n::tr_args(
+                        "config-node-types",
+                        &[("count", &pack.nodes.len())],
+                    ));
+            }
+            for (path, err) in library.errors() {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("⚠ {}: {}", path.display(), err),
+                );
+            }
+        });
+        if ui.button(i18n::tr("config-reload-plugins")).clicked() {
+            if let Err(err) = plugins::reload() {
+                self.notifications
+                    .error(format!("Failed to reload plugins: {}", err));
+            }
+            // Plugin nodes can have different pins after reloading
+            self.viewer.adjacency = None;
+            self.evaluate_if_automatic(None);
+        }
+
+        ui.separator();
+        ui.label(i18n::tr("config-hooks"))
+            .on_hover_text(i18n::tr("config-hooks-hint"));
+        for (name, hook) in [
+            ("config-before-run", &mut self.config.hooks.before_run),
+            ("config-after-run", &mut self.config.hooks.after_run),
+        ] {
+            let mut script = hook.clone().unwrap_or_default();
+            ui.small(i18n::tr(name));
+            if ui
+                .add(
+                    egui::TextEdit::multiline(&mut script)
+                        .code_editor()
+                        .desired_rows(3),
+                )
+                .changed()
+            {
+                *hook = (!script.is_empty()).then_some(script);
+            }
+        }
+
+        ui.separator();
+        ui.label(i18n::tr("config-templates"))
+            .on_hover_text(i18n::tr("config-templates-hint"));
+        let templates = &mut self.viewer.templates.templates;
+        if templates.is_empty() {
+            ui.weak(i18n::tr("config-no-templates"));
+        }
+        let mut moved = None;
+        let mut removed = None;
+        let count = templates.len();
+        for (idx, template) in templates.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(idx > 0, egui::Button::new("⏶").small())
+                    .on_hover_text(i18n::tr("config-move-up"))
+                    .clicked()
+                {
+                    moved = Some((idx, idx - 1));
+                }
+                if ui
+                    .add_enabled(idx + 1 < count, egui::Button::new("⏷").small())
+                    .on_hover_text(i18n::tr("config-move-down"))
+                    .clicked()
+                {
+                    moved = Some((idx, idx + 1));
+                }
+                if ui
+                    .small_button("✖")
+                    .on_hover_text(i18n::tr("remove"))
+                    .clicked()
+                {
+                    removed = Some(idx);
+                }
+                ui.label(&template.name).on_hover_text(i18n::tr_args(
+                    "node-count",
+                    &[("count", &template.nodes.len())],
+                ));
+            });
+        }
+        if let Some((from, to)) = moved {
+            self.viewer.templates.move_template(from, to);
+            self.viewer.templates_modified = true;
+        }
+        if let Some(idx) = removed {
+            self.viewer.templates.templates.remove(idx);
+            self.viewer.templates_modified = true;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.separator();
+            ui.label(i18n::tr("config-files"));
+            ui.checkbox(
+                &mut self.config.save_thumbnails,
+                i18n::tr("config-save-thumbnails"),
+            )
+            .on_hover_text(i18n::tr("config-save-thumbnails-hint"));
+
+            ui.separator();
+            ui.label(i18n::tr("config-monitoring"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.config.serve_state, i18n::tr("config-serve-state"));
+                ui.add(egui::DragValue::new(&mut self.config.serve_port).clamp_range(1024..=65535));
+            })
+            .response
+            .on_hover_text(i18n::tr_args(
+                "config-serve-state-hint",
+                &[("port", &self.config.serve_port)],
+            ));
+            ui.horizontal(|ui| {
+                ui.label(i18n::tr("config-allowed-origin"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.config.serve_origin)
+                        .hint_text(i18n::tr("config-allowed-origin-none"))
+                        .desired_width(160.),
+                );
+            })
+            .response
+            .on_hover_text(i18n::tr("config-allowed-origin-hint"));
+        }
+
+        ui.separator();
+        language_ui(ui);
+
+        if let Some(settings) = &mut self.settings {
+            ui.separator();
+            ui.label(i18n::tr("config-appearance"));
+            settings.ui(ui);
+        }
+    }
+}
+
+/// Picks the language the application is shown in, which starts as that of
+/// `GRAPH_GUI_LANG` or `LANG`
+fn language_ui(ui: &mut egui::Ui) {
+    let current = i18n::language();
+    ui.horizontal(|ui| {
+        ui.label(i18n::tr("config-language"));
+        egui::ComboBox::from_id_source("language")
+            .selected_text(current.name)
+            .show_ui(ui, |ui| {
+                for language in i18n::LANGUAGES {
+                    if ui
+                        .selectable_label(language.code == current.code, language.name)
+                        .clicked()
+                    {
+                        i18n::set_language(language.code);
+                    }
+                }
+            });
+    });
+}
+
+impl<'a> egui_tiles::Behavior<Pane> for TreeBehavior<'a> {
+    fn pane_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        _tile_id: egui_tiles::TileId,
+        pane: &mut Pane,
+    ) -> egui_tiles::UiResponse {
+        // Title bar
+        let (response, painter) = ui.allocate_painter(
+            egui::Vec2::new(ui.available_width(), 20.),
+            egui::Sense::click_and_drag(),
+        );
+        painter.rect_filled(painter.clip_rect(), Rounding::same(3.), Color32::LIGHT_GRAY);
+
+        // Title
+        let label_resp = ui
+            .child_ui(
+                painter.clip_rect(),
+                Layout::left_to_right(egui::Align::Center),
+            )
+            .add(Label::new(self.tab_title_for_pane(pane)));
+
+        match pane {
+            Pane::Config => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.config_ui(ui));
+                });
+            }
+            Pane::Nodes => {
+                current_snarl(self.snarl, self.group_path);
+                if !self.group_path.is_empty() {
+                    ui.horizontal(|ui| {
+                        if ui.button(i18n::tr("node-leave-group")).clicked() {
+                            self.group_path.pop();
+                            self.viewer.selected.clear();
+                            self.viewer.inspected = None;
+                        }
+                        let path = self
+                            .group_path
+                            .iter()
+                            .map(|id| id.0.to_string())
+                            .collect::<Vec<_>>()
+                            .join(" / ");
+                        ui.label(i18n::tr_args("node-editing-group", &[("path", &path)]));
+                    });
+                }
+
+                // Failures are only tracked for the root graph
+                self.viewer.failures = if self.group_path.is_empty() {
+                    self.stats.failures.clone()
+                } else {
+                    Default::default()
+                };
+                self.viewer.background = self.config.background;
+                self.viewer.changes = match &self.stats.last_report {
+                    Some(report) if self.viewer.show_changes => report.changes.clone(),
+                    _ => Default::default(),
+                };
+                self.viewer.compared = self
+                    .comparison
+                    .iter()
+                    .flat_map(|comparison| comparison.diff.node_diffs())
+                    .map(|(id, diff)| (NodeId(id), diff))
+                    .collect();
+
+                let id = self.canvas.view_id(
+                    ui,
+                    self.id.with("snarl").with(&*self.group_path),
+                    self.style,
+                );
+                let canvas = ui.available_rect_before_wrap();
+                let at_root = self.group_path.is_empty();
+                self.viewer.at_root = at_root;
+                let snarl = current_snarl(self.snarl, self.group_path);
+                self.canvas.before_nodes(ui, snarl, at_root);
+                self.viewer.before_canvas(ui, canvas, snarl);
+                let scope = profiler::scope("render graph");
+                snarl.show(self.viewer, self.style, id, ui);
+                drop(scope);
+                self.viewer.after_canvas(ui, canvas, snarl);
+                self.viewer.show_wire_values(ui, canvas, snarl, self.style);
+
+                let nodes = self
+                    .canvas
+                    .node_areas(snarl, |id| self.viewer.node_rect(id));
+                if let Some(navigation) = self.viewer.navigation.take() {
+                    self.canvas
+                        .navigate(navigation, &nodes, &self.viewer.selected, canvas);
+                }
+                if self.canvas.minimap {
+                    let visible = egui::Rect::from_min_max(
+                        self.canvas.screen_to_graph(canvas.min, canvas),
+                        self.canvas.screen_to_graph(canvas.max, canvas),
+                    );
+                    let nodes = nodes
+                        .iter()
+                        .map(|(id, area)| (*area, self.viewer.selected.contains(id)))
+                        .collect::<Vec<_>>();
+                    if let Some(pos) = minimap::show(ui, canvas, &nodes, visible) {
+                        self.canvas.centre_on(pos, canvas);
+                    }
+                }
+
+                match self.viewer.new_comment.take() {
+                    Some(NewComment::At(pos)) => self.canvas.add_comment(pos),
+                    Some(NewComment::AroundSelected) => {
+                        if let Some(area) = self.viewer.selection_rect() {
+                            self.canvas.add_comment_around(area, canvas);
+                        }
+                    }
+                    None => {}
+                }
+                self.canvas.edit_window(ui.ctx());
+
+                let quick_add =
+                    egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::Space);
+                if ui.rect_contains_pointer(canvas)
+                    && ui.input_mut(|i| i.consume_shortcut(&quick_add))
+                {
+                    if let Some(pointer) = ui.ctx().pointer_hover_pos() {
+                        let pos = self.canvas.screen_to_graph(pointer, canvas);
+                        self.viewer.palette = Some(NodePalette::new(pos, pointer));
+                    }
+                }
+                // Opened with Enter when editing from the keyboard
+                if let Some(at) = self.viewer.palette_at.take() {
+                    let pos = self.canvas.screen_to_graph(at, canvas);
+                    self.viewer.palette = Some(NodePalette::new(pos, at));
+                }
+                if let Some(palette) = &mut self.viewer.palette {
+                    let mut open = true;
+                    let pos = palette.pos;
+                    let snarl = current_snarl(self.snarl, self.group_path);
+                    match palette.show(ui.ctx(), &mut open, &self.viewer.templates) {
+                        Some(Picked::Node(kind)) => {
+                            let node = snarl.insert_node(pos, (kind.create)());
+                            self.viewer.focus_added(node);
+                        }
+                        Some(Picked::Template(idx)) => self.viewer.insert_template(idx, snarl, pos),
+                        None => {}
+                    }
+                    self.viewer.refresh_added_nodes(snarl);
+                    if !open {
+                        self.viewer.palette = None;
+                    }
+                }
+
+                if let Some(group) = self.viewer.open_group.take() {
+                    self.group_path.push(group);
+                    self.viewer.selected.clear();
+                    self.viewer.inspected = None;
+                }
+                self.actions.extend(self.viewer.pending.take());
+                let reports = std::mem::take(&mut self.viewer.reports);
+                if self.group_path.is_empty() {
+                    self.reports.extend(reports);
+                } else if std::mem::take(&mut self.viewer.modified) {
+                    // Changes inside a group need to be propagated through the
+                    // enclosing graphs
+                    self.evaluate_if_automatic(Some(self.group_path[0]));
+                }
+                self.viewer.modified = false;
+            }
+            Pane::Statistics => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    if let Some(report) = &self.stats.last_report {
+                        let sinks = node_graph::nodes_with_role(self.snarl, NodeRole::Sink).len();
+                        ui.label(i18n::tr_args(
+                            "statistics-last-run",
+                            &[
+                                ("produced", &report.sinks_produced(self.snarl)),
+                                ("sinks", &sinks),
+                                ("updated", &report.runs.len()),
+                            ],
+                        ));
+                    }
+                    let cache = self.viewer.cache.stats();
+                    ui.horizontal(|ui| {
+                        ui.label(i18n::tr_args(
+                            "statistics-cache",
+                            &[
+                                ("entries", &cache.entries),
+                                ("hits", &cache.hits),
+                                ("misses", &cache.misses),
+                            ],
+                        ))
+                        .on_hover_text(i18n::tr("statistics-cache-hint"));
+                        if ui.button(i18n::tr("statistics-clear-cache")).clicked() {
+                            self.viewer.cache.clear();
+                        }
+                    });
+                    if ui
+                        .button(i18n::tr("statistics-calculate-task-dag"))
+                        .clicked()
+                    {
+                        let graph = node_graph::as_petgraph(self.snarl);
+                        *self.task_execution = Some(
+                            TaskDag::new(&graph)
+                                .with_priorities(self.viewer.task_priorities())
+                                .with_stages(&self.viewer.stages),
+                        );
+                        *self.processes = ProcessExecutor::default();
+                    }
+
+                    if let Some(task_dag) = self.task_execution {
+                        // Estimate from how long each node took when last run
+                        let durations = self.stats.durations(self.snarl);
+                        let graph = node_graph::as_petgraph(self.snarl);
+                        let dag = TaskDag::new(&graph)
+                            .with_priorities(self.viewer.task_priorities())
+                            .with_stages(&self.viewer.stages);
+                        let estimate = Scheduler::new(dag, self.config.workers)
+                            .priority(self.config.priority)
+                            .run(&durations);
+                        ui.horizontal(|ui| {
+                            ui.label(i18n::tr("statistics-priority"));
+                            egui::ComboBox::from_id_source("priority")
+                                .selected_text(self.config.priority.to_string())
+                                .show_ui(ui, |ui| {
+                                    for priority in Priority::ALL {
+                                        ui.selectable_value(
+                                            &mut self.config.priority,
+                                            priority,
+                                            priority.to_string(),
+                                        );
+                                    }
+                                });
+                        });
+                        ui.label(i18n::tr_args(
+                            "statistics-estimate",
+                            &[
+                                ("workers", &self.config.workers),
+                                ("time", &format!("{:?}", estimate.makespan)),
+                            ],
+                        ));
+                        ui.collapsing(i18n::tr("pane-timeline"), |ui| {
+                            let mut gantt = String::new();
+                            let _ = estimate.write_gantt(&mut gantt, 40);
+                            ui.monospace(gantt);
+                        });
+
+                        let critical = task_dag.critical_path(&durations);
+                        let highlight = ui.visuals().warn_fg_color;
+                        ui.label(i18n::tr_args(
+                            "statistics-critical-path",
+                            &[("time", &format!("{:?}", critical.makespan))],
+                        ));
+                        ui.colored_label(
+                            highlight,
+                            critical
+                                .tasks
+                                .iter()
+                                .map(|id| {
+                                    match self.snarl.get_node(*id).and_then(|node| node.label()) {
+                                        Some(label) => label.to_owned(),
+                                        None => id.0.to_string(),
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                                .join(" → "),
+                        );
+                        let next = task_dag.ready_tasks_by(self.config.priority, &durations);
+                        if !next.is_empty() {
+                            let tasks = next
+                                .iter()
+                                .map(|id| id.0.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            ui.label(i18n::tr_args("statistics-ready", &[("tasks", &tasks)]));
+                        }
+
+                        let ready_tasks = task_dag.ready_tasks().collect::<HashSet<_>>();
+                        let blocked_tasks = task_dag.blocked_tasks().collect::<HashSet<_>>();
+                        for (id, node) in self.snarl.node_ids() {
+                            ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    let label = match node.label() {
+                                        Some(label) => label.to_owned(),
+                                        None => {
+                                            i18n::tr_args("statistics-task-id", &[("id", &id.0)])
+                                        }
+                                    };
+                                    if critical.tasks.contains(&id) {
+                                        ui.colored_label(highlight, label)
+                                            .on_hover_text(i18n::tr("statistics-critical"));
+                                    } else {
+                                        ui.label(label);
+                                    }
+                                    ui.separator();
+                                    match self.stats.get(id) {
+                                        Some(stats) => ui.label(i18n::tr_args(
+                                            "statistics-runs",
+                                            &[
+                                                ("count", &stats.count),
+                                                ("elapsed", &format_elapsed(stats.last_run)),
+                                            ],
+                                        )),
+                                        None => ui.label(i18n::tr("statistics-no-runs")),
+                                    };
+                                    ui.separator();
+                                    if self.processes.is_running(id) {
+                                        ui.spinner();
+                                        ui.label(i18n::tr("statistics-running"));
+                                    } else if self.processes.has_failed(id) {
+                                        ui.colored_label(
+                                            ui.visuals().error_fg_color,
+                                            i18n::tr("statistics-failed"),
+                                        )
+                                        .on_hover_text(node.error().unwrap_or_default());
+                                    } else if ready_tasks.contains(&id) {
+                                        let unblocked = task_dag
+                                            .unblocked_by(id)
+                                            .iter()
+                                            .map(|task| task.0.to_string())
+                                            .collect::<Vec<_>>();
+                                        let button = ui.button(i18n::tr("statistics-complete"));
+                                        let button = if unblocked.is_empty() {
+                                            button
+                                        } else {
+                                            button.on_hover_text(i18n::tr_args(
+                                                "statistics-makes-ready",
+                                                &[("tasks", &unblocked.join(", "))],
+                                            ))
+                                        };
+                                        if button.clicked() {
+                                            // TODO: Do something with the newly ready tasks
+                                            if let Err(err) = task_dag.complete_task(id) {
+                                                self.notifications.error(err.to_string());
+                                            }
+                                        }
+                                    } else if blocked_tasks.contains(&id) {
+                                        let waiting_on = task_dag
+                                            .pending_dependencies(id)
+                                            .unwrap_or_default()
+                                            .iter()
+                                            .map(|dep| dep.0.to_string())
+                                            .collect::<Vec<_>>();
+                                        ui.label(i18n::tr("statistics-blocked")).on_hover_text(
+                                            i18n::tr_args(
+                                                "statistics-waiting-on",
+                                                &[("tasks", &waiting_on.join(", "))],
+                                            ),
+                                        );
+                                    } else {
+                                        ui.label(i18n::tr("statistics-completed"));
+                                    }
+                                })
+                            });
+                        }
+                    }
+
+                    ui.collapsing(i18n::tr("statistics-run-report"), |ui| {
+                        let mut report = String::new();
+                        let _ = self.stats.write_report(&mut report);
+                        ui.monospace(report);
+                    });
+                });
+            }
+            Pane::Timeline => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    if let Some((name, report)) = self.timeline.imported.take() {
+                        let mut close = false;
+                        ui.horizontal(|ui| {
+                            ui.label(i18n::tr_args("timeline-trace-from", &[("name", &name)]));
+                            close = ui.small_button(i18n::tr("close")).clicked();
+                        });
+                        // Node ids in a trace may not match the current graph
+                        self.timeline.ui(ui, &report, self.snarl);
+                        if !close {
+                            self.timeline.imported = Some((name, report));
+                        }
+                        return;
+                    }
+                    let Some(report) = &self.stats.last_report else {
+                        ui.label(i18n::tr("timeline-no-runs"));
+                        return;
+                    };
+                    // Runs are only recorded for the root graph
+                    if let Some(node) = self
+                        .timeline
+                        .ui(ui, report, self.snarl)
+                        .filter(|_| self.group_path.is_empty())
+                    {
+                        self.viewer.highlighted = Some(node);
+                    }
+                });
+            }
+            Pane::Sweep => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    // Sweeps are run on the root graph, whose sinks are its
+                    // results
+                    self.sweep.ui(ui, self.snarl, self.notifications);
+                });
+            }
+            Pane::Simulation => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        self.simulation.ui(
+                            ui,
+                            self.snarl,
+                            self.stats,
+                            self.viewer.task_priorities(),
+                            &self.viewer.stages,
+                        );
+                    });
+                });
+            }
+            Pane::Log => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    // Messages are only recorded for runs of the root graph
+                    let response = self.log.ui(ui, &self.stats.log, self.snarl);
+                    if let Some(node) = response.hovered.filter(|_| self.group_path.is_empty()) {
+                        self.viewer.highlighted = Some(node);
+                    }
+                    if let Some(node) = response.clicked {
+                        self.group_path.clear();
+                        self.viewer.selected = [node].into();
+                        self.viewer.inspected = Some(node);
+                        self.viewer.navigation = Some(Navigation::JumpTo(node));
+                    }
+                });
+            }
+            Pane::Problems => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.problems_ui(ui));
+                });
+            }
+            Pane::Differences => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.differences_ui(ui));
+                });
+            }
+            Pane::History => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.history_ui(ui));
+                });
+            }
+            Pane::Inspector => {
+                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
+                    let inspected = self.viewer.inspected;
+                    let at_root = self.group_path.is_empty();
+                    let snarl = current_snarl(self.snarl, self.group_path);
+                    let Some(id) = inspected.filter(|id| snarl.get_node(*id).is_some()) else {
+                        ui.label(i18n::tr("inspector-hint"));
+                        return;
+                    };
+                    let node = &snarl[id];
+                    egui::Grid::new("inspector").num_columns(2).show(ui, |ui| {
+                        ui.label(i18n::tr("node"));
+                        ui.label(format!("{} (ID: {})", i18n::node_name(&node.name()), id.0));
+                        ui.end_row();
+                        if let Some(label) = node.label() {
+                            ui.label(i18n::tr("inspector-label"));
+                            ui.label(label);
+                            ui.end_row();
+                        }
+                        ui.label(i18n::tr("inspector-inputs"));
+                        ui.label(format!("{:?}", node.inputs()));
+                        ui.end_row();
+                        ui.label(i18n::tr("inspector-outputs"));
+                        ui.label(format!("{:?}", node.outputs()));
+                        ui.end_row();
+                        ui.label(i18n::tr("inspector-role"));
+                        ui.label(node.role().to_string());
+                        ui.end_row();
+                        ui.label(i18n::tr("inspector-values"));
+                        ui.vertical(|ui| {
+                            for idx in 0..node.outputs().len() {
+                                match node.output_value(idx) {
+                                    Some(value) => node_graph::show_value(&value, ui),
+                                    None => {
+                                        ui.weak(i18n::tr("none"));
+                                    }
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        if let Some(message) = self.stats.failures.get(&id).filter(|_| at_root) {
+                            ui.label(i18n::tr("inspector-error"));
+                            ui.colored_label(ui.visuals().error_fg_color, message);
+                            ui.end_row();
+                        }
+
+                        let stats = if at_root { self.stats.get(id) } else { None };
+                        ui.label(i18n::tr("inspector-executions"));
+                        ui.label(stats.map_or(0, |stats| stats.count).to_string());
+                        ui.end_row();
+                        if let Some(stats) = stats {
+                            ui.label(i18n::tr("inspector-last-run"));
+                            ui.label(format!(
+                                "{} ({})",
+                                format_elapsed(stats.last_run),
+                                format_timestamp(stats.last_run)
+                            ));
+                            ui.end_row();
+                            ui.label(i18n::tr("inspector-last-duration"));
+                            ui.label(format!("{:?}", stats.last_duration));
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+        }
+
+        // Allow dragging from the title bar
+        if response
+            .union(label_resp)
+            .on_hover_cursor(egui::CursorIcon::Grab)
+            .dragged_by(egui::PointerButton::Primary)
+        {
+            egui_tiles::UiResponse::DragStarted
+        } else {
+            egui_tiles::UiResponse::None
+        }
+    }
+
+    fn tab_title_for_pane(&mut self, pane: &Pane) -> egui::WidgetText {
+        match pane {
+            Pane::Config => i18n::tr("pane-config").into(),
+            Pane::Nodes => i18n::tr("pane-nodes").into(),
+            Pane::Statistics => i18n::tr("pane-statistics").into(),
+            Pane::Inspector => i18n::tr("pane-inspector").into(),
+            Pane::Timeline => i18n::tr("pane-timeline").into(),
+            Pane::Sweep => i18n::tr("pane-sweep").into(),
+            Pane::Simulation => i18n::tr("pane-simulation").into(),
+            Pane::Log => i18n::tr("pane-log").into(),
+            Pane::Problems if self.diagnostics.is_empty() => i18n::tr("pane-problems").into(),
+            Pane::Problems => {
+                i18n::tr_args("pane-problems-count", &[("count", &self.diagnostics.len())]).into()
+            }
+            Pane::Differences => i18n::tr("pane-differences").into(),
+            Pane::History => i18n::tr("pane-history").into(),
+        }
+    }
+}
+
+/// Everything needed to display and edit a graph with [`GraphEditorWidget`]
+pub struct EditorState {
+    tree: egui_tiles::Tree<Pane>,
+    snarl: Snarl<Box<dyn Node>>,
+    viewer: DemoViewer,
+    group_path: Vec<NodeId>,
+    style: SnarlStyle,
+    task_execution: Option<TaskDag>,
+    /// Processes of the tasks of `task_execution`
+    processes: ProcessExecutor,
+    stats: ExecutionStats,
+    /// Action waiting for the user to confirm it
+    pending: Option<DestructiveAction>,
+    history: UndoHistory,
+    config: ConfigState,
+    opener: FileOpener,
+    trace_opener: FileOpener,
+    checkpoint_opener: FileOpener,
+    compare_opener: FileOpener,
+    comparison: Option<Comparison>,
+    /// Data files of the package the graph was imported from, kept so that
+    /// they are included when the graph is packaged again. Files read by
+    /// nodes aren't kept, as they are gathered from the nodes instead.
+    package_data: BTreeMap<String, Vec<u8>>,
+    canvas: Canvas,
+    timeline: Timeline,
+    sweep: SweepPanel,
+    simulation: SimulationPanel,
+    log: LogConsole,
+    image_options: ImageOptions,
+    /// Problems found in the root graph, checked every frame so that they
+    /// follow each edit
+    diagnostics: Vec<Diagnostic>,
+    debug: Option<Evaluation>,
+    /// When nodes that need continuous updates were last updated
+    last_continuous: Option<web_time::Instant>,
+    /// Graph file the graph was last opened from or saved to, if any
+    file_name: Option<String>,
+    /// Graph files opened or saved this session, most recent first
+    recent: Vec<PathBuf>,
+    /// Thumbnails of the recent graph files, loaded when first shown. `None`
+    /// if a file has no thumbnail.
+    thumbnails: HashMap<PathBuf, Option<egui::TextureHandle>>,
+    notifications: Notifications,
+    /// Watches the files read by nodes, started when the editor is first
+    /// shown
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<graph_core::file_nodes::FileWatcher>,
+    #[cfg(not(target_arch = "wasm32"))]
+    server: Option<crate::state_server::StateServer>,
+    /// Recalculates edits to the root graph on a worker thread
+    #[cfg(not(target_arch = "wasm32"))]
+    background: graph_core::background::BackgroundEvaluation,
+    /// File the user's templates are kept in, if there is one
+    templates_path: Option<PathBuf>,
+}
+
+impl EditorState {
+    pub fn snarl(&self) -> &Snarl<Box<dyn Node>> {
+        &self.snarl
+    }
+
+    pub fn snarl_mut(&mut self) -> &mut Snarl<Box<dyn Node>> {
+        &mut self.snarl
+    }
+
+    pub fn stats(&self) -> &ExecutionStats {
+        &self.stats
+    }
+
+    /// Clears the state kept by nodes between evaluations, such as running
+    /// totals, then recalculates the graph unless evaluation has been left
+    /// to the user
+    pub fn reset_state(&mut self) {
+        node_graph::reset_state(&mut self.snarl);
+        if !self.viewer.manual {
+            self.evaluate();
+        }
+    }
+
+    /// Messages shown over the editor, for reporting problems outside of
+    /// the editor's own actions
+    pub fn notifications(&mut self) -> &mut Notifications {
+        &mut self.notifications
+    }
+
+    /// Problems found in the graph when it was last checked
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Recalculates the whole graph, checking it for problems first
+    pub fn evaluate(&mut self) -> &RunReport {
+        self.diagnostics = validation::validate(&self.snarl);
+        self.stats
+            .record(self.viewer.evaluate_root(&mut self.snarl, None));
+        self.stats.last_report.as_ref().unwrap()
+    }
+
+    pub fn history(&self) -> &UndoHistory {
+        &self.history
+    }
+
+    /// Name of the graph file the graph was last opened from or saved to,
+    /// or "Untitled" if there isn't one
+    pub fn title(&self) -> String {
+        self.file_name.as_deref().map_or_else(
+            || "Untitled".to_owned(),
+            |name| {
+                Path::new(name).file_name().map_or_else(
+                    || name.to_owned(),
+                    |name| name.to_string_lossy().into_owned(),
+                )
+            },
+        )
+    }
+
+    /// Whether the graph is a new one that hasn't been added to or saved
+    pub fn is_blank(&self) -> bool {
+        self.file_name.is_none() && self.snarl.nodes().next().is_none()
+    }
+
+    /// Replaces the current graph with a file picked or dropped by the user,
+    /// telling them if it couldn't be opened
+    pub fn open_file(&mut self, file: PickedFile) {
+        if let Err(err) = self.load_bytes(&file.name, &file.contents) {
+            self.notifications
+                .error(format!("Failed to open {}: {}", file.name, err));
+            return;
+        }
+        // Files picked natively are named by their path
+        #[cfg(not(target_arch = "wasm32"))]
+        self.remember(Path::new(&file.name));
+    }
+
+    /// Saves the root graph to a graph file
+    pub fn save(&mut self, path: &Path) -> std::io::Result<()> {
+        self.remember(path);
+        self.file_name = Some(path.display().to_string());
+        self.apply(DestructiveAction::WriteFile {
+            path: path.to_owned(),
+            contents: self.graph_file_contents(),
+            thumbnail: self.thumbnail(),
+        })
+    }
+
+    /// Replaces the current graph with one loaded from a graph file
+    pub fn load(&mut self, path: &Path) -> Result<(), LoadError> {
+        let contents = std::fs::read(path)?;
+        self.load_bytes(&path.display().to_string(), &contents)?;
+        self.remember(path);
+        Ok(())
+    }
+
+    /// Graph files opened or saved this session, most recent first
+    pub fn recent(&self) -> &[PathBuf] {
+        &self.recent
+    }
+
+    /// Moves a graph file to the top of the recent files
+    fn remember(&mut self, path: &Path) {
+        self.recent.retain(|recent| recent != path);
+        self.recent.insert(0, path.to_owned());
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// A picture of the root graph to save alongside it, if thumbnails are
+    /// turned on
+    fn thumbnail(&self) -> Option<Vec<u8>> {
+        self.config
+            .save_thumbnails
+            .then(|| thumbnail::render(&self.snarl, &self.canvas.comments, &self.viewer.colours))
+    }
+
+    /// Replaces the current graph with the contents of a graph file or
+    /// package. The files read by the package's nodes are extracted beside
+    /// it, see [`package::data_dir`].
+    pub fn load_bytes(&mut self, name: &str, contents: &[u8]) -> Result<(), LoadError> {
+        let mut package = read_graph(contents)?;
+        let snapshots = std::mem::take(&mut package.graph.snapshots);
+        self.replace_graph(format!("Open {}", name), std::mem::take(&mut package.graph))?;
+        self.config.snapshots = snapshots;
+        let data_dir = package::data_dir(Path::new(name));
+        match package.extract_files(&data_dir, &mut self.snarl) {
+            Ok(()) if !package.files.is_empty() && !self.viewer.manual => {
+                self.evaluate();
+            }
+            Ok(()) => {}
+            Err(err) => self.notifications.error(format!(
+                "Failed to extract the data files of {} to {}: {}",
+                name,
+                data_dir.display(),
+                err
+            )),
+        }
+        self.package_data = package.data;
+        self.file_name = Some(name.to_owned());
+        Ok(())
+    }
+
+    /// Replaces the current graph and its settings, which can be undone
+    fn replace_graph(&mut self, description: String, saved: SavedGraph) -> Result<(), LoadError> {
+        let snarl = saved.to_snarl()?;
+        self.history.push(description, self.undo_snapshot());
+        self.snarl = snarl;
+        // Read before the fields below are moved out of `saved`
+        let priorities = loaded_priorities(&saved);
+        let stages = Stages::from_saved(&saved.stages);
+        self.config.profiles = saved.profiles;
+        self.config.triggers = saved.triggers;
+        self.config.hooks = saved.hooks;
+        self.config.active_profile = None;
+        self.canvas.comments = saved.comments;
+        self.reset();
+        // Set after the reset, which starts the viewer afresh
+        self.viewer.priorities = priorities;
+        self.viewer.stages = stages;
+        Ok(())
+    }
+
+    /// Writes the graph to a file in another tool's format, along with how
+    /// its nodes fared in the last evaluation
+    pub fn export_graph(&mut self, path: &Path, format: &dyn ExportFormat) -> std::io::Result<()> {
+        let contents = self.export_contents(format);
+        self.apply(DestructiveAction::WriteFile {
+            path: path.to_owned(),
+            contents,
+            thumbnail: None,
+        })
+    }
+
+    /// Bundles the graph and its data files into a single archive
+    pub fn export_package(&mut self, path: &Path) -> std::io::Result<()> {
+        let contents = self.package_contents()?;
+        self.apply(DestructiveAction::WriteFile {
+            path: path.to_owned(),
+            contents,
+            thumbnail: None,
+        })
+    }
+
+    fn saved_graph(&self) -> SavedGraph {
+        let mut saved = graph_with_settings(
+            &self.snarl,
+            &self.config,
+            &self.canvas.comments,
+            &self.viewer.priorities,
+            &self.viewer.stages,
+        );
+        saved.snapshots = self.config.snapshots.clone();
+        saved.cache_outputs(&self.snarl);
+        saved
+    }
+
+    /// The graph, its comments and the priorities and stages of its nodes,
+    /// for undoing changes to them
+    fn undo_snapshot(&self) -> UndoEntry {
+        let mut saved = SavedGraph::from_snarl(&self.snarl);
+        saved.comments = self.canvas.comments.clone();
+        saved.priorities = saved_priorities(&self.snarl, &self.viewer.priorities);
+        saved.stages = self.viewer.stages.to_saved(&self.snarl);
+        UndoEntry::Graph(saved)
+    }
+
+    fn graph_file_contents(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(&self.saved_graph()).expect("graph could not be serialised")
+    }
+
+    fn package_contents(&self) -> std::io::Result<Vec<u8>> {
+        Package::gather(self.saved_graph(), &self.snarl, self.package_data.clone())?.to_bytes()
+    }
+
+    fn export_contents(&self, format: &dyn ExportFormat) -> Vec<u8> {
+        let graph = ExportGraph::new(&self.snarl, self.stats.last_report.as_ref());
+        format.write(&graph).into_bytes()
+    }
+
+    /// A picture of the root graph, with its nodes the size they were last
+    /// drawn if the root graph is being shown
+    fn image_contents(&self, format: ImageFormat) -> Vec<u8> {
+        let areas = self.canvas.node_areas(&self.snarl, |id| {
+            self.group_path
+                .is_empty()
+                .then(|| self.viewer.node_rect(id))
+                .flatten()
+        });
+        image_export::render(
+            format,
+            &self.snarl,
+            &areas,
+            &self.canvas.comments,
+            &self.viewer.colours,
+            self.stats.last_report.as_ref(),
+            &self.image_options,
+        )
+    }
+
+    /// The state of each task of the task DAG, as served by
+    /// [`StateServer`](crate::state_server::StateServer)
+    #[cfg(not(target_arch = "wasm32"))]
+    fn task_states(&self) -> serde_json::Value {
+        let Some(task_dag) = &self.task_execution else {
+            return serde_json::json!({ "tasks": [] });
+        };
+        let ready = task_dag.ready_tasks().collect::<HashSet<_>>();
+        let blocked = task_dag.blocked_tasks().collect::<HashSet<_>>();
+        let mut tasks = self
+            .snarl
+            .node_ids()
+            .map(|(id, node)| {
+                let state = if self.processes.is_running(id) || node.is_pending() {
+                    "running"
+                } else if self.processes.has_failed(id) {
+                    "failed"
+                } else if ready.contains(&id) {
+                    "ready"
+                } else if blocked.contains(&id) {
+                    "blocked"
+                } else {
+                    "completed"
+                };
+                (id, node.label(), node.name(), state)
+            })
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|(id, ..)| *id);
+        let tasks = tasks
+            .into_iter()
+            .map(|(id, label, kind, state)| {
+                serde_json::json!({ "id": id.0, "label": label, "type": kind, "state": state })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "tasks": tasks })
+    }
+
+    /// Starts or stops the [`StateServer`] to match the config, and sends it
+    /// the latest state and the evaluations that have just finished
+    #[cfg(not(target_arch = "wasm32"))]
+    fn serve_state(&mut self, reports: &[RunReport]) {
+        use crate::state_server::StateServer;
+
+        let origin = self.config.serve_origin.trim();
+        let origin = (!origin.is_empty()).then_some(origin);
+        let port = self.config.serve_state.then_some(self.config.serve_port);
+        let running = self
+            .server
+            .as_ref()
+            .map(|server| (server.port(), server.allowed_origin()));
+        if running != port.map(|port| (port, origin)) {
+            self.server = None;
+            if let Some(port) = port {
+                match StateServer::start(port, origin.map(str::to_owned)) {
+                    Ok(server) => self.server = Some(server),
+                    Err(err) => {
+                        self.notifications
+                            .error(format!("Failed to serve on port {}: {}", port, err));
+                        self.config.serve_state = false;
+                    }
+                }
+            }
+        }
+        let Some(server) = &self.server else {
+            return;
+        };
+        for report in reports {
+            let failures = report
+                .failures
+                .iter()
+                .map(|failure| {
+                    serde_json::json!({ "id": failure.node.0, "message": failure.message })
+                })
+                .collect::<Vec<_>>();
+            let progress = serde_json::json!({
+                "updated": report.runs.iter().map(|run| run.node.0).collect::<Vec<_>>(),
+                "failures": failures,
+                "cancelled": report.cancelled,
+            });
+            server.send("progress", &progress.to_string());
+        }
+        server.publish_state(self.task_states().to_string());
+    }
+
+    /// Loads the user's templates from their file, when running natively
+    fn with_templates(mut self) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.templates_path = TemplateLibrary::default_path();
+        }
+        if let Some(path) = &self.templates_path {
+            match TemplateLibrary::load(path) {
+                Ok(templates) => self.viewer.templates = templates,
+                Err(err) => self.notifications.error(format!(
+                    "Failed to load templates from {}: {}",
+                    path.display(),
+                    err
+                )),
+            }
+        }
+        self
+    }
+
+    /// Writes the user's templates to their file if they have changed, and
+    /// shows any problems the viewer ran into
+    fn save_templates(&mut self) {
+        for error in self.viewer.errors.drain(..) {
+            self.notifications.error(error);
+        }
+        if !std::mem::take(&mut self.viewer.templates_modified) {
+            return;
+        }
+        if let Some(path) = &self.templates_path {
+            if let Err(err) = self.viewer.templates.save(path) {
+                self.notifications.error(format!(
+                    "Failed to save templates to {}: {}",
+                    path.display(),
+                    err
+                ));
+            }
+        }
+    }
+
+    /// Forgets everything tied to the previous graph after it is replaced
+    fn reset(&mut self) {
+        // Templates belong to the user rather than the graph, and cached
+        // outputs don't depend on the graph they came from
+        let templates = std::mem::take(&mut self.viewer.templates);
+        let cache = self.viewer.cache.clone();
+        self.viewer = DemoViewer::default();
+        self.viewer.templates = templates;
+        self.viewer.cache = cache;
+        self.group_path.clear();
+        self.canvas.reset();
+        self.sweep.reset();
+        self.simulation.reset();
+        self.debug = None;
+        self.last_continuous = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.background = Default::default();
+        }
+        self.task_execution = None;
+        self.processes = ProcessExecutor::default();
+        self.stats = ExecutionStats::default();
+        // Nodes loaded with their cached outputs don't need recalculating
+        self.stats
+            .record(node_graph::evaluate_stale(&mut self.snarl));
+    }
+
+    /// Performs an action without asking for confirmation, recording what is
+    /// needed to undo it
+    pub fn apply(&mut self, action: DestructiveAction) -> std::io::Result<()> {
+        let description = action.description();
+        match action {
+            DestructiveAction::RemoveNodes(nodes) => {
+                self.history.push(description, self.undo_snapshot());
+                let at_root = self.group_path.is_empty();
+                let snarl = current_snarl(&mut self.snarl, &mut self.group_path);
+                for node in nodes {
+                    if snarl.get_node(node).is_some() {
+                        snarl.remove_node(node);
+                    }
+                    self.viewer.selected.remove(&node);
+                    // Another node may be added with the same id later
+                    if at_root {
+                        self.viewer.priorities.remove(&node);
+                        self.viewer.stages.set_stage(node, None);
+                        self.viewer.refresh_adjacency(snarl, [node]);
+                    }
+                }
+                if let Some(&group) = self.group_path.first() {
+                    // Changes inside a group need to be propagated through the
+                    // enclosing graphs
+                    self.stats
+                        .record(self.viewer.evaluate_root(&mut self.snarl, Some(group)));
+                }
+            }
+            DestructiveAction::DisconnectNodes(nodes) => {
+                self.history.push(description, self.undo_snapshot());
+                let at_root = self.group_path.is_empty();
+                let snarl = current_snarl(&mut self.snarl, &mut self.group_path);
+                for node in nodes {
+                    let Some(value) = snarl.get_node(node) else {
+                        continue;
+                    };
+                    let (inputs, outputs) = (value.inputs().len(), value.outputs().len());
+                    for input in 0..inputs {
+                        snarl.drop_inputs(InPinId { node, input });
+                    }
+                    for output in 0..outputs {
+                        snarl.drop_outputs(OutPinId { node, output });
+                    }
+                    if at_root {
+                        self.viewer.refresh_adjacency(snarl, [node]);
+                    }
+                }
+                // The nodes that were downstream are no longer reachable from
+                // the disconnected ones, so the whole graph is recalculated
+                if !self.viewer.manual {
+                    let start = self.group_path.first().copied();
+                    self.stats
+                        .record(self.viewer.evaluate_root(&mut self.snarl, start));
+                }
+            }
+            DestructiveAction::ClearGraph => {
+                self.history.push(description, self.undo_snapshot());
+                self.snarl = Snarl::new();
+                self.canvas.comments.clear();
+                self.viewer.priorities.clear();
+                self.viewer.stages = Stages::default();
+                self.reset();
+            }
+            DestructiveAction::RestoreSnapshot { index, .. } => {
+                if let Some(snapshot) = self.config.snapshots.get(index) {
+                    let saved = snapshot.graph.clone();
+                    self.replace_graph(description, saved)
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+                }
+            }
+            DestructiveAction::DeleteSnapshot { index, .. } => {
+                if index < self.config.snapshots.len() {
+                    self.config.snapshots.remove(index);
+                }
+            }
+            DestructiveAction::WriteFile {
+                path,
+                contents,
+                thumbnail,
+            } => {
+                let previous = match std::fs::read(&path) {
+                    Ok(previous) => Some(previous),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(err) => return Err(err),
+                };
+                std::fs::write(&path, contents)?;
+                // Thumbnails are only pictures of the graph, so undoing the
+                // write leaves them in place
+                if let Some(thumbnail) = thumbnail {
+                    let thumbnail_path = thumbnail::path_for(&path);
+                    std::fs::write(&thumbnail_path, thumbnail)?;
+                    self.thumbnails.remove(&path);
+                }
+                self.history.push(
+                    description,
+                    UndoEntry::File {
+                        path,
+                        contents: previous,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Reverts the most recent destructive action, returning whether there
+    /// was anything to undo
+    pub fn undo(&mut self) -> Result<bool, LoadError> {
+        let Some(entry) = self.history.pop() else {
+            return Ok(false);
+        };
+        match entry {
+            UndoEntry::Graph(saved) => {
+                self.snarl = saved.to_snarl()?;
+                self.viewer.adjacency = None;
+                self.viewer.priorities = loaded_priorities(&saved);
+                self.viewer.stages = Stages::from_saved(&saved.stages);
+                self.canvas.comments = saved.comments;
+                self.canvas.reset();
+                self.viewer.selected.clear();
+                self.task_execution = None;
+                self.processes = ProcessExecutor::default();
+                self.evaluate();
+            }
+            UndoEntry::File {
+                path,
+                contents: Some(contents),
+            } => std::fs::write(path, contents)?,
+            UndoEntry::File {
+                path,
+                contents: None,
+            } => std::fs::remove_file(path)?,
+        }
+        Ok(true)
+    }
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        let mut tiles = egui_tiles::Tiles::default();
+
+        let mut snarl = Snarl::new();
+
+        let mut stats = ExecutionStats::default();
+        stats.record(node_graph::evaluate(&mut snarl, None));
+
+        let config_pane = tiles.insert_pane(Pane::Config);
+        let nodes_pane = tiles.insert_pane(Pane::Nodes);
+        let stats_pane = tiles.insert_pane(Pane::Statistics);
+        let inspector_pane = tiles.insert_pane(Pane::Inspector);
+        let timeline_pane = tiles.insert_pane(Pane::Timeline);
+        let sweep_pane = tiles.insert_pane(Pane::Sweep);
+        let simulation_pane = tiles.insert_pane(Pane::Simulation);
+        let log_pane = tiles.insert_pane(Pane::Log);
+        let problems_pane = tiles.insert_pane(Pane::Problems);
+        let differences_pane = tiles.insert_pane(Pane::Differences);
+        let history_pane = tiles.insert_pane(Pane::History);
+        let runs = tiles.insert_tab_tile(vec![
+            stats_pane,
+            timeline_pane,
+            sweep_pane,
+            simulation_pane,
+            log_pane,
+        ]);
+        let details = tiles.insert_tab_tile(vec![
+            inspector_pane,
+            problems_pane,
+            differences_pane,
+            history_pane,
+        ]);
+
+        let side = tiles.insert_new(Tile::Container(Container::Linear(Linear {
+            children: vec![runs, details],
+            dir: LinearDir::Vertical,
+            ..Default::default()
+        })));
+
+        let mut inner = Linear {
+            children: vec![config_pane, nodes_pane, side],
+            dir: LinearDir::Horizontal,
+            ..Default::default()
+        };
+        inner.shares.set_share(config_pane, 1.);
+        inner.shares.set_share(nodes_pane, 3.);
+        inner.shares.set_share(side, 1.);
+        let root = tiles.insert_new(Tile::Container(Container::Linear(inner)));
+
+        // Each editor has its own id, so that editors open side by side
+        // don't share the state egui keeps for their panes
+        static EDITORS: AtomicU64 = AtomicU64::new(0);
+        let id = egui::Id::new("tree").with(EDITORS.fetch_add(1, Ordering::Relaxed));
+        let tree = egui_tiles::Tree::new(id, root, tiles);
+
+        Self {
+            tree,
+            snarl,
+            viewer: DemoViewer::default(),
+            group_path: Vec::new(),
+            style: Settings::default().style(),
+            task_execution: None,
+            processes: ProcessExecutor::default(),
+            stats,
+            pending: None,
+            history: UndoHistory::default(),
+            config: ConfigState::default(),
+            opener: FileOpener::default(),
+            trace_opener: FileOpener::default(),
+            checkpoint_opener: FileOpener::default(),
+            compare_opener: FileOpener::default(),
+            comparison: None,
+            package_data: BTreeMap::new(),
+            canvas: Canvas::default(),
+            timeline: Timeline::default(),
+            sweep: SweepPanel::default(),
+            simulation: SimulationPanel::default(),
+            log: LogConsole::default(),
+            image_options: ImageOptions::default(),
+            diagnostics: Vec::new(),
+            debug: None,
+            last_continuous: None,
+            file_name: None,
+            recent: Vec::new(),
+            thumbnails: HashMap::new(),
+            notifications: Notifications::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            server: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            background: Default::default(),
+            templates_path: None,
+        }
+        .with_templates()
+    }
+}
+
+type Callback<'a, T> = Box<dyn FnMut(&T) + 'a>;
+
+/// The complete graph editor, made up of the node canvas and its
+/// supporting panes, for embedding in other egui applications.
+///
+/// ```ignore
+/// let mut editor = GraphEditorWidget::new().on_evaluate(|report| {
+///     println!("{} nodes updated", report.runs.len());
+/// });
+/// editor.show(ui, &mut state);
+/// ```
+#[derive(Default)]
+pub struct GraphEditorWidget<'a> {
+    on_evaluate: Option<Callback<'a, RunReport>>,
+    on_save: Option<Callback<'a, Path>>,
+    settings: Option<&'a mut Settings>,
+}
+
+impl<'a> GraphEditorWidget<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Called after every evaluation of the graph
+    pub fn on_evaluate(mut self, callback: impl FnMut(&RunReport) + 'a) -> Self {
+        self.on_evaluate = Some(Box::new(callback));
+        self
+    }
+
+    /// Called after the graph has been written to a file
+    pub fn on_save(mut self, callback: impl FnMut(&Path) + 'a) -> Self {
+        self.on_save = Some(Box::new(callback));
+        self
+    }
+
+    /// Lets the user change how the graph is drawn from the Config pane,
+    /// using `settings` rather than the default appearance
+    pub fn settings(mut self, settings: &'a mut Settings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Adds the editor's entries to a "File" menu
+    pub fn file_menu(&mut self, ui: &mut egui::Ui, state: &mut EditorState) {
+        if ui.button(i18n::tr("menu-open-graph")).clicked() {
+            match state.opener.open("Graph File or Package", &["json", "zip"]) {
+                Ok(Some(file)) => Self::open(state, file),
+                Ok(None) => {}
+                Err(err) => state.notifications.error(err.to_string()),
+            }
+            ui.close_menu();
+        }
+        self.recent_menu(ui, state);
+        if ui.button(i18n::tr("menu-save-graph")).clicked() {
+            self.save_graph_as(state);
+            ui.close_menu();
+        }
+        if ui.button(i18n::tr("menu-export-package")).clicked() {
+            match state.package_contents() {
+                Ok(contents) => self.save_as(state, "graph.zip", contents),
+                Err(err) => state
+                    .notifications
+                    .error(format!("Failed to package graph: {}", err)),
+            }
+            ui.close_menu();
+        }
+        ui.menu_button(i18n::tr("menu-export-graph"), |ui| {
+            for format in export::formats() {
+                if ui.button(format!("{}…", format.name())).clicked() {
+                    let contents = state.export_contents(format.as_ref());
+                    self.save_as(state, &format!("graph.{}", format.extension()), contents);
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button(i18n::tr("menu-export-image"), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(i18n::tr("menu-export-image-scale"));
+                ui.add(
+                    egui::DragValue::new(&mut state.image_options.scale)
+                        .clamp_range(0.25..=8.)
+                        .speed(0.05)
+                        .suffix("×"),
+                )
+                .on_hover_text(i18n::tr("menu-export-image-scale-hint"));
+            });
+            ui.checkbox(
+                &mut state.image_options.statuses,
+                i18n::tr("menu-export-image-statuses"),
+            )
+            .on_hover_text(i18n::tr("menu-export-image-statuses-hint"));
+            ui.separator();
+            for format in ImageFormat::ALL {
+                if ui.button(format!("{}…", format.name())).clicked() {
+                    let contents = state.image_contents(format);
+                    self.save_as(state, &format!("graph.{}", format.extension()), contents);
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button(i18n::tr("menu-export-plan"), |ui| {
+            // Cloned so that `state` can be borrowed mutably when saving
+            let stages = &state.viewer.stages.clone();
+            let plan = ExecutionPlan::for_graph(&state.snarl, stages);
+            if ui
+                .button("JSON…")
+                .on_hover_text(i18n::tr("menu-export-plan-hint"))
+                .clicked()
+            {
+                let contents = plan.to_json(&state.snarl, stages).into_bytes();
+                self.save_as(state, "plan.json", contents);
+                ui.close_menu();
+            }
+            if ui.button("YAML…").clicked() {
+                let contents = plan.to_yaml(&state.snarl, stages).into_bytes();
+                self.save_as(state, "plan.yaml", contents);
+                ui.close_menu();
+            }
+        });
+        if ui
+            .add_enabled(
+                state.stats.last_report.is_some(),
+                egui::Button::new(i18n::tr("menu-export-trace")),
+            )
+            .on_hover_text(i18n::tr("menu-export-trace-hint"))
+            .clicked()
+        {
+            if let Some(report) = &state.stats.last_report {
+                let contents = trace::to_chrome_trace(report, &state.snarl).into_bytes();
+                self.save_as(state, "trace.json", contents);
+            }
+            ui.close_menu();
+        }
+        if ui
+            .button(i18n::tr("menu-compare"))
+            .on_hover_text(i18n::tr("menu-compare-hint"))
+            .clicked()
+        {
+            match state.compare_opener.open("Graph", &["json", "zip"]) {
+                Ok(Some(file)) => Self::compare(state, file),
+                Ok(None) => {}
+                Err(err) => state.notifications.error(err.to_string()),
+            }
+            ui.close_menu();
+        }
+        if ui.button(i18n::tr("menu-import-trace")).clicked() {
+            match state.trace_opener.open("Chrome Trace", &["json"]) {
+                Ok(Some(file)) => Self::import_trace(state, file),
+                Ok(None) => {}
+                Err(err) => state.notifications.error(err.to_string()),
+            }
+            ui.close_menu();
+        }
+        if ui
+            .add_enabled(
+                state.task_execution.is_some(),
+                egui::Button::new(i18n::tr("menu-save-checkpoint")),
+            )
+            .on_hover_text(i18n::tr("menu-save-checkpoint-hint"))
+            .clicked()
+        {
+            if let Some(task_dag) = &state.task_execution {
+                let contents = Checkpoint::new(task_dag, &state.snarl)
+                    .to_json()
+                    .into_bytes();
+                self.save_as(state, "checkpoint.json", contents);
+            }
+            ui.close_menu();
+        }
+        if ui
+            .button(i18n::tr("menu-resume-checkpoint"))
+            .on_hover_text(i18n::tr("menu-resume-checkpoint-hint"))
+            .clicked()
+        {
+            match state.checkpoint_opener.open("Checkpoint", &["json"]) {
+                Ok(Some(file)) => Self::resume_checkpoint(state, file),
+                Ok(None) => {}
+                Err(err) => state.notifications.error(err.to_string()),
+            }
+            ui.close_menu();
+        }
+        if ui.button(i18n::tr("menu-eval")).clicked() {
+            let report = state.evaluate();
+            if let Some(on_evaluate) = &mut self.on_evaluate {
+                on_evaluate(report);
+            }
+        }
+    }
+
+    /// Shows a trace in the Timeline pane
+    fn import_trace(state: &mut EditorState, file: PickedFile) {
+        match trace::from_chrome_trace(&file.contents) {
+            Ok(report) => state.timeline.import(file.name, report),
+            Err(err) => state
+                .notifications
+                .error(format!("Failed to import trace {}: {}", file.name, err)),
+        }
+    }
+
+    /// Starts comparing the graph with a saved one, in the Differences pane
+    fn compare(state: &mut EditorState, file: PickedFile) {
+        match read_graph(&file.contents) {
+            Ok(package) => {
+                state.comparison = Some(Comparison {
+                    name: file.name,
+                    saved: package.graph,
+                    diff: GraphDiff::default(),
+                })
+            }
+            Err(err) => state
+                .notifications
+                .error(format!("Failed to compare with {}: {}", file.name, err)),
+        }
+    }
+
+    /// Replaces the task DAG with one resuming from a checkpoint
+    fn resume_checkpoint(state: &mut EditorState, file: PickedFile) {
+        let checkpoint = match Checkpoint::from_json(&file.contents) {
+            Ok(checkpoint) => checkpoint,
+            Err(err) => {
+                state
+                    .notifications
+                    .error(format!("Failed to open checkpoint {}: {}", file.name, err));
+                return;
+            }
+        };
+        let task_dag = checkpoint.resume(&mut state.snarl);
+        let skipped = task_dag.completed_tasks().count();
+        if skipped < checkpoint.completed.len() {
+            state.notifications.info(format!(
+                "{} of the {} completed tasks have changed since the checkpoint, and will run again",
+                checkpoint.completed.len() - skipped,
+                checkpoint.completed.len()
+            ));
+        }
+        state.task_execution = Some(task_dag);
+        state.processes = ProcessExecutor::default();
+        // Pass on the outputs restored to the skipped tasks
+        state
+            .stats
+            .record(node_graph::evaluate_stale(&mut state.snarl));
+    }
+
+    fn open(state: &mut EditorState, file: PickedFile) {
+        state.open_file(file);
+    }
+
+    /// Lists the graph files opened or saved this session, with their
+    /// thumbnails, for opening again
+    fn recent_menu(&mut self, ui: &mut egui::Ui, state: &mut EditorState) {
+        if state.recent.is_empty() {
+            return;
+        }
+        ui.menu_button(i18n::tr("menu-open-recent"), |ui| {
+            for path in state.recent.clone() {
+                let texture = state
+                    .thumbnails
+                    .entry(path.clone())
+                    .or_insert_with(|| {
+                        let image = thumbnail::load(&thumbnail::path_for(&path))?;
+                        let name = path.display().to_string();
+                        Some(ui.ctx().load_texture(name, image, Default::default()))
+                    })
+                    .clone();
+                let name = path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                );
+                let button = match &texture {
+                    Some(texture) => egui::Button::image_and_text(
+                        egui::Image::new(texture).max_width(THUMBNAIL_WIDTH),
+                        name,
+                    ),
+                    None => egui::Button::new(name),
+                };
+                if ui
+                    .add(button)
+                    .on_hover_text(path.display().to_string())
+                    .clicked()
+                {
+                    if let Err(err) = state.load(&path) {
+                        state.notifications.error(format!(
+                            "Failed to open {}: {}",
+                            path.display(),
+                            err
+                        ));
+                    }
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    /// Asks where to save the root graph and writes it there, along with its
+    /// thumbnail
+    fn save_graph_as(&mut self, state: &mut EditorState) {
+        let contents = state.graph_file_contents();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = file_io::pick_save_path("Graph File", &["json"], "graph.json") {
+            state.remember(&path);
+            state.file_name = Some(path.display().to_string());
+            let thumbnail = state.thumbnail();
+            self.request(
+                state,
+                DestructiveAction::WriteFile {
+                    path,
+                    contents,
+                    thumbnail,
+                },
+            );
+        }
+        #[cfg(target_arch = "wasm32")]
+        self.save_as(state, "graph.json", contents);
+    }
+
+    /// Asks where to save a file and writes it there, or downloads it when
+    /// running in a browser
+    fn save_as(&mut self, state: &mut EditorState, file_name: &str, contents: Vec<u8>) {
+        let extension = file_name.rsplit('.').next().unwrap_or_default();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = file_io::pick_save_path("Graph File", &[extension], file_name) {
+            self.request(
+                state,
+                DestructiveAction::WriteFile {
+                    path,
+                    contents,
+                    thumbnail: None,
+                },
+            );
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (state, extension);
+            file_io::download(file_name, &contents);
+            if let Some(on_save) = &mut self.on_save {
+                on_save(Path::new(file_name));
+            }
+        }
+    }
+
+    /// Adds the editor's entries to an "Edit" menu
+    pub fn edit_menu(&mut self, ui: &mut egui::Ui, state: &mut EditorState) {
+        let undo_label = match state.history.last_description() {
+            Some(description) => i18n::tr_args(
+                "menu-undo-action",
+                &[("action", &description.to_lowercase())],
+            ),
+            None => i18n::tr("menu-undo"),
+        };
+        if ui
+            .add_enabled(!state.history.is_empty(), egui::Button::new(undo_label))
+            .clicked()
+        {
+            self.undo(state);
+            ui.close_menu();
+        }
+        if ui.button(i18n::tr("menu-clear-graph")).clicked() {
+            self.request(state, DestructiveAction::ClearGraph);
+            ui.close_menu();
+        }
+        if ui
+            .button(i18n::tr("menu-reset-state"))
+            .on_hover_text(i18n::tr("menu-reset-state-hint"))
+            .clicked()
+        {
+            state.reset_state();
+            ui.close_menu();
+        }
+    }
+
+    /// Adds the editor's entries to a "View" menu
+    pub fn view_menu(&mut self, ui: &mut egui::Ui, state: &mut EditorState) {
+        if ui.button(i18n::tr("menu-fit-graph")).clicked() {
+            state.viewer.navigation = Some(Navigation::FitAll);
+            ui.close_menu();
+        }
+        if ui
+            .add_enabled(
+                !state.viewer.selected.is_empty(),
+                egui::Button::new(i18n::tr("menu-zoom-to-selected")),
+            )
+            .clicked()
+        {
+            state.viewer.navigation = Some(Navigation::FitSelected);
+            ui.close_menu();
+        }
+        ui.menu_button(i18n::tr("menu-go-to-node"), |ui| {
+            let snarl = current_snarl(&mut state.snarl, &mut state.group_path);
+            state.viewer.go_to_ui(ui, snarl);
+        });
+        ui.checkbox(&mut state.canvas.minimap, i18n::tr("menu-minimap"));
+        ui.checkbox(&mut state.viewer.wire_labels, i18n::tr("menu-wire-values"))
+            .on_hover_text(i18n::tr("menu-wire-values-hint"));
+    }
+
+    /// Applies an action, or holds it until the user confirms it
+    fn request(&mut self, state: &mut EditorState, action: DestructiveAction) {
+        if action.needs_confirmation() {
+            state.pending = Some(action);
+        } else {
+            self.apply(state, action);
+        }
+    }
+
+    fn apply(&mut self, state: &mut EditorState, action: DestructiveAction) {
+        let description = action.description();
+        let written = match &action {
+            DestructiveAction::WriteFile { path, .. } => Some(path.clone()),
+            _ => None,
+        };
+        if let Err(err) = state.apply(action) {
+            state
+                .notifications
+                .error(format!("Failed to {}: {}", description.to_lowercase(), err));
+            return;
+        }
+        if let (Some(path), Some(on_save)) = (written, &mut self.on_save) {
+            on_save(&path);
+        }
+    }
+
+    fn undo(&mut self, state: &mut EditorState) {
+        if let Err(err) = state.undo() {
+            state
+                .notifications
+                .error(format!("Failed to undo: {}", err));
+        }
+    }
+
+    /// Asks the user to confirm the pending action, if there is one
+    fn show_confirmation(&mut self, ui: &mut egui::Ui, state: &mut EditorState) {
+        let Some(action) = &state.pending else {
+            return;
+        };
+        let mut confirmed = None;
+        egui::Window::new(i18n::tr("confirm-title"))
+            .id(egui::Id::new("confirmation"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ui.ctx(), |ui| {
+                ui.label(format!("{}?", action.description()));
+                if action.can_undo() {
+                    ui.label(i18n::tr("confirm-can-undo"));
+                } else {
+                    ui.label(i18n::tr("confirm-cannot-undo"));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(i18n::tr("confirm")).clicked() {
+                        confirmed = Some(true);
+                    }
+                    if ui.button(i18n::tr("confirm-cancel")).clicked() {
+                        confirmed = Some(false);
+                    }
+                });
+            });
+        match confirmed {
+            Some(true) => {
+                let action = state.pending.take().unwrap();
+                self.apply(state, action);
+            }
+            Some(false) => state.pending = None,
+            None => {}
+        }
+    }
+
+    /// Shows the editor's panes, filling the available space
+    pub fn show(&mut self, ui: &mut egui::Ui, state: &mut EditorState) {
+        if ui.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::Z,
+            ))
+        }) {
+            self.undo(state);
+        }
+        if let Some(file) = state.opener.poll() {
+            Self::open(state, file);
+        }
+        if let Some(file) = state.trace_opener.poll() {
+            Self::import_trace(state, file);
+        }
+        if let Some(file) = state.checkpoint_opener.poll() {
+            Self::resume_checkpoint(state, file);
+        }
+        if let Some(file) = state.compare_opener.poll() {
+            Self::compare(state, file);
+        }
+        if let Some(comparison) = &mut state.comparison {
+            comparison.diff =
+                GraphDiff::between(&comparison.saved, &SavedGraph::from_snarl(&state.snarl));
+        }
+
+        if let Some(settings) = &self.settings {
+            state.style = settings.style();
+            state.viewer.colours = settings.colours;
+        }
+        state.canvas.install(&mut state.style);
+        state.diagnostics = validation::validate(&state.snarl);
+        state.viewer.highlighted = None;
+        state.viewer.paused_at = state
+            .debug
+            .as_mut()
+            .and_then(|evaluation| evaluation.next(&state.snarl));
+
+        let mut reports = Vec::new();
+        let mut actions = Vec::new();
+        let id = state.tree.id();
+        state.tree.ui(
+            &mut TreeBehavior {
+                id,
+                reports: &mut reports,
+                actions: &mut actions,
+                snarl: &mut state.snarl,
+                viewer: &mut state.viewer,
+                group_path: &mut state.group_path,
+                style: &state.style,
+                task_execution: &mut state.task_execution,
+                processes: &mut state.processes,
+                stats: &state.stats,
+                config: &mut state.config,
+                settings: self.settings.as_deref_mut(),
+                canvas: &mut state.canvas,
+                timeline: &mut state.timeline,
+                sweep: &mut state.sweep,
+                simulation: &mut state.simulation,
+                log: &mut state.log,
+                diagnostics: &state.diagnostics,
+                debug: &mut state.debug,
+                comparison: &mut state.comparison,
+                notifications: &mut state.notifications,
+            },
+            ui,
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if state.watcher.is_none() {
+                let ctx = ui.ctx().clone();
+                match graph_core::file_nodes::FileWatcher::new(WATCH_INTERVAL, move || {
+                    ctx.request_repaint()
+                }) {
+                    Ok(watcher) => state.watcher = Some(watcher),
+                    Err(err) => state
+                        .notifications
+                        .error(format!("Failed to watch files: {}", err)),
+                }
+            }
+            if let Some(watcher) = &state.watcher {
+                watcher.watch(&state.snarl);
+                for node in watcher.changed() {
+                    reports.push(state.viewer.evaluate_root(&mut state.snarl, Some(node)));
+                }
+            }
+
+            for node in std::mem::take(&mut state.viewer.requested) {
+                state.background.request(node);
+            }
+            let ctx = ui.ctx().clone();
+            reports.extend(state.background.poll(
+                &mut state.snarl,
+                &state.viewer.eval_context(),
+                move || ctx.request_repaint(),
+            ));
+            state.viewer.computing = state.background.computing().collect();
+        }
+        // Nodes that finished their background work complete their task, and
+        // pass their result on
+        for node in node_graph::poll_pending(&mut state.snarl) {
+            if let Some(task_dag) = &mut state.task_execution {
+                let _ = task_dag.complete_task(node);
+            }
+            reports.push(state.viewer.evaluate_root(&mut state.snarl, Some(node)));
+        }
+        if state.snarl.nodes().any(|node| node.is_pending()) {
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
+        // Clocks and other nodes that change on their own keep the graph
+        // downstream of them updating, unless it's only updated on request
+        if !state.viewer.manual
+            && state.debug.is_none()
+            && state
+                .snarl
+                .nodes()
+                .any(|node| node.needs_continuous_update())
+        {
+            let now = web_time::Instant::now();
+            if state
+                .last_continuous
+                .is_none_or(|last| now - last >= CONTINUOUS_INTERVAL)
+            {
+                reports.push(node_graph::evaluate_continuous(
+                    &mut state.snarl,
+                    &state.viewer.eval_context(),
+                ));
+                state.last_continuous = Some(now);
+            }
+            ui.ctx().request_repaint_after(CONTINUOUS_INTERVAL);
+        }
+
+        if let Some(task_dag) = &mut state.task_execution {
+            for task in state.processes.poll(task_dag, &mut state.snarl) {
+                // Pass the process's output on to the nodes that use it
+                if !state.viewer.manual {
+                    reports.push(state.viewer.evaluate_root(&mut state.snarl, Some(task)));
+                }
+            }
+            if state.processes.is_busy() {
+                ui.ctx()
+                    .request_repaint_after(std::time::Duration::from_millis(100));
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        state.serve_state(&reports);
+
+        for report in reports {
+            if let Some(on_evaluate) = &mut self.on_evaluate {
+                on_evaluate(&report);
+            }
+            state.stats.record(report);
+        }
+
+        for action in actions {
+            self.request(state, action);
+        }
+        state.save_templates();
+        if let Some(csv) = state.sweep.take_export() {
+            self.save_as(state, "sweep.csv", csv.into_bytes());
+        }
+        self.show_confirmation(ui, state);
+        state.notifications.ui(ui.ctx());
+    }
+}
@@ -0,0 +1,147 @@
+//! Opening and saving files, both natively and in the browser
+
+use eframe::egui;
+
+/// A file picked by the user to be opened
+pub struct PickedFile {
+    pub name: String,
+    pub contents: Vec<u8>,
+}
+
+/// Asks the user for a file to open.
+///
+/// Natively the dialog blocks and the file is returned straight away. In the
+/// browser the dialog is asynchronous, so the file is returned by a later
+/// call to [`FileOpener::poll`].
+#[derive(Default)]
+pub struct FileOpener {
+    #[cfg(target_arch = "wasm32")]
+    picked: std::sync::Arc<std::sync::Mutex<Option<PickedFile>>>,
+}
+
+impl FileOpener {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(
+        &self,
+        filter_name: &str,
+        extensions: &[&str],
+    ) -> std::io::Result<Option<PickedFile>> {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(filter_name, extensions)
+            .pick_file()
+        else {
+            return Ok(None);
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(Some(PickedFile {
+                name: path.display().to_string(),
+                contents,
+            })),
+            Err(err) => Err(std::io::Error::new(
+                err.kind(),
+                format!("Failed to read {}: {}", path.display(), err),
+            )),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn open(
+        &self,
+        filter_name: &str,
+        extensions: &[&str],
+    ) -> std::io::Result<Option<PickedFile>> {
+        let picked = self.picked.clone();
+        let dialog = rfd::AsyncFileDialog::new().add_filter(filter_name, extensions);
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Some(file) = dialog.pick_file().await {
+                *picked.lock().unwrap() = Some(PickedFile {
+                    name: file.file_name(),
+                    contents: file.read().await,
+                });
+            }
+        });
+        Ok(None)
+    }
+
+    /// Takes a file picked since the last call, if the dialog is asynchronous
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn poll(&self) -> Option<PickedFile> {
+        None
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn poll(&self) -> Option<PickedFile> {
+        self.picked.lock().unwrap().take()
+    }
+}
+
+/// Files dropped onto the window this frame. Natively they are read from
+/// disk, while the browser hands over their contents.
+pub fn dropped_files(ctx: &egui::Context) -> Vec<std::io::Result<PickedFile>> {
+    let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+    dropped
+        .into_iter()
+        .map(|file| {
+            if let Some(bytes) = file.bytes {
+                return Ok(PickedFile {
+                    name: file.name,
+                    contents: bytes.to_vec(),
+                });
+            }
+            let Some(path) = file.path else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Failed to read {}", file.name),
+                ));
+            };
+            match std::fs::read(&path) {
+                Ok(contents) => Ok(PickedFile {
+                    name: path.display().to_string(),
+                    contents,
+                }),
+                Err(err) => Err(std::io::Error::new(
+                    err.kind(),
+                    format!("Failed to read {}: {}", path.display(), err),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Asks the user where to save a file
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_save_path(
+    filter_name: &str,
+    extensions: &[&str],
+    file_name: &str,
+) -> Option<std::path::PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter(filter_name, extensions)
+        .set_file_name(file_name)
+        .save_file()
+}
+
+/// Hands a file to the browser to be downloaded
+#[cfg(target_arch = "wasm32")]
+pub fn download(file_name: &str, contents: &[u8]) {
+    use wasm_bindgen::JsCast;
+
+    let result = (|| -> Result<(), wasm_bindgen::JsValue> {
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+        let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("no document")?;
+        let anchor = document
+            .create_element("a")?
+            .dyn_into::<web_sys::HtmlAnchorElement>()?;
+        anchor.set_href(&url);
+        anchor.set_download(file_name);
+        anchor.click();
+        web_sys::Url::revoke_object_url(&url)
+    })();
+    if let Err(err) = result {
+        web_sys::console::error_1(&err);
+    }
+}
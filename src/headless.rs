@@ -0,0 +1,352 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use egui_snarl::{InPinId, NodeId, Snarl};
+use graph_core::{
+    context::EvalContext,
+    cron::Trigger,
+    execution_engine::{ProcessExecutor, TaskDag},
+    graph_file::{self, LoadError, SavedGraph},
+    node_graph::{self, format_value, Node, TypedData},
+    pipeline::{self, Pipeline, PipelineReport},
+    run_report::RunReport,
+    stages::Stages,
+    sweep::{self, Sweep},
+    trace, validation,
+};
+use web_time::SystemTime;
+
+/// How often processes and background work are checked on while waiting
+/// for them to finish
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Loads and evaluates a saved graph without the GUI, then executes its
+/// task DAG to completion, running the processes of Command nodes and
+/// waiting for nodes with background work such as HTTP requests. Writes the
+/// values reaching each sink and the time taken by each task to `out`. Any
+/// problems found in the graph are written before it is evaluated. The
+/// graph's hooks are run before and after it is evaluated. The run is also
+/// written as a Chrome trace to `trace`, if given.
+///
+/// `args` are read by the graph's [`ArgNode`](graph_core::args::ArgNode)s,
+/// as they are by every function here.
+pub fn run(
+    path: &Path,
+    args: &BTreeMap<String, TypedData>,
+    out: &mut impl Write,
+    trace: Option<&Path>,
+) -> Result<RunReport, Box<dyn Error>> {
+    let saved = graph_file::read_file(path)?;
+    let mut snarl = saved.to_snarl()?;
+    let diagnostics = validation::validate(&snarl);
+    if !diagnostics.is_empty() {
+        writeln!(out, "Problems:")?;
+        for diagnostic in &diagnostics {
+            writeln!(
+                out,
+                "  {} {} {}: {}",
+                diagnostic.severity,
+                snarl[diagnostic.node].name(),
+                diagnostic.node.0,
+                diagnostic.message
+            )?;
+        }
+    }
+    write_lines(out, &saved.hooks.run_before(&mut snarl)?)?;
+    let ctx = context(args);
+    let mut report = node_graph::evaluate_with(&mut snarl, None, &ctx);
+    let (background, processes) = execute_tasks(&saved, &mut snarl, &ctx, &mut report);
+
+    write_sinks(&snarl, out)?;
+    writeln!(out, "{} sinks produced", report.sinks_produced(&snarl))?;
+
+    writeln!(out, "Tasks:")?;
+    for run in &report.runs {
+        writeln!(
+            out,
+            "  {} {} updated in {:?}",
+            snarl[run.node].name(),
+            run.node.0,
+            run.duration
+        )?;
+    }
+    if !background.is_empty() {
+        writeln!(out, "Background work:")?;
+        for (node, duration) in &background {
+            let outcome = if processes.has_failed(*node) {
+                "failed after"
+            } else {
+                "finished in"
+            };
+            writeln!(
+                out,
+                "  {} {} {} {:?}",
+                snarl[*node].name(),
+                node.0,
+                outcome,
+                duration
+            )?;
+        }
+    }
+
+    if !report.messages.is_empty() {
+        writeln!(out, "Messages:")?;
+        for message in &report.messages {
+            writeln!(
+                out,
+                "  {} {} {}: {}",
+                snarl[message.node].name(),
+                message.node.0,
+                message.level,
+                message.message
+            )?;
+        }
+    }
+
+    if !report.failures.is_empty() {
+        writeln!(out, "Failures:")?;
+        for failure in &report.failures {
+            writeln!(
+                out,
+                "  {} {}: {}",
+                snarl[failure.node].name(),
+                failure.node.0,
+                failure.message
+            )?;
+        }
+    }
+
+    if let Some(trace) = trace {
+        std::fs::write(trace, trace::to_chrome_trace(&report, &snarl))?;
+    }
+
+    write_lines(out, &saved.hooks.run_after(&snarl, &report)?)?;
+    Ok(report)
+}
+
+/// Drives the graph's task DAG until nothing is left running, after the
+/// graph has been evaluated once. Tasks done by that evaluation are
+/// completed straight away, the processes of tasks with one are run as they
+/// become ready, and nodes with background work are waited on. Each task
+/// that finishes is evaluated again, passing its result downstream, with
+/// what was updated merged into `report`.
+///
+/// Returns how long each process or piece of background work took, and the
+/// executor that ran the processes. Tasks that fail, and those depending on
+/// them, are left outstanding.
+fn execute_tasks(
+    saved: &SavedGraph,
+    snarl: &mut Snarl<Box<dyn Node>>,
+    ctx: &EvalContext,
+    report: &mut RunReport,
+) -> (BTreeMap<NodeId, Duration>, ProcessExecutor) {
+    let mut dag = TaskDag::new(&node_graph::as_petgraph(snarl))
+        .with_priorities(saved.task_priorities())
+        .with_stages(&Stages::from_saved(&saved.stages));
+    let mut processes = ProcessExecutor::default();
+    let mut started = HashMap::<NodeId, Instant>::new();
+    let mut background = BTreeMap::new();
+    loop {
+        let done = dag
+            .ready_tasks()
+            .filter(|task| {
+                snarl.get_node(*task).is_some_and(|node| {
+                    node.process().is_none() && !node.is_pending() && node.error().is_none()
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in &done {
+            let _ = dag.complete_task(*task);
+        }
+
+        let mut finished = processes.poll(&mut dag, snarl);
+        for node in node_graph::poll_pending(snarl) {
+            let _ = dag.complete_task(node);
+            finished.push(node);
+        }
+        for task in &finished {
+            if let Some(start) = started.remove(task) {
+                background.insert(*task, start.elapsed());
+            }
+            report.merge(node_graph::evaluate_with(snarl, Some(*task), ctx));
+        }
+
+        let now = Instant::now();
+        for (id, node) in snarl.node_ids() {
+            if processes.is_running(id) || node.is_pending() {
+                started.entry(id).or_insert(now);
+            }
+        }
+        if done.is_empty() && finished.is_empty() {
+            if started.is_empty() {
+                return (background, processes);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Evaluates a saved graph once for every combination of the swept values,
+/// writing what reached its sinks each time to `out` as CSV
+pub fn run_sweep(
+    path: &Path,
+    args: &BTreeMap<String, TypedData>,
+    sweeps: &[Sweep],
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut snarl = graph_file::load_from_file(path)?;
+    let results = sweep::run(&mut snarl, sweeps, &context(args))?;
+    out.write_all(results.to_csv().as_bytes())?;
+    Ok(())
+}
+
+/// Evaluates a saved graph, then streams the list from `pipeline`'s source
+/// through the nodes downstream of it in chunks, see
+/// [`pipeline`](graph_core::pipeline). Writes what reached the end of the
+/// pipeline for each chunk to `out`.
+pub fn run_pipeline(
+    path: &Path,
+    args: &BTreeMap<String, TypedData>,
+    pipeline: Pipeline,
+    out: &mut impl Write,
+) -> Result<PipelineReport, Box<dyn Error>> {
+    let mut snarl = graph_file::load_from_file(path)?;
+    let ctx = context(args);
+    node_graph::evaluate_with(&mut snarl, None, &ctx);
+    let report = pipeline::run(&snarl, pipeline, &ctx)?;
+
+    writeln!(
+        out,
+        "{} chunks of {} streamed in {:?}",
+        report.chunks, pipeline.chunk_size, report.elapsed
+    )?;
+    if let Some(first_chunk) = report.first_chunk {
+        writeln!(out, "First chunk through in {:?}", first_chunk)?;
+    }
+    writeln!(out, "Received:")?;
+    for (pin, values) in &report.received {
+        writeln!(
+            out,
+            "  {} {} input {}: {}",
+            snarl[pin.node].name(),
+            pin.node.0,
+            pin.input,
+            values
+                .iter()
+                .map(format_value)
+                .collect::<Vec<_>>()
+                .join(" | ")
+        )?;
+    }
+    if !report.failures.is_empty() {
+        writeln!(out, "Failures:")?;
+        for failure in &report.failures {
+            writeln!(
+                out,
+                "  {} {}: {}",
+                snarl[failure.node].name(),
+                failure.node.0,
+                failure.message
+            )?;
+        }
+    }
+    Ok(report)
+}
+
+/// Runs a saved graph as an engine, evaluating it whenever one of its
+/// triggers, or one of `extra_triggers`, fires. The report of every run is
+/// appended to `log`, along with any errors from the graph's hooks. A run is
+/// skipped if its before-run hook fails.
+///
+/// Only returns once none of the triggers will fire again.
+pub fn run_engine(
+    path: &Path,
+    args: &BTreeMap<String, TypedData>,
+    extra_triggers: Vec<Trigger>,
+    log: &mut impl Write,
+) -> Result<(), LoadError> {
+    let saved = graph_file::read_file(path)?;
+    let mut snarl = saved.to_snarl()?;
+    let mut triggers = saved.triggers.clone();
+    triggers.extend(extra_triggers);
+    let mut ctx = context(args);
+
+    loop {
+        let now = SystemTime::now();
+        let upcoming = triggers
+            .iter()
+            .filter_map(|trigger| Some((trigger.schedule.next_after(now)?, trigger)))
+            .collect::<Vec<_>>();
+        let Some(next) = upcoming.iter().map(|(time, _)| *time).min() else {
+            writeln!(log, "No triggers left to fire")?;
+            return Ok(());
+        };
+        std::thread::sleep(next.duration_since(SystemTime::now()).unwrap_or_default());
+
+        // Every trigger due at the same time fires together
+        for (_, trigger) in upcoming.iter().filter(|(time, _)| *time == next) {
+            writeln!(log, "Trigger \"{}\" fired", trigger.schedule)?;
+            match saved.hooks.run_before(&mut snarl) {
+                Ok(printed) => write_lines(log, &printed)?,
+                Err(err) => {
+                    writeln!(log, "{}", err)?;
+                    continue;
+                }
+            }
+            let report = node_graph::evaluate_with(&mut snarl, trigger.start.map(NodeId), &ctx);
+            ctx.tick += 1;
+            let mut text = String::new();
+            let _ = report.write(&mut text);
+            log.write_all(text.as_bytes())?;
+            write_sinks(&snarl, log)?;
+            match saved.hooks.run_after(&snarl, &report) {
+                Ok(printed) => write_lines(log, &printed)?,
+                Err(err) => writeln!(log, "{}", err)?,
+            }
+        }
+        log.flush()?;
+    }
+}
+
+/// Settings for evaluating a graph with the given arguments
+fn context(args: &BTreeMap<String, TypedData>) -> EvalContext {
+    EvalContext {
+        variables: args.clone(),
+        ..EvalContext::default()
+    }
+}
+
+/// Writes what a hook printed
+fn write_lines(out: &mut impl Write, lines: &[String]) -> std::io::Result<()> {
+    for line in lines {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Writes the value reaching every input of the graph's sinks
+fn write_sinks(snarl: &Snarl<Box<dyn Node>>, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "Sinks:")?;
+    for (id, node) in snarl.node_ids() {
+        if !node.outputs().is_empty() {
+            continue;
+        }
+        for input in 0..node.inputs().len() {
+            let value = node_graph::input_value(snarl, InPinId { node: id, input });
+            writeln!(
+                out,
+                "  {} {} input {}: {}",
+                node.name(),
+                id.0,
+                input,
+                value.as_ref().map_or("no value".to_owned(), format_value)
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,464 @@
+//! Pictures of the root graph for docs and design reviews. The graph is laid
+//! out in graph coordinates and drawn at a chosen scale, whatever the zoom of
+//! the canvas, either as an SVG or drawn in software into a PNG.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use eframe::egui::{pos2, vec2, Color32, Pos2, Rect, Vec2};
+use egui_snarl::{NodeId, Snarl};
+use graph_core::{
+    comment::Comment,
+    export::{xml_escape, ExportGraph, Status},
+    node_graph::{Node, TypeColours},
+    run_report::RunReport,
+};
+
+use crate::thumbnail::Image;
+
+/// Space around the graph, in graph coordinates
+const MARGIN: f32 = 20.;
+/// Height of the title bar of a node, and of each of its pins
+const ROW_HEIGHT: f32 = 18.;
+const TEXT_SIZE: f32 = 11.;
+const WIRE_WIDTH: f32 = 2.;
+/// Longest side of a PNG, in pixels, so that huge graphs still fit in
+/// memory. The scale is lowered to fit.
+const MAX_SIDE: f32 = 8192.;
+const BACKGROUND: Color32 = Color32::from_rgb(0x1b, 0x1b, 0x1b);
+const NODE_FILL: Color32 = Color32::from_rgb(0x3c, 0x3c, 0x3c);
+const TEXT: Color32 = Color32::from_rgb(0xdc, 0xdc, 0xdc);
+const UPDATED: Color32 = Color32::from_rgb(0x40, 0xb0, 0x40);
+const FAILED: Color32 = Color32::from_rgb(0xd0, 0x30, 0x30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    pub const ALL: [ImageFormat; 2] = [ImageFormat::Svg, ImageFormat::Png];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "SVG",
+            ImageFormat::Png => "PNG",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+/// Settings of File → Export Image, kept between exports
+#[derive(Debug, Clone)]
+pub struct ImageOptions {
+    /// Pixels for each unit of graph coordinates
+    pub scale: f32,
+    /// Outline nodes by how they fared in the last evaluation
+    pub statuses: bool,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            scale: 2.,
+            statuses: true,
+        }
+    }
+}
+
+struct PictureNode {
+    rect: Rect,
+    label: String,
+    /// Colour of the title bar, that of the node's first output as in the
+    /// thumbnails
+    header: Color32,
+    outline: Option<Color32>,
+    /// Shown when hovering the node in an SVG viewer
+    failure: Option<String>,
+}
+
+/// What is drawn, in pixels
+struct Picture {
+    size: Vec2,
+    scale: f32,
+    comments: Vec<(Rect, Color32, String)>,
+    wires: Vec<(Pos2, Pos2, Color32)>,
+    nodes: Vec<PictureNode>,
+}
+
+impl Picture {
+    /// Lays out the graph. `areas` is the area of the graph covered by each
+    /// node, see [`Canvas::node_areas`](crate::canvas::Canvas::node_areas).
+    fn new(
+        snarl: &Snarl<Box<dyn Node>>,
+        areas: &BTreeMap<NodeId, Rect>,
+        comments: &[Comment],
+        colours: &TypeColours,
+        report: Option<&RunReport>,
+        options: &ImageOptions,
+    ) -> Self {
+        let graph = ExportGraph::new(snarl, report.filter(|_| options.statuses));
+
+        let mut bounds = areas.values().copied().fold(Rect::NOTHING, Rect::union);
+        for comment in comments {
+            bounds = bounds.union(Rect::from_min_size(comment.pos.into(), comment.size.into()));
+        }
+        if !bounds.is_positive() {
+            bounds = Rect::from_min_size(Pos2::ZERO, Vec2::ZERO);
+        }
+        let bounds = bounds.expand(MARGIN);
+        let scale = options
+            .scale
+            .min(MAX_SIDE / bounds.width())
+            .min(MAX_SIDE / bounds.height());
+        let to_image = |pos: Pos2| ((pos - bounds.min) * scale).to_pos2();
+        let to_image_rect = |rect: Rect| Rect::from_min_max(to_image(rect.min), to_image(rect.max));
+
+        let comments = comments
+            .iter()
+            .map(|comment| {
+                let [r, g, b] = comment.colour;
+                let rect = Rect::from_min_size(comment.pos.into(), comment.size.into());
+                (
+                    to_image_rect(rect),
+                    Color32::from_rgb(r, g, b),
+                    comment.title.clone(),
+                )
+            })
+            .collect();
+
+        // Pins are spaced down the sides of their node below its title
+        let pin_y = |rect: Rect, idx: usize| {
+            (rect.min.y + ROW_HEIGHT * (idx as f32 + 1.5)).min(rect.max.y - ROW_HEIGHT / 2.)
+        };
+        let wires = graph
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let from = areas.get(&edge.from)?;
+                let to = areas.get(&edge.to)?;
+                Some((
+                    to_image(pos2(from.max.x, pin_y(*from, edge.output))),
+                    to_image(pos2(to.min.x, pin_y(*to, edge.input))),
+                    edge.data_type.colour(colours),
+                ))
+            })
+            .collect();
+
+        let nodes = graph
+            .nodes
+            .into_iter()
+            .filter_map(|node| {
+                let rect = areas.get(&node.id)?;
+                let header = snarl[node.id]
+                    .outputs()
+                    .first()
+                    .map_or(colours.unknown, |output| output.colour(colours));
+                let (outline, failure) = match node.status {
+                    Some(Status::Updated) => (Some(UPDATED), None),
+                    Some(Status::Failed(message)) => (Some(FAILED), Some(message)),
+                    None => (None, None),
+                };
+                Some(PictureNode {
+                    rect: to_image_rect(*rect),
+                    label: node.label,
+                    header,
+                    outline,
+                    failure,
+                })
+            })
+            .collect();
+
+        Self {
+            size: bounds.size() * scale,
+            scale,
+            comments,
+            wires,
+            nodes,
+        }
+    }
+
+    /// Control points of the curve of a wire, leaving and entering its pins
+    /// horizontally as on the canvas
+    fn wire_curve(&self, from: Pos2, to: Pos2) -> [Pos2; 4] {
+        let bend = ((to.x - from.x).abs() / 2.).max(30. * self.scale);
+        [from, from + vec2(bend, 0.), to - vec2(bend, 0.), to]
+    }
+
+    fn to_svg(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" \
+             viewBox=\"0 0 {w:.1} {h:.1}\" font-family=\"sans-serif\" font-size=\"{:.1}\">",
+            TEXT_SIZE * self.scale,
+            w = self.size.x,
+            h = self.size.y,
+        );
+        let _ = writeln!(
+            out,
+            "  <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+            hex(BACKGROUND)
+        );
+
+        for (rect, colour, title) in &self.comments {
+            let _ = writeln!(
+                out,
+                "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{c}\" \
+                 fill-opacity=\"0.2\" stroke=\"{c}\"/>",
+                rect.min.x,
+                rect.min.y,
+                rect.width(),
+                rect.height(),
+                c = hex(*colour),
+            );
+            self.svg_text(
+                &mut out,
+                rect.min + vec2(4., 4.) * self.scale,
+                title,
+                *colour,
+            );
+        }
+
+        for (from, to, colour) in &self.wires {
+            let [a, b, c, d] = self.wire_curve(*from, *to);
+            let _ = writeln!(
+                out,
+                "  <path d=\"M {:.1} {:.1} C {:.1} {:.1}, {:.1} {:.1}, {:.1} {:.1}\" fill=\"none\" \
+                 stroke=\"{}\" stroke-width=\"{:.1}\"/>",
+                a.x,
+                a.y,
+                b.x,
+                b.y,
+                c.x,
+                c.y,
+                d.x,
+                d.y,
+                hex(*colour),
+                WIRE_WIDTH * self.scale
+            );
+        }
+
+        for node in &self.nodes {
+            let rect = node.rect;
+            out.push_str("  <g>\n");
+            if let Some(failure) = &node.failure {
+                let _ = writeln!(out, "    <title>{}</title>", xml_escape(failure));
+            }
+            let _ = writeln!(
+                out,
+                "    <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{:.1}\" \
+                 fill=\"{}\" stroke=\"{}\" stroke-width=\"{:.1}\"/>",
+                rect.min.x,
+                rect.min.y,
+                rect.width(),
+                rect.height(),
+                3. * self.scale,
+                hex(NODE_FILL),
+                hex(node.outline.unwrap_or(NODE_FILL)),
+                WIRE_WIDTH * self.scale,
+            );
+            let _ = writeln!(
+                out,
+                "    <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\" \
+                 fill-opacity=\"0.6\"/>",
+                rect.min.x,
+                rect.min.y,
+                rect.width(),
+                (ROW_HEIGHT * self.scale).min(rect.height()),
+                hex(node.header),
+            );
+            out.push_str("  ");
+            self.svg_text(
+                &mut out,
+                rect.min + vec2(4., 4.) * self.scale,
+                &node.label,
+                TEXT,
+            );
+            out.push_str("  </g>\n");
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Text whose top left corner is at `pos`
+    fn svg_text(&self, out: &mut String, pos: Pos2, text: &str, colour: Color32) {
+        let _ = writeln!(
+            out,
+            "  <text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\">{}</text>",
+            pos.x,
+            pos.y + TEXT_SIZE * self.scale,
+            hex(colour),
+            xml_escape(text)
+        );
+    }
+
+    fn to_png(&self) -> Vec<u8> {
+        let mut image = Image::new(
+            self.size.x.ceil().max(1.) as usize,
+            self.size.y.ceil().max(1.) as usize,
+            BACKGROUND,
+        );
+        let text_pos = |rect: Rect| rect.min + vec2(4., 4.) * self.scale;
+
+        for (rect, colour, title) in &self.comments {
+            let fill = Color32::from_rgba_unmultiplied(colour.r(), colour.g(), colour.b(), 48);
+            image.fill(*rect, fill);
+            outline(&mut image, *rect, *colour, 1.);
+            self.png_text(&mut image, text_pos(*rect), rect.width(), title, *colour);
+        }
+
+        let width = WIRE_WIDTH * self.scale;
+        for (from, to, colour) in &self.wires {
+            let [a, b, c, d] = self.wire_curve(*from, *to);
+            let point = |t: f32| {
+                let u = 1. - t;
+                let weights = [u * u * u, 3. * u * u * t, 3. * u * t * t, t * t * t];
+                let sum = [a, b, c, d]
+                    .iter()
+                    .zip(weights)
+                    .fold(Vec2::ZERO, |sum, (point, weight)| {
+                        sum + point.to_vec2() * weight
+                    });
+                sum.to_pos2()
+            };
+            const SEGMENTS: usize = 24;
+            for segment in 0..SEGMENTS {
+                let start = point(segment as f32 / SEGMENTS as f32);
+                let end = point((segment + 1) as f32 / SEGMENTS as f32);
+                image.line(start, end, *colour, width);
+            }
+        }
+
+        for node in &self.nodes {
+            let rect = node.rect;
+            image.fill(rect, NODE_FILL);
+            image.fill(
+                rect.with_max_y((rect.min.y + ROW_HEIGHT * self.scale).min(rect.max.y)),
+                node.header.linear_multiply(0.6),
+            );
+            if let Some(colour) = node.outline {
+                outline(&mut image, rect, colour, width);
+            }
+            self.png_text(&mut image, text_pos(rect), rect.width(), &node.label, TEXT);
+        }
+        image.encode()
+    }
+
+    /// Draws text with a small built in font, as there are no fonts to
+    /// rasterise without a window. Text that doesn't fit in `width` is cut
+    /// short.
+    fn png_text(&self, image: &mut Image, pos: Pos2, width: f32, text: &str, colour: Color32) {
+        // The font is 5 dots high, with a dot of space between lines
+        let dot = (TEXT_SIZE * self.scale / 6.).round().max(1.);
+        let advance = dot * 4.;
+        let fits = ((width - 8. * self.scale) / advance).max(0.) as usize;
+        for (idx, chr) in text.chars().take(fits).enumerate() {
+            let origin = pos + vec2(advance * idx as f32, 0.);
+            for (row, bits) in glyph(chr).into_iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0 {
+                        let min = origin + vec2(column as f32, row as f32) * dot;
+                        image.fill(Rect::from_min_size(min, Vec2::splat(dot)), colour);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn outline(image: &mut Image, rect: Rect, colour: Color32, width: f32) {
+    let [a, b, c, d] = [
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+    ];
+    for (from, to) in [(a, b), (b, c), (c, d), (d, a)] {
+        image.line(from, to, colour, width);
+    }
+}
+
+fn hex(colour: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", colour.r(), colour.g(), colour.b())
+}
+
+/// Rows of a character in a 3 by 5 dot font, with the leftmost dot in the
+/// highest of the three bits. Letters are drawn in capitals.
+fn glyph(chr: char) -> [u8; 5] {
+    match chr.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        ' ' => [0; 5],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010],
+    }
+}
+
+/// Draws the graph as an image file. `areas` is the area of the graph
+/// covered by each node, and `report` the evaluation whose statuses are
+/// shown, if [`ImageOptions::statuses`] is set.
+pub fn render(
+    format: ImageFormat,
+    snarl: &Snarl<Box<dyn Node>>,
+    areas: &BTreeMap<NodeId, Rect>,
+    comments: &[Comment],
+    colours: &TypeColours,
+    report: Option<&RunReport>,
+    options: &ImageOptions,
+) -> Vec<u8> {
+    let picture = Picture::new(snarl, areas, comments, colours, report, options);
+    match format {
+        ImageFormat::Svg => picture.to_svg().into_bytes(),
+        ImageFormat::Png => picture.to_png(),
+    }
+}
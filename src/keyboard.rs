@@ -0,0 +1,140 @@
+//! Editing the graph without a mouse. The canvas takes the keyboard focus
+//! like any other widget, after which Tab moves between its nodes and pins,
+//! Alt and the arrow keys jump to the nearest node in that direction, and
+//! Enter on one pin and then another wires them together.
+
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use eframe::egui::{self, Rect, Vec2};
+use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+use graph_core::node_graph::{self, DataType, Node};
+
+/// Id of the widget covering the canvas, which has the keyboard focus while
+/// the graph is being edited from the keyboard
+pub fn canvas_id() -> egui::Id {
+    egui::Id::new("graph_keyboard")
+}
+
+/// A node or pin the keyboard is focused on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Node(NodeId),
+    Input(InPinId),
+    Output(OutPinId),
+}
+
+impl Focus {
+    pub fn node(self) -> NodeId {
+        match self {
+            Focus::Node(node) => node,
+            Focus::Input(pin) => pin.node,
+            Focus::Output(pin) => pin.node,
+        }
+    }
+
+    pub fn is_pin(self) -> bool {
+        !matches!(self, Focus::Node(_))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct KeyboardNav {
+    pub focus: Option<Focus>,
+    /// Pin picked as the first end of a wire, waiting for the other end
+    pub connecting: Option<Focus>,
+    /// Set while the palette opened from the keyboard is showing, so that
+    /// the canvas gets the focus back, on the added node, once it closes
+    pub palette_open: bool,
+}
+
+/// Every node drawn on the canvas and its pins, in the order Tab visits
+/// them: nodes top to bottom then left to right, each followed by its
+/// inputs and then its outputs
+pub fn tab_order(snarl: &Snarl<Box<dyn Node>>, rects: &BTreeMap<NodeId, Rect>) -> Vec<Focus> {
+    let mut nodes = rects
+        .iter()
+        .filter(|(id, _)| snarl.get_node(**id).is_some())
+        .collect::<Vec<_>>();
+    nodes.sort_by(|(_, a), (_, b)| {
+        (a.top(), a.left())
+            .partial_cmp(&(b.top(), b.left()))
+            .unwrap_or(Ordering::Equal)
+    });
+    let mut order = Vec::new();
+    for (&node, _) in nodes {
+        order.push(Focus::Node(node));
+        order.extend(
+            (0..snarl[node].inputs().len()).map(|input| Focus::Input(InPinId { node, input })),
+        );
+        order.extend(
+            (0..snarl[node].outputs().len()).map(|output| Focus::Output(OutPinId { node, output })),
+        );
+    }
+    order
+}
+
+/// What comes after `current` in `order`, or before it if `backwards`,
+/// wrapping around at the ends
+pub fn step(order: &[Focus], current: Option<Focus>, backwards: bool) -> Option<Focus> {
+    let position = current.and_then(|current| order.iter().position(|focus| *focus == current));
+    let idx = match (position, backwards) {
+        (Some(idx), false) => (idx + 1) % order.len(),
+        (Some(idx), true) => (idx + order.len() - 1) % order.len(),
+        (None, false) => 0,
+        (None, true) => order.len().checked_sub(1)?,
+    };
+    order.get(idx).copied()
+}
+
+/// The node closest to `from` in `direction`, preferring those in line with
+/// it over those off to the side
+pub fn nearest(rects: &BTreeMap<NodeId, Rect>, from: NodeId, direction: Vec2) -> Option<NodeId> {
+    let origin = rects.get(&from)?.center();
+    rects
+        .iter()
+        .filter(|(id, _)| **id != from)
+        .filter_map(|(id, rect)| {
+            let offset = rect.center() - origin;
+            let along = offset.dot(direction);
+            let across = (offset - direction * along).length();
+            (along > 0.).then_some((along + 2. * across, *id))
+        })
+        .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map(|(_, id)| id)
+}
+
+/// Spoken description of a node or pin, for screen readers
+pub fn describe(snarl: &Snarl<Box<dyn Node>>, focus: Focus) -> String {
+    let Some(node) = snarl.get_node(focus.node()) else {
+        return String::new();
+    };
+    let name = node_graph::display_name(node.as_ref(), focus.node());
+    match focus {
+        Focus::Node(_) => {
+            let mut text = format!(
+                "{} node, {} input(s), {} output(s)",
+                name,
+                node.inputs().len(),
+                node.outputs().len()
+            );
+            if let Some(error) = node.error() {
+                text.push_str(&format!(", failed: {}", error));
+            }
+            text
+        }
+        Focus::Input(pin) => format!(
+            "Input {} of {}, {:?}, {} wire(s)",
+            pin.input + 1,
+            name,
+            node.inputs().get(pin.input).unwrap_or(&DataType::Unknown),
+            snarl.in_pin(pin).remotes.len()
+        ),
+        Focus::Output(pin) => format!(
+            "Output {} of {}, {:?}, {} wire(s)",
+            pin.output + 1,
+            name,
+            node.outputs().get(pin.output).unwrap_or(&DataType::Unknown),
+            snarl.out_pin(pin).remotes.len()
+        ),
+    }
+}
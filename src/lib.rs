@@ -0,0 +1,21 @@
+pub mod canvas;
+pub mod documents;
+pub mod editor;
+pub mod file_io;
+pub mod headless;
+pub mod image_export;
+pub mod keyboard;
+pub mod log_console;
+pub mod minimap;
+pub mod notifications;
+pub mod palette;
+pub mod profiler;
+pub mod settings;
+pub mod simulation;
+pub mod state_server;
+pub mod sweep;
+pub mod thumbnail;
+pub mod timeline;
+pub mod undo;
+pub mod viewer;
+pub mod wires;
@@ -0,0 +1,121 @@
+//! Messages logged by nodes across evaluations, filtered by node and level
+
+use std::collections::VecDeque;
+
+use eframe::egui;
+use egui_snarl::{NodeId, Snarl};
+use graph_core::{
+    node_graph::{display_name, Node},
+    run_report::{format_timestamp, LogLevel, NodeMessage},
+};
+use web_time::SystemTime;
+
+/// Most entries shown at once, newest first, to keep long logs responsive
+const MAX_SHOWN: usize = 500;
+
+/// Filters applied to the log
+#[derive(Debug, Default)]
+pub struct LogConsole {
+    /// Least important level shown
+    pub level: LogLevel,
+    /// Only show messages from this node
+    pub node: Option<NodeId>,
+    /// Only show messages containing this text
+    pub search: String,
+    /// Messages logged before this time are hidden, once the log is cleared
+    pub cleared_at: Option<SystemTime>,
+}
+
+/// Entries the user pointed at in the log
+#[derive(Debug, Default)]
+pub struct LogResponse {
+    pub hovered: Option<NodeId>,
+    pub clicked: Option<NodeId>,
+}
+
+impl LogConsole {
+    /// Shows the entries of `log` matching the filters, newest first
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        log: &VecDeque<NodeMessage>,
+        snarl: &Snarl<Box<dyn Node>>,
+    ) -> LogResponse {
+        let mut response = LogResponse::default();
+        let name = |id: NodeId| match snarl.get_node(id) {
+            Some(node) => display_name(node.as_ref(), id),
+            None => format!("Node {}", id.0),
+        };
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("log_level")
+                .selected_text(self.level.to_string())
+                .show_ui(ui, |ui| {
+                    for level in LogLevel::ALL {
+                        ui.selectable_value(&mut self.level, level, level.to_string());
+                    }
+                });
+            egui::ComboBox::from_id_source("log_node")
+                .selected_text(self.node.map_or_else(|| "All nodes".to_owned(), name))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.node, None, "All nodes");
+                    for (id, _) in snarl.node_ids() {
+                        ui.selectable_value(&mut self.node, Some(id), name(id));
+                    }
+                });
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("Search")
+                    .desired_width(100.),
+            );
+            if ui.small_button("Clear").clicked() {
+                self.cleared_at = Some(SystemTime::now());
+            }
+        });
+
+        let search = self.search.to_lowercase();
+        let shown = log
+            .iter()
+            .rev()
+            .filter(|entry| self.cleared_at.is_none_or(|cleared| entry.time > cleared))
+            .filter(|entry| entry.level >= self.level)
+            .filter(|entry| self.node.is_none_or(|node| entry.node == node))
+            .filter(|entry| search.is_empty() || entry.message.to_lowercase().contains(&search))
+            .take(MAX_SHOWN)
+            .collect::<Vec<_>>();
+        if shown.is_empty() {
+            ui.label("No messages");
+            return response;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for entry in shown {
+                let colour = match entry.level {
+                    LogLevel::Debug => ui.visuals().weak_text_color(),
+                    LogLevel::Info => ui.visuals().text_color(),
+                    LogLevel::Warning => ui.visuals().warn_fg_color,
+                    LogLevel::Error => ui.visuals().error_fg_color,
+                };
+                let row = ui
+                    .horizontal(|ui| {
+                        ui.weak(format_timestamp(entry.time));
+                        ui.colored_label(colour, entry.level.to_string());
+                        ui.add(
+                            egui::Label::new(format!("{}: {}", name(entry.node), entry.message))
+                                .sense(egui::Sense::click()),
+                        )
+                    })
+                    .inner
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text("Click to go to the node");
+                if row.hovered() {
+                    response.hovered = Some(entry.node);
+                }
+                if row.clicked() {
+                    response.clicked = Some(entry.node);
+                }
+            }
+        });
+        response
+    }
+}
@@ -1,19 +1,15 @@
-use std::collections::{HashMap, HashSet};
-
-use eframe::{
-    egui::{self, Label, Layout, Margin},
-    epaint::{Color32, Rounding},
-};
-use egui_snarl::{
-    ui::{BackgroundPattern, SnarlStyle},
-    Snarl,
+use eframe::egui::{self, Margin};
+use graph_gui::{
+    documents::Documents, editor::GraphEditorWidget, profiler::ProfilerWindow, settings::Settings,
 };
-use egui_tiles::{Container, Linear, LinearDir, Tile};
 
-mod execution_engine;
-mod node_graph;
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = std::env::args().collect::<Vec<_>>();
+    if let Some(idx) = args.iter().position(|arg| arg == "--headless") {
+        return run_headless(&args[idx + 1..]);
+    }
 
-fn main() -> Result<(), eframe::Error> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([900.0, 600.0]),
         ..Default::default()
@@ -27,248 +23,195 @@ fn main() -> Result<(), eframe::Error> {
             });
             Box::<MyApp>::default()
         }),
-    )
-}
-
-pub enum Pane {
-    Config,
-    Nodes,
-    Statistics,
-}
-
-impl Pane {
-    pub fn ui(&mut self, ui: &mut egui::Ui) -> egui_tiles::UiResponse {
-        ui.label("some pane");
-        let dragged = ui
-            .allocate_rect(ui.max_rect(), egui::Sense::drag())
-            .on_hover_cursor(egui::CursorIcon::Grab)
-            .dragged();
-        if dragged {
-            egui_tiles::UiResponse::DragStarted
-        } else {
-            egui_tiles::UiResponse::None
-        }
-    }
-}
-
-struct TreeBehavior<'a> {
-    snarl: &'a mut Snarl<Box<dyn node_graph::Node>>,
-    style: &'a SnarlStyle,
-    task_execution: &'a mut Option<execution_engine::TaskDag>,
+    )?;
+    Ok(())
 }
 
-impl<'a> egui_tiles::Behavior<Pane> for TreeBehavior<'a> {
-    fn pane_ui(
-        &mut self,
-        ui: &mut egui::Ui,
-        _tile_id: egui_tiles::TileId,
-        pane: &mut Pane,
-    ) -> egui_tiles::UiResponse {
-        // Title bar
-        let (response, painter) = ui.allocate_painter(
-            egui::Vec2::new(ui.available_width(), 20.),
-            egui::Sense::click_and_drag(),
-        );
-        painter.rect_filled(painter.clip_rect(), Rounding::same(3.), Color32::LIGHT_GRAY);
-
-        // Title
-        let label_resp = ui
-            .child_ui(
-                painter.clip_rect(),
-                Layout::left_to_right(egui::Align::Center),
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    wasm_bindgen_futures::spawn_local(async {
+        eframe::WebRunner::new()
+            .start(
+                "graph_gui_canvas",
+                eframe::WebOptions::default(),
+                Box::new(|_cc| Box::<MyApp>::default()),
             )
-            .add(Label::new(self.tab_title_for_pane(pane)));
+            .await
+            .expect("failed to start eframe");
+    });
+}
 
-        match pane {
-            Pane::Config => {}
-            Pane::Nodes => {
-                self.snarl.show(
-                    &mut node_graph::DemoViewer,
-                    self.style,
-                    egui::Id::new("snarl"),
-                    ui,
-                );
+/// Evaluates a saved graph and prints the results instead of opening the
+/// editor.
+///
+/// `--headless <graph.json> [--set <name>=<value>]... [--stdin] [--engine]
+/// [--cron <schedule>]... [--log <file>] [--trace <file>]
+/// [--sweep <node>=<start>:<end>:<steps>]...
+/// [--pipeline <node>[:<output>] [--chunk-size <n>]]`
+///
+/// Argument nodes take their values from `--set`, or from a JSON object of
+/// names to values piped in with `--stdin`, with `--set` taking precedence.
+/// With `--engine` the graph keeps running, and is evaluated whenever one of
+/// the triggers saved with it or given with `--cron` fires. Reports are
+/// appended to the `--log` file, or printed if there isn't one. With
+/// `--sweep` the graph is evaluated for every combination of values of the
+/// swept Number nodes, and the results printed as CSV. With `--pipeline` the
+/// list from the given output is streamed through the nodes after it in
+/// chunks. Otherwise the graph is evaluated once, and `--trace` saves the run
+/// as a Chrome trace.
+#[cfg(not(target_arch = "wasm32"))]
+fn run_headless(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    use graph_gui::headless;
+
+    let mut path = None;
+    let mut values = std::collections::BTreeMap::new();
+    let mut stdin = false;
+    let mut engine = false;
+    let mut triggers = Vec::new();
+    let mut log = None;
+    let mut trace = None;
+    let mut sweeps = Vec::new();
+    let mut pipeline = None;
+    let mut chunk_size = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--set" => {
+                let assignment = args.next().ok_or("--set requires a name and value")?;
+                let (name, value) = graph_core::args::parse_assignment(assignment)?;
+                values.insert(name, value);
             }
-            Pane::Statistics => {
-                egui::Frame::central_panel(ui.style()).show(ui, |ui| {
-                    if ui.button("Calculate Task Dag").clicked() {
-                        let graph = node_graph::DemoViewer::as_petgraph(self.snarl);
-                        *self.task_execution = Some(execution_engine::TaskDag::new(&graph))
-                    }
-
-                    if let Some(task_dag) = self.task_execution {
-                        let ready_tasks = task_dag.ready_tasks().collect::<HashSet<_>>();
-                        let blocked_tasks = task_dag.blocked_tasks().collect::<HashSet<_>>();
-                        for (id, _node) in self.snarl.node_ids() {
-                            ui.group(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(format!("Task ID: {}", id.0));
-                                    ui.separator();
-                                    if ready_tasks.contains(&id) {
-                                        if ui.button("Complete").clicked() {
-                                            let _res = task_dag.complete_task(id);
-                                            // TODO: Do something with the newly ready tasks
-                                        }
-                                    } else if blocked_tasks.contains(&id) {
-                                        ui.label("Blocked");
-                                    } else {
-                                        ui.label("Completed");
-                                    }
-                                })
-                            });
-                        }
-                    }
+            "--stdin" => stdin = true,
+            "--engine" => engine = true,
+            "--cron" => {
+                let schedule = args.next().ok_or("--cron requires a schedule")?;
+                triggers.push(graph_core::cron::Trigger {
+                    schedule: schedule.parse()?,
+                    start: None,
                 });
+                engine = true;
+            }
+            "--log" => log = Some(args.next().ok_or("--log requires a file")?),
+            "--trace" => {
+                trace = Some(std::path::Path::new(
+                    args.next().ok_or("--trace requires a file")?,
+                ))
+            }
+            "--sweep" => {
+                let sweep = args.next().ok_or("--sweep requires a node and range")?;
+                sweeps.push(sweep.parse()?);
+            }
+            "--pipeline" => {
+                let source = args.next().ok_or("--pipeline requires a node")?;
+                let (node, output) = source.split_once(':').unwrap_or((source, "0"));
+                pipeline = Some(egui_snarl::OutPinId {
+                    node: egui_snarl::NodeId(node.parse()?),
+                    output: output.parse()?,
+                });
+            }
+            "--chunk-size" => {
+                let size = args.next().ok_or("--chunk-size requires a number")?;
+                chunk_size = Some(size.parse()?);
             }
+            arg if path.is_none() => path = Some(std::path::Path::new(arg)),
+            arg => return Err(format!("unexpected argument {:?}", arg).into()),
         }
+    }
+    let path = path.ok_or("--headless requires a graph file")?;
+    if stdin {
+        let mut text = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)?;
+        let mut piped = graph_core::args::parse_json(&text)?;
+        piped.append(&mut values);
+        values = piped;
+    }
 
-        // Allow dragging from the title bar
-        if response
-            .union(label_resp)
-            .on_hover_cursor(egui::CursorIcon::Grab)
-            .dragged_by(egui::PointerButton::Primary)
-        {
-            egui_tiles::UiResponse::DragStarted
-        } else {
-            egui_tiles::UiResponse::None
+    if engine {
+        match log {
+            Some(log) => {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(log)?;
+                headless::run_engine(path, &values, triggers, &mut file)?;
+            }
+            None => headless::run_engine(path, &values, triggers, &mut std::io::stdout().lock())?,
         }
+        return Ok(());
     }
-
-    fn tab_title_for_pane(&mut self, pane: &Pane) -> egui::WidgetText {
-        match pane {
-            Pane::Config => "Config".into(),
-            Pane::Nodes { .. } => "Nodes".into(),
-            Pane::Statistics => "Statistics".into(),
+    if let Some(source) = pipeline {
+        let mut pipeline = graph_core::pipeline::Pipeline::new(source);
+        if let Some(chunk_size) = chunk_size {
+            pipeline = pipeline.chunk_size(chunk_size);
         }
+        let report =
+            headless::run_pipeline(path, &values, pipeline, &mut std::io::stdout().lock())?;
+        if !report.failures.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if !sweeps.is_empty() {
+        return headless::run_sweep(path, &values, &sweeps, &mut std::io::stdout().lock());
     }
-}
 
-struct MyApp {
-    tree: egui_tiles::Tree<Pane>,
-    snarl: Snarl<Box<dyn node_graph::Node>>,
-    style: SnarlStyle,
-    task_execution: Option<execution_engine::TaskDag>,
+    let report = headless::run(path, &values, &mut std::io::stdout().lock(), trace)?;
+    if !report.failures.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
 }
 
-impl Default for MyApp {
-    fn default() -> Self {
-        let mut tiles = egui_tiles::Tiles::default();
-
-        node_graph::DemoViewer::evaluate(&mut snarl, None);
-
-        let mut style = SnarlStyle::new();
-        style.downscale_wire_frame = true;
-        style.bg_pattern = BackgroundPattern::Grid(egui_snarl::ui::Grid {
-            angle: 0.,
-            ..Default::default()
-        });
-
-        let config_pane = tiles.insert_pane(Pane::Config);
-        let nodes_pane = tiles.insert_pane(Pane::Nodes);
-        let stats_pane = tiles.insert_pane(Pane::Statistics);
-
-        let mut inner = Linear {
-            children: vec![config_pane, nodes_pane, stats_pane],
-            dir: LinearDir::Horizontal,
-            ..Default::default()
-        };
-        inner.shares.set_share(config_pane, 1.);
-        inner.shares.set_share(nodes_pane, 3.);
-        inner.shares.set_share(stats_pane, 1.);
-        let root = tiles.insert_new(Tile::Container(Container::Linear(inner)));
-
-        let tree = egui_tiles::Tree::new("tree", root, tiles);
-
-        Self {
-            tree,
-            snarl,
-            style,
-            task_execution: None,
-        }
-    }
+#[derive(Default)]
+struct MyApp {
+    documents: Documents,
+    settings: Settings,
+    profiler: ProfilerWindow,
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let scope = graph_core::profiler::scope("frame");
+        self.documents.handle_dropped_files(ctx);
+        let mut editor = GraphEditorWidget::new().settings(&mut self.settings);
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
-                    if ui.button("Quit").clicked() {
+                ui.menu_button(graph_core::i18n::tr("menu-file"), |ui| {
+                    if ui.button(graph_core::i18n::tr("menu-quit")).clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close)
                     }
-                    if ui.button("Export Graph").clicked() {
-                        if let Some(path) = rfd::FileDialog::new()
-                            .add_filter("Graph File", &["dot"])
-                            .save_file()
-                        {
-                            let graph = node_graph::DemoViewer::as_petgraph(&mut self.snarl);
-
-                            // Write to file
-                            std::fs::write(
-                                path,
-                                format!("{:?}", petgraph::dot::Dot::with_config(&graph, &[])),
-                            )
-                            .unwrap();
-                        }
-                    }
-                    if ui.button("Eval").clicked() {
-                        node_graph::DemoViewer::evaluate(&mut self.snarl, None);
+                    if ui.button(graph_core::i18n::tr("menu-new-graph")).clicked() {
+                        self.documents.new_graph();
+                        ui.close_menu();
                     }
+                    editor.file_menu(ui, self.documents.active());
+                });
+                ui.menu_button(graph_core::i18n::tr("menu-edit"), |ui| {
+                    editor.edit_menu(ui, self.documents.active());
+                });
+                ui.menu_button(graph_core::i18n::tr("menu-view"), |ui| {
+                    editor.view_menu(ui, self.documents.active());
+                    ui.checkbox(
+                        &mut self.profiler.open,
+                        graph_core::i18n::tr("menu-profiler"),
+                    )
+                    .on_hover_text("Break down where the time of each frame goes");
                 });
 
                 egui::widgets::global_dark_light_mode_switch(ui);
             });
         });
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            self.documents.tabs_ui(ui);
+        });
 
         egui::CentralPanel::default()
             .frame(egui::Frame::central_panel(&ctx.style()).inner_margin(Margin::ZERO))
             .show(ctx, |ui| {
-                self.tree.ui(
-                    &mut TreeBehavior {
-                        snarl: &mut self.snarl,
-                        style: &self.style,
-                        task_execution: &mut self.task_execution,
-                    },
-                    ui,
-                );
+                editor.show(ui, self.documents.active());
             });
-    }
-}
-
-#[allow(dead_code)]
-fn series_parallel(graph: &petgraph::prelude::Graph<egui_snarl::NodeId, ()>) {
-    // Create map of all nodes and their dependencies
-    let mut data = HashMap::new();
-    for idx in graph.node_indices() {
-        let node_deps = graph
-            .neighbors_directed(idx, petgraph::Direction::Incoming)
-            .map(|idx| graph[idx])
-            .collect::<HashSet<_>>();
-        data.insert(graph[idx], node_deps);
-    }
-
-    loop {
-        // Find all dependents with no outstanding dependencies
-        let ordered = data
-            .iter()
-            .filter_map(|(k, v)| v.is_empty().then_some(*k))
-            .collect::<HashSet<_>>();
-        // If there is none remaining, break
-        if ordered.is_empty() {
-            break;
-        }
-
-        let mut temp_ordered = ordered.iter().copied().collect::<Vec<_>>();
-        temp_ordered.sort_unstable();
-        println!("{:?}", temp_ordered);
-
-        data = data
-            .into_iter()
-            .filter(|(k, _v)| !ordered.contains(k))
-            .map(|(k, v)| (k, v.difference(&ordered).copied().collect()))
-            .collect();
-    }
-    if !data.is_empty() {
-        panic!("cyclic graph");
+        drop(scope);
+        self.profiler.show(ctx);
     }
 }
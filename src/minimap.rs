@@ -0,0 +1,72 @@
+//! Overview of the whole graph in a corner of the canvas, showing which part
+//! of it is in view
+
+use eframe::egui::{self, vec2, Pos2, Rect, Sense, Stroke, Vec2};
+
+const SIZE: Vec2 = vec2(180., 120.);
+/// Space between the minimap and the edges of the canvas
+const MARGIN: f32 = 8.;
+/// Space inside the minimap around the graph
+const PADDING: f32 = 6.;
+
+/// Draws the minimap over the bottom right of the canvas, returning the
+/// position on the graph that was clicked.
+///
+/// `nodes` are the areas of the graph covered by each node, and whether it
+/// is selected, and `visible` is the area of the graph in view.
+pub fn show(ui: &egui::Ui, canvas: Rect, nodes: &[(Rect, bool)], visible: Rect) -> Option<Pos2> {
+    if nodes.is_empty() || canvas.width() < SIZE.x * 2. || canvas.height() < SIZE.y * 2. {
+        return None;
+    }
+    let bounds = nodes
+        .iter()
+        .fold(visible, |bounds, (area, _)| bounds.union(*area));
+
+    egui::Area::new(ui.id().with("minimap"))
+        .order(egui::Order::Middle)
+        .fixed_pos(canvas.right_bottom() - SIZE - Vec2::splat(MARGIN))
+        .show(ui.ctx(), |ui| {
+            let (rect, response) = ui.allocate_exact_size(SIZE, Sense::click());
+            let visuals = ui.visuals();
+            let painter = ui.painter_at(rect);
+            painter.rect(
+                rect,
+                4.,
+                visuals.extreme_bg_color.gamma_multiply(0.9),
+                visuals.widgets.noninteractive.bg_stroke,
+            );
+
+            let inner = rect.shrink(PADDING);
+            let scale = (inner.width() / bounds.width()).min(inner.height() / bounds.height());
+            let to_screen = |pos: Pos2| inner.center() + (pos - bounds.center()) * scale;
+            let to_graph = |pos: Pos2| bounds.center() + (pos - inner.center()) / scale;
+
+            for (area, selected) in nodes {
+                let colour = if *selected {
+                    visuals.selection.bg_fill
+                } else {
+                    visuals.weak_text_color()
+                };
+                painter.rect_filled(
+                    Rect::from_min_max(to_screen(area.min), to_screen(area.max)),
+                    1.,
+                    colour,
+                );
+            }
+            painter.rect_stroke(
+                Rect::from_min_max(to_screen(visible.min), to_screen(visible.max)),
+                1.,
+                Stroke::new(1., visuals.strong_text_color()),
+            );
+
+            let response = response
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Click to move the view there");
+            if response.clicked() {
+                response.interact_pointer_pos().map(to_graph)
+            } else {
+                None
+            }
+        })
+        .inner
+}
@@ -0,0 +1,96 @@
+//! Messages shown briefly in the corner of the editor, such as an action
+//! failing, so that problems are reported to the user rather than only to
+//! the terminal
+
+use eframe::egui::{self, Align2};
+
+/// How long a notification is shown for, in seconds
+const SHOW_FOR: f64 = 6.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    /// When the notification was first drawn, from [`egui::InputState::time`]
+    shown_at: Option<f64>,
+}
+
+/// Notifications waiting to be shown or still being shown, oldest first
+#[derive(Debug, Default)]
+pub struct Notifications {
+    notifications: Vec<Notification>,
+}
+
+impl Notifications {
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Level::Info, message.into());
+    }
+
+    /// Reports that something the user asked for failed. Errors are also
+    /// written to stderr, where they were reported before.
+    pub fn error(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        eprintln!("{}", message);
+        self.push(Level::Error, message);
+    }
+
+    fn push(&mut self, level: Level, message: String) {
+        self.notifications.push(Notification {
+            level,
+            message,
+            shown_at: None,
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Notification> {
+        self.notifications.iter()
+    }
+
+    /// Draws the notifications in the bottom right corner, stacked upwards,
+    /// dropping those that have been shown for long enough. Clicking one
+    /// dismisses it.
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        self.notifications
+            .retain(|notification| notification.shown_at.is_none_or(|at| now - at < SHOW_FOR));
+        if self.notifications.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new("notifications")
+            .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-8., -8.))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for (idx, notification) in self.notifications.iter_mut().enumerate().rev() {
+                    notification.shown_at.get_or_insert(now);
+                    let colour = match notification.level {
+                        Level::Info => ui.visuals().text_color(),
+                        Level::Error => ui.visuals().error_fg_color,
+                    };
+                    let response = egui::Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(1., colour.gamma_multiply(0.5)))
+                        .show(ui, |ui| {
+                            ui.set_max_width(320.);
+                            ui.colored_label(colour, &notification.message);
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to dismiss");
+                    if response.clicked() {
+                        dismissed = Some(idx);
+                    }
+                }
+            });
+        if let Some(idx) = dismissed {
+            self.notifications.remove(idx);
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(SHOW_FOR));
+    }
+}
@@ -0,0 +1,197 @@
+//! Searching the registered node types by name to add one to the graph, and
+//! the graph's own nodes to go to one
+
+use eframe::egui::{self, Pos2};
+use egui_snarl::{NodeId, Snarl};
+use graph_core::{
+    i18n,
+    node_graph::{self, Node},
+    registry::{self, NodeKind},
+    template::TemplateLibrary,
+};
+
+/// A node type or template picked to be added to the graph
+pub enum Picked {
+    Node(NodeKind),
+    /// Index of a template in the [`TemplateLibrary`]
+    Template(usize),
+}
+
+/// Popup for quickly adding a node, opened with Ctrl+Space
+pub struct NodePalette {
+    query: String,
+    /// Where the chosen node is inserted, in graph coordinates
+    pub pos: Pos2,
+    /// Where the popup is shown on screen
+    screen_pos: Pos2,
+    selected: usize,
+}
+
+impl NodePalette {
+    pub fn new(pos: Pos2, screen_pos: Pos2) -> Self {
+        Self {
+            query: String::new(),
+            pos,
+            screen_pos,
+            selected: 0,
+        }
+    }
+
+    /// Shows the popup, returning the node type or template the user picked.
+    /// `open` is cleared when the user dismisses the popup.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        templates: &TemplateLibrary,
+    ) -> Option<Picked> {
+        let mut picked = None;
+        let area = egui::Area::new("node_palette")
+            .order(egui::Order::Foreground)
+            .fixed_pos(self.screen_pos)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(200.);
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.query).hint_text("Search nodes…"),
+                    );
+                    response.request_focus();
+                    if response.changed() {
+                        self.selected = 0;
+                    }
+                    picked = results_ui(ui, &self.query, &mut self.selected, templates);
+                });
+            });
+        if picked.is_some()
+            || area.response.clicked_elsewhere()
+            || ctx.input(|i| i.key_pressed(egui::Key::Escape))
+        {
+            *open = false;
+        }
+        picked
+    }
+}
+
+/// How well `query` matches `text`, if every character of the query appears
+/// in the text in order. Higher scores are better matches.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut start = 0;
+    let mut previous = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let idx = start + text[start..].iter().position(|t| *t == c)?;
+        // Reward runs of consecutive characters and the starts of words
+        if previous == Some(idx.wrapping_sub(1)) {
+            score += 5;
+        }
+        if idx == 0 || !text[idx - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (idx - start) as i32;
+        previous = Some(idx);
+        start = idx + 1;
+    }
+    Some(score)
+}
+
+/// Score of a match by name, or by category and name
+fn match_score(query: &str, name: &str, category: &str) -> Option<i32> {
+    let by_name = fuzzy_score(query, name);
+    let by_category = fuzzy_score(query, &format!("{} {}", category, name));
+    by_name.map(|score| score + 10).max(by_category)
+}
+
+/// Templates and node types matching `query` by name or category, best
+/// match first
+pub fn search(query: &str, templates: &TemplateLibrary) -> Vec<Picked> {
+    let templates = templates
+        .templates
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, template)| {
+            let score = match_score(query, &template.name, "Templates")?;
+            Some((score, Picked::Template(idx)))
+        });
+    let nodes = registry::node_kinds()
+        .into_iter()
+        // Group nodes are made by grouping existing nodes instead
+        .filter(|kind| kind.category != "Groups")
+        .filter_map(|kind| {
+            // Match the names shown as well as the English ones, which
+            // users of other languages may still know the nodes by
+            let translated = match_score(
+                query,
+                &i18n::node_name(&kind.name),
+                &i18n::category_name(&kind.category),
+            );
+            let score = match_score(query, &kind.name, &kind.category).max(translated)?;
+            Some((score, Picked::Node(kind)))
+        });
+    let mut matches = templates.chain(nodes).collect::<Vec<_>>();
+    // Stable, so equal matches keep the templates first, then the registry's
+    // order
+    matches.sort_by_key(|(score, _)| -score);
+    matches.into_iter().map(|(_, picked)| picked).collect()
+}
+
+/// Lists the templates and node types matching `query`, which can be picked
+/// by clicking them or with the arrow keys and Enter
+pub fn results_ui(
+    ui: &mut egui::Ui,
+    query: &str,
+    selected: &mut usize,
+    templates: &TemplateLibrary,
+) -> Option<Picked> {
+    let mut results = search(query, templates);
+    if results.is_empty() {
+        ui.weak("No matching nodes");
+        return None;
+    }
+
+    let (up, down, enter) = ui.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+        )
+    });
+    if up {
+        *selected = selected.saturating_sub(1);
+    }
+    if down {
+        *selected += 1;
+    }
+    *selected = (*selected).min(results.len() - 1);
+
+    let mut picked = enter.then_some(*selected);
+    for (idx, result) in results.iter().enumerate() {
+        let text = match result {
+            Picked::Node(kind) => format!(
+                "{}  ·  {}",
+                i18n::node_name(&kind.name),
+                i18n::category_name(&kind.category)
+            ),
+            Picked::Template(template) => {
+                format!("{}  ·  Template", templates.templates[*template].name)
+            }
+        };
+        if ui.selectable_label(idx == *selected, text).clicked() {
+            picked = Some(idx);
+        }
+    }
+    picked.map(|idx| results.swap_remove(idx))
+}
+
+/// Nodes of a graph matching `query` by label or type, best match first
+pub fn find_nodes(query: &str, snarl: &Snarl<Box<dyn Node>>) -> Vec<NodeId> {
+    let mut matches = snarl
+        .node_ids()
+        .filter_map(|(id, node)| {
+            let name = node_graph::display_name(node.as_ref(), id);
+            Some((match_score(query, &name, &node.name())?, id))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(score, id)| (-score, *id));
+    matches.into_iter().map(|(_, id)| id).collect()
+}
@@ -0,0 +1,284 @@
+//! Window breaking recent frames down into the profiled scopes they spent
+//! their time in
+
+use std::{collections::VecDeque, time::Duration};
+
+use eframe::egui::{self, pos2, vec2, Rect, Sense, Stroke};
+use graph_core::profiler::{self, ScopeRecord};
+use web_time::Instant;
+
+/// Number of recent frames kept
+const MAX_FRAMES: usize = 300;
+const CHART_HEIGHT: f32 = 60.;
+const ROW_HEIGHT: f32 = 18.;
+/// Most scope names listed under the slowest scopes
+const MAX_TOTALS: usize = 20;
+
+/// Scopes finished during one frame
+struct ProfiledFrame {
+    start: Instant,
+    duration: Duration,
+    scopes: Vec<ScopeRecord>,
+}
+
+impl ProfiledFrame {
+    fn new(scopes: Vec<ScopeRecord>) -> Option<Self> {
+        let start = scopes.iter().map(|scope| scope.start).min()?;
+        let end = scopes.iter().map(ScopeRecord::end).max()?;
+        Some(Self {
+            start,
+            duration: end.duration_since(start),
+            scopes,
+        })
+    }
+}
+
+/// Profiler shown over the editor, which only records while it is open
+#[derive(Default)]
+pub struct ProfilerWindow {
+    pub open: bool,
+    /// Stop recording new frames, to look at the recorded ones
+    paused: bool,
+    frames: VecDeque<ProfiledFrame>,
+    /// Frame being broken down, counted back from the newest
+    selected: Option<usize>,
+}
+
+impl ProfilerWindow {
+    /// Collects the scopes of the frame that has just been drawn, and shows
+    /// the window if it is open
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if profiler::is_enabled() != self.open {
+            profiler::set_enabled(self.open);
+        }
+        if !self.open {
+            self.frames.clear();
+            return;
+        }
+
+        let scopes = profiler::take_scopes();
+        if let Some(frame) = ProfiledFrame::new(scopes).filter(|_| !self.paused) {
+            self.frames.push_back(frame);
+            if self.frames.len() > MAX_FRAMES {
+                self.frames.pop_front();
+            }
+            self.selected = self
+                .selected
+                .map(|idx| idx + 1)
+                .filter(|idx| *idx < MAX_FRAMES);
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Profiler")
+            .open(&mut open)
+            .default_size(vec2(500., 400.))
+            .show(ctx, |ui| self.ui(ui));
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.paused, "Pause");
+            if ui.small_button("Clear").clicked() {
+                self.frames.clear();
+                self.selected = None;
+            }
+            if let Some(slowest) = self.frames.iter().map(|frame| frame.duration).max() {
+                let mean = self
+                    .frames
+                    .iter()
+                    .map(|frame| frame.duration)
+                    .sum::<Duration>()
+                    / self.frames.len() as u32;
+                ui.label(format!(
+                    "{} frames, mean {:.2} ms, slowest {:.2} ms",
+                    self.frames.len(),
+                    mean.as_secs_f64() * 1e3,
+                    slowest.as_secs_f64() * 1e3
+                ));
+            }
+        });
+        if self.frames.is_empty() {
+            ui.label("No frames recorded yet");
+            return;
+        }
+
+        self.frames_ui(ui);
+        ui.separator();
+
+        let idx = self.frames.len() - 1 - self.selected.unwrap_or(0).min(self.frames.len() - 1);
+        let frame = &self.frames[idx];
+        ui.label(format!(
+            "{} frame, {:.2} ms",
+            if self.selected.is_some() {
+                "Selected"
+            } else {
+                "Latest"
+            },
+            frame.duration.as_secs_f64() * 1e3
+        ));
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            scopes_ui(ui, frame);
+            ui.collapsing("Slowest scopes", |ui| {
+                egui::Grid::new("profiler_totals")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Scope");
+                        ui.strong("Total");
+                        ui.strong("Calls");
+                        ui.end_row();
+                        for (name, total, calls) in
+                            profiler::totals(&frame.scopes).into_iter().take(MAX_TOTALS)
+                        {
+                            ui.label(name);
+                            ui.label(format!("{:.3} ms", total.as_secs_f64() * 1e3));
+                            ui.label(calls.to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    }
+
+    /// Bar chart of the duration of each recorded frame. Clicking a bar
+    /// selects its frame and pauses recording.
+    fn frames_ui(&mut self, ui: &mut egui::Ui) {
+        let (rect, response) =
+            ui.allocate_exact_size(vec2(ui.available_width(), CHART_HEIGHT), Sense::click());
+        let slowest = self
+            .frames
+            .iter()
+            .map(|frame| frame.duration.as_secs_f32())
+            .fold(f32::MIN_POSITIVE, f32::max);
+        let width = rect.width() / MAX_FRAMES as f32;
+        let visuals = ui.visuals();
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0., visuals.extreme_bg_color);
+
+        let pointer = response.hover_pos();
+        let mut hovered = None;
+        for (age, frame) in self.frames.iter().rev().enumerate() {
+            let x = rect.max.x - width * (age + 1) as f32;
+            let height = frame.duration.as_secs_f32() / slowest * rect.height();
+            let bar = Rect::from_min_max(pos2(x, rect.max.y - height), pos2(x + width, rect.max.y));
+            let column = Rect::from_x_y_ranges(bar.x_range(), rect.y_range());
+            if pointer.is_some_and(|pos| column.contains(pos)) {
+                hovered = Some((age, frame.duration));
+            }
+            let fill = if self.selected == Some(age) || hovered.is_some_and(|(idx, _)| idx == age) {
+                visuals.selection.bg_fill
+            } else {
+                visuals.widgets.inactive.fg_stroke.color
+            };
+            painter.rect_filled(bar, 0., fill);
+        }
+
+        if let Some((age, duration)) = hovered {
+            let response =
+                response.on_hover_text(format!("{:.2} ms", duration.as_secs_f64() * 1e3));
+            if response.clicked() {
+                self.selected = Some(age);
+                self.paused = true;
+            }
+        } else if response.clicked() {
+            self.selected = None;
+        }
+    }
+}
+
+/// Flame graph of the scopes of `frame`, with a group of rows for each
+/// thread and a row for each level of nesting
+fn scopes_ui(ui: &mut egui::Ui, frame: &ProfiledFrame) {
+    let mut threads = frame
+        .scopes
+        .iter()
+        .map(|scope| scope.thread.as_str())
+        .collect::<Vec<_>>();
+    threads.sort_unstable();
+    threads.dedup();
+    let rows = threads
+        .iter()
+        .map(|thread| {
+            frame
+                .scopes
+                .iter()
+                .filter(|scope| scope.thread == *thread)
+                .map(|scope| scope.depth + 1)
+                .max()
+                .unwrap_or(1)
+        })
+        .collect::<Vec<_>>();
+
+    let (rect, response) = ui.allocate_exact_size(
+        vec2(
+            ui.available_width(),
+            ROW_HEIGHT * (rows.iter().sum::<usize>() + threads.len()) as f32,
+        ),
+        Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let total = frame.duration.as_secs_f32().max(f32::MIN_POSITIVE);
+    let x_of = |time: Instant| {
+        rect.min.x + time.duration_since(frame.start).as_secs_f32() / total * rect.width()
+    };
+
+    let pointer = response.hover_pos();
+    let mut hovered = None;
+    let mut y = rect.min.y;
+    for (thread, rows) in threads.iter().zip(rows) {
+        painter.text(
+            pos2(rect.min.x + 2., y + ROW_HEIGHT / 2.),
+            egui::Align2::LEFT_CENTER,
+            thread,
+            egui::FontId::proportional(11.),
+            visuals.weak_text_color(),
+        );
+        y += ROW_HEIGHT;
+        for scope in frame.scopes.iter().filter(|scope| scope.thread == *thread) {
+            let top = y + ROW_HEIGHT * scope.depth as f32;
+            let x_start = x_of(scope.start);
+            // Keep very short scopes visible
+            let x_end = x_of(scope.end()).max(x_start + 1.);
+            let bar =
+                Rect::from_min_max(pos2(x_start, top + 1.), pos2(x_end, top + ROW_HEIGHT - 1.));
+            let is_hovered = pointer.is_some_and(|pos| bar.contains(pos));
+            if is_hovered {
+                hovered = Some(scope);
+            }
+            let fill = if is_hovered {
+                visuals.selection.bg_fill
+            } else {
+                visuals.widgets.inactive.bg_fill
+            };
+            painter.rect(
+                bar,
+                2.,
+                fill,
+                Stroke::new(1., visuals.widgets.inactive.fg_stroke.color),
+            );
+            let galley = painter.layout_no_wrap(
+                scope.name.to_string(),
+                egui::FontId::proportional(11.),
+                visuals.text_color(),
+            );
+            if galley.size().x + 4. < bar.width() {
+                painter.galley(
+                    pos2(bar.min.x + 2., bar.center().y - galley.size().y / 2.),
+                    galley,
+                    visuals.text_color(),
+                );
+            }
+        }
+        y += ROW_HEIGHT * rows as f32;
+    }
+
+    if let Some(scope) = hovered {
+        response.on_hover_text(format!(
+            "{}: {:.3} ms",
+            scope.name,
+            scope.duration.as_secs_f64() * 1e3
+        ));
+    }
+}
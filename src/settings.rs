@@ -0,0 +1,140 @@
+//! Appearance of the node canvas, edited from the Config pane
+
+use eframe::egui::{self, Vec2};
+use egui_snarl::ui::{BackgroundPattern, Grid, SnarlStyle, WireLayer};
+use graph_core::node_graph::TypeColours;
+
+/// Options controlling how graphs are drawn, from which the [`SnarlStyle`]
+/// is built every frame so that changes show up straight away
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub wire_width: f32,
+    /// Controls the curvature of wires
+    pub wire_frame_size: f32,
+    pub wires_above_nodes: bool,
+    /// Flatten wires between nodes that are close together
+    pub downscale_wire_frame: bool,
+    pub grid: bool,
+    pub grid_spacing: Vec2,
+    /// Rotation of the grid, in radians
+    pub grid_angle: f32,
+    pub pin_size: f32,
+    pub colours: TypeColours,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            wire_width: 1.8,
+            wire_frame_size: 45.,
+            wires_above_nodes: false,
+            downscale_wire_frame: true,
+            grid: true,
+            grid_spacing: Grid::default().spacing,
+            grid_angle: 0.,
+            pin_size: 9.,
+            colours: TypeColours::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn style(&self) -> SnarlStyle {
+        let mut style = SnarlStyle::new();
+        style.pin_size = Some(self.pin_size);
+        style.wire_width = Some(self.wire_width);
+        style.wire_frame_size = Some(self.wire_frame_size);
+        style.downscale_wire_frame = self.downscale_wire_frame;
+        style.wire_layer = if self.wires_above_nodes {
+            WireLayer::AboveNodes
+        } else {
+            WireLayer::BehindNodes
+        };
+        style.bg_pattern = if self.grid {
+            BackgroundPattern::Grid(Grid::new(self.grid_spacing, self.grid_angle))
+        } else {
+            BackgroundPattern::NoPattern
+        };
+        style
+    }
+
+    /// Shows controls for every setting
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("settings").num_columns(2).show(ui, |ui| {
+            ui.label("Wire width");
+            ui.add(egui::Slider::new(&mut self.wire_width, 0.5..=8.));
+            ui.end_row();
+
+            ui.label("Wire curvature");
+            ui.add(egui::Slider::new(&mut self.wire_frame_size, 0.0..=150.));
+            ui.end_row();
+
+            ui.label("Wires");
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.wires_above_nodes, false, "Behind nodes");
+                ui.radio_value(&mut self.wires_above_nodes, true, "Above nodes");
+            });
+            ui.end_row();
+
+            ui.label("");
+            ui.checkbox(&mut self.downscale_wire_frame, "Flatten short wires");
+            ui.end_row();
+
+            ui.label("Grid");
+            ui.checkbox(&mut self.grid, "Show grid");
+            ui.end_row();
+
+            if self.grid {
+                ui.label("Grid spacing");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.grid_spacing.x)
+                            .speed(0.1)
+                            .clamp_range(1.0..=50.),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut self.grid_spacing.y)
+                            .speed(0.1)
+                            .clamp_range(1.0..=50.),
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Grid angle");
+                ui.drag_angle(&mut self.grid_angle);
+                ui.end_row();
+            }
+
+            ui.label("Pin size");
+            ui.add(egui::Slider::new(&mut self.pin_size, 4.0..=24.));
+            ui.end_row();
+
+            for (name, colour) in [
+                ("Number", &mut self.colours.number),
+                ("String", &mut self.colours.string),
+                ("Bool", &mut self.colours.bool),
+                ("Colour", &mut self.colours.color),
+                ("Vector", &mut self.colours.vector),
+                ("Any", &mut self.colours.unknown),
+            ] {
+                ui.label(name);
+                egui::color_picker::color_edit_button_srgba(
+                    ui,
+                    colour,
+                    egui::color_picker::Alpha::Opaque,
+                );
+                ui.end_row();
+            }
+        });
+
+        if ui
+            .add_enabled(
+                *self != Self::default(),
+                egui::Button::new("Reset to default"),
+            )
+            .clicked()
+        {
+            *self = Self::default();
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Predicts how long the graph would take to run on a number of workers,
+//! from an estimate of each node's cost, without updating any node
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use eframe::egui::{self, pos2, vec2, Rect, Sense};
+use egui_snarl::{NodeId, Snarl};
+use graph_core::{
+    execution_engine::TaskDag,
+    node_graph::{self, Node},
+    run_report::ExecutionStats,
+    schedule::{Priority, Schedule, Scheduler},
+    stages::Stages,
+};
+
+const ROW_HEIGHT: f32 = 18.;
+/// Most idle gaps listed, longest first
+const MAX_GAPS: usize = 20;
+
+#[derive(Debug)]
+pub struct SimulationPanel {
+    workers: usize,
+    priority: Priority,
+    /// Costs entered by the user, used instead of the measured or estimated
+    /// cost of those nodes
+    costs: BTreeMap<NodeId, Duration>,
+    schedule: Option<Schedule>,
+}
+
+impl Default for SimulationPanel {
+    fn default() -> Self {
+        Self {
+            workers: 4,
+            priority: Priority::CriticalPath,
+            costs: BTreeMap::new(),
+            schedule: None,
+        }
+    }
+}
+
+impl SimulationPanel {
+    /// Forgets the costs and results, whose nodes belong to the previous
+    /// graph
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Cost of each node: the one entered by the user, otherwise how long
+    /// it took when last run, otherwise its own estimate
+    fn durations(
+        &self,
+        snarl: &Snarl<Box<dyn Node>>,
+        stats: &ExecutionStats,
+    ) -> HashMap<NodeId, Duration> {
+        let mut durations = stats.durations(snarl);
+        durations.extend(
+            self.costs
+                .iter()
+                .filter(|(node, _)| snarl.get_node(**node).is_some())
+                .map(|(node, cost)| (*node, *cost)),
+        );
+        durations
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        snarl: &Snarl<Box<dyn Node>>,
+        stats: &ExecutionStats,
+        priorities: HashMap<NodeId, i32>,
+        stages: &Stages,
+    ) {
+        self.costs.retain(|node, _| snarl.get_node(*node).is_some());
+        let durations = self.durations(snarl, stats);
+
+        ui.horizontal(|ui| {
+            ui.label("Workers");
+            ui.add(egui::DragValue::new(&mut self.workers).clamp_range(1..=64));
+            egui::ComboBox::from_id_source("simulation_priority")
+                .selected_text(self.priority.to_string())
+                .show_ui(ui, |ui| {
+                    for priority in Priority::ALL {
+                        ui.selectable_value(&mut self.priority, priority, priority.to_string());
+                    }
+                });
+            if ui
+                .button("Simulate")
+                .on_hover_text("Schedule the nodes using their costs, without running them")
+                .clicked()
+            {
+                let graph = node_graph::as_petgraph(snarl);
+                let dag = TaskDag::new(&graph)
+                    .with_priorities(priorities)
+                    .with_stages(stages);
+                self.schedule = Some(
+                    Scheduler::new(dag, self.workers)
+                        .priority(self.priority)
+                        .run(&durations),
+                );
+            }
+        });
+
+        ui.collapsing("Costs", |ui| {
+            ui.label("Nodes that have run cost what they last took, unless set here");
+            egui::Grid::new("simulation_costs")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (id, node) in snarl.node_ids() {
+                        ui.label(node_graph::display_name(node.as_ref(), id));
+                        let cost = durations.get(&id).copied().unwrap_or_default();
+                        let mut millis = cost.as_secs_f64() * 1e3;
+                        let edited = ui
+                            .add(
+                                egui::DragValue::new(&mut millis)
+                                    .clamp_range(0. ..=f64::MAX)
+                                    .speed(1.)
+                                    .suffix(" ms"),
+                            )
+                            .changed();
+                        if edited {
+                            self.costs.insert(id, Duration::from_secs_f64(millis / 1e3));
+                        }
+                        if self.costs.contains_key(&id) {
+                            if ui.small_button("Reset").clicked() {
+                                self.costs.remove(&id);
+                            }
+                        } else if !durations.contains_key(&id) {
+                            ui.weak("No estimate");
+                        } else {
+                            ui.label("");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        let Some(schedule) = &self.schedule else {
+            return;
+        };
+        ui.separator();
+        ui.label(format!(
+            "Predicted run time with {} worker(s): {:?}",
+            schedule.workers, schedule.makespan
+        ));
+        gantt_ui(ui, schedule, snarl);
+
+        egui::Grid::new("simulation_utilisation")
+            .num_columns(3)
+            .show(ui, |ui| {
+                for (worker, utilisation) in schedule.utilisation().into_iter().enumerate() {
+                    ui.label(format!("Worker {}", worker));
+                    ui.add(
+                        egui::ProgressBar::new(utilisation as f32)
+                            .desired_width(120.)
+                            .text(format!("{:.0}%", utilisation * 100.)),
+                    );
+                    ui.label(format!("{:?} busy", schedule.busy_time(worker)));
+                    ui.end_row();
+                }
+            });
+
+        let gaps = schedule.idle_gaps();
+        ui.collapsing(format!("Idle gaps ({})", gaps.len()), |ui| {
+            for gap in gaps.iter().take(MAX_GAPS) {
+                ui.label(format!(
+                    "Worker {} idle for {:?} from {:?}",
+                    gap.worker, gap.duration, gap.start
+                ));
+            }
+            if gaps.len() > MAX_GAPS {
+                ui.weak(format!("{} shorter gaps", gaps.len() - MAX_GAPS));
+            }
+        });
+    }
+}
+
+/// Bars for the tasks of each worker, positioned by when they would run
+fn gantt_ui(ui: &mut egui::Ui, schedule: &Schedule, snarl: &Snarl<Box<dyn Node>>) {
+    let (rect, response) = ui.allocate_exact_size(
+        vec2(ui.available_width(), ROW_HEIGHT * schedule.workers as f32),
+        Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 0., visuals.extreme_bg_color);
+    let total = schedule.makespan.as_secs_f32().max(f32::MIN_POSITIVE);
+    let x_of = |time: Duration| rect.min.x + time.as_secs_f32() / total * rect.width();
+
+    let pointer = response.hover_pos();
+    let mut hovered = None;
+    for task in &schedule.tasks {
+        let top = rect.min.y + ROW_HEIGHT * task.worker as f32;
+        let x_start = x_of(task.start);
+        // Keep tasks that take no time visible
+        let x_end = x_of(task.end()).max(x_start + 1.);
+        let bar = Rect::from_min_max(pos2(x_start, top + 1.), pos2(x_end, top + ROW_HEIGHT - 1.));
+        let is_hovered = pointer.is_some_and(|pos| bar.contains(pos));
+        if is_hovered {
+            hovered = Some(task);
+        }
+        let fill = if is_hovered {
+            visuals.selection.bg_fill
+        } else {
+            visuals.widgets.inactive.bg_fill
+        };
+        painter.rect_filled(bar, 2., fill);
+    }
+
+    if let Some(task) = hovered {
+        let name = match snarl.get_node(task.task) {
+            Some(node) => node_graph::display_name(node.as_ref(), task.task),
+            None => format!("Node {}", task.task.0),
+        };
+        response.on_hover_text(format!("{}: {:?} to {:?}", name, task.start, task.end()));
+    }
+}
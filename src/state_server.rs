@@ -0,0 +1,249 @@
+//! A small HTTP server streaming the state of the task DAG as server-sent
+//! events, so that dashboards outside the editor can follow long runs.
+//!
+//! `GET /state` returns the latest state as JSON, and `GET /events` streams
+//! it as `state` events whenever it changes, along with a `progress` event
+//! each time nodes are evaluated.
+//!
+//! Only the local machine can connect, and of the web pages open in its
+//! browsers only those from the one allowed origin, if any, can read the
+//! state.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// How long a client can take to accept an event before it is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a client has to send its request after connecting
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Events waiting to be written to a client, past which it is dropped as
+/// too slow to keep up
+const CLIENT_BACKLOG: usize = 64;
+/// Connections served at once, past which new ones are refused
+const MAX_CONNECTIONS: usize = 16;
+
+pub struct StateServer {
+    port: u16,
+    allowed_origin: Option<String>,
+    /// Events waiting to be written to each client following `/events`, by
+    /// that client's own thread
+    clients: Arc<Mutex<Vec<SyncSender<Arc<str>>>>>,
+    /// Latest state published, sent to new clients when they connect
+    state: Arc<Mutex<String>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl StateServer {
+    /// Starts serving on a port of the local machine. Web pages can only
+    /// read the state if they are from `allowed_origin`, such as
+    /// `http://localhost:3000`; requests from any other web page are refused.
+    pub fn start(port: u16, allowed_origin: Option<String>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let state = Arc::new(Mutex::new("{}".to_owned()));
+        let stop = Arc::new(AtomicBool::new(false));
+        std::thread::Builder::new()
+            .name("state server".to_owned())
+            .spawn({
+                let clients = clients.clone();
+                let state = state.clone();
+                let stop = stop.clone();
+                let allowed_origin = allowed_origin.clone();
+                let connections = Arc::new(AtomicUsize::new(0));
+                move || {
+                    for mut stream in listener.incoming().flatten() {
+                        if stop.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        if connections.fetch_add(1, Ordering::Relaxed) >= MAX_CONNECTIONS {
+                            connections.fetch_sub(1, Ordering::Relaxed);
+                            let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                            let _ = write!(
+                                stream,
+                                "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\
+                                 Connection: close\r\n\r\n"
+                            );
+                            continue;
+                        }
+                        // Each connection gets its own thread, so that one
+                        // slow or idle client can't hold up the others
+                        let clients = clients.clone();
+                        let state = state.clone();
+                        let stop = stop.clone();
+                        let allowed_origin = allowed_origin.clone();
+                        let slot = ConnectionSlot(connections.clone());
+                        let _ = std::thread::Builder::new()
+                            .name("state server client".to_owned())
+                            .spawn(move || {
+                                let _slot = slot;
+                                let _ = accept(
+                                    stream,
+                                    allowed_origin.as_deref(),
+                                    &clients,
+                                    &state,
+                                    &stop,
+                                );
+                            });
+                    }
+                }
+            })?;
+        Ok(Self {
+            port,
+            allowed_origin,
+            clients,
+            state,
+            stop,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn allowed_origin(&self) -> Option<&str> {
+        self.allowed_origin.as_deref()
+    }
+
+    /// Sends the state to every client following `/events`, if it has
+    /// changed since it was last published
+    pub fn publish_state(&self, json: String) {
+        let mut state = self.state.lock().unwrap();
+        if *state != json {
+            self.send("state", &json);
+            *state = json;
+        }
+    }
+
+    /// Queues an event for every client following `/events`, dropping those
+    /// that have disconnected or fallen too far behind
+    pub fn send(&self, event: &str, json: &str) {
+        let message = Arc::<str>::from(format!("event: {}\ndata: {}\n\n", event, json));
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|client| match client.try_send(message.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => false,
+            });
+    }
+}
+
+impl Drop for StateServer {
+    fn drop(&mut self) {
+        // Wake the listening thread so that it notices and stops
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, self.port));
+        // Dropping the senders ends each client's stream, and its thread
+        self.clients.lock().unwrap().clear();
+    }
+}
+
+/// One of the [`MAX_CONNECTIONS`] connections being served, given back when
+/// its thread finishes or fails to start
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The `Access-Control-Allow-Origin` header for a request, or `None` if the
+/// request comes from a web page that isn't allowed to read the state.
+/// Requests without an `Origin`, such as from curl, are always answered.
+fn cors_header(origin: Option<&str>, allowed_origin: Option<&str>) -> Option<String> {
+    match (origin, allowed_origin) {
+        (None, _) => Some(String::new()),
+        (Some(origin), Some(allowed)) if origin == allowed => Some(format!(
+            "Access-Control-Allow-Origin: {}\r\nVary: Origin\r\n",
+            allowed
+        )),
+        (Some(_), _) => None,
+    }
+}
+
+/// Answers a request, streaming events to the connection until it closes
+/// if it is for `/events`
+fn accept(
+    mut stream: TcpStream,
+    allowed_origin: Option<&str>,
+    clients: &Mutex<Vec<SyncSender<Arc<str>>>>,
+    state: &Mutex<String>,
+    stop: &AtomicBool,
+) -> std::io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    let mut origin = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_owned());
+            }
+        }
+    }
+    let Some(cors) = cors_header(origin.as_deref(), allowed_origin) else {
+        return write!(
+            stream,
+            "HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+    };
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let state = state.lock().unwrap().clone();
+    match path {
+        "/state" => write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+             {}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            cors,
+            state.len(),
+            state
+        ),
+        "/events" => {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                 Cache-Control: no-cache\r\n{}\r\n\
+                 event: state\ndata: {}\n\n",
+                cors, state
+            )?;
+            let (sender, receiver) = mpsc::sync_channel(CLIENT_BACKLOG);
+            {
+                // Checked while holding the lock, so that a server being
+                // dropped either sees this client or it sees the server stop
+                let mut clients = clients.lock().unwrap();
+                if stop.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+                clients.push(sender);
+            }
+            stream_events(stream, receiver)
+        }
+        _ => write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ),
+    }
+}
+
+/// Writes events to a client until it disconnects or the server stops
+fn stream_events(mut stream: TcpStream, events: Receiver<Arc<str>>) -> std::io::Result<()> {
+    for message in events {
+        stream.write_all(message.as_bytes())?;
+    }
+    Ok(())
+}
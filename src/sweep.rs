@@ -0,0 +1,273 @@
+//! Sets up parameter sweeps of the graph's Number nodes, and shows their
+//! results as a table and a plot
+
+use eframe::egui::{self, pos2, vec2, Sense, Stroke};
+use egui_snarl::Snarl;
+use graph_core::{
+    context::EvalContext,
+    node_graph::{self, format_value, Node, NumberNode},
+    sweep::{self, Sweep, SweepResults},
+};
+
+use crate::notifications::Notifications;
+
+const PLOT_HEIGHT: f32 = 160.;
+/// Most evaluations a sweep can be set up to run
+const MAX_STEPS: usize = 1000;
+
+#[derive(Debug, Default)]
+pub struct SweepPanel {
+    sweeps: Vec<Sweep>,
+    results: Option<SweepResults>,
+    /// Column of the results plotted against the first swept value
+    plotted: Option<usize>,
+    /// Set when the results should be saved as CSV
+    export: bool,
+}
+
+impl SweepPanel {
+    /// Forgets the sweeps, whose nodes belong to the previous graph
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Results the user asked to save, as CSV
+    pub fn take_export(&mut self) -> Option<String> {
+        if !std::mem::take(&mut self.export) {
+            return None;
+        }
+        self.results.as_ref().map(SweepResults::to_csv)
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        snarl: &mut Snarl<Box<dyn Node>>,
+        notifications: &mut Notifications,
+    ) {
+        self.sweeps.retain(|sweep| {
+            snarl
+                .get_node(sweep.node)
+                .is_some_and(|node| node.as_any().is::<NumberNode>())
+        });
+
+        let mut removed = None;
+        egui::Grid::new("sweeps").num_columns(5).show(ui, |ui| {
+            for (idx, sweep) in self.sweeps.iter_mut().enumerate() {
+                ui.label(node_graph::display_name(
+                    snarl[sweep.node].as_ref(),
+                    sweep.node,
+                ));
+                ui.add(egui::DragValue::new(&mut sweep.start).prefix("from "));
+                ui.add(egui::DragValue::new(&mut sweep.end).prefix("to "));
+                ui.add(
+                    egui::DragValue::new(&mut sweep.steps)
+                        .clamp_range(1..=MAX_STEPS)
+                        .suffix(" steps"),
+                );
+                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                    removed = Some(idx);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(idx) = removed {
+            self.sweeps.remove(idx);
+        }
+
+        let evaluations = self
+            .sweeps
+            .iter()
+            .map(|sweep| sweep.steps)
+            .product::<usize>();
+        ui.horizontal(|ui| {
+            ui.menu_button("Add Sweep", |ui| {
+                let mut numbers = snarl
+                    .node_ids()
+                    .filter_map(|(id, node)| {
+                        let number = node.as_any().downcast_ref::<NumberNode>()?;
+                        Some((
+                            id,
+                            number.value(),
+                            node_graph::display_name(node.as_ref(), id),
+                        ))
+                    })
+                    .filter(|(id, _, _)| self.sweeps.iter().all(|sweep| sweep.node != *id))
+                    .collect::<Vec<_>>();
+                numbers.sort_by_key(|(id, _, _)| *id);
+                if numbers.is_empty() {
+                    ui.weak("No Number nodes left to sweep");
+                }
+                for (node, value, name) in numbers {
+                    if ui.button(name).clicked() {
+                        self.sweeps.push(Sweep {
+                            node,
+                            start: value,
+                            end: value + 1.,
+                            steps: 11,
+                        });
+                        ui.close_menu();
+                    }
+                }
+            });
+            let run = ui
+                .add_enabled(
+                    !self.sweeps.is_empty() && evaluations <= MAX_STEPS,
+                    egui::Button::new("Run"),
+                )
+                .on_hover_text(format!("Evaluate the graph {} times", evaluations))
+                .on_disabled_hover_text(format!(
+                    "Sweeps can evaluate the graph at most {} times",
+                    MAX_STEPS
+                ));
+            if run.clicked() {
+                match sweep::run(snarl, &self.sweeps, &EvalContext::default()) {
+                    Ok(results) => {
+                        self.plotted = (results.swept..results.columns.len())
+                            .find(|column| results.numbers(*column).iter().any(Option::is_some));
+                        self.results = Some(results);
+                    }
+                    Err(err) => notifications.error(format!("Sweep failed: {}", err)),
+                }
+            }
+        });
+
+        let Some(results) = &self.results else {
+            return;
+        };
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} evaluations", results.rows.len()));
+            if ui.button("Export CSV…").clicked() {
+                self.export = true;
+            }
+        });
+        if results.swept > 0 && results.columns.len() > results.swept {
+            ui.horizontal(|ui| {
+                ui.label("Plot");
+                egui::ComboBox::from_id_source("sweep plot")
+                    .selected_text(
+                        self.plotted
+                            .map_or("Nothing", |column| results.columns[column].as_str()),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.plotted, None, "Nothing");
+                        for column in results.swept..results.columns.len() {
+                            ui.selectable_value(
+                                &mut self.plotted,
+                                Some(column),
+                                &results.columns[column],
+                            );
+                        }
+                    });
+                ui.label(format!("against {}", results.columns[0]));
+            });
+            if let Some(column) = self.plotted {
+                plot(ui, results, column);
+            }
+        }
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui::Grid::new("sweep results")
+                .striped(true)
+                .show(ui, |ui| {
+                    for column in &results.columns {
+                        ui.strong(column);
+                    }
+                    ui.end_row();
+                    for row in &results.rows {
+                        for value in row {
+                            match value {
+                                Some(value) => ui.label(format_value(value)),
+                                None => ui.weak("None"),
+                            };
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+/// Draws a column of the results against the first swept value. Points are
+/// joined in order when only one value was swept, as they then form a line.
+fn plot(ui: &mut egui::Ui, results: &SweepResults, column: usize) {
+    let points = results
+        .numbers(0)
+        .into_iter()
+        .zip(results.numbers(column))
+        .filter_map(|(x, y)| Some((x?, y?)))
+        .collect::<Vec<_>>();
+    if points.is_empty() {
+        ui.weak("No numbers to plot");
+        return;
+    }
+    let bounds = |values: &mut dyn Iterator<Item = f64>| {
+        let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+        // Flat ranges are widened so that they are drawn in the middle
+        if min == max {
+            (min - 1., max + 1.)
+        } else {
+            (min, max)
+        }
+    };
+    let (x_min, x_max) = bounds(&mut points.iter().map(|(x, _)| *x));
+    let (y_min, y_max) = bounds(&mut points.iter().map(|(_, y)| *y));
+
+    let (rect, response) =
+        ui.allocate_exact_size(vec2(ui.available_width(), PLOT_HEIGHT), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2., visuals.extreme_bg_color);
+    let area = rect.shrink(8.);
+    let to_screen = |(x, y): (f64, f64)| {
+        pos2(
+            area.min.x + ((x - x_min) / (x_max - x_min)) as f32 * area.width(),
+            area.max.y - ((y - y_min) / (y_max - y_min)) as f32 * area.height(),
+        )
+    };
+    let colour = visuals.selection.bg_fill;
+    let screen = points.iter().copied().map(to_screen).collect::<Vec<_>>();
+    if results.swept == 1 {
+        painter.add(egui::Shape::line(screen.clone(), Stroke::new(1.5, colour)));
+    }
+    for point in &screen {
+        painter.circle_filled(*point, 2.5, colour);
+    }
+
+    let font = egui::FontId::monospace(10.);
+    let text = visuals.weak_text_color();
+    for (pos, align, value) in [
+        (area.left_top(), egui::Align2::LEFT_TOP, y_max),
+        (area.left_bottom(), egui::Align2::LEFT_BOTTOM, y_min),
+        (area.right_bottom(), egui::Align2::RIGHT_BOTTOM, x_max),
+    ] {
+        painter.text(
+            pos,
+            align,
+            node_graph::format_float(value),
+            font.clone(),
+            text,
+        );
+    }
+
+    // Show the values of the point nearest the pointer
+    if let Some(pointer) = response.hover_pos() {
+        let nearest = screen
+            .iter()
+            .zip(&points)
+            .min_by(|(a, _), (b, _)| a.distance_sq(pointer).total_cmp(&b.distance_sq(pointer)));
+        if let Some((pos, (x, y))) = nearest {
+            painter.circle_stroke(*pos, 4., Stroke::new(1., visuals.strong_text_color()));
+            response.on_hover_text_at_pointer(format!(
+                "{} = {}\n{} = {}",
+                results.columns[0],
+                node_graph::format_float(*x),
+                results.columns[column],
+                node_graph::format_float(*y)
+            ));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Small pictures of graphs, saved alongside graph files so that they can be
+//! recognised before they are opened. Graphs are drawn in software, as a
+//! simplified outline of their nodes, wires and comments, so that no window
+//! is needed to render them.
+
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, vec2, Color32, Pos2, Rect, Vec2};
+use egui_snarl::{InPinId, Snarl};
+use graph_core::{
+    comment::Comment,
+    node_graph::{Node, TypeColours},
+};
+
+pub const WIDTH: usize = 240;
+pub const HEIGHT: usize = 150;
+/// Rough size of a node on the canvas, which is only known once drawn
+const NODE_SIZE: Vec2 = vec2(120., 60.);
+const MARGIN: f32 = 8.;
+const BACKGROUND: Color32 = Color32::from_rgb(0x1b, 0x1b, 0x1b);
+const NODE_FILL: Color32 = Color32::from_rgb(0x3c, 0x3c, 0x3c);
+
+/// Where the thumbnail of a graph file is kept
+pub fn path_for(graph: &Path) -> PathBuf {
+    graph.with_extension("png")
+}
+
+/// Draws a graph and encodes it as a PNG
+pub fn render(
+    snarl: &Snarl<Box<dyn Node>>,
+    comments: &[Comment],
+    colours: &TypeColours,
+) -> Vec<u8> {
+    let mut image = Image::new(WIDTH, HEIGHT, BACKGROUND);
+
+    // Fit everything on the graph into the image
+    let mut bounds = Rect::NOTHING;
+    for (_, pos, _) in snarl.nodes_pos_ids() {
+        bounds = bounds.union(Rect::from_min_size(pos, NODE_SIZE));
+    }
+    for comment in comments {
+        bounds = bounds.union(Rect::from_min_size(comment.pos.into(), comment.size.into()));
+    }
+    if !bounds.is_positive() {
+        return image.encode();
+    }
+    let area = Rect::from_min_size(Pos2::ZERO, vec2(WIDTH as f32, HEIGHT as f32)).shrink(MARGIN);
+    let scale = (area.width() / bounds.width()).min(area.height() / bounds.height());
+    let offset = area.center() - bounds.center().to_vec2() * scale;
+    let to_image = |pos: Pos2| offset + pos.to_vec2() * scale;
+    let node_rect = |pos: Pos2| Rect::from_min_size(to_image(pos), NODE_SIZE * scale);
+
+    for comment in comments {
+        let [r, g, b] = comment.colour;
+        let rect = Rect::from_min_size(
+            to_image(comment.pos.into()),
+            Vec2::from(comment.size) * scale,
+        );
+        image.fill(rect, Color32::from_rgba_unmultiplied(r, g, b, 48));
+    }
+
+    let positions = snarl
+        .nodes_pos_ids()
+        .map(|(id, pos, _)| (id, pos))
+        .collect::<Vec<_>>();
+    for (id, pos) in &positions {
+        let node = &snarl[*id];
+        for input in 0..node.inputs().len() {
+            for remote in snarl.in_pin(InPinId { node: *id, input }).remotes {
+                let Some((_, from)) = positions.iter().find(|(id, _)| *id == remote.node) else {
+                    continue;
+                };
+                let colour = snarl[remote.node]
+                    .outputs()
+                    .get(remote.output)
+                    .map_or(colours.unknown, |output| output.colour(colours));
+                image.line(
+                    node_rect(*from).right_center(),
+                    node_rect(*pos).left_center(),
+                    colour,
+                    1.,
+                );
+            }
+        }
+    }
+
+    for (id, pos) in &positions {
+        let rect = node_rect(*pos);
+        image.fill(rect, NODE_FILL);
+        // A header coloured by the node's first output, as its wires are
+        let header = snarl[*id]
+            .outputs()
+            .first()
+            .map_or(colours.unknown, |output| output.colour(colours));
+        image.fill(rect.with_max_y(rect.min.y + rect.height() / 4.), header);
+    }
+
+    image.encode()
+}
+
+/// Reads a thumbnail saved by [`render`], for showing in the editor
+pub fn load(path: &Path) -> Option<egui::ColorImage> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    let size = [info.width as usize, info.height as usize];
+    let pixels = &buffer[..info.buffer_size()];
+    match info.color_type {
+        png::ColorType::Rgba => Some(egui::ColorImage::from_rgba_unmultiplied(size, pixels)),
+        png::ColorType::Rgb => Some(egui::ColorImage::from_rgb(size, pixels)),
+        _ => None,
+    }
+}
+
+/// An RGB image being drawn into, also used to export whole graphs as
+/// pictures
+pub(crate) struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color32>,
+}
+
+impl Image {
+    pub(crate) fn new(width: usize, height: usize, background: Color32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Blends a colour over one pixel, ignoring those outside the image
+    fn blend(&mut self, x: i32, y: i32, colour: Color32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let pixel = &mut self.pixels[y as usize * self.width + x as usize];
+        let alpha = colour.a() as f32 / 255.;
+        let mix = |over: u8, under: u8| (over as f32 * alpha + under as f32 * (1. - alpha)) as u8;
+        *pixel = Color32::from_rgb(
+            mix(colour.r(), pixel.r()),
+            mix(colour.g(), pixel.g()),
+            mix(colour.b(), pixel.b()),
+        );
+    }
+
+    pub(crate) fn fill(&mut self, rect: Rect, colour: Color32) {
+        let colour = colour.to_srgba_unmultiplied();
+        let colour = Color32::from_rgba_premultiplied(colour[0], colour[1], colour[2], colour[3]);
+        for y in rect.min.y.round() as i32..rect.max.y.round() as i32 {
+            for x in rect.min.x.round() as i32..rect.max.x.round() as i32 {
+                self.blend(x, y, colour);
+            }
+        }
+    }
+
+    /// Draws a line `width` pixels wide. Colours should be opaque, as
+    /// thicker lines blend over some pixels more than once.
+    pub(crate) fn line(&mut self, from: Pos2, to: Pos2, colour: Color32, width: f32) {
+        let steps = (to - from).abs().max_elem().ceil().max(1.) as i32;
+        for step in 0..=steps {
+            let pos = from.lerp(to, step as f32 / steps as f32);
+            if width <= 1. {
+                self.blend(pos.x.round() as i32, pos.y.round() as i32, colour);
+            } else {
+                self.fill(Rect::from_center_size(pos, Vec2::splat(width)), colour);
+            }
+        }
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let data = self
+            .pixels
+            .iter()
+            .flat_map(|pixel| [pixel.r(), pixel.g(), pixel.b()])
+            .collect::<Vec<_>>();
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&data))
+            .expect("writing a PNG to memory can't fail");
+        bytes
+    }
+}
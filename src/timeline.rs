@@ -0,0 +1,183 @@
+//! Gantt chart of the node updates performed by the last evaluation
+
+use eframe::egui::{self, pos2, vec2, Rect, Sense, Stroke};
+use egui_snarl::{NodeId, Snarl};
+use graph_core::{i18n, node_graph::Node, run_report::RunReport};
+
+const ROW_HEIGHT: f32 = 22.;
+const LABEL_WIDTH: f32 = 70.;
+
+/// Part of the timeline being shown, which can be zoomed with the scroll
+/// wheel and panned by dragging
+#[derive(Debug, Default)]
+pub struct Timeline {
+    /// Start and end of the visible span, in seconds since the evaluation
+    /// started, or `None` to fit the whole evaluation
+    view: Option<(f64, f64)>,
+    /// Trace loaded from a file, shown instead of the last evaluation, and
+    /// the name of the file
+    pub imported: Option<(String, RunReport)>,
+}
+
+impl Timeline {
+    /// Shows a trace loaded from a file until it is closed
+    pub fn import(&mut self, name: String, report: RunReport) {
+        self.imported = Some((name, report));
+        self.view = None;
+    }
+
+    /// Shows a bar for every node updated in `report`, with a row for each
+    /// worker. Returns the node whose bar is hovered.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        report: &RunReport,
+        snarl: &Snarl<Box<dyn Node>>,
+    ) -> Option<NodeId> {
+        // Start and end of each update, relative to the start of the run
+        let bars = report
+            .runs
+            .iter()
+            .map(|run| {
+                let end = run
+                    .finished
+                    .duration_since(report.started)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                (run, (end - run.duration.as_secs_f64()).max(0.), end)
+            })
+            .collect::<Vec<_>>();
+        if bars.is_empty() {
+            ui.label("No nodes were updated in the last run");
+            return None;
+        }
+        let total = bars.iter().map(|(_, _, end)| *end).fold(0., f64::max);
+        let workers = bars.iter().map(|(run, _, _)| run.worker).max().unwrap_or(0) + 1;
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{} updates over {:.3} ms", bars.len(), total * 1e3));
+            if ui.small_button("Fit").clicked() {
+                self.view = None;
+            }
+        });
+
+        let (rect, response) = ui.allocate_exact_size(
+            vec2(
+                ui.available_width(),
+                ROW_HEIGHT * workers as f32 + ROW_HEIGHT,
+            ),
+            Sense::click_and_drag(),
+        );
+        let chart = rect.with_min_x(rect.min.x + LABEL_WIDTH);
+        let (mut start, mut end) = self.view.unwrap_or((0., total.max(f64::MIN_POSITIVE)));
+        let x_of = |time: f64, start: f64, end: f64| {
+            chart.min.x + ((time - start) / (end - start)) as f32 * chart.width()
+        };
+
+        // Zoom around the pointer, and pan by dragging
+        if let Some(pointer) = response.hover_pos().filter(|pos| chart.contains(*pos)) {
+            let scroll = ui.input(|i| i.scroll_delta.y);
+            if scroll != 0. {
+                let at =
+                    start + (pointer.x - chart.min.x) as f64 / chart.width() as f64 * (end - start);
+                let factor = (-scroll as f64 / 200.).exp();
+                start = at - (at - start) * factor;
+                end = at + (end - at) * factor;
+                self.view = Some((start, end));
+            }
+        }
+        if response.dragged() {
+            let shift = -response.drag_delta().x as f64 / chart.width() as f64 * (end - start);
+            start += shift;
+            end += shift;
+            self.view = Some((start, end));
+        }
+        if response.double_clicked() {
+            self.view = None;
+        }
+
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        for worker in 0..workers {
+            let y = rect.min.y + ROW_HEIGHT * worker as f32;
+            painter.text(
+                pos2(rect.min.x + 4., y + ROW_HEIGHT / 2.),
+                egui::Align2::LEFT_CENTER,
+                format!("Worker {}", worker),
+                egui::FontId::proportional(12.),
+                visuals.text_color(),
+            );
+        }
+
+        let pointer = response.hover_pos();
+        let mut hovered = None;
+        let painter = painter.with_clip_rect(chart);
+        for (run, bar_start, bar_end) in &bars {
+            let y = rect.min.y + ROW_HEIGHT * run.worker as f32;
+            let x_start = x_of(*bar_start, start, end);
+            // Keep very short updates visible
+            let x_end = x_of(*bar_end, start, end).max(x_start + 2.);
+            let bar = Rect::from_min_max(pos2(x_start, y + 3.), pos2(x_end, y + ROW_HEIGHT - 3.));
+            let is_hovered = pointer.is_some_and(|pos| bar.contains(pos));
+            let fill = if is_hovered {
+                visuals.selection.bg_fill
+            } else {
+                visuals.widgets.inactive.bg_fill
+            };
+            painter.rect(
+                bar,
+                2.,
+                fill,
+                Stroke::new(1., visuals.widgets.inactive.fg_stroke.color),
+            );
+
+            let name = snarl.get_node(run.node).map_or_else(
+                || "Removed".to_owned(),
+                |node| i18n::node_name(&node.name()),
+            );
+            let label = format!("{} {}", name, run.node.0);
+            let galley = painter.layout_no_wrap(
+                label.clone(),
+                egui::FontId::proportional(11.),
+                visuals.text_color(),
+            );
+            if galley.size().x + 4. < bar.width() {
+                painter.galley(
+                    bar.left_center() + vec2(2., -galley.size().y / 2.),
+                    galley,
+                    visuals.text_color(),
+                );
+            }
+            if is_hovered {
+                hovered = Some(run.node);
+                response.clone().on_hover_text_at_pointer(format!(
+                    "{}: {:.3} ms, from {:.3} ms",
+                    label,
+                    run.duration.as_secs_f64() * 1e3,
+                    bar_start * 1e3
+                ));
+            }
+        }
+
+        // Time axis along the bottom
+        let axis_y = rect.max.y - ROW_HEIGHT / 2.;
+        for tick in 0..=4 {
+            let time = start + (end - start) * tick as f64 / 4.;
+            let x = x_of(time, start, end);
+            let align = match tick {
+                0 => egui::Align2::LEFT_CENTER,
+                4 => egui::Align2::RIGHT_CENTER,
+                _ => egui::Align2::CENTER_CENTER,
+            };
+            painter.text(
+                pos2(x, axis_y),
+                align,
+                format!("{:.3} ms", time * 1e3),
+                egui::FontId::proportional(10.),
+                visuals.weak_text_color(),
+            );
+        }
+
+        hovered
+    }
+}
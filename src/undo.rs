@@ -0,0 +1,114 @@
+use std::{collections::BTreeSet, path::PathBuf};
+
+use egui_snarl::NodeId;
+use graph_core::graph_file::SavedGraph;
+
+/// Maximum number of actions that can be undone
+const MAX_UNDO: usize = 100;
+
+/// An action that throws away part of the user's work
+#[derive(Debug, Clone)]
+pub enum DestructiveAction {
+    /// Removes nodes from the graph currently being displayed
+    RemoveNodes(BTreeSet<NodeId>),
+    /// Removes every wire to and from nodes in the graph currently being
+    /// displayed
+    DisconnectNodes(BTreeSet<NodeId>),
+    /// Removes every node from the root graph
+    ClearGraph,
+    /// Replaces the root graph with one of its snapshots
+    RestoreSnapshot { index: usize, name: String },
+    /// Forgets one of the root graph's snapshots, which can't be undone
+    DeleteSnapshot { index: usize, name: String },
+    /// Writes a file, replacing anything already at the path, along with a
+    /// thumbnail PNG of the graph when one is given
+    WriteFile {
+        path: PathBuf,
+        contents: Vec<u8>,
+        thumbnail: Option<Vec<u8>>,
+    },
+}
+
+impl DestructiveAction {
+    pub fn description(&self) -> String {
+        match self {
+            DestructiveAction::RemoveNodes(nodes) if nodes.len() == 1 => "Remove node".to_owned(),
+            DestructiveAction::RemoveNodes(nodes) => format!("Remove {} nodes", nodes.len()),
+            DestructiveAction::DisconnectNodes(nodes) if nodes.len() == 1 => {
+                "Disconnect node".to_owned()
+            }
+            DestructiveAction::DisconnectNodes(nodes) => {
+                format!("Disconnect {} nodes", nodes.len())
+            }
+            DestructiveAction::ClearGraph => "Clear graph".to_owned(),
+            DestructiveAction::RestoreSnapshot { name, .. } => format!("Restore {:?}", name),
+            DestructiveAction::DeleteSnapshot { name, .. } => {
+                format!("Delete snapshot {:?}", name)
+            }
+            DestructiveAction::WriteFile { path, .. } => format!("Write {}", path.display()),
+        }
+    }
+
+    /// Whether the action can be undone from the undo history
+    pub fn can_undo(&self) -> bool {
+        !matches!(self, DestructiveAction::DeleteSnapshot { .. })
+    }
+
+    /// Whether the user should be asked before the action is applied
+    pub fn needs_confirmation(&self) -> bool {
+        match self {
+            DestructiveAction::RemoveNodes(nodes) => nodes.len() > 1,
+            DestructiveAction::DisconnectNodes(_) => false,
+            DestructiveAction::ClearGraph => true,
+            DestructiveAction::RestoreSnapshot { .. } => true,
+            DestructiveAction::DeleteSnapshot { .. } => true,
+            DestructiveAction::WriteFile { path, .. } => path.exists(),
+        }
+    }
+}
+
+/// State that can be put back by undoing an action
+#[derive(Debug, Clone)]
+pub enum UndoEntry {
+    /// The whole root graph, including the contents of any groups
+    Graph(SavedGraph),
+    /// A file's previous contents, or `None` if it did not exist
+    File {
+        path: PathBuf,
+        contents: Option<Vec<u8>>,
+    },
+}
+
+/// Previous states, most recent last
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    entries: Vec<(String, UndoEntry)>,
+}
+
+impl UndoHistory {
+    pub fn push(&mut self, description: String, entry: UndoEntry) {
+        if self.entries.len() == MAX_UNDO {
+            self.entries.remove(0);
+        }
+        self.entries.push((description, entry));
+    }
+
+    pub fn pop(&mut self) -> Option<UndoEntry> {
+        self.entries.pop().map(|(_, entry)| entry)
+    }
+
+    /// Description of the action that would be undone next
+    pub fn last_description(&self) -> Option<&str> {
+        self.entries
+            .last()
+            .map(|(description, _)| description.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,1642 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use eframe::egui::{self, Color32, Pos2, Rect, Ui, Vec2};
+use egui_snarl::{
+    ui::{PinInfo, SnarlStyle, SnarlViewer},
+    InPin, InPinId, NodeId, OutPin, OutPinId, Snarl,
+};
+use graph_core::{
+    adjacency::Adjacency,
+    args::ArgNode,
+    bypass,
+    cache::NodeCache,
+    command::CommandNode,
+    context::EvalContext,
+    convert_nodes::{ParseNumberNode, ToTextNode},
+    diff::NodeDiff,
+    expression::ExpressionNode,
+    file_nodes::{ReadFileNode, WriteFileNode},
+    flow_nodes::{DeadbandNode, ThrottleNode},
+    graphics_nodes::{MakeColorNode, MakeVecNode, SplitVecNode},
+    group,
+    http::HttpRequestNode,
+    i18n,
+    list_nodes::{LengthNode, ListLiteralNode, MapAddNode, RangeNode, SumNode},
+    node_graph::{
+        self, AddNode, CompareNode, CompareOp, DataType, IfNode, LogicNode, LogicOp, Node, NotNode,
+        NumberNode, SinkNode, TypeColours, TypedData,
+    },
+    plot::PlotNode,
+    plugins, registry,
+    run_report::{OutputChange, RunReport},
+    script::ScriptNode,
+    secrets::SecretNode,
+    stages::Stages,
+    state_nodes::{CounterNode, DelayNode, MovingAverageNode, RunningSumNode, TimeNode},
+    table::{CsvSourceNode, TableSinkNode},
+    template::{Template, TemplateLibrary},
+};
+
+use crate::{
+    canvas::Navigation,
+    keyboard::{self, Focus, KeyboardNav},
+    palette::{self, NodePalette, Picked},
+    undo::DestructiveAction,
+    wires::PinPositions,
+};
+
+/// Outline of nodes whose outputs changed in the last evaluation
+const CHANGED_COLOUR: Color32 = Color32::from_rgb(0xe0, 0x9a, 0x2a);
+/// Outline of nodes added since the graph being compared with
+const ADDED_COLOUR: Color32 = Color32::from_rgb(0x3c, 0xb0, 0x4a);
+/// Outline of nodes changed since the graph being compared with
+const EDITED_COLOUR: Color32 = Color32::from_rgb(0x3a, 0x8e, 0xe0);
+/// Most nodes listed when searching for one to go to
+/// Colours of the bands drawn around the nodes of each stage, in turn
+const STAGE_COLOURS: [Color32; 4] = [
+    Color32::from_rgb(0x8e, 0x6a, 0xd8),
+    Color32::from_rgb(0x2a, 0xa8, 0x9a),
+    Color32::from_rgb(0xd8, 0x6a, 0x8e),
+    Color32::from_rgb(0x9a, 0xa8, 0x2a),
+];
+const MAX_GO_TO: usize = 12;
+
+pub enum NewComment {
+    /// At a position on the graph
+    At(Pos2),
+    /// Around the selected nodes
+    AroundSelected,
+}
+
+#[derive(Default)]
+pub struct DemoViewer {
+    /// Nodes selected in the currently displayed graph
+    pub selected: BTreeSet<NodeId>,
+    /// Group node the user has asked to open
+    pub open_group: Option<NodeId>,
+    /// Node shown in the inspector
+    pub inspected: Option<NodeId>,
+    /// Set when the displayed graph was changed in a way that affects values
+    pub modified: bool,
+    /// Reports of evaluations triggered from within the graph UI
+    pub reports: Vec<RunReport>,
+    /// Action the user has asked for, to be applied by the editor so that it
+    /// can be confirmed and undone
+    pub pending: Option<DestructiveAction>,
+    /// Errors of the nodes that failed when last evaluated
+    pub failures: BTreeMap<NodeId, String>,
+    /// Only evaluate the graph when asked to, rather than after every edit
+    pub manual: bool,
+    /// Recalculate edits to the root graph on a worker thread, by adding
+    /// the edited nodes to `requested` rather than evaluating them
+    pub background: bool,
+    /// Nodes edited since the last frame, to be recalculated in the
+    /// background
+    pub requested: BTreeSet<NodeId>,
+    /// Nodes being recalculated in the background, shown with a spinner
+    pub computing: BTreeSet<NodeId>,
+    /// Outputs of pure nodes kept between the editor's evaluations
+    pub cache: NodeCache,
+    /// Colours of the pins and wires of each data type
+    pub colours: TypeColours,
+    /// Node to draw attention to, such as one hovered in the timeline
+    pub highlighted: Option<NodeId>,
+    /// Quick-add popup, if it is open
+    pub palette: Option<NodePalette>,
+    /// Where on the screen the quick-add popup was asked for from the
+    /// keyboard, to be opened by the editor
+    pub palette_at: Option<Pos2>,
+    /// Node or pin the keyboard is on, and the wire being connected
+    pub keyboard: KeyboardNav,
+    /// Nodes saved by the user to be added again, listed in the graph menu
+    /// and the palette
+    pub templates: TemplateLibrary,
+    /// Set when `templates` has changed and needs saving
+    pub templates_modified: bool,
+    /// Problems to tell the user about, such as a template that couldn't be
+    /// added
+    pub errors: Vec<String>,
+    /// Comment the user has asked to add
+    pub new_comment: Option<NewComment>,
+    /// Change to the view the user has asked for
+    pub navigation: Option<Navigation>,
+    /// Whether the displayed graph is the root graph, as comments and
+    /// breakpoints are only kept for it
+    pub at_root: bool,
+    /// Nodes of the root graph that pause a debugging run before they are
+    /// updated
+    pub breakpoints: BTreeSet<NodeId>,
+    /// Priorities given to nodes of the root graph, used to order their
+    /// tasks by [`Priority::Assigned`](graph_core::schedule::Priority::Assigned)
+    pub priorities: BTreeMap<NodeId, i32>,
+    /// Named stages the nodes of the root graph are tagged with, each
+    /// finishing before the next starts
+    pub stages: Stages,
+    /// Node a debugging run is paused before
+    pub paused_at: Option<NodeId>,
+    /// Colour nodes by whether the last evaluation changed their outputs
+    pub show_changes: bool,
+    /// Outputs of the nodes updated by the last evaluation of the root
+    /// graph, from before and after the update
+    pub changes: BTreeMap<NodeId, OutputChange>,
+    /// How the nodes of the root graph differ from a graph it is being
+    /// compared with, see [`GraphDiff`](graph_core::diff::GraphDiff)
+    pub compared: BTreeMap<NodeId, NodeDiff>,
+    /// Label every wire with the value flowing along it, rather than only
+    /// the wire that is hovered
+    pub wire_labels: bool,
+    /// Dependencies between the nodes of the root graph, kept up to date
+    /// with the edits made through the viewer. Cleared when the root graph
+    /// is changed in other ways, and rebuilt when next needed.
+    pub adjacency: Option<Adjacency>,
+    /// Screen area of the header and pins of each node drawn this frame
+    node_rects: BTreeMap<NodeId, Rect>,
+    /// Where the pins were drawn this frame, to find the wires between them
+    pins: PinPositions,
+    /// Where the rubber band being dragged out started and currently ends
+    band: Option<(Pos2, Pos2)>,
+    band_released: bool,
+    /// Positions of the selected nodes before the canvas was drawn, to find
+    /// out if one of them was dragged
+    selected_positions: BTreeMap<NodeId, Pos2>,
+    /// Search typed into the graph menu, and when the menu was last shown
+    menu_search: String,
+    menu_selected: usize,
+    menu_frame: u64,
+    /// Search for a node to go to
+    go_to_search: String,
+    /// Node whose label is being edited in its header, and the text typed
+    /// so far
+    renaming: Option<(NodeId, String)>,
+    /// Node whose menu is saving a template, and the name typed for it
+    template_name: Option<(NodeId, String)>,
+}
+
+impl DemoViewer {
+    /// Settings for evaluating the graph from the editor, sharing its cache
+    pub fn eval_context(&self) -> EvalContext {
+        EvalContext {
+            cache: self.cache.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Recalculates `start` and everything downstream of it in the root
+    /// graph, or the whole graph, reusing the dependencies between its nodes
+    pub fn evaluate_root(
+        &mut self,
+        snarl: &mut Snarl<Box<dyn Node>>,
+        start: Option<NodeId>,
+    ) -> RunReport {
+        let ctx = self.eval_context();
+        let adjacency = self.adjacency.get_or_insert_with(|| Adjacency::new(snarl));
+        node_graph::evaluate_in(snarl, adjacency, start, &ctx)
+    }
+
+    /// Brings the dependencies of the root graph up to date after the wires
+    /// of `nodes` were changed, or they were added or removed
+    pub fn refresh_adjacency(
+        &mut self,
+        snarl: &Snarl<Box<dyn Node>>,
+        nodes: impl IntoIterator<Item = NodeId>,
+    ) {
+        if let Some(adjacency) = &mut self.adjacency {
+            adjacency.refresh(snarl, nodes);
+        }
+    }
+
+    /// Adds the nodes just added to the displayed graph, such as from a
+    /// menu, to the dependencies of the root graph
+    pub fn refresh_added_nodes(&mut self, snarl: &Snarl<Box<dyn Node>>) {
+        if !self.at_root {
+            return;
+        }
+        let Some(adjacency) = &mut self.adjacency else {
+            return;
+        };
+        let added = snarl
+            .node_ids()
+            .map(|(id, _)| id)
+            .filter(|id| !adjacency.contains(*id))
+            .collect::<Vec<_>>();
+        adjacency.refresh(snarl, added);
+    }
+
+    /// Draws a band around the nodes of each stage, labelled with its name
+    fn stage_bands(&self, ui: &Ui) {
+        for (idx, name) in self.stages.names().iter().enumerate() {
+            let Some(band) = self
+                .stages
+                .members(idx)
+                .filter_map(|node| self.node_rects.get(&node))
+                .copied()
+                .reduce(|band, rect| band.union(rect))
+            else {
+                continue;
+            };
+            let band = band.expand(12.);
+            let colour = STAGE_COLOURS[idx % STAGE_COLOURS.len()];
+            ui.painter().rect(
+                band,
+                6.,
+                colour.gamma_multiply(0.08),
+                egui::Stroke::new(1., colour.gamma_multiply(0.6)),
+            );
+            ui.painter().text(
+                band.left_top() + Vec2::new(6., 2.),
+                egui::Align2::LEFT_TOP,
+                format!("{}. {}", idx + 1, name),
+                egui::FontId::proportional(11.),
+                colour,
+            );
+        }
+    }
+
+    /// Priorities of the root graph's tasks, see
+    /// [`TaskDag::with_priorities`](graph_core::execution_engine::TaskDag::with_priorities)
+    pub fn task_priorities(&self) -> HashMap<NodeId, i32> {
+        self.priorities
+            .iter()
+            .map(|(id, priority)| (*id, *priority))
+            .collect()
+    }
+
+    /// Lets the user edit a node's label in its header
+    fn start_renaming(&mut self, id: NodeId, node: &dyn Node) {
+        self.renaming = Some((id, node.label().unwrap_or_default().to_owned()));
+    }
+
+    /// Adds the nodes of a template to the graph, selecting them
+    pub fn insert_template(&mut self, idx: usize, snarl: &mut Snarl<Box<dyn Node>>, pos: Pos2) {
+        let Some(template) = self.templates.templates.get(idx) else {
+            return;
+        };
+        match template.insert(snarl, pos) {
+            Ok(nodes) => self.selected = nodes.into_iter().collect(),
+            Err(err) => self.errors.push(format!(
+                "Failed to add template \"{}\": {}",
+                template.name, err
+            )),
+        }
+    }
+
+    /// Searches the nodes of the displayed graph, selecting the one picked
+    /// and moving the view to it
+    pub fn go_to_ui(&mut self, ui: &mut Ui, snarl: &Snarl<Box<dyn Node>>) {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.go_to_search)
+                .hint_text(i18n::tr("search"))
+                .desired_width(120.),
+        );
+        response.request_focus();
+        let found = palette::find_nodes(&self.go_to_search, snarl);
+        if found.is_empty() {
+            ui.weak(i18n::tr("graph-no-matching-nodes"));
+        }
+        let mut picked = ui
+            .input(|i| i.key_pressed(egui::Key::Enter))
+            .then(|| found.first().copied())
+            .flatten();
+        for &id in found.iter().take(MAX_GO_TO) {
+            let node = snarl[id].as_ref();
+            let text = format!(
+                "{}  ·  {}",
+                node_graph::display_name(node, id),
+                i18n::node_name(&node.name())
+            );
+            if ui.button(text).clicked() {
+                picked = Some(id);
+            }
+        }
+        if found.len() > MAX_GO_TO {
+            ui.weak(i18n::tr_args(
+                "graph-more-nodes",
+                &[("count", &(found.len() - MAX_GO_TO))],
+            ));
+        }
+        if let Some(id) = picked {
+            self.selected = [id].into();
+            self.navigation = Some(Navigation::JumpTo(id));
+            self.go_to_search.clear();
+            ui.close_menu();
+        }
+    }
+
+    /// Recalculates a node that was edited and everything downstream of it,
+    /// unless evaluation has been left to the user
+    fn node_changed(&mut self, snarl: &mut Snarl<Box<dyn Node>>, node: NodeId) {
+        if !self.manual {
+            if self.background && self.at_root {
+                self.requested.insert(node);
+            } else if self.at_root {
+                let report = self.evaluate_root(snarl, Some(node));
+                self.reports.push(report);
+            } else {
+                self.reports.push(node_graph::evaluate_with(
+                    snarl,
+                    Some(node),
+                    &self.eval_context(),
+                ));
+            }
+        }
+        self.modified = true;
+    }
+
+    /// Handles input that has to be seen before the canvas is drawn. Dragging
+    /// on the canvas with shift held draws a rubber band to select nodes,
+    /// rather than panning.
+    pub fn before_canvas(&mut self, ui: &mut Ui, canvas: Rect, snarl: &Snarl<Box<dyn Node>>) {
+        self.node_rects.clear();
+        self.pins.clear();
+        self.selected_positions = snarl
+            .nodes_pos_ids()
+            .filter(|(id, _, _)| self.selected.contains(id))
+            .map(|(id, pos, _)| (id, pos))
+            .collect();
+
+        if self.band.is_none() && !ui.input(|i| i.modifiers.shift) {
+            return;
+        }
+        let response = ui.interact(canvas, ui.id().with("rubber_band"), egui::Sense::drag());
+        if let Some(pointer) = response.interact_pointer_pos() {
+            if response.drag_started() {
+                self.band = Some((pointer, pointer));
+            } else if let Some((_, end)) = &mut self.band {
+                *end = pointer;
+            }
+        }
+        self.band_released = response.drag_released();
+    }
+
+    /// Applies selection and bulk operations once the canvas has been drawn
+    pub fn after_canvas(&mut self, ui: &mut Ui, canvas: Rect, snarl: &mut Snarl<Box<dyn Node>>) {
+        if self.at_root {
+            self.stage_bands(ui);
+        }
+        if self.show_changes && self.at_root {
+            for (id, change) in &self.changes {
+                let Some(rect) = self.node_rects.get(id) else {
+                    continue;
+                };
+                let stroke = if change.changed() {
+                    egui::Stroke::new(2., CHANGED_COLOUR)
+                } else {
+                    egui::Stroke::new(1., ui.visuals().weak_text_color())
+                };
+                ui.painter().rect_stroke(rect.expand(3.), 4., stroke);
+            }
+        }
+        if self.at_root {
+            for (id, diff) in &self.compared {
+                let Some(rect) = self.node_rects.get(id) else {
+                    continue;
+                };
+                let colour = match diff {
+                    NodeDiff::Added => ADDED_COLOUR,
+                    NodeDiff::Changed => EDITED_COLOUR,
+                };
+                ui.painter()
+                    .rect_stroke(rect.expand(5.), 4., egui::Stroke::new(2., colour));
+            }
+        }
+        // Disabled nodes are greyed out
+        for (id, rect) in &self.node_rects {
+            if snarl
+                .get_node(*id)
+                .is_some_and(|node| bypass::is_disabled(node.as_ref()))
+            {
+                ui.painter().rect_filled(
+                    rect.expand(3.),
+                    4.,
+                    ui.visuals().extreme_bg_color.gamma_multiply(0.6),
+                );
+            }
+        }
+        if let Some(rect) = self.highlighted.and_then(|id| self.node_rects.get(&id)) {
+            ui.painter().rect_stroke(
+                rect.expand(6.),
+                4.,
+                egui::Stroke::new(2., ui.visuals().selection.bg_fill),
+            );
+        }
+        if let Some(rect) = self
+            .paused_at
+            .filter(|_| self.at_root)
+            .and_then(|id| self.node_rects.get(&id))
+        {
+            ui.painter().rect_stroke(
+                rect.expand(6.),
+                4.,
+                egui::Stroke::new(2., ui.visuals().warn_fg_color),
+            );
+        }
+        if let Some((start, end)) = self.band {
+            let band = Rect::from_two_pos(start, end);
+            let visuals = &ui.visuals().selection;
+            ui.painter().rect(
+                band,
+                0.,
+                visuals.bg_fill.gamma_multiply(0.2),
+                visuals.stroke,
+            );
+            if self.band_released {
+                self.selected.extend(
+                    self.node_rects
+                        .iter()
+                        .filter(|(_, rect)| rect.intersects(band))
+                        .map(|(id, _)| *id),
+                );
+                self.band = None;
+            }
+        }
+
+        // Dragging one of the selected nodes drags the rest along with it
+        let dragged = snarl
+            .nodes_pos_ids()
+            .filter_map(|(id, pos, _)| Some(pos - *self.selected_positions.get(&id)?))
+            .find(|delta| *delta != Vec2::ZERO);
+        if let Some(delta) = dragged {
+            for (&id, &before) in &self.selected_positions {
+                if snarl
+                    .nodes_pos_ids()
+                    .any(|(other, pos, _)| other == id && pos == before)
+                {
+                    move_node(snarl, id, delta);
+                }
+            }
+        }
+
+        let focused = self.keyboard_input(ui, canvas, snarl);
+        if !(focused || ui.rect_contains_pointer(canvas) && !ui.ctx().wants_keyboard_input())
+            || self.palette.is_some()
+        {
+            return;
+        }
+        let select_all = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::A);
+        let disconnect = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::D,
+        );
+        ui.input_mut(|i| {
+            if i.consume_shortcut(&select_all) {
+                self.selected = snarl.node_ids().map(|(id, _)| id).collect();
+            }
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Escape) {
+                self.selected.clear();
+            }
+            if self.selected.is_empty() {
+                return;
+            }
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Delete)
+                || i.consume_key(egui::Modifiers::NONE, egui::Key::Backspace)
+            {
+                self.pending = Some(DestructiveAction::RemoveNodes(self.selected.clone()));
+            }
+            if i.consume_shortcut(&disconnect) {
+                self.pending = Some(DestructiveAction::DisconnectNodes(self.selected.clone()));
+            }
+
+            let step = if i.modifiers.shift { 50. } else { 10. };
+            let mut delta = Vec2::ZERO;
+            for (key, direction) in [
+                (egui::Key::ArrowLeft, egui::vec2(-1., 0.)),
+                (egui::Key::ArrowRight, egui::vec2(1., 0.)),
+                (egui::Key::ArrowUp, egui::vec2(0., -1.)),
+                (egui::Key::ArrowDown, egui::vec2(0., 1.)),
+            ] {
+                if i.key_pressed(key) {
+                    delta += direction * step;
+                }
+            }
+            if delta != Vec2::ZERO {
+                for &id in &self.selected {
+                    move_node(snarl, id, delta);
+                }
+            }
+        });
+    }
+
+    /// Moves the keyboard focus around the canvas and connects pins picked
+    /// from the keyboard, returning whether the canvas has the focus
+    fn keyboard_input(
+        &mut self,
+        ui: &mut Ui,
+        canvas: Rect,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) -> bool {
+        let description = match self.keyboard.focus {
+            Some(focus) => keyboard::describe(snarl, focus),
+            None => i18n::tr("graph-description"),
+        };
+        let response = ui.interact(
+            canvas,
+            keyboard::canvas_id(),
+            egui::Sense::focusable_noninteractive(),
+        );
+        response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Other, &description));
+        if self.keyboard.palette_open && self.palette.is_none() {
+            self.keyboard.palette_open = false;
+            response.request_focus();
+        }
+        if !response.has_focus() {
+            self.keyboard.connecting = None;
+            return false;
+        }
+        // Keep Tab and the arrow keys for moving around the graph, rather
+        // than to other widgets
+        ui.memory_mut(|memory| {
+            memory.set_focus_lock_filter(
+                response.id,
+                egui::EventFilter {
+                    tab: true,
+                    horizontal_arrows: true,
+                    vertical_arrows: true,
+                    escape: true,
+                },
+            )
+        });
+
+        let order = keyboard::tab_order(snarl, &self.node_rects);
+        let mut moved = None;
+        let mut picked = None;
+        let mut leave = false;
+        ui.input_mut(|i| {
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Tab) {
+                moved = keyboard::step(&order, self.keyboard.focus, false);
+            }
+            if i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab) {
+                moved = keyboard::step(&order, self.keyboard.focus, true);
+            }
+            for (key, direction) in [
+                (egui::Key::ArrowLeft, egui::vec2(-1., 0.)),
+                (egui::Key::ArrowRight, egui::vec2(1., 0.)),
+                (egui::Key::ArrowUp, egui::vec2(0., -1.)),
+                (egui::Key::ArrowDown, egui::vec2(0., 1.)),
+            ] {
+                if !i.consume_key(egui::Modifiers::ALT, key) {
+                    continue;
+                }
+                let nearest = match self.keyboard.focus {
+                    Some(focus) => keyboard::nearest(&self.node_rects, focus.node(), direction),
+                    None => order.first().map(|focus| focus.node()),
+                };
+                moved = nearest.map(Focus::Node).or(moved);
+            }
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Enter) {
+                match self.keyboard.focus {
+                    Some(pin) if pin.is_pin() => picked = Some(pin),
+                    focus => {
+                        // Next to the focused node, or in the middle of the
+                        // canvas
+                        let at = focus
+                            .and_then(|focus| self.node_rects.get(&focus.node()))
+                            .map_or(canvas.center(), |rect| {
+                                rect.right_top() + egui::vec2(40., 0.)
+                            });
+                        self.palette_at = Some(at);
+                        self.keyboard.palette_open = true;
+                    }
+                }
+            }
+            if let Some(node) = self.keyboard.focus.map(Focus::node) {
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::Space)
+                    && !self.selected.remove(&node)
+                {
+                    self.selected.insert(node);
+                }
+            }
+            if self.keyboard.connecting.is_some() {
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::Escape) {
+                    self.keyboard.connecting = None;
+                }
+            } else if self.selected.is_empty()
+                && i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)
+            {
+                leave = true;
+            }
+        });
+        if leave {
+            self.keyboard.focus = None;
+            ui.memory_mut(|memory| memory.surrender_focus(response.id));
+            return false;
+        }
+
+        if let Some(focus) = moved {
+            self.keyboard.focus = Some(focus);
+            if let Some(rect) = self.node_rects.get(&focus.node()) {
+                if !canvas.contains_rect(*rect) {
+                    self.navigation = Some(Navigation::JumpTo(focus.node()));
+                }
+            }
+        }
+        if let Some(pin) = picked {
+            self.pick_pin(pin, snarl);
+        }
+        self.draw_keyboard_focus(ui);
+        true
+    }
+
+    /// Picks a pin as one end of a wire, connecting it once the other end
+    /// is picked
+    fn pick_pin(&mut self, pin: Focus, snarl: &mut Snarl<Box<dyn Node>>) {
+        match (self.keyboard.connecting, pin) {
+            (Some(Focus::Output(from)), Focus::Input(to))
+            | (Some(Focus::Input(to)), Focus::Output(from))
+                if from.node != to.node =>
+            {
+                let (from, to) = (snarl.out_pin(from), snarl.in_pin(to));
+                self.connect(&from, &to, snarl);
+                self.keyboard.connecting = None;
+            }
+            (Some(connecting), _) if connecting == pin => self.keyboard.connecting = None,
+            _ => self.keyboard.connecting = Some(pin),
+        }
+    }
+
+    /// Outlines the node or pin the keyboard is on, along with the wire
+    /// being connected
+    fn draw_keyboard_focus(&self, ui: &Ui) {
+        let stroke = egui::Stroke::new(2., ui.visuals().selection.stroke.color);
+        let pin_pos = |focus: Focus| match focus {
+            Focus::Node(_) => None,
+            Focus::Input(pin) => self.pins.input(pin),
+            Focus::Output(pin) => self.pins.output(pin),
+        };
+        match self.keyboard.focus {
+            Some(Focus::Node(node)) => {
+                if let Some(rect) = self.node_rects.get(&node) {
+                    ui.painter().rect_stroke(rect.expand(9.), 4., stroke);
+                }
+            }
+            Some(pin) => {
+                if let Some(pos) = pin_pos(pin) {
+                    ui.painter().circle_stroke(pos, 8., stroke);
+                }
+            }
+            None => {}
+        }
+        if let Some(start) = self.keyboard.connecting.and_then(pin_pos) {
+            ui.painter().circle_filled(start, 5., stroke.color);
+            if let Some(end) = self.keyboard.focus.and_then(pin_pos) {
+                ui.painter().line_segment([start, end], stroke);
+            }
+        }
+    }
+
+    /// Moves the keyboard focus to a node just added from the palette it
+    /// opened
+    pub fn focus_added(&mut self, node: NodeId) {
+        if self.keyboard.palette_open {
+            self.keyboard.focus = Some(Focus::Node(node));
+        }
+    }
+
+    /// Shows the values flowing along the wires drawn this frame
+    pub fn show_wire_values(
+        &self,
+        ui: &Ui,
+        canvas: Rect,
+        snarl: &Snarl<Box<dyn Node>>,
+        style: &SnarlStyle,
+    ) {
+        let covered = self.node_rects.values().copied().collect::<Vec<_>>();
+        self.pins
+            .show_values(ui, canvas, snarl, style, &covered, self.wire_labels);
+    }
+
+    /// Screen area of a node drawn this frame
+    pub fn node_rect(&self, node: NodeId) -> Option<Rect> {
+        self.node_rects.get(&node).copied()
+    }
+
+    /// Screen area covered by the selected nodes drawn this frame
+    pub fn selection_rect(&self) -> Option<Rect> {
+        self.selected
+            .iter()
+            .filter_map(|id| self.node_rects.get(id))
+            .copied()
+            .reduce(Rect::union)
+    }
+
+    fn extend_node_rect(&mut self, node: NodeId, rect: Rect) {
+        self.node_rects
+            .entry(node)
+            .and_modify(|existing| *existing = existing.union(rect))
+            .or_insert(rect);
+    }
+
+    /// Entries of the graph menu, for adding nodes and moving the view
+    fn add_node_menu(&mut self, pos: egui::Pos2, ui: &mut Ui, snarl: &mut Snarl<Box<dyn Node>>) {
+        // Start a new search each time the menu is opened
+        let frame = ui.ctx().frame_nr();
+        if self.menu_frame + 1 < frame {
+            self.menu_search.clear();
+            self.menu_selected = 0;
+        }
+        self.menu_frame = frame;
+
+        ui.label(i18n::tr("graph-add-node"));
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.menu_search)
+                .hint_text(i18n::tr("search"))
+                .desired_width(120.),
+        );
+        response.request_focus();
+        if response.changed() {
+            self.menu_selected = 0;
+        }
+        if !self.menu_search.is_empty() {
+            let picked = palette::results_ui(
+                ui,
+                &self.menu_search,
+                &mut self.menu_selected,
+                &self.templates,
+            );
+            match picked {
+                Some(Picked::Node(kind)) => {
+                    snarl.insert_node(pos, (kind.create)());
+                }
+                Some(Picked::Template(idx)) => self.insert_template(idx, snarl, pos),
+                None => return,
+            }
+            ui.close_menu();
+            return;
+        }
+        if !self.templates.templates.is_empty() {
+            ui.menu_button(i18n::tr("graph-templates"), |ui| {
+                let mut picked = None;
+                for (idx, template) in self.templates.templates.iter().enumerate() {
+                    if ui.button(&template.name).clicked() {
+                        picked = Some(idx);
+                    }
+                }
+                if let Some(idx) = picked {
+                    self.insert_template(idx, snarl, pos);
+                    ui.close_menu();
+                }
+            });
+        }
+        if ui.button(i18n::node_name("Number")).clicked() {
+            snarl.insert_node(pos, Box::new(NumberNode::new(0.)));
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Sink")).clicked() {
+            snarl.insert_node(pos, Box::<SinkNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Plot")).clicked() {
+            snarl.insert_node(pos, Box::<PlotNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Table")).clicked() {
+            snarl.insert_node(pos, Box::<TableSinkNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Secret")).clicked() {
+            snarl.insert_node(pos, Box::<SecretNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Argument")).clicked() {
+            snarl.insert_node(pos, Box::<ArgNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Add")).clicked() {
+            snarl.insert_node(pos, Box::<AddNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("If")).clicked() {
+            snarl.insert_node(pos, Box::<IfNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Expression")).clicked() {
+            snarl.insert_node(pos, Box::<ExpressionNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Script")).clicked() {
+            snarl.insert_node(pos, Box::<ScriptNode>::default());
+            ui.close_menu();
+        }
+        ui.menu_button(i18n::category_name("Lists"), |ui| {
+            list_menu(pos, ui, snarl);
+        });
+        ui.menu_button(i18n::category_name("Logic"), |ui| {
+            for op in [
+                CompareOp::GreaterThan,
+                CompareOp::LessThan,
+                CompareOp::Equals,
+            ] {
+                let node = CompareNode::new(op);
+                if ui.button(i18n::node_name(&node.name())).clicked() {
+                    snarl.insert_node(pos, Box::new(node));
+                    ui.close_menu();
+                }
+            }
+            for op in [LogicOp::And, LogicOp::Or] {
+                let node = LogicNode::new(op);
+                if ui.button(i18n::node_name(&node.name())).clicked() {
+                    snarl.insert_node(pos, Box::new(node));
+                    ui.close_menu();
+                }
+            }
+            if ui.button(i18n::node_name("Not")).clicked() {
+                snarl.insert_node(pos, Box::<NotNode>::default());
+                ui.close_menu();
+            }
+        });
+        ui.menu_button(i18n::category_name("Conversions"), |ui| {
+            if ui.button(i18n::node_name("To Text")).clicked() {
+                snarl.insert_node(pos, Box::<ToTextNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Parse Number")).clicked() {
+                snarl.insert_node(pos, Box::<ParseNumberNode>::default());
+                ui.close_menu();
+            }
+        });
+        ui.menu_button(i18n::category_name("Files"), |ui| {
+            if ui.button(i18n::node_name("Read File")).clicked() {
+                snarl.insert_node(pos, Box::<ReadFileNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Write File")).clicked() {
+                snarl.insert_node(pos, Box::<WriteFileNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("CSV Source")).clicked() {
+                snarl.insert_node(pos, Box::<CsvSourceNode>::default());
+                ui.close_menu();
+            }
+        });
+        ui.menu_button(i18n::category_name("Flow"), |ui| {
+            if ui.button(i18n::node_name("Throttle")).clicked() {
+                snarl.insert_node(pos, Box::<ThrottleNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Deadband")).clicked() {
+                snarl.insert_node(pos, Box::<DeadbandNode>::default());
+                ui.close_menu();
+            }
+        });
+        ui.menu_button(i18n::category_name("State"), |ui| {
+            if ui.button(i18n::node_name("Running Sum")).clicked() {
+                snarl.insert_node(pos, Box::<RunningSumNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Moving Average")).clicked() {
+                snarl.insert_node(pos, Box::<MovingAverageNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Counter")).clicked() {
+                snarl.insert_node(pos, Box::<CounterNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Delay")).clicked() {
+                snarl.insert_node(pos, Box::<DelayNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Time")).clicked() {
+                snarl.insert_node(pos, Box::<TimeNode>::default());
+                ui.close_menu();
+            }
+        });
+        ui.menu_button(i18n::category_name("Graphics"), |ui| {
+            if ui.button(i18n::node_name("Make Colour")).clicked() {
+                snarl.insert_node(pos, Box::<MakeColorNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Make Vector")).clicked() {
+                snarl.insert_node(pos, Box::<MakeVecNode>::default());
+                ui.close_menu();
+            }
+            if ui.button(i18n::node_name("Split Vector")).clicked() {
+                snarl.insert_node(pos, Box::<SplitVecNode>::default());
+                ui.close_menu();
+            }
+        });
+        if ui.button(i18n::node_name("HTTP Request")).clicked() {
+            snarl.insert_node(pos, Box::<HttpRequestNode>::default());
+            ui.close_menu();
+        }
+        if ui.button(i18n::node_name("Command")).clicked() {
+            snarl.insert_node(pos, Box::<CommandNode>::default());
+            ui.close_menu();
+        }
+        let plugin_kinds = plugins::node_kinds();
+        if !plugin_kinds.is_empty() {
+            ui.menu_button(i18n::category_name("Plugins"), |ui| {
+                let mut categories = BTreeMap::<_, Vec<_>>::new();
+                for kind in plugin_kinds {
+                    categories
+                        .entry(kind.category.clone())
+                        .or_default()
+                        .push(kind);
+                }
+                for (category, kinds) in categories {
+                    ui.menu_button(i18n::category_name(&category), |ui| {
+                        for kind in kinds {
+                            if ui.button(i18n::node_name(&kind.name)).clicked() {
+                                snarl.insert_node(pos, (kind.create)());
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+            });
+        }
+        if self.at_root && ui.button(i18n::node_name("Comment")).clicked() {
+            self.new_comment = Some(NewComment::At(pos));
+            ui.close_menu();
+        }
+        ui.separator();
+        ui.menu_button(i18n::tr("menu-go-to-node"), |ui| self.go_to_ui(ui, snarl));
+        if ui.button(i18n::tr("menu-fit-graph")).clicked() {
+            self.navigation = Some(Navigation::FitAll);
+            ui.close_menu();
+        }
+        if !self.selected.is_empty() {
+            ui.separator();
+            if ui.button(i18n::tr("menu-zoom-to-selected")).clicked() {
+                self.navigation = Some(Navigation::FitSelected);
+                ui.close_menu();
+            }
+            if self.at_root && ui.button(i18n::tr("graph-comment-selected")).clicked() {
+                self.new_comment = Some(NewComment::AroundSelected);
+                ui.close_menu();
+            }
+            if ui
+                .button(i18n::tr_args(
+                    "graph-group-selected",
+                    &[("count", &self.selected.len())],
+                ))
+                .clicked()
+            {
+                let selected = std::mem::take(&mut self.selected);
+                match group::collapse_into_group(snarl, &selected) {
+                    Ok(group) => {
+                        if self.at_root {
+                            self.refresh_adjacency(snarl, selected.into_iter().chain([group]));
+                        }
+                        self.node_changed(snarl, group);
+                    }
+                    Err(err) => {
+                        self.errors.push(format!("Failed to group nodes: {}", err));
+                        self.selected = selected;
+                    }
+                }
+                ui.close_menu();
+            }
+        }
+    }
+}
+
+impl SnarlViewer<Box<dyn Node>> for DemoViewer {
+    fn show_header(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) {
+        //ui.label(self.title(&snarl[node]));
+        match &mut self.renaming {
+            Some((renaming, label)) if *renaming == node => {
+                let response = ui.add(egui::TextEdit::singleline(label).desired_width(80.));
+                response.request_focus();
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    self.renaming = None;
+                } else if response.lost_focus() {
+                    let label = label.trim();
+                    snarl[node].set_label((!label.is_empty()).then(|| label.to_owned()));
+                    self.renaming = None;
+                }
+            }
+            _ => {
+                let mut text =
+                    egui::RichText::new(node_graph::display_name(snarl[node].as_ref(), node));
+                if self.selected.contains(&node) {
+                    text = text.strong().underline();
+                }
+                let disabled = bypass::is_disabled(snarl[node].as_ref());
+                if disabled {
+                    text = text.weak().strikethrough();
+                }
+                let mut response = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                if snarl[node].label().is_some() {
+                    response = response.on_hover_text(format!("ID: {}", node.0));
+                }
+                if disabled {
+                    response = response.on_hover_text(disabled_text(snarl[node].as_ref()));
+                }
+                let description = keyboard::describe(snarl, Focus::Node(node));
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Label, &description)
+                });
+                if response.clicked() {
+                    if !ui.input(|i| i.modifiers.shift) {
+                        self.inspected = Some(node);
+                    } else if !self.selected.remove(&node) {
+                        self.selected.insert(node);
+                    }
+                }
+                // Groups are opened by double-clicking them, so they can only
+                // be renamed from the node menu
+                if response.double_clicked() {
+                    if snarl[node].subgraph().is_some() {
+                        self.open_group = Some(node);
+                    } else {
+                        self.start_renaming(node, snarl[node].as_ref());
+                    }
+                }
+            }
+        }
+
+        self.extend_node_rect(node, ui.min_rect());
+
+        if self.at_root && self.breakpoints.contains(&node) {
+            let marker = egui::RichText::new("●").color(ui.visuals().error_fg_color);
+            let text = if self.paused_at == Some(node) {
+                "node-breakpoint-paused"
+            } else {
+                "node-breakpoint"
+            };
+            ui.label(marker).on_hover_text(i18n::tr(text));
+        }
+        if let Some(priority) = self.priorities.get(&node).filter(|_| self.at_root) {
+            ui.weak(format!("P{}", priority))
+                .on_hover_text(i18n::tr_args("node-priority", &[("priority", priority)]));
+        }
+        if let Some(stage) = self.stages.stage_of(node).filter(|_| self.at_root) {
+            let colour = STAGE_COLOURS[stage % STAGE_COLOURS.len()];
+            ui.label(egui::RichText::new(format!("S{}", stage + 1)).color(colour))
+                .on_hover_text(i18n::tr_args(
+                    "node-stage",
+                    &[("stage", &self.stages.names()[stage])],
+                ));
+        }
+        if let Some(change) = self
+            .changes
+            .get(&node)
+            .filter(|change| self.show_changes && self.at_root && change.changed())
+        {
+            let badge = egui::RichText::new("Δ").color(CHANGED_COLOUR);
+            ui.label(badge).on_hover_text(describe_change(change));
+        }
+        if self.computing.contains(&node) {
+            ui.add(egui::Spinner::new())
+                .on_hover_text(i18n::tr("node-computing"));
+        }
+        if let Some(message) = self.failures.get(&node) {
+            let badge = egui::RichText::new("⚠").color(ui.visuals().error_fg_color);
+            if ui
+                .add(egui::Label::new(badge).sense(egui::Sense::click()))
+                .on_hover_text(message)
+                .clicked()
+            {
+                self.inspected = Some(node);
+            }
+        }
+    }
+
+    fn connect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<Box<dyn Node>>) {
+        let from_node = &snarl[from.id.node];
+        let to_node = &snarl[to.id.node];
+
+        let (Some(output), Some(input)) = (
+            from_node.outputs().get(from.id.output).cloned(),
+            to_node.inputs().get(to.id.input).cloned(),
+        ) else {
+            return;
+        };
+        // Pins of different types are joined through a conversion node, if
+        // there is one, and wires between them are ignored otherwise
+        let coercion = if output.compatible_with(&input) {
+            None
+        } else {
+            match registry::create_coercion(&output, &input) {
+                Some(node) => Some(node),
+                None => return,
+            }
+        };
+        // Wires that would make the graph cyclic are kept, but nothing is
+        // updated through them
+        let cyclic = if self.at_root {
+            self.adjacency
+                .get_or_insert_with(|| Adjacency::new(snarl))
+                .would_create_cycle(from.id.node, to.id.node)
+        } else {
+            Adjacency::new(snarl).would_create_cycle(from.id.node, to.id.node)
+        };
+
+        // Inputs without a reducer only take one wire, so it replaces the
+        // others
+        if to_node.input_reducer(to.id.input).is_none() {
+            for &remote in &to.remotes {
+                snarl.disconnect(remote, to.id);
+            }
+        }
+
+        // Add the new connection
+        let changed = match coercion {
+            Some(node) => {
+                let position = |node| {
+                    snarl
+                        .nodes_pos_ids()
+                        .find(|(id, _, _)| *id == node)
+                        .map_or(Pos2::ZERO, |(_, pos, _)| pos)
+                };
+                let pos = position(from.id.node).lerp(position(to.id.node), 0.5);
+                let conversion = snarl.insert_node(pos, node);
+                snarl.connect(
+                    from.id,
+                    InPinId {
+                        node: conversion,
+                        input: 0,
+                    },
+                );
+                snarl.connect(
+                    OutPinId {
+                        node: conversion,
+                        output: 0,
+                    },
+                    to.id,
+                );
+                conversion
+            }
+            None => {
+                snarl.connect(from.id, to.id);
+                to.id.node
+            }
+        };
+        // Every changed wire ends at the destination or the conversion node
+        if self.at_root {
+            self.refresh_adjacency(snarl, [to.id.node, changed]);
+        }
+        if cyclic {
+            return;
+        }
+
+        // Update the destination node and propogate its value
+        self.node_changed(snarl, changed);
+    }
+
+    fn disconnect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<Box<dyn Node>>) {
+        snarl.disconnect(from.id, to.id);
+        if self.at_root {
+            self.refresh_adjacency(snarl, [to.id.node]);
+        }
+        self.modified = true;
+    }
+
+    fn title(&mut self, node: &Box<dyn Node>) -> String {
+        i18n::node_name(&node.name())
+    }
+
+    fn inputs(&mut self, node: &Box<dyn Node>) -> usize {
+        node.inputs().len()
+    }
+
+    fn outputs(&mut self, node: &Box<dyn Node>) -> usize {
+        node.outputs().len()
+    }
+
+    fn show_input(
+        &mut self,
+        pin: &InPin,
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) -> PinInfo {
+        // The canvas makes space for the pin before the input is shown
+        let pin_space = ui.min_rect();
+        // Several wires are shown combined, as the node will receive them
+        let remote = node_graph::input_value(snarl, pin.id);
+        let should_update = snarl[pin.id.node].show_input(pin.id.input, remote, ui);
+        if pin.remotes.is_empty() {
+            if let Some(default) = snarl[pin.id.node]
+                .input_default(pin.id.input)
+                .filter(|default| !matches!(default, TypedData::Unknown))
+            {
+                ui.weak(node_graph::format_value(&default))
+                    .on_hover_text(i18n::tr("node-input-default"));
+            }
+        }
+        self.extend_node_rect(pin.id.node, ui.min_rect());
+        self.pins.record_input(pin.id, ui, pin_space);
+        label_pin(ui, snarl, Focus::Input(pin.id));
+        if should_update {
+            self.node_changed(snarl, pin.id.node);
+        }
+        snarl[pin.id.node].inputs()[pin.id.input].pin_info(&self.colours)
+    }
+
+    fn show_output(
+        &mut self,
+        pin: &OutPin,
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) -> PinInfo {
+        let pin_space = ui.min_rect();
+        let input_count = snarl[pin.id.node].inputs().len();
+        let output_count = snarl[pin.id.node].outputs().len();
+        let should_update = snarl[pin.id.node].show_output(pin.id.output, ui);
+        self.extend_node_rect(pin.id.node, ui.min_rect());
+        self.pins.record_output(pin.id, ui, pin_space);
+        label_pin(ui, snarl, Focus::Output(pin.id));
+        if should_update {
+            drop_removed_pins(snarl, pin.id.node, input_count, output_count);
+            if self.at_root {
+                self.refresh_adjacency(snarl, [pin.id.node]);
+            }
+            self.node_changed(snarl, pin.id.node);
+        }
+        // The pin may have just been removed, in which case it is drawn for
+        // one last frame
+        snarl[pin.id.node]
+            .outputs()
+            .get(pin.id.output)
+            .unwrap_or(&DataType::Unknown)
+            .pin_info(&self.colours)
+    }
+
+    fn has_body(&mut self, node: &Box<dyn Node>) -> bool {
+        node.has_body()
+    }
+
+    fn show_body(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) {
+        let input_count = snarl[node].inputs().len();
+        let output_count = snarl[node].outputs().len();
+        let should_update = snarl[node].show_body(ui);
+        self.extend_node_rect(node, ui.min_rect());
+        if should_update {
+            drop_removed_pins(snarl, node, input_count, output_count);
+            if self.at_root {
+                self.refresh_adjacency(snarl, [node]);
+            }
+            self.node_changed(snarl, node);
+        }
+    }
+
+    fn input_color(
+        &mut self,
+        pin: &InPin,
+        style: &egui::Style,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) -> Color32 {
+        let missing = pin
+            .remotes
+            .first()
+            .is_some_and(|remote| snarl[remote.node].output_value(remote.output).is_none());
+        if missing && !self.failures.is_empty() {
+            return style.visuals.error_fg_color;
+        }
+        snarl[pin.id.node].inputs()[pin.id.input].colour(&self.colours)
+    }
+
+    fn output_color(
+        &mut self,
+        pin: &OutPin,
+        style: &egui::Style,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) -> Color32 {
+        if snarl[pin.id.node].error_output() == Some(pin.id.output) {
+            return style.visuals.error_fg_color;
+        }
+        // Wires take their colour from both ends, so tint both to highlight
+        // wires that are missing a value after a failed run
+        let missing = snarl[pin.id.node].output_value(pin.id.output).is_none();
+        if missing && !pin.remotes.is_empty() && !self.failures.is_empty() {
+            return style.visuals.error_fg_color;
+        }
+        snarl[pin.id.node]
+            .outputs()
+            .get(pin.id.output)
+            .unwrap_or(&DataType::Unknown)
+            .colour(&self.colours)
+    }
+
+    fn graph_menu(
+        &mut self,
+        pos: egui::Pos2,
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) {
+        self.add_node_menu(pos, ui, snarl);
+        // Nodes added from the menu have no wires yet, except those of a
+        // template
+        self.refresh_added_nodes(snarl);
+    }
+
+    fn node_menu(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Box<dyn Node>>,
+    ) {
+        ui.label(i18n::tr("node-menu"));
+        let selected = self.selected.contains(&node);
+        if ui
+            .button(i18n::tr(if selected {
+                "node-menu-deselect"
+            } else {
+                "node-menu-select"
+            }))
+            .clicked()
+        {
+            if selected {
+                self.selected.remove(&node);
+            } else {
+                self.selected.insert(node);
+            }
+            ui.close_menu();
+        }
+        if snarl[node].subgraph().is_some() && ui.button(i18n::tr("node-menu-open-group")).clicked()
+        {
+            self.open_group = Some(node);
+            ui.close_menu();
+        }
+        if ui.button(i18n::tr("node-menu-rename")).clicked() {
+            self.start_renaming(node, snarl[node].as_ref());
+            ui.close_menu();
+        }
+        let disabled = bypass::is_disabled(snarl[node].as_ref());
+        if ui
+            .button(i18n::tr(if disabled {
+                "node-menu-enable"
+            } else {
+                "node-menu-disable"
+            }))
+            .on_hover_text(i18n::tr("node-menu-disable-hint"))
+            .clicked()
+        {
+            bypass::set_disabled(snarl, node, !disabled);
+            self.node_changed(snarl, node);
+            ui.close_menu();
+        }
+        if self.at_root {
+            let has_breakpoint = self.breakpoints.contains(&node);
+            let text = if has_breakpoint {
+                "node-menu-remove-breakpoint"
+            } else {
+                "node-menu-add-breakpoint"
+            };
+            if ui.button(i18n::tr(text)).clicked() {
+                if has_breakpoint {
+                    self.breakpoints.remove(&node);
+                } else {
+                    self.breakpoints.insert(node);
+                }
+                ui.close_menu();
+            }
+            ui.horizontal(|ui| {
+                ui.label(i18n::tr("node-menu-priority"));
+                let mut priority = self.priorities.get(&node).copied().unwrap_or_default();
+                let response = ui
+                    .add(egui::DragValue::new(&mut priority))
+                    .on_hover_text(i18n::tr("node-menu-priority-hint"));
+                if response.changed() {
+                    if priority == 0 {
+                        self.priorities.remove(&node);
+                    } else {
+                        self.priorities.insert(node, priority);
+                    }
+                    self.modified = true;
+                }
+            });
+            if !self.stages.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.label(i18n::tr("node-menu-stage"));
+                    let current = self.stages.stage_of(node);
+                    let mut stage = current;
+                    egui::ComboBox::from_id_source(("node_stage", node))
+                        .selected_text(
+                            self.stages
+                                .name_of(node)
+                                .map_or_else(|| i18n::tr("none"), str::to_owned),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut stage, None, i18n::tr("none"));
+                            for (idx, name) in self.stages.names().iter().enumerate() {
+                                ui.selectable_value(&mut stage, Some(idx), name);
+                            }
+                        });
+                    if stage != current {
+                        self.stages.set_stage(node, stage);
+                        self.modified = true;
+                    }
+                });
+            }
+        }
+        // Saves the whole selection if the node is part of it
+        let saved = if selected {
+            self.selected.clone()
+        } else {
+            BTreeSet::from([node])
+        };
+        let title = match saved.len() {
+            1 => i18n::tr("node-menu-save-template"),
+            count => i18n::tr_args("node-menu-save-selected-template", &[("count", &count)]),
+        };
+        ui.menu_button(title, |ui| {
+            if self.template_name.as_ref().map(|(id, _)| *id) != Some(node) {
+                let name = snarl[node]
+                    .label()
+                    .map_or_else(|| snarl[node].name(), str::to_owned);
+                self.template_name = Some((node, name));
+            }
+            let Some((_, name)) = &mut self.template_name else {
+                return;
+            };
+            ui.add(
+                egui::TextEdit::singleline(name)
+                    .hint_text(i18n::tr("node-menu-template-name"))
+                    .desired_width(120.),
+            );
+            let name = name.trim();
+            let exists = self
+                .templates
+                .templates
+                .iter()
+                .any(|template| template.name == name);
+            let text = i18n::tr(if exists {
+                "node-menu-replace-template"
+            } else {
+                "node-menu-add-template"
+            });
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new(text))
+                .clicked()
+            {
+                let template = Template::capture(name.to_owned(), snarl, &saved);
+                self.templates.add(template);
+                self.templates_modified = true;
+                self.template_name = None;
+                ui.close_menu();
+            }
+        });
+        if ui.button(i18n::tr("node-menu-disconnect")).clicked() {
+            self.pending = Some(DestructiveAction::DisconnectNodes(BTreeSet::from([node])));
+            ui.close_menu();
+        }
+        if ui.button(i18n::tr("remove")).clicked() {
+            self.pending = Some(DestructiveAction::RemoveNodes(BTreeSet::from([node])));
+            ui.close_menu();
+        }
+        if selected && self.selected.len() > 1 {
+            ui.separator();
+            let count = self.selected.len();
+            if ui
+                .button(i18n::tr_args(
+                    "node-menu-disconnect-selected",
+                    &[("count", &count)],
+                ))
+                .clicked()
+            {
+                self.pending = Some(DestructiveAction::DisconnectNodes(self.selected.clone()));
+                ui.close_menu();
+            }
+            if ui
+                .button(i18n::tr_args(
+                    "node-menu-remove-selected",
+                    &[("count", &count)],
+                ))
+                .clicked()
+            {
+                self.pending = Some(DestructiveAction::RemoveNodes(self.selected.clone()));
+                ui.close_menu();
+            }
+        }
+    }
+}
+
+/// Disconnects pins removed by editing a node, such as when a variable is
+/// deleted from an expression
+fn drop_removed_pins(
+    snarl: &mut Snarl<Box<dyn Node>>,
+    node: NodeId,
+    input_count: usize,
+    output_count: usize,
+) {
+    for input in snarl[node].inputs().len()..input_count {
+        snarl.drop_inputs(InPinId { node, input });
+    }
+    for output in snarl[node].outputs().len()..output_count {
+        snarl.drop_outputs(OutPinId { node, output });
+    }
+}
+
+/// Describes a pin to screen readers
+fn label_pin(ui: &mut Ui, snarl: &Snarl<Box<dyn Node>>, pin: Focus) {
+    let id = match pin {
+        Focus::Input(pin) => egui::Id::new(("graph_input", pin.node, pin.input)),
+        Focus::Output(pin) => egui::Id::new(("graph_output", pin.node, pin.output)),
+        Focus::Node(node) => egui::Id::new(("graph_node", node)),
+    };
+    let description = keyboard::describe(snarl, pin);
+    ui.interact(ui.min_rect(), id, egui::Sense::hover())
+        .widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Other, &description));
+}
+
+/// Explains what a disabled node does in place of updating
+fn disabled_text(node: &dyn Node) -> String {
+    let passes_through = node
+        .as_any()
+        .downcast_ref::<bypass::Bypassed>()
+        .is_some_and(bypass::Bypassed::passes_through);
+    i18n::tr(if passes_through {
+        "node-disabled-passing-through"
+    } else {
+        "node-disabled-without-values"
+    })
+}
+
+/// Lists the outputs that changed, with how much numbers changed by
+fn describe_change(change: &OutputChange) -> String {
+    let describe = |value: Option<&Option<TypedData>>| match value {
+        Some(Some(value)) => node_graph::format_value(value),
+        _ => i18n::tr("node-change-nothing"),
+    };
+    let deltas = change.deltas();
+    (0..change.after.len())
+        .filter(|idx| change.before.get(*idx) != change.after.get(*idx))
+        .map(|idx| {
+            let mut line = i18n::tr_args(
+                "node-change-output",
+                &[
+                    ("output", &idx),
+                    ("before", &describe(change.before.get(idx))),
+                    ("after", &describe(change.after.get(idx))),
+                ],
+            );
+            if let Some(delta) = deltas[idx] {
+                let sign = if delta >= 0. { "+" } else { "" };
+                line += &format!(" ({}{})", sign, node_graph::format_float(delta));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Moves a node, keeping its id and wires. The canvas can't move a node in
+/// place, so it is taken out and put back at its new position, which reuses
+/// the id it was removed from.
+pub fn move_node(snarl: &mut Snarl<Box<dyn Node>>, node: NodeId, delta: Vec2) {
+    let Some((_, pos, _)) = snarl.nodes_pos_ids().find(|(id, _, _)| *id == node) else {
+        return;
+    };
+    let mut wires = Vec::new();
+    for input in 0..snarl[node].inputs().len() {
+        let pin = snarl.in_pin(InPinId { node, input });
+        wires.extend(pin.remotes.iter().map(|remote| (*remote, pin.id)));
+    }
+    for output in 0..snarl[node].outputs().len() {
+        let pin = snarl.out_pin(OutPinId { node, output });
+        wires.extend(pin.remotes.iter().map(|remote| (pin.id, *remote)));
+    }
+
+    let value = snarl.remove_node(node);
+    let inserted = snarl.insert_node(pos + delta, value);
+    debug_assert_eq!(inserted, node);
+    for (from, to) in wires {
+        snarl.connect(from, to);
+    }
+}
+
+/// Entries for adding list nodes to the graph
+fn list_menu(pos: Pos2, ui: &mut Ui, snarl: &mut Snarl<Box<dyn Node>>) {
+    if ui.button(i18n::node_name("Range")).clicked() {
+        snarl.insert_node(pos, Box::<RangeNode>::default());
+        ui.close_menu();
+    }
+    if ui.button(i18n::node_name("List")).clicked() {
+        snarl.insert_node(pos, Box::<ListLiteralNode>::default());
+        ui.close_menu();
+    }
+    if ui.button(i18n::node_name("Sum")).clicked() {
+        snarl.insert_node(pos, Box::<SumNode>::default());
+        ui.close_menu();
+    }
+    if ui.button(i18n::node_name("Length")).clicked() {
+        snarl.insert_node(pos, Box::<LengthNode>::default());
+        ui.close_menu();
+    }
+    if ui.button(i18n::node_name("Map Add")).clicked() {
+        snarl.insert_node(pos, Box::<MapAddNode>::default());
+        ui.close_menu();
+    }
+}
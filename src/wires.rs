@@ -0,0 +1,262 @@
+//! Values flowing along the graph's wires, shown when a wire is hovered or
+//! as labels at the middle of every wire. The canvas doesn't say where it
+//! draws its wires, so they are traced here in the same way.
+
+use std::collections::BTreeMap;
+
+use eframe::egui::{self, pos2, Pos2, Rect, Ui};
+use egui_snarl::{ui::SnarlStyle, InPinId, OutPinId, Snarl};
+use graph_core::node_graph::{self, format_value, Node};
+
+/// Longest label drawn on a wire, in characters
+const MAX_LABEL: usize = 24;
+/// Points along a wire used to find whether it is hovered
+const SAMPLES: usize = 32;
+
+/// Where the pins of the displayed graph were drawn this frame
+#[derive(Debug, Default)]
+pub struct PinPositions {
+    inputs: BTreeMap<InPinId, Pos2>,
+    outputs: BTreeMap<OutPinId, Pos2>,
+}
+
+impl PinPositions {
+    pub fn clear(&mut self) {
+        self.inputs.clear();
+        self.outputs.clear();
+    }
+
+    /// Records where an input pin is drawn, from the UI it was shown in.
+    /// `pin` is the space the canvas made for the pin before showing it.
+    pub fn record_input(&mut self, id: InPinId, ui: &Ui, pin: Rect) {
+        let margin = egui::Frame::window(ui.style()).total_margin();
+        let x = ui.max_rect().left() + margin.left + pin.width() / 2.;
+        self.inputs.insert(id, pos2(x, row_centre(ui, pin)));
+    }
+
+    /// Records where an output pin is drawn, see [`Self::record_input`]
+    pub fn record_output(&mut self, id: OutPinId, ui: &Ui, pin: Rect) {
+        let margin = egui::Frame::window(ui.style()).total_margin();
+        let x = ui.max_rect().right() - margin.right - pin.width() / 2.;
+        self.outputs.insert(id, pos2(x, row_centre(ui, pin)));
+    }
+
+    pub fn input(&self, id: InPinId) -> Option<Pos2> {
+        self.inputs.get(&id).copied()
+    }
+
+    pub fn output(&self, id: OutPinId) -> Option<Pos2> {
+        self.outputs.get(&id).copied()
+    }
+
+    /// Shows the value of the source of the wire under the pointer, and
+    /// labels every wire with its value if `labels` is set. Nothing is shown
+    /// for wires under `covered`, such as the nodes drawn above them.
+    pub fn show_values(
+        &self,
+        ui: &Ui,
+        canvas: Rect,
+        snarl: &Snarl<Box<dyn Node>>,
+        style: &SnarlStyle,
+        covered: &[Rect],
+        labels: bool,
+    ) {
+        let pin_size = style.pin_size.unwrap_or(ui.spacing().interact_size.y * 0.5);
+        let frame_size = style.wire_frame_size.unwrap_or(pin_size * 5.);
+        let width = style.wire_width.unwrap_or(pin_size * 0.2).max(1.5);
+        let pointer = ui
+            .ctx()
+            .pointer_hover_pos()
+            .filter(|pos| canvas.contains(*pos) && !covered.iter().any(|rect| rect.contains(*pos)));
+
+        let painter = ui.painter().with_clip_rect(canvas);
+        // Wire nearest the pointer, if it is close enough to be hovered
+        let mut hovered = None;
+        let mut closest = width;
+        let wires = snarl.node_ids().flat_map(|(id, node)| {
+            (0..node.outputs().len()).flat_map(move |output| {
+                let out_pin = OutPinId { node: id, output };
+                snarl
+                    .out_pin(out_pin)
+                    .remotes
+                    .into_iter()
+                    .map(move |in_pin| (out_pin, in_pin))
+            })
+        });
+        for (out_pin, in_pin) in wires {
+            let (Some(&from), Some(&to)) = (self.outputs.get(&out_pin), self.inputs.get(&in_pin))
+            else {
+                continue;
+            };
+            let points = wire_bezier(frame_size, style, from, to);
+            if !canvas.intersects(Rect::from_points(&points)) {
+                continue;
+            }
+            let value = snarl
+                .get_node(out_pin.node)
+                .and_then(|node| node.output_value(out_pin.output));
+
+            if labels {
+                if let Some(value) = &value {
+                    let mut text = format_value(value);
+                    if text.chars().count() > MAX_LABEL {
+                        text = text.chars().take(MAX_LABEL - 1).collect::<String>() + "…";
+                    }
+                    let galley = painter.layout_no_wrap(
+                        text,
+                        egui::FontId::monospace(10.),
+                        ui.visuals().text_color(),
+                    );
+                    let rect = Rect::from_center_size(sample_bezier(&points, 0.5), galley.size());
+                    painter.rect_filled(rect.expand(2.), 2., ui.visuals().extreme_bg_color);
+                    painter.galley(rect.min, galley, ui.visuals().text_color());
+                }
+            }
+
+            if let Some(pointer) = pointer {
+                let distance = distance_to_bezier(pointer, &points);
+                if distance <= closest {
+                    closest = distance;
+                    hovered = Some((out_pin, in_pin, value));
+                }
+            }
+        }
+
+        let Some((out_pin, in_pin, value)) = hovered else {
+            return;
+        };
+        egui::show_tooltip_at_pointer(ui.ctx(), ui.id().with("wire_value"), |ui| {
+            ui.weak(format!(
+                "{} → {}",
+                node_graph::display_name(snarl[out_pin.node].as_ref(), out_pin.node),
+                node_graph::display_name(snarl[in_pin.node].as_ref(), in_pin.node),
+            ));
+            match &value {
+                Some(value) => node_graph::show_value(value, ui),
+                None => {
+                    ui.weak("No value");
+                }
+            }
+        });
+    }
+}
+
+/// Height at which the canvas draws a pin, in the middle of its row
+fn row_centre(ui: &Ui, pin: Rect) -> f32 {
+    (pin.top() + ui.min_rect().bottom()) / 2.
+}
+
+fn distance_to_bezier(pos: Pos2, points: &[Pos2; 6]) -> f32 {
+    let samples = (0..SAMPLES)
+        .map(|i| sample_bezier(points, i as f32 / (SAMPLES - 1) as f32))
+        .collect::<Vec<_>>();
+    samples
+        .windows(2)
+        .map(|segment| {
+            let (a, b) = (segment[0], segment[1]);
+            let along = b - a;
+            let t = ((pos - a).dot(along) / along.length_sq().max(f32::EPSILON)).clamp(0., 1.);
+            pos.distance(a + along * t)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Control points of a wire between two pins, matching how the canvas
+/// draws it
+fn wire_bezier(mut frame_size: f32, style: &SnarlStyle, from: Pos2, to: Pos2) -> [Pos2; 6] {
+    if style.upscale_wire_frame {
+        frame_size = frame_size.max((from - to).length() / 4.);
+    }
+    if style.downscale_wire_frame {
+        frame_size = frame_size.min((from - to).length() / 4.);
+    }
+
+    let from_2 = pos2(from.x + frame_size, from.y);
+    let to_2 = pos2(to.x - frame_size, to.y);
+    let between = (from_2 - to_2).length();
+
+    let (middle_1, middle_2) = if from_2.x <= to_2.x && between >= frame_size * 2. {
+        (
+            from_2 + (to_2 - from_2).normalized() * frame_size,
+            to_2 + (from_2 - to_2).normalized() * frame_size,
+        )
+    } else if from_2.x <= to_2.x {
+        let t =
+            (between - (to_2.y - from_2.y).abs()) / (frame_size * 2. - (to_2.y - from_2.y).abs());
+        let middle_1 = from_2 + (to_2 - from_2).normalized() * frame_size;
+        let middle_2 = to_2 + (from_2 - to_2).normalized() * frame_size;
+
+        let (start_1, start_2) = if from_2.y >= to_2.y + frame_size {
+            let u = (from_2.y - to_2.y - frame_size) / frame_size;
+            (
+                pos2(from_2.x + (1. - u) * frame_size, from_2.y - frame_size * u),
+                pos2(to_2.x, to_2.y + frame_size),
+            )
+        } else if from_2.y >= to_2.y {
+            let u = (from_2.y - to_2.y) / frame_size;
+            (
+                pos2(from_2.x + u * frame_size, from_2.y + frame_size * (1. - u)),
+                pos2(to_2.x, to_2.y + frame_size),
+            )
+        } else if to_2.y >= from_2.y + frame_size {
+            let u = (to_2.y - from_2.y - frame_size) / frame_size;
+            (
+                pos2(from_2.x, from_2.y + frame_size),
+                pos2(to_2.x - (1. - u) * frame_size, to_2.y - frame_size * u),
+            )
+        } else {
+            let u = (to_2.y - from_2.y) / frame_size;
+            (
+                pos2(from_2.x, from_2.y + frame_size),
+                pos2(to_2.x - u * frame_size, to_2.y + frame_size * (1. - u)),
+            )
+        };
+        (start_1.lerp(middle_1, t), start_2.lerp(middle_2, t))
+    } else if from_2.y >= to_2.y + frame_size * 2. {
+        (
+            pos2(from_2.x, from_2.y - frame_size),
+            pos2(to_2.x, to_2.y + frame_size),
+        )
+    } else if from_2.y >= to_2.y + frame_size {
+        let t = (from_2.y - to_2.y - frame_size) / frame_size;
+        (
+            pos2(from_2.x + (1. - t) * frame_size, from_2.y - frame_size * t),
+            pos2(to_2.x, to_2.y + frame_size),
+        )
+    } else if from_2.y >= to_2.y {
+        let t = (from_2.y - to_2.y) / frame_size;
+        (
+            pos2(from_2.x + t * frame_size, from_2.y + frame_size * (1. - t)),
+            pos2(to_2.x, to_2.y + frame_size),
+        )
+    } else if to_2.y >= from_2.y + frame_size * 2. {
+        (
+            pos2(from_2.x, from_2.y + frame_size),
+            pos2(to_2.x, to_2.y - frame_size),
+        )
+    } else if to_2.y >= from_2.y + frame_size {
+        let t = (to_2.y - from_2.y - frame_size) / frame_size;
+        (
+            pos2(from_2.x, from_2.y + frame_size),
+            pos2(to_2.x - (1. - t) * frame_size, to_2.y - frame_size * t),
+        )
+    } else {
+        let t = (to_2.y - from_2.y) / frame_size;
+        (
+            pos2(from_2.x, from_2.y + frame_size),
+            pos2(to_2.x - t * frame_size, to_2.y + frame_size * (1. - t)),
+        )
+    };
+    [from, from_2, middle_1, middle_2, to_2, to]
+}
+
+/// Point at `t` along a wire, from 0 at its start to 1 at its end
+fn sample_bezier(points: &[Pos2; 6], t: f32) -> Pos2 {
+    let mut points = *points;
+    for len in (1..points.len()).rev() {
+        for i in 0..len {
+            points[i] = points[i].lerp(points[i + 1], t);
+        }
+    }
+    points[0]
+}